@@ -0,0 +1,12 @@
+-- 0007_app_setting.sql
+-- Configuración de la app en clave/valor (p.ej. snapshots automáticos).
+
+CREATE TABLE IF NOT EXISTS app_setting (
+  key        TEXT PRIMARY KEY,
+  value      TEXT NOT NULL,
+  updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ','now'))
+);
+
+-- Snapshots automáticos: cada 5 minutos con algún evento 'active', 24 generaciones por tipo.
+INSERT INTO app_setting (key, value) VALUES ('snapshot_interval_minutes', '5') ON CONFLICT DO NOTHING;
+INSERT INTO app_setting (key, value) VALUES ('snapshot_keep', '24') ON CONFLICT DO NOTHING;
@@ -0,0 +1,10 @@
+-- 0008_audit_changes.sql
+-- Valores antes/después en audit_log y quién corrigió cada corrida.
+
+ALTER TABLE audit_log ADD COLUMN before_json TEXT;
+ALTER TABLE audit_log ADD COLUMN after_json TEXT;
+
+ALTER TABLE run ADD COLUMN corrected_by INTEGER REFERENCES app_user(id) ON DELETE SET NULL;
+ALTER TABLE run ADD COLUMN corrected_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_audit_created ON audit_log(created_at);
@@ -0,0 +1,10 @@
+-- 0009_activity_sessions.sql
+-- `activity` pasa a guardar también sesiones (jornada de evento, captura de una ronda):
+-- `created_at` es el inicio y `ended_at` el fin (NULL mientras siga abierta).
+
+ALTER TABLE activity ADD COLUMN event_id INTEGER REFERENCES event(id) ON DELETE SET NULL;
+ALTER TABLE activity ADD COLUMN round INTEGER;
+ALTER TABLE activity ADD COLUMN ended_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_activity_event ON activity(event_id, kind);
+CREATE INDEX IF NOT EXISTS idx_activity_time ON activity(created_at);
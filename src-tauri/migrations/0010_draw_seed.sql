@@ -0,0 +1,16 @@
+-- 0010_draw_seed.sql
+-- Semilla de cada ronda sorteada: con `seed` y las inscripciones (`entries_json`) se vuelve a
+-- calcular el mismo orden (`verify_draw`). `rng` es la versión del generador usado.
+
+CREATE TABLE IF NOT EXISTS draw_round (
+  id           INTEGER PRIMARY KEY AUTOINCREMENT,
+  event_id     INTEGER NOT NULL REFERENCES event(id) ON DELETE CASCADE,
+  round        INTEGER NOT NULL CHECK (round >= 1),
+  method       TEXT NOT NULL,
+  seed         TEXT,
+  rng          TEXT NOT NULL,
+  entries_json TEXT NOT NULL,
+  created_by   INTEGER REFERENCES app_user(id) ON DELETE SET NULL,
+  created_at   TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ','now')),
+  UNIQUE(event_id, round)
+);
@@ -0,0 +1,6 @@
+-- 0011_draw_spacing.sql
+-- Separación mínima entre salidas del mismo roper usada al sortear cada ronda y cuántas
+-- salidas no la cumplieron. `verify_draw` la necesita para recalcular el orden.
+
+ALTER TABLE draw_round ADD COLUMN min_spacing INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE draw_round ADD COLUMN violations INTEGER NOT NULL DEFAULT 0;
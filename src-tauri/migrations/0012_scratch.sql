@@ -0,0 +1,9 @@
+-- 0012_scratch.sql
+-- Retiro (scratch) de un equipo ya sorteado. `event.scratch_policy` decide qué pasa con su
+-- lugar en las rondas que faltan: 'close_gap' (se recorre el orden) o 'keep_slot' (queda el
+-- hueco marcado, `run.scratched = 1`).
+
+ALTER TABLE event ADD COLUMN scratch_policy TEXT NOT NULL DEFAULT 'close_gap' CHECK (scratch_policy IN ('close_gap', 'keep_slot'));
+ALTER TABLE team ADD COLUMN scratched_at TEXT;
+ALTER TABLE team ADD COLUMN scratch_reason TEXT;
+ALTER TABLE run ADD COLUMN scratched INTEGER NOT NULL DEFAULT 0;
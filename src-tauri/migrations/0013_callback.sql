@@ -0,0 +1,9 @@
+-- 0013_callback.sql
+-- Short-go: la ronda final sólo llama a los mejores N (o N%) de la clasificación previa.
+-- `callback_ties` decide qué pasa con los empatados en la línea de corte. Los que no pasan
+-- quedan con `team.called_back = 0` (no eliminados: sus rondas previas siguen contando).
+
+ALTER TABLE event ADD COLUMN callback_mode TEXT NOT NULL DEFAULT 'all' CHECK (callback_mode IN ('all', 'top_n', 'top_percent'));
+ALTER TABLE event ADD COLUMN callback_value INTEGER;
+ALTER TABLE event ADD COLUMN callback_ties TEXT NOT NULL DEFAULT 'include' CHECK (callback_ties IN ('include', 'exclude', 'tiebreak'));
+ALTER TABLE team ADD COLUMN called_back INTEGER;
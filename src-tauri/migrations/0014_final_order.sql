@@ -0,0 +1,8 @@
+-- 0014_final_order.sql
+-- Cómo se ordena la ronda final de cada evento (sin corte de short-go):
+-- 'aggregate' (mayor tiempo acumulado primero, el comportamiento de siempre), 'average'
+-- (mayor promedio primero), 'standings' (inverso de la clasificación), 'random' o 'round_one'
+-- (el mismo orden de la ronda 1).
+
+ALTER TABLE event ADD COLUMN final_order TEXT NOT NULL DEFAULT 'aggregate'
+  CHECK (final_order IN ('aggregate', 'average', 'standings', 'random', 'round_one'));
@@ -0,0 +1,34 @@
+-- 0015_penalties.sql
+-- Penalizaciones con tipo. Cada evento tiene su catálogo (`penalty_type`: las dos estándar más
+-- las que agregue el admin) y cada corrida guarda sus renglones en `run_penalty`, con la
+-- etiqueta y los segundos copiados al capturar: cambiar un tipo después no reescribe corridas.
+-- `run.penalty` se queda como la suma de sus renglones.
+
+CREATE TABLE IF NOT EXISTS penalty_type (
+  id         INTEGER PRIMARY KEY AUTOINCREMENT,
+  event_id   INTEGER NOT NULL REFERENCES event(id) ON DELETE CASCADE,
+  code       TEXT NOT NULL,
+  label      TEXT NOT NULL,
+  seconds    REAL NOT NULL CHECK (seconds >= 0),
+  is_active  INTEGER NOT NULL DEFAULT 1,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ','now')),
+  UNIQUE(event_id, code)
+);
+
+CREATE TABLE IF NOT EXISTS run_penalty (
+  id              INTEGER PRIMARY KEY AUTOINCREMENT,
+  run_id          INTEGER NOT NULL REFERENCES run(id) ON DELETE CASCADE,
+  penalty_type_id INTEGER REFERENCES penalty_type(id) ON DELETE SET NULL,
+  label           TEXT NOT NULL,
+  seconds         REAL NOT NULL CHECK (seconds >= 0),
+  created_at      TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ','now'))
+);
+CREATE INDEX IF NOT EXISTS idx_run_penalty_run ON run_penalty(run_id);
+
+-- tipos estándar para los eventos que ya existen
+INSERT INTO penalty_type (event_id, code, label, seconds) SELECT id, 'leg', 'One-leg catch', 5.0 FROM event;
+INSERT INTO penalty_type (event_id, code, label, seconds) SELECT id, 'barrier', 'Broken barrier', 10.0 FROM event;
+
+-- las penalizaciones sueltas de antes quedan como un renglón sin tipo
+INSERT INTO run_penalty (run_id, penalty_type_id, label, seconds)
+SELECT id, NULL, 'Penalización (sin tipo)', penalty FROM run WHERE penalty > 0;
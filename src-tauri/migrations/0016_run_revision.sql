@@ -0,0 +1,66 @@
+-- 0016_run_revision.sql
+-- Historial de solo-inserción de cada corrida: un renglón por versión con los valores de antes
+-- y de después, quién, cuándo y por qué. `runs::save` escribe 'capture' (primera captura) o
+-- 'correction' (sobre una corrida ya completada); `recompute_totals` escribe 'recompute'.
+-- Las corridas ya completadas arrancan con un renglón 'baseline' con su estado actual.
+--
+-- El historial es de la corrida del equipo en la ronda (`event_id`, `team_id`, `round`), no de
+-- la fila de `run`: si un sorteo se rehace y la fila se borra, las revisiones quedan (con
+-- `run_id` en NULL) y la fila nueva sigue la numeración.
+
+CREATE TABLE IF NOT EXISTS run_revision (
+  id            INTEGER PRIMARY KEY AUTOINCREMENT,
+  run_id        INTEGER REFERENCES run(id) ON DELETE SET NULL,
+  event_id      INTEGER NOT NULL REFERENCES event(id) ON DELETE CASCADE,
+  team_id       INTEGER NOT NULL REFERENCES team(id) ON DELETE CASCADE,
+  round         INTEGER NOT NULL,
+  revision      INTEGER NOT NULL,
+  action        TEXT NOT NULL,
+  old_time_sec  REAL,
+  old_penalty   REAL,
+  old_total_sec REAL,
+  old_no_time   INTEGER,
+  old_dq        INTEGER,
+  old_status    TEXT,
+  new_time_sec  REAL,
+  new_penalty   REAL NOT NULL DEFAULT 0,
+  new_total_sec REAL,
+  new_no_time   INTEGER NOT NULL DEFAULT 0,
+  new_dq        INTEGER NOT NULL DEFAULT 0,
+  new_status    TEXT NOT NULL,
+  penalties     TEXT,
+  reason        TEXT,
+  user_id       INTEGER REFERENCES app_user(id) ON DELETE SET NULL,
+  created_at    TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ','now')),
+  UNIQUE(event_id, team_id, round, revision)
+);
+CREATE INDEX IF NOT EXISTS idx_run_revision_run ON run_revision(run_id);
+
+-- Una revisión no se edita nunca. Sólo `run_id` y `user_id` pueden cambiar, y sólo a NULL:
+-- es el `ON DELETE SET NULL` al borrar la corrida o el usuario.
+CREATE TRIGGER IF NOT EXISTS trg_run_revision_no_update
+BEFORE UPDATE OF event_id, team_id, round, revision, action,
+                 old_time_sec, old_penalty, old_total_sec, old_no_time, old_dq, old_status,
+                 new_time_sec, new_penalty, new_total_sec, new_no_time, new_dq, new_status,
+                 penalties, reason, created_at
+ON run_revision
+BEGIN
+  SELECT RAISE(ABORT, 'run_revision es de solo inserción');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_run_revision_no_relink
+BEFORE UPDATE OF run_id, user_id ON run_revision
+WHEN (NEW.run_id IS NOT NULL AND NEW.run_id IS NOT OLD.run_id)
+  OR (NEW.user_id IS NOT NULL AND NEW.user_id IS NOT OLD.user_id)
+BEGIN
+  SELECT RAISE(ABORT, 'run_revision es de solo inserción');
+END;
+
+INSERT INTO run_revision (run_id, event_id, team_id, round, revision, action,
+                          new_time_sec, new_penalty, new_total_sec, new_no_time, new_dq,
+                          new_status, penalties, user_id, created_at)
+SELECT r.id, r.event_id, r.team_id, r.round, 1, 'baseline', r.time_sec, r.penalty, r.total_sec, r.no_time, r.dq, r.status,
+       (SELECT group_concat(p.label || ' +' || p.seconds, '; ') FROM run_penalty p WHERE p.run_id = r.id),
+       COALESCE(r.corrected_by, r.captured_by), COALESCE(r.corrected_at, r.updated_at)
+FROM run r
+WHERE r.status = 'completed';
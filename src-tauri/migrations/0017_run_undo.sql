@@ -0,0 +1,27 @@
+-- 0017_run_undo.sql
+-- Lo que hace falta para deshacer/rehacer una captura desde el historial:
+-- - `target_id`: la revisión (de la misma corrida) que deshace o rehace una fila 'undo'/'redo'.
+-- - `cascade_json`: cambios de status que el guardado hizo en rondas siguientes
+--   ([{"run_id", "from", "to"}], p. ej. NT → 'skipped').
+-- - `old_penalties_json`/`new_penalties_json`: renglones de `run_penalty` antes y después.
+-- Las revisiones anteriores a esta migración no tienen estos datos y no se pueden deshacer.
+
+ALTER TABLE run_revision ADD COLUMN target_id INTEGER;
+ALTER TABLE run_revision ADD COLUMN cascade_json TEXT;
+ALTER TABLE run_revision ADD COLUMN old_penalties_json TEXT;
+ALTER TABLE run_revision ADD COLUMN new_penalties_json TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_run_revision_target ON run_revision(target_id);
+
+-- El trigger de 0016 cubre también las columnas nuevas
+DROP TRIGGER IF EXISTS trg_run_revision_no_update;
+CREATE TRIGGER IF NOT EXISTS trg_run_revision_no_update
+BEFORE UPDATE OF event_id, team_id, round, revision, action,
+                 old_time_sec, old_penalty, old_total_sec, old_no_time, old_dq, old_status,
+                 new_time_sec, new_penalty, new_total_sec, new_no_time, new_dq, new_status,
+                 penalties, reason, created_at,
+                 target_id, cascade_json, old_penalties_json, new_penalties_json
+ON run_revision
+BEGIN
+  SELECT RAISE(ABORT, 'run_revision es de solo inserción');
+END;
@@ -0,0 +1,39 @@
+-- 0018_row_version.sql
+-- Concurrencia optimista: `version` sube en 1 con cada UPDATE de la fila, venga de donde venga
+-- (guardado, cascada de NT/DQ, recálculo, scratch, deshacer…). Quien guarda manda la versión que
+-- vio (`expected_version`); si ya no coincide, el backend responde Conflict con la fila actual.
+-- `updated_at` no sirve para esto: tiene resolución de un segundo.
+
+ALTER TABLE run ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE event ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE team ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE roper ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Sólo si el UPDATE no tocó `version` (los triggers no son recursivos en SQLite por defecto)
+CREATE TRIGGER IF NOT EXISTS trg_run_version
+AFTER UPDATE ON run
+WHEN NEW.version = OLD.version
+BEGIN
+  UPDATE run SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_event_version
+AFTER UPDATE ON event
+WHEN NEW.version = OLD.version
+BEGIN
+  UPDATE event SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_team_version
+AFTER UPDATE ON team
+WHEN NEW.version = OLD.version
+BEGIN
+  UPDATE team SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_roper_version
+AFTER UPDATE ON roper
+WHEN NEW.version = OLD.version
+BEGIN
+  UPDATE roper SET version = OLD.version + 1 WHERE id = NEW.id;
+END;
@@ -0,0 +1,321 @@
+//! `roping-admin`: mantenimiento de `roping_manager.db` sin abrir la GUI.
+//!
+//! Usa la misma ruta que la app (directorio de datos local + `roping_manager.db`)
+//! salvo que se pase `--db <ruta>`.
+
+use anyhow::{bail, Context, Result};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+use roping_manager_tauri_lib::timer::TimerFrame;
+use roping_manager_tauri_lib::timer_sim::{TcpSimulator, TimerEmitter};
+use roping_manager_tauri_lib::{db, license, schema};
+use roping_manager_tauri_lib::domain::export::{self, ExportOptions};
+use roping_manager_tauri_lib::domain::{events, maintenance, runs, standings};
+
+const USAGE: &str = "\
+Uso: roping-admin [--db <ruta>] <comando> [args]
+
+Comandos:
+  migrate                          Aplica las migraciones pendientes (crea la DB si no existe)
+  backup <destino.db>              Copia consistente de la base (VACUUM INTO)
+  integrity-check                  PRAGMA integrity_check + foreign_key_check + esquema esperado
+  list-events [--all]              Lista eventos (--all incluye los borrados)
+  export <event_id> <archivo>      Exporta a .xlsx (todas las hojas) o .csv (standings; --runs para el orden de corridas)
+  recompute-standings <event_id>   Recalcula total_sec de las corridas y muestra los standings
+  purge-deleted [--yes]            Borra definitivamente las filas soft-deleted (pide --yes)
+  license-status                   Estado de la licencia guardada para este equipo
+  device-id                        Huella de este equipo (para pedir una licencia)
+  issue-license --key <archivo> [--from AAAA-MM-DD] <cliente> <device_id> <vence AAAA-MM-DD>
+                                   Firma una licencia con la clave privada (sólo quien emite licencias)
+  timer-sim [--tcp <host:puerto> | --pty] [--every <seg>] [--count <n>]
+                                   Simula un timer (START/FINISH al azar) para probar la captura sin
+                                   hardware; por defecto escucha en 127.0.0.1:5005
+";
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(std::env::args().skip(1).collect()).await {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(mut args: Vec<String>) -> Result<()> {
+    let db_path = match take_option(&mut args, "--db")? {
+        Some(p) => PathBuf::from(p),
+        None => db::default_db_path()?,
+    };
+    let Some(command) = (!args.is_empty()).then(|| args.remove(0)) else {
+        print!("{}", USAGE);
+        return Ok(());
+    };
+    if command == "help" || command == "--help" || command == "-h" {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    // sin base de datos
+    match command.as_str() {
+        "device-id" => {
+            println!("{}", license::device_fingerprint());
+            return Ok(());
+        }
+        "issue-license" => return issue_license(&mut args),
+        "timer-sim" => return timer_sim(&mut args).await,
+        _ => {}
+    }
+
+    let is_migrate = command == "migrate";
+    if !is_migrate && !db_path.exists() {
+        bail!("No existe la base de datos en {}", db_path.display());
+    }
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    eprintln!("DB path -> {}", db_path.display());
+    let pool = db::open_pool(&db_path, is_migrate).await?;
+    if !is_migrate {
+        warn_pending_migrations(&pool).await;
+    }
+
+    match command.as_str() {
+        "migrate" => migrate(&pool).await,
+        "backup" => {
+            let dest = positional(&args, 0, "destino")?;
+            maintenance::backup_to(&pool, Path::new(dest)).await?;
+            println!("Backup escrito en {}", dest);
+            Ok(())
+        }
+        "integrity-check" => {
+            let mut problems = maintenance::integrity_check(&pool).await?;
+            if let Err(e) = schema::check_drift(&pool).await {
+                problems.push(e.to_string());
+            }
+            if problems.is_empty() {
+                println!("ok");
+                Ok(())
+            } else {
+                for p in &problems {
+                    println!("{}", p);
+                }
+                bail!("{} problema(s) de integridad", problems.len())
+            }
+        }
+        "list-events" => list_events(&pool, has_flag(&args, "--all")).await,
+        "export" => {
+            let event_id = event_id_arg(&args)?;
+            let file = positional(&args, 1, "archivo")?;
+            export_event(&pool, event_id, file, has_flag(&args, "--runs")).await
+        }
+        "recompute-standings" => recompute_standings(&pool, event_id_arg(&args)?).await,
+        "purge-deleted" => {
+            if !has_flag(&args, "--yes") {
+                bail!("purge-deleted borra filas de forma irreversible; haz un backup y repite con --yes");
+            }
+            let report = maintenance::purge_soft_deleted(&pool).await?;
+            println!(
+                "Borrados: {} series, {} eventos, {} equipos, {} ropers, {} reglas de payoff",
+                report.series, report.events, report.teams, report.ropers, report.payoff_rules
+            );
+            if report.ropers_kept > 0 {
+                println!("{} ropers inactivos se conservan porque aún tienen equipos", report.ropers_kept);
+            }
+            Ok(())
+        }
+        "license-status" => {
+            let payload = license::load(&pool, &license::embedded_public_key()).await?;
+            let trial_started = license::stored_trial_start(&pool, &license::trial_marker(&db_path)).await?;
+            let status = license::License::new(license::device_fingerprint(), payload, trial_started).status();
+            println!("Equipo: {}", status.device_id);
+            println!("{}", status.message);
+            Ok(())
+        }
+        other => bail!("Comando desconocido: {}\n\n{}", other, USAGE),
+    }
+}
+
+/* ---- comandos ---- */
+
+async fn migrate(pool: &SqlitePool) -> Result<()> {
+    let report = schema::migrate(pool).await?;
+    for r in &report.repaired {
+        println!("Reparado: migración {} omitió ADD COLUMN {}.{} (ya existía)", r.version, r.table, r.column);
+    }
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(pool)
+            .await?;
+    println!(
+        "Migraciones al día (versión {}, {} aplicada(s) ahora)",
+        version.unwrap_or(0),
+        report.applied.len()
+    );
+    Ok(())
+}
+
+async fn list_events(pool: &SqlitePool, all: bool) -> Result<()> {
+    let rows = if all {
+        events::list_all_raw(pool).await?
+    } else {
+        events::list(pool, None).await?
+    };
+    println!("{:>5}  {:<10}  {:<10}  {:>6}  {:>5}  NOMBRE", "ID", "FECHA", "ESTADO", "RONDAS", "EQ.");
+    for e in rows {
+        println!(
+            "{:>5}  {:<10}  {:<10}  {:>6}  {:>5}  {}",
+            e.id,
+            e.date,
+            e.status.as_deref().unwrap_or("-"),
+            e.rounds,
+            e.teams_count,
+            e.name
+        );
+    }
+    Ok(())
+}
+
+async fn export_event(pool: &SqlitePool, event_id: i64, file: &str, runs_only: bool) -> Result<()> {
+    events::get(pool, event_id).await?;
+    let ext = Path::new(file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("xlsx") => {
+            export::event_to_excel(
+                pool,
+                event_id,
+                ExportOptions {
+                    overview: true,
+                    teams: true,
+                    run_order: true,
+                    standings: true,
+                    payoffs: true,
+                    event_logs: true,
+                    file_path: file.to_string(),
+                },
+            )
+            .await?
+        }
+        Some("csv") if runs_only => export::runs_to_csv(pool, event_id, file).await?,
+        Some("csv") => export::standings_to_csv(pool, event_id, file).await?,
+        _ => bail!("Formato no soportado: usa .xlsx o .csv"),
+    }
+    println!("Exportado a {}", file);
+    Ok(())
+}
+
+async fn recompute_standings(pool: &SqlitePool, event_id: i64) -> Result<()> {
+    events::get(pool, event_id).await?;
+    let changed = runs::recompute_totals(pool, event_id).await?;
+    println!("{} corrida(s) con total_sec corregido", changed);
+
+    println!("{:>4}  {:<24}  {:<24}  {:>6}  {:>9}  {:>3}  {:>3}", "RANK", "HEADER", "HEELER", "VUELT.", "TOTAL", "NT", "DQ");
+    for s in standings::get(pool, event_id).await? {
+        println!(
+            "{:>4}  {:<24}  {:<24}  {:>6}  {:>9}  {:>3}  {:>3}",
+            s.rank,
+            s.header_name,
+            s.heeler_name,
+            s.completed_runs,
+            s.total_time.map(|t| format!("{:.2}", t)).unwrap_or_else(|| "-".into()),
+            s.nt_cnt,
+            s.dq_cnt
+        );
+    }
+    Ok(())
+}
+
+fn issue_license(args: &mut Vec<String>) -> Result<()> {
+    let key_file = take_option(args, "--key")?.with_context(|| format!("falta --key <archivo>\n\n{}", USAGE))?;
+    let valid_from = take_option(args, "--from")?;
+    let secret = std::fs::read_to_string(&key_file).with_context(|| format!("no se pudo leer {}", key_file))?;
+    let payload = license::LicensePayload {
+        customer: positional(args, 0, "cliente")?.to_string(),
+        device_id: positional(args, 1, "device_id")?.to_string(),
+        valid_from,
+        valid_until: positional(args, 2, "vence")?.to_string(),
+    };
+    println!("{}", license::issue(&secret, &payload)?);
+    Ok(())
+}
+
+async fn timer_sim(args: &mut Vec<String>) -> Result<()> {
+    let every: f64 = take_option(args, "--every")?.map_or(Ok(15.0), |v| v.parse()).context("--every debe ser un número")?;
+    let count: Option<u64> = take_option(args, "--count")?.map(|v| v.parse()).transpose().context("--count debe ser un número")?;
+    let tcp = take_option(args, "--tcp")?;
+
+    let mut emitter: Box<dyn TimerEmitter> = if has_flag(args, "--pty") {
+        #[cfg(unix)]
+        {
+            let pty = roping_manager_tauri_lib::timer_sim::PtySimulator::open()?;
+            println!("Puerto serie simulado: {} (conéctalo como timer serie)", pty.path());
+            Box::new(pty)
+        }
+        #[cfg(not(unix))]
+        bail!("--pty sólo existe en macOS/Linux; usa --tcp")
+    } else {
+        let sim = TcpSimulator::bind(tcp.as_deref().unwrap_or("127.0.0.1:5005"))?;
+        println!("Timer simulado escuchando en {} (conéctalo como timer TCP)", sim.addr());
+        Box::new(sim)
+    };
+
+    // sin --count, sin límite práctico
+    let limit = count.unwrap_or(u64::MAX);
+    let mut sent = 0;
+    while sent < limit {
+        tokio::time::sleep(std::time::Duration::from_secs_f64(every / 2.0)).await;
+        emitter.send(TimerFrame::Start)?;
+        let seconds = rand::random::<f64>() * 8.0 + 5.0;
+        tokio::time::sleep(std::time::Duration::from_secs_f64(every / 2.0)).await;
+        emitter.send(TimerFrame::Finish { seconds })?;
+        sent += 1;
+        println!("#{} FINISH {:.3}", sent, seconds);
+    }
+    Ok(())
+}
+
+async fn warn_pending_migrations(pool: &SqlitePool) {
+    let latest = db::MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0);
+    let applied: Option<i64> =
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(None);
+    if applied.unwrap_or(0) < latest {
+        eprintln!("aviso: la base tiene migraciones pendientes; ejecuta `roping-admin migrate`");
+    }
+}
+
+/* ---- argumentos ---- */
+
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+    let Some(i) = args.iter().position(|a| a == name) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        bail!("{} requiere un valor", name);
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
+fn has_flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|a| a == name)
+}
+
+fn positional<'a>(args: &'a [String], index: usize, what: &str) -> Result<&'a str> {
+    args.iter()
+        .filter(|a| !a.starts_with("--"))
+        .nth(index)
+        .map(String::as_str)
+        .with_context(|| format!("falta el argumento <{}>\n\n{}", what, USAGE))
+}
+
+fn event_id_arg(args: &[String]) -> Result<i64> {
+    positional(args, 0, "event_id")?
+        .parse()
+        .context("event_id debe ser un número")
+}
@@ -0,0 +1,806 @@
+// Comandos Tauri: envoltorios delgados sobre `crate::domain`.
+// Aquí sólo se extrae el pool del estado y se comprueba el rol del usuario logueado
+// (`Session::require_role`); la lógica vive en los módulos de dominio. Los comandos que
+// modifican datos corren dentro de `audit::as_user` para que el audit y las corridas
+// queden firmados por ese usuario, y piden `License::require_writable` (sin licencia
+// vigente la app queda en sólo lectura; exportar y respaldar siguen permitidos).
+//
+// Permisos: `Viewer` sólo lectura; `Operator` además captura (`save_run`, deshacer/rehacer) y sorteos;
+// el resto de cambios —incluidos borrados masivos, bloqueo de eventos, payoffs,
+// exportaciones y backups— es de `Admin`.
+
+use std::path::Path;
+use tauri::State;
+
+use crate::domain::{
+    self, activity, audit, dashboard, draw, draw_report, events, export, maintenance, payoffs, penalties, revisions, ropers, runs, series, standings,
+    teams, users::{self, Role},
+};
+use crate::error::{AppError, AppResult};
+use crate::license::{self, License, LicenseStatus};
+use crate::session::Session;
+use crate::snapshots::{self, SnapshotInfo, SnapshotSettings};
+use crate::timer::{self, Timer, TimerOffer, TimerSource, TimerStatus};
+use crate::db::{Db, RestoreReport};
+
+/* ------------------- HEALTH ------------------- */
+#[tauri::command]
+pub async fn health_check(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<String> {
+    session.require_role(Role::Viewer)?;
+    domain::health_check(&db.pool()).await
+}
+
+/* ------------------- SERIES ------------------- */
+#[tauri::command]
+pub async fn list_series(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<Vec<series::SeriesRow>> {
+    session.require_role(Role::Viewer)?;
+    series::list(&db.pool()).await
+}
+
+#[tauri::command]
+pub async fn create_series(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    payload: series::NewSeries,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), series::create(&db.pool(), payload)).await
+}
+
+#[tauri::command]
+pub async fn update_series(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+    patch: series::UpdateSeries,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), series::update(&db.pool(), id, patch)).await
+}
+
+#[tauri::command]
+pub async fn delete_series(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), series::delete(&db.pool(), id)).await
+}
+
+/* ------------------- EVENTS ------------------- */
+#[tauri::command]
+pub async fn list_events(db: State<'_, Db>, session: State<'_, Session>, series_id: Option<i64>) -> AppResult<Vec<events::EventRow>> {
+    session.require_role(Role::Viewer)?;
+    events::list(&db.pool(), series_id).await
+}
+
+#[tauri::command]
+pub async fn list_all_events_raw(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<Vec<events::EventRow>> {
+    session.require_role(Role::Viewer)?;
+    events::list_all_raw(&db.pool()).await
+}
+
+#[tauri::command]
+pub async fn create_event(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    payload: events::NewEvent,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), events::create(&db.pool(), payload)).await
+}
+
+#[tauri::command]
+pub async fn update_event_status(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+    status: String,
+    pin: Option<String>,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), events::update_status(&db.pool(), id, &status, pin.as_deref())).await?;
+    if events::normalize_status(&status) == "completed" {
+        snapshots::after_change(&db, "completed").await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_event(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+    patch: events::EventPatch,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    // completar el evento por el patch toma el mismo snapshot que `update_event_status`
+    let completing = patch.status.as_deref().is_some_and(|s| events::normalize_status(s) == "completed");
+    audit::as_user(Some(user.id), events::update(&db.pool(), id, patch)).await?;
+    if completing {
+        snapshots::after_change(&db, "completed").await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_event(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), events::delete(&db.pool(), id)).await
+}
+
+#[tauri::command]
+pub async fn duplicate_event(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), events::duplicate(&db.pool(), id)).await
+}
+
+#[tauri::command]
+pub async fn lock_event(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    event_id: i64,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), events::lock(&db.pool(), event_id)).await?;
+    snapshots::after_change(&db, "lock").await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn verify_event_pin(db: State<'_, Db>, session: State<'_, Session>, event_id: i64, pin: String) -> AppResult<bool> {
+    let user = session.require_role(Role::Operator)?;
+    audit::as_user(Some(user.id), events::verify_pin(&db.pool(), event_id, &pin)).await
+}
+
+/* ------------------- PAYOFF RULES ------------------- */
+#[tauri::command]
+pub async fn list_payoff_rules(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    event_id: Option<i64>,
+) -> AppResult<Vec<payoffs::PayoffRuleRow>> {
+    session.require_role(Role::Viewer)?;
+    payoffs::list_rules(&db.pool(), event_id).await
+}
+
+#[tauri::command]
+pub async fn delete_payoff_rule(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), payoffs::delete_rule(&db.pool(), id)).await
+}
+
+#[tauri::command]
+pub async fn create_payoff_rule(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    rule: payoffs::NewPayoffRule,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), payoffs::create_rule(&db.pool(), rule)).await
+}
+
+/* ------------------- PENALTIES ------------------- */
+#[tauri::command]
+pub async fn list_penalty_types(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    event_id: i64,
+    include_inactive: Option<bool>,
+) -> AppResult<Vec<penalties::PenaltyTypeRow>> {
+    session.require_role(Role::Viewer)?;
+    penalties::list_types(&db.pool(), event_id, include_inactive.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub async fn create_penalty_type(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    payload: penalties::NewPenaltyType,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), penalties::create_type(&db.pool(), payload)).await
+}
+
+#[tauri::command]
+pub async fn update_penalty_type(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+    patch: penalties::PenaltyTypePatch,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), penalties::update_type(&db.pool(), id, patch)).await
+}
+
+#[tauri::command]
+pub async fn get_payout_breakdown(db: State<'_, Db>, session: State<'_, Session>, event_id: i64) -> AppResult<payoffs::PayoutBreakdown> {
+    session.require_role(Role::Viewer)?;
+    payoffs::payout_breakdown(&db.pool(), event_id).await
+}
+
+/* ------------------- RUNS (CAPTURE) ------------------- */
+#[tauri::command]
+pub async fn save_run(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    payload: runs::SaveRun,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), runs::save(&db.pool(), payload)).await
+}
+
+#[tauri::command]
+pub async fn get_runs(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    event_id: i64,
+    round: Option<i64>,
+) -> AppResult<Vec<runs::RunRow>> {
+    session.require_role(Role::Viewer)?;
+    runs::list(&db.pool(), event_id, round).await
+}
+
+#[tauri::command]
+pub async fn get_runs_expanded(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    event_id: i64,
+    round: Option<i64>,
+) -> AppResult<Vec<runs::RunExpandedRow>> {
+    session.require_role(Role::Viewer)?;
+    runs::list_expanded(&db.pool(), event_id, round).await
+}
+
+#[tauri::command]
+pub async fn get_run_history(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    run_id: i64,
+) -> AppResult<Vec<revisions::RunRevisionRow>> {
+    session.require_role(Role::Viewer)?;
+    revisions::history(&db.pool(), run_id).await
+}
+
+#[tauri::command]
+pub async fn get_run_undo_state(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    event_id: i64,
+) -> AppResult<revisions::UndoState> {
+    session.require_role(Role::Viewer)?;
+    revisions::undo_state(&db.pool(), event_id).await
+}
+
+#[tauri::command]
+pub async fn undo_last_run_change(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    event_id: i64,
+    pin: Option<String>,
+) -> AppResult<revisions::UndoOutcome> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), revisions::undo_last(&db.pool(), event_id, pin.as_deref())).await
+}
+
+#[tauri::command]
+pub async fn redo_last_run_change(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    event_id: i64,
+    pin: Option<String>,
+) -> AppResult<revisions::UndoOutcome> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), revisions::redo_last(&db.pool(), event_id, pin.as_deref())).await
+}
+
+/* ------------------- TIMER ------------------- */
+#[tauri::command]
+pub async fn timer_status(session: State<'_, Session>, timer: State<'_, Timer>) -> AppResult<TimerStatus> {
+    session.require_role(Role::Viewer)?;
+    Ok(timer.status())
+}
+
+#[tauri::command]
+pub async fn connect_timer(session: State<'_, Session>, timer: State<'_, Timer>, source: TimerSource) -> AppResult<TimerStatus> {
+    session.require_role(Role::Operator)?;
+    timer.connect(source)?;
+    Ok(timer.status())
+}
+
+#[tauri::command]
+pub async fn disconnect_timer(session: State<'_, Session>, timer: State<'_, Timer>) -> AppResult<TimerStatus> {
+    session.require_role(Role::Operator)?;
+    timer.disconnect();
+    Ok(timer.status())
+}
+
+#[tauri::command]
+pub async fn get_timer_offer(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    timer: State<'_, Timer>,
+    event_id: i64,
+    round: Option<i64>,
+) -> AppResult<TimerOffer> {
+    session.require_role(Role::Viewer)?;
+    timer::offer(&db.pool(), &timer, event_id, round).await
+}
+
+#[tauri::command]
+pub async fn confirm_timer_time(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    timer: State<'_, Timer>,
+    payload: timer::ConfirmTimerTime,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), timer::confirm(&db.pool(), &timer, payload)).await
+}
+
+#[tauri::command]
+pub async fn dismiss_timer_reading(session: State<'_, Session>, timer: State<'_, Timer>, seq: u64) -> AppResult<bool> {
+    session.require_role(Role::Operator)?;
+    Ok(timer.dismiss(seq))
+}
+
+/* ------------------- TEAMS ------------------- */
+#[tauri::command]
+pub async fn list_teams(db: State<'_, Db>, session: State<'_, Session>, event_id: i64) -> AppResult<Vec<teams::TeamRow>> {
+    session.require_role(Role::Viewer)?;
+    teams::list(&db.pool(), event_id).await
+}
+
+#[tauri::command]
+pub async fn create_team(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    t: teams::NewTeam,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), teams::create(&db.pool(), t)).await
+}
+
+#[tauri::command]
+pub async fn update_team(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    t: teams::UpdateTeam,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), teams::update(&db.pool(), t)).await
+}
+
+#[tauri::command]
+pub async fn delete_team(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), teams::delete(&db.pool(), id)).await
+}
+
+#[tauri::command]
+pub async fn scratch_team(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    team_id: i64,
+    reason: Option<String>,
+) -> AppResult<teams::ScratchOutcome> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), teams::scratch(&db.pool(), team_id, reason)).await
+}
+
+#[tauri::command]
+pub async fn hard_delete_teams_for_event(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    event_id: i64,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), teams::hard_delete_for_event(&db.pool(), event_id)).await
+}
+
+/* ------------------- ROPERS ------------------- */
+#[tauri::command]
+pub async fn list_ropers(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<Vec<ropers::RoperRow>> {
+    session.require_role(Role::Viewer)?;
+    ropers::list(&db.pool()).await
+}
+
+#[tauri::command]
+pub async fn create_roper(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    r: ropers::NewRoper,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), ropers::create(&db.pool(), r)).await
+}
+
+#[tauri::command]
+pub async fn update_roper(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    r: ropers::UpdateRoper,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), ropers::update(&db.pool(), r)).await
+}
+
+#[tauri::command]
+pub async fn delete_roper(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    id: i64,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), ropers::delete(&db.pool(), id)).await
+}
+
+#[tauri::command]
+pub async fn delete_all_ropers(db: State<'_, Db>, session: State<'_, Session>, license: State<'_, License>) -> AppResult<i64> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), ropers::delete_all(&db.pool())).await
+}
+
+/* ------------------- DRAW ------------------- */
+#[tauri::command]
+pub async fn generate_draw(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    opts: draw::GenerateDrawOptions,
+) -> AppResult<draw::DrawSummary> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), draw::generate(&db.pool(), opts)).await
+}
+
+#[tauri::command]
+pub async fn generate_draw_batch(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    opts: draw::GenerateBatchDrawOptions,
+) -> AppResult<draw::DrawSummary> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), draw::generate_batch(&db.pool(), opts)).await
+}
+
+#[tauri::command]
+pub async fn get_draw(db: State<'_, Db>, session: State<'_, Session>, event_id: i64, round: i64) -> AppResult<Vec<draw::DrawRow>> {
+    session.require_role(Role::Viewer)?;
+    draw::get(&db.pool(), event_id, round).await
+}
+
+#[tauri::command]
+pub async fn swap_draw_positions(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    opts: draw::SwapDrawOptions,
+) -> AppResult<Vec<draw::DrawRow>> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), draw::swap_positions(&db.pool(), opts)).await
+}
+
+#[tauri::command]
+pub async fn move_draw_team(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    opts: draw::MoveDrawOptions,
+) -> AppResult<Vec<draw::DrawRow>> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), draw::move_team(&db.pool(), opts)).await
+}
+
+#[tauri::command]
+pub async fn insert_draw_team(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    opts: draw::InsertDrawOptions,
+) -> AppResult<Vec<draw::DrawRow>> {
+    let user = session.require_role(Role::Operator)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), draw::insert_team(&db.pool(), opts)).await
+}
+
+#[tauri::command]
+pub async fn verify_draw(db: State<'_, Db>, session: State<'_, Session>, event_id: i64, round: i64) -> AppResult<draw::DrawVerification> {
+    session.require_role(Role::Viewer)?;
+    draw::verify(&db.pool(), event_id, round).await
+}
+
+#[tauri::command]
+pub async fn get_draw_report(db: State<'_, Db>, session: State<'_, Session>, event_id: i64) -> AppResult<draw_report::DrawReport> {
+    session.require_role(Role::Viewer)?;
+    draw_report::get(&db.pool(), event_id).await
+}
+
+/* ------------------- STANDINGS ------------------- */
+#[tauri::command]
+pub async fn get_standings(db: State<'_, Db>, session: State<'_, Session>, event_id: i64) -> AppResult<Vec<standings::StandingRow>> {
+    session.require_role(Role::Viewer)?;
+    standings::get(&db.pool(), event_id).await
+}
+
+/* ------------------- DASHBOARD & ACTIVITY ------------------- */
+#[tauri::command]
+pub async fn get_recent_activity(db: State<'_, Db>, session: State<'_, Session>, limit: i64, offset: Option<i64>) -> AppResult<Vec<audit::AuditLogItem>> {
+    session.require_role(Role::Viewer)?;
+    audit::recent_activity(&db.pool(), limit, offset).await
+}
+
+#[tauri::command]
+pub async fn get_series_logs(db: State<'_, Db>, session: State<'_, Session>, series_id: i64, limit: i64) -> AppResult<Vec<audit::AuditLogItem>> {
+    session.require_role(Role::Viewer)?;
+    audit::series_logs(&db.pool(), series_id, limit).await
+}
+
+#[tauri::command]
+pub async fn get_dashboard_stats(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<dashboard::DashboardStats> {
+    session.require_role(Role::Viewer)?;
+    dashboard::stats(&db.pool()).await
+}
+
+/* ------------------- ACTIVIDAD (sesiones de uso) ------------------- */
+#[tauri::command]
+pub async fn list_activity(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    filter: activity::ActivityFilter,
+) -> AppResult<Vec<activity::ActivityRow>> {
+    session.require_role(Role::Viewer)?;
+    activity::list(&db.pool(), filter).await
+}
+
+#[tauri::command]
+pub async fn get_event_sessions(db: State<'_, Db>, session: State<'_, Session>, event_id: i64) -> AppResult<Vec<activity::ActivityRow>> {
+    session.require_role(Role::Viewer)?;
+    activity::event_sessions(&db.pool(), event_id).await
+}
+
+/// `kind`: `event_day` o `capture` (por ronda). No modifica datos, así que no pide licencia.
+#[tauri::command]
+pub async fn start_activity_session(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    kind: String,
+    event_id: i64,
+    round: Option<i64>,
+) -> AppResult<i64> {
+    let user = session.require_role(Role::Operator)?;
+    audit::as_user(Some(user.id), activity::start_session(&db.pool(), &kind, event_id, round)).await
+}
+
+#[tauri::command]
+pub async fn end_activity_session(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    kind: String,
+    event_id: i64,
+    round: Option<i64>,
+) -> AppResult<u64> {
+    let user = session.require_role(Role::Operator)?;
+    audit::as_user(Some(user.id), activity::end_session(&db.pool(), &kind, event_id, round)).await
+}
+
+/* ------------------- EXPORT ------------------- */
+#[tauri::command]
+pub async fn export_event_to_excel(db: State<'_, Db>, session: State<'_, Session>, event_id: i64, options: export::ExportOptions) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    audit::as_user(Some(user.id), export::event_to_excel(&db.pool(), event_id, options)).await
+}
+
+/* ------------------- BACKUP / RESTORE ------------------- */
+#[tauri::command]
+pub async fn backup_database(db: State<'_, Db>, session: State<'_, Session>, dest: String) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    audit::as_user(Some(user.id), maintenance::backup_to(&db.pool(), Path::new(&dest))).await
+}
+
+#[tauri::command]
+pub async fn restore_database(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    src: String,
+) -> AppResult<RestoreReport> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    let report = audit::as_user(Some(user.id), db.restore_from(Path::new(&src))).await?;
+    // el id de la sesión apuntaba a la tabla de usuarios de la base anterior
+    session.set(None);
+    // la base restaurada trae su propia licencia; la prueba sigue contando desde el archivo
+    license.set(license::load(&db.pool(), &license::embedded_public_key()).await?);
+    license.set_trial_started(Some(license::trial_start(&db.pool(), &license::trial_marker(db.path())).await?));
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn list_snapshots(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<Vec<SnapshotInfo>> {
+    session.require_role(Role::Viewer)?;
+    snapshots::list(&db)
+}
+
+#[tauri::command]
+pub async fn get_snapshot_settings(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<SnapshotSettings> {
+    session.require_role(Role::Viewer)?;
+    snapshots::load_settings(&db).await
+}
+
+#[tauri::command]
+pub async fn update_snapshot_settings(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    settings: SnapshotSettings,
+) -> AppResult<()> {
+    let user = session.require_role(Role::Admin)?;
+    license.require_writable()?;
+    audit::as_user(Some(user.id), snapshots::save_settings(&db, settings)).await
+}
+
+/* ------------------- USUARIOS / SESIÓN ------------------- */
+#[derive(serde::Serialize)]
+pub struct AuthStatus {
+    /// `false` en una instalación nueva: la UI ofrece crear el primer administrador.
+    pub has_users: bool,
+    pub user: Option<users::UserRow>,
+}
+
+#[tauri::command]
+pub async fn auth_status(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<AuthStatus> {
+    Ok(AuthStatus {
+        has_users: users::count(&db.pool()).await? > 0,
+        user: session.current(),
+    })
+}
+
+/// Sin usuarios, cualquiera puede crear el primero (tiene que ser admin); después, sólo un admin.
+#[tauri::command]
+pub async fn create_user(db: State<'_, Db>, session: State<'_, Session>, payload: users::NewUser) -> AppResult<i64> {
+    let pool = db.pool();
+    let actor = if users::count(&pool).await? == 0 {
+        if payload.role != "admin" {
+            return Err(AppError::validation("role", "El primer usuario debe ser administrador."));
+        }
+        None
+    } else {
+        Some(session.require_role(Role::Admin)?.id)
+    };
+    audit::as_user(actor, users::create(&pool, payload)).await
+}
+
+#[tauri::command]
+pub async fn login(db: State<'_, Db>, session: State<'_, Session>, email: String, password: String) -> AppResult<users::UserRow> {
+    let user = users::authenticate(&db.pool(), &email, &password).await?;
+    session.set(Some(user.clone()));
+    Ok(user)
+}
+
+#[tauri::command]
+pub async fn logout(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<()> {
+    if let Some(user) = session.current() {
+        audit::as_user(Some(user.id), users::record_logout(&db.pool(), &user)).await?;
+    }
+    session.set(None);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_current_user(session: State<'_, Session>) -> AppResult<Option<users::UserRow>> {
+    Ok(session.current())
+}
+
+#[tauri::command]
+pub async fn change_password(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    current_password: String,
+    new_password: String,
+) -> AppResult<()> {
+    let user = session.require()?;
+    audit::as_user(Some(user.id), users::change_password(&db.pool(), user.id, &current_password, &new_password)).await
+}
+
+#[tauri::command]
+pub async fn list_users(db: State<'_, Db>, session: State<'_, Session>) -> AppResult<Vec<users::UserRow>> {
+    session.require_role(Role::Admin)?;
+    users::list(&db.pool()).await
+}
+
+/* ------------------- LICENCIA ------------------- */
+/// Sin sesión: la pantalla de login avisa si la licencia venció.
+#[tauri::command]
+pub async fn license_status(license: State<'_, License>) -> AppResult<LicenseStatus> {
+    Ok(license.status())
+}
+
+#[tauri::command]
+pub async fn activate_license(
+    db: State<'_, Db>,
+    session: State<'_, Session>,
+    license: State<'_, License>,
+    key: String,
+) -> AppResult<LicenseStatus> {
+    let user = session.require_role(Role::Admin)?;
+    let payload = audit::as_user(
+        Some(user.id),
+        license::activate(&db.pool(), &license::embedded_public_key(), license.device_id(), &key),
+    )
+    .await?;
+    license.set(Some(payload));
+    Ok(license.status())
+}
@@ -0,0 +1,183 @@
+use anyhow::Result;
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use crate::domain::audit::log_audit;
+use crate::domain::maintenance;
+use crate::error::{AppError, AppResult};
+use crate::schema;
+
+/// Nombre del archivo de la base de datos dentro del directorio de datos de la app.
+pub const DB_FILE_NAME: &str = "roping_manager.db";
+
+/// Debe coincidir con `identifier` en `tauri.conf.json`: Tauri lo usa como nombre de la
+/// carpeta de `app_local_data_dir()`, y las herramientas sin GUI lo necesitan para
+/// encontrar la misma base de datos.
+pub const APP_IDENTIFIER: &str = "com.emilianoneaves.roping-manager-tauri";
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Ruta de la DB sin `AppHandle` (equivalente a `resolve_db_path` en lib.rs).
+pub fn default_db_path() -> Result<PathBuf> {
+    let base = dirs_next::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("No se pudo resolver el directorio de datos local"))?;
+    Ok(base.join(APP_IDENTIFIER).join(DB_FILE_NAME))
+}
+
+pub async fn open_pool(path: &Path, create_if_missing: bool) -> Result<SqlitePool> {
+    // Evita pasar la ruta como URL; usa SqliteConnectOptions::filename para
+    // evitar problemas con espacios en rutas (p.ej. "Application Support").
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(create_if_missing)
+        .journal_mode(SqliteJournalMode::Wal)
+        .foreign_keys(true);
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
+
+/* ---- estado compartido ---- */
+
+/// Pool de la app más la ruta del archivo. El pool va detrás de un `RwLock` para que
+/// `restore_database` pueda reemplazarlo sin reiniciar; los comandos toman un clon con `pool()`.
+pub struct Db {
+    pool: RwLock<SqlitePool>,
+    path: PathBuf,
+    restoring: AtomicBool,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RestoreReport {
+    /// Copia de la base que había antes de restaurar.
+    pub safety_backup: String,
+    /// Versión de migración del archivo restaurado (antes de actualizarlo).
+    pub restored_version: i64,
+    pub migrations_applied: usize,
+    /// Siempre `true`: los usuarios ahora son los del respaldo, así que la sesión se cierra y
+    /// hay que volver a entrar.
+    pub requires_login: bool,
+}
+
+impl Db {
+    pub fn new(pool: SqlitePool, path: PathBuf) -> Self {
+        Db {
+            pool: RwLock::new(pool),
+            path,
+            restoring: AtomicBool::new(false),
+        }
+    }
+
+    pub fn pool(&self) -> SqlitePool {
+        self.pool.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reemplaza la base en uso por `src` (validada con `maintenance::inspect_backup`).
+    /// Antes guarda una copia de la actual junto a ella; si algo falla tras cerrar el pool,
+    /// vuelve a esa copia y reabre.
+    pub async fn restore_from(&self, src: &Path) -> AppResult<RestoreReport> {
+        if self.restoring.swap(true, Ordering::SeqCst) {
+            return Err(AppError::conflict("Ya hay una restauración en curso."));
+        }
+        let res = self.restore_inner(src).await;
+        self.restoring.store(false, Ordering::SeqCst);
+        res
+    }
+
+    async fn restore_inner(&self, src: &Path) -> AppResult<RestoreReport> {
+        let restored_version = maintenance::inspect_backup(src).await?;
+
+        let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+        let safety = self.path.with_file_name(format!("roping_manager.before-restore-{}.db", stamp));
+        let old = self.pool();
+        maintenance::backup_to(&old, &safety).await?;
+
+        // Copiamos primero junto a la DB para que el cambio final sea un rename.
+        let staged = self.staged_path();
+        std::fs::copy(src, &staged).map_err(io_err)?;
+
+        old.close().await;
+        if let Err(e) = replace_file(&staged, &self.path) {
+            self.reopen_from(&safety).await?;
+            return Err(e);
+        }
+
+        match open_and_migrate(&self.path).await {
+            Ok((pool, applied)) => {
+                *self.pool.write().unwrap_or_else(|e| e.into_inner()) = pool.clone();
+                log_audit(
+                    &pool,
+                    "restore_database",
+                    "database",
+                    None,
+                    Some(format!("{} v{} (copia previa: {})", src.display(), restored_version, safety.display())),
+                )
+                .await?;
+                Ok(RestoreReport {
+                    safety_backup: safety.display().to_string(),
+                    restored_version,
+                    migrations_applied: applied,
+                    requires_login: true,
+                })
+            }
+            Err(e) => {
+                self.reopen_from(&safety).await?;
+                Err(AppError::validation("src", format!("El respaldo no se pudo abrir: {}. Se mantuvo la base anterior.", e)))
+            }
+        }
+    }
+
+    fn staged_path(&self) -> PathBuf {
+        self.path.with_file_name(format!("{}.restoring", DB_FILE_NAME))
+    }
+
+    /// Vuelve a poner `backup` como base activa y reabre el pool.
+    async fn reopen_from(&self, backup: &Path) -> AppResult<()> {
+        let staged = self.staged_path();
+        std::fs::copy(backup, &staged).map_err(io_err)?;
+        replace_file(&staged, &self.path)?;
+        let (pool, _) = open_and_migrate(&self.path)
+            .await
+            .map_err(|e| AppError::internal(format!("No se pudo reabrir la base: {}", e)))?;
+        *self.pool.write().unwrap_or_else(|e| e.into_inner()) = pool;
+        Ok(())
+    }
+}
+
+async fn open_and_migrate(path: &Path) -> Result<(SqlitePool, usize)> {
+    let pool = open_pool(path, false).await?;
+    match schema::migrate(&pool).await {
+        Ok(report) => Ok((pool, report.applied.len())),
+        Err(e) => {
+            pool.close().await;
+            Err(e.into())
+        }
+    }
+}
+
+/// Renombra `staged` sobre `dest` descartando el WAL/SHM de la base anterior
+/// (ya cerrada), que no corresponden al archivo nuevo.
+fn replace_file(staged: &Path, dest: &Path) -> AppResult<()> {
+    for suffix in ["-wal", "-shm"] {
+        let side = PathBuf::from(format!("{}{}", dest.display(), suffix));
+        if side.exists() {
+            std::fs::remove_file(&side).map_err(io_err)?;
+        }
+    }
+    std::fs::rename(staged, dest).map_err(io_err)
+}
+
+fn io_err(e: std::io::Error) -> AppError {
+    AppError::internal(e.to_string())
+}
@@ -0,0 +1,254 @@
+//! Uso de la app en la tabla `activity`: marcas sueltas (login, logout, arranque y cierre)
+//! y sesiones con inicio/fin (`created_at`/`ended_at`): la jornada de un evento y la
+//! captura de cada ronda en la mesa del cronómetro.
+//!
+//! A diferencia de `audit_log`, aquí no hay cambios de datos sino quién estaba trabajando y cuánto tiempo.
+
+use chrono::NaiveDate;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use super::audit::acting_user;
+
+pub const LOGIN: &str = "login";
+pub const LOGOUT: &str = "logout";
+pub const APP_START: &str = "app_start";
+pub const APP_STOP: &str = "app_stop";
+/// Jornada de un evento: desde la primera captura hasta que se completa (o se cierra a mano).
+pub const EVENT_DAY: &str = "event_day";
+/// Captura de una ronda en la mesa del cronómetro, por usuario.
+pub const CAPTURE: &str = "capture";
+
+pub const SESSION_KINDS: [&str; 2] = [EVENT_DAY, CAPTURE];
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct ActivityRow {
+    pub id: i64,
+    pub kind: String,
+    pub user_id: Option<i64>,
+    pub user_name: Option<String>,
+    pub event_id: Option<i64>,
+    pub event_name: Option<String>,
+    pub round: Option<i64>,
+    pub details: Option<String>,
+    pub created_at: String,
+    /// Sólo sesiones; `None` mientras siga abierta.
+    pub ended_at: Option<String>,
+    pub duration_sec: Option<i64>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ActivityFilter {
+    /// `AAAA-MM-DD`, inclusive.
+    pub from: Option<String>,
+    /// `AAAA-MM-DD`, inclusive.
+    pub to: Option<String>,
+    pub kind: Option<String>,
+    pub user_id: Option<i64>,
+    pub event_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+const SELECT_ACTIVITY: &str = r#"
+    SELECT a.id, a.kind, a.user_id, u.full_name AS user_name, a.event_id, e.name AS event_name,
+           a.round, a.details, a.created_at, a.ended_at,
+           CAST(ROUND((julianday(a.ended_at) - julianday(a.created_at)) * 86400) AS INTEGER) AS duration_sec
+    FROM activity a
+    LEFT JOIN app_user u ON u.id = a.user_id
+    LEFT JOIN event e ON e.id = a.event_id
+    WHERE 1 = 1
+"#;
+
+/// Marca suelta (login, arranque...) a nombre del usuario que actúa.
+pub async fn record(pool: &SqlitePool, kind: &str, details: Option<String>) -> AppResult<()> {
+    sqlx::query("INSERT INTO activity (user_id, kind, details) VALUES (?1, ?2, ?3)")
+        .bind(acting_user())
+        .bind(kind)
+        .bind(details)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+fn check_session_kind(kind: &str) -> AppResult<()> {
+    if !SESSION_KINDS.contains(&kind) {
+        return Err(AppError::validation("kind", format!("Tipo de sesión desconocido: {}", kind)));
+    }
+    Ok(())
+}
+
+/// Abre una sesión si no hay una abierta igual y devuelve su id. La jornada del evento es
+/// una sola para todos; la captura es por usuario, y abrir otra ronda cierra la anterior
+/// de ese usuario (se cambió de ronda en la mesa).
+pub async fn start_session(pool: &SqlitePool, kind: &str, event_id: i64, round: Option<i64>) -> AppResult<i64> {
+    check_session_kind(kind)?;
+    let user = acting_user();
+    if kind == EVENT_DAY {
+        return open_session(pool, EVENT_DAY, event_id, None, user).await;
+    }
+
+    if let Some(id) = find_open(pool, CAPTURE, event_id, round, user).await? {
+        return Ok(id);
+    }
+    sqlx::query(
+        r#"UPDATE activity SET ended_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+           WHERE kind = 'capture' AND ended_at IS NULL AND user_id IS ?1"#,
+    )
+    .bind(user)
+    .execute(pool)
+    .await?;
+    open_session(pool, EVENT_DAY, event_id, None, user).await?;
+    open_session(pool, CAPTURE, event_id, round, user).await
+}
+
+async fn find_open(pool: &SqlitePool, kind: &str, event_id: i64, round: Option<i64>, user: Option<i64>) -> AppResult<Option<i64>> {
+    sqlx::query_scalar(
+        r#"SELECT id FROM activity
+           WHERE kind = ?1 AND event_id = ?2 AND round IS ?3 AND ended_at IS NULL
+             AND (?1 <> 'capture' OR user_id IS ?4)
+           ORDER BY id DESC LIMIT 1"#,
+    )
+    .bind(kind)
+    .bind(event_id)
+    .bind(round)
+    .bind(user)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+async fn open_session(pool: &SqlitePool, kind: &str, event_id: i64, round: Option<i64>, user: Option<i64>) -> AppResult<i64> {
+    if let Some(id) = find_open(pool, kind, event_id, round, user).await? {
+        return Ok(id);
+    }
+    let res = sqlx::query("INSERT INTO activity (user_id, kind, event_id, round) VALUES (?1, ?2, ?3, ?4)")
+        .bind(user)
+        .bind(kind)
+        .bind(event_id)
+        .bind(round)
+        .execute(pool)
+        .await?;
+    Ok(res.last_insert_rowid())
+}
+
+/// Cierra las sesiones abiertas de ese tipo/evento/ronda (`round = None`: todas las rondas).
+/// Cerrar la jornada cierra también las capturas del evento.
+pub async fn end_session(pool: &SqlitePool, kind: &str, event_id: i64, round: Option<i64>) -> AppResult<u64> {
+    check_session_kind(kind)?;
+    let kinds = if kind == EVENT_DAY { "'event_day', 'capture'" } else { "'capture'" };
+    let sql = format!(
+        r#"UPDATE activity SET ended_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+           WHERE kind IN ({}) AND event_id = ?1 AND (?2 IS NULL OR round = ?2) AND ended_at IS NULL"#,
+        kinds
+    );
+    let res = sqlx::query(&sql).bind(event_id).bind(round).execute(pool).await?;
+    Ok(res.rows_affected())
+}
+
+/// Llamado después de guardar una corrida: abre la captura de la ronda y la cierra cuando
+/// ya no quedan corridas pendientes.
+pub async fn track_capture(pool: &SqlitePool, event_id: i64, round: i64) -> AppResult<()> {
+    start_session(pool, CAPTURE, event_id, Some(round)).await?;
+    let pending: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM run WHERE event_id = ?1 AND round = ?2 AND status = 'pending'")
+            .bind(event_id)
+            .bind(round)
+            .fetch_one(pool)
+            .await?;
+    if pending == 0 {
+        end_session(pool, CAPTURE, event_id, Some(round)).await?;
+    }
+    Ok(())
+}
+
+/// Cierra las capturas abiertas de `user_id` (logout).
+pub async fn end_user_sessions(pool: &SqlitePool, user_id: i64) -> AppResult<()> {
+    sqlx::query(
+        r#"UPDATE activity SET ended_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+           WHERE kind = 'capture' AND ended_at IS NULL AND user_id = ?1"#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Al arrancar: las sesiones que quedaron abiertas (la app se cerró de golpe) se cierran en
+/// la última corrida guardada dentro de la sesión, o en su inicio si no hubo ninguna.
+pub async fn close_dangling(pool: &SqlitePool) -> AppResult<u64> {
+    let res = sqlx::query(
+        r#"
+        UPDATE activity SET ended_at = COALESCE(
+            (SELECT MAX(r.updated_at) FROM run r
+             WHERE r.event_id = activity.event_id
+               AND (activity.round IS NULL OR r.round = activity.round)
+               AND r.status <> 'pending'
+               AND r.updated_at >= activity.created_at),
+            created_at)
+        WHERE ended_at IS NULL AND kind IN ('event_day', 'capture')
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Al cerrar la app: cierra todo lo abierto y deja la marca `app_stop`.
+pub async fn app_stopped(pool: &SqlitePool) -> AppResult<()> {
+    sqlx::query(
+        r#"UPDATE activity SET ended_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+           WHERE ended_at IS NULL AND kind IN ('event_day', 'capture')"#,
+    )
+    .execute(pool)
+    .await?;
+    record(pool, APP_STOP, None).await
+}
+
+fn check_date(field: &str, value: &str) -> AppResult<()> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|_| ())
+        .map_err(|_| AppError::validation(field, format!("Fecha inválida: {} (usa AAAA-MM-DD).", value)))
+}
+
+pub async fn list(pool: &SqlitePool, filter: ActivityFilter) -> AppResult<Vec<ActivityRow>> {
+    let mut builder = QueryBuilder::<Sqlite>::new(SELECT_ACTIVITY);
+    if let Some(from) = filter.from.as_deref() {
+        check_date("from", from)?;
+        builder.push(" AND date(a.created_at) >= ").push_bind(from.to_string());
+    }
+    if let Some(to) = filter.to.as_deref() {
+        check_date("to", to)?;
+        builder.push(" AND date(a.created_at) <= ").push_bind(to.to_string());
+    }
+    if let Some(kind) = filter.kind {
+        builder.push(" AND a.kind = ").push_bind(kind);
+    }
+    if let Some(user_id) = filter.user_id {
+        builder.push(" AND a.user_id = ").push_bind(user_id);
+    }
+    if let Some(event_id) = filter.event_id {
+        builder.push(" AND a.event_id = ").push_bind(event_id);
+    }
+    builder
+        .push(" ORDER BY a.created_at DESC, a.id DESC LIMIT ")
+        .push_bind(filter.limit.unwrap_or(500).clamp(1, 5000));
+
+    builder
+        .build_query_as::<ActivityRow>()
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Jornada y capturas de un evento, en orden: cuánto duró cada ronda y quién cronometró.
+pub async fn event_sessions(pool: &SqlitePool, event_id: i64) -> AppResult<Vec<ActivityRow>> {
+    let sql = format!(
+        "{} AND a.event_id = ?1 AND a.kind IN ('event_day', 'capture') ORDER BY a.created_at ASC, a.id ASC",
+        SELECT_ACTIVITY
+    );
+    sqlx::query_as::<_, ActivityRow>(&sql)
+        .bind(event_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+}
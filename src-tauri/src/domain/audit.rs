@@ -0,0 +1,170 @@
+use sqlx::{Acquire, Sqlite, SqliteExecutor, SqlitePool};
+use std::future::Future;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct AuditLogItem {
+    pub id: i64,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<i64>,
+    pub user_id: Option<i64>,
+    /// Nombre del usuario que hizo el cambio (`None` para procesos del sistema o el CLI).
+    pub user_name: Option<String>,
+    pub metadata: Option<String>,
+    /// Fila antes/después del cambio, como JSON (ver `snapshot`).
+    pub before_json: Option<String>,
+    pub after_json: Option<String>,
+    pub created_at: String,
+}
+
+/* ---- usuario que actúa ---- */
+
+tokio::task_local! {
+    static ACTING_USER: Option<i64>;
+}
+
+/// Ejecuta `fut` con `user_id` como autor: `log_audit` lo guarda en `audit_log.user_id`
+/// y `runs::save` en `captured_by`/`corrected_by`. Los comandos envuelven así las
+/// llamadas que modifican datos; fuera de este ámbito (CLI, arranque) el autor es `None`.
+pub async fn as_user<F: Future>(user_id: Option<i64>, fut: F) -> F::Output {
+    ACTING_USER.scope(user_id, fut).await
+}
+
+pub fn acting_user() -> Option<i64> {
+    ACTING_USER.try_with(|u| *u).ok().flatten()
+}
+
+/// Columnas que nunca se copian al audit.
+const SECRET_COLUMNS: [&str; 2] = ["password_hash", "admin_pin"];
+
+/// Fila `table.id` como objeto JSON (sin columnas secretas); `None` si no existe.
+/// `table` viene siempre del código, nunca del usuario. Acepta el pool o una transacción abierta.
+pub async fn snapshot<'c>(db: impl Acquire<'c, Database = Sqlite>, table: &str, id: i64) -> AppResult<Option<String>> {
+    let mut conn = db.acquire().await?;
+    let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?1) ORDER BY cid")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await?;
+    let pairs: Vec<String> = columns
+        .iter()
+        .filter(|c| !SECRET_COLUMNS.contains(&c.as_str()))
+        .map(|c| format!("'{0}', \"{0}\"", c))
+        .collect();
+    if pairs.is_empty() {
+        return Ok(None);
+    }
+    let sql = format!("SELECT json_object({}) FROM \"{}\" WHERE id = ?1", pairs.join(", "), table);
+    sqlx::query_scalar(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn log_audit<'e>(
+    executor: impl SqliteExecutor<'e>,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<i64>,
+    metadata: Option<String>,
+) -> AppResult<()> {
+    log_change(executor, action, entity_type, entity_id, metadata, None, None).await
+}
+
+/// Como `log_audit`, con la fila antes y después del cambio (ver `snapshot`). Con una
+/// transacción como `executor` el registro se confirma o se descarta junto con el cambio.
+pub async fn log_change<'e>(
+    executor: impl SqliteExecutor<'e>,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<i64>,
+    metadata: Option<String>,
+    before: Option<String>,
+    after: Option<String>,
+) -> AppResult<()> {
+    // We ignore errors here to not block the main operation, but we log them
+    let res = sqlx::query(
+        r#"
+        INSERT INTO audit_log (user_id, action, entity_type, entity_id, metadata, before_json, after_json, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, strftime('%Y-%m-%dT%H:%M:%SZ','now'))
+        "#
+    )
+    .bind(acting_user())
+    .bind(action)
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(metadata)
+    .bind(before)
+    .bind(after)
+    .execute(executor)
+    .await;
+
+    if let Err(e) = res {
+        tracing::error!("Failed to write audit log: {}", e);
+    }
+    Ok(())
+}
+
+pub async fn recent_activity(pool: &SqlitePool, limit: i64, offset: Option<i64>) -> AppResult<Vec<AuditLogItem>> {
+    let off = offset.unwrap_or(0);
+    sqlx::query_as::<_, AuditLogItem>(
+        r#"
+        SELECT a.id, a.action, a.entity_type, a.entity_id, a.user_id, u.full_name AS user_name,
+               a.metadata, a.before_json, a.after_json, a.created_at
+        FROM audit_log a
+        LEFT JOIN app_user u ON u.id = a.user_id
+        ORDER BY a.created_at DESC, a.id DESC
+        LIMIT ?1 OFFSET ?2
+        "#
+    )
+    .bind(limit)
+    .bind(off)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+pub async fn series_logs(pool: &SqlitePool, series_id: i64, limit: i64) -> AppResult<Vec<AuditLogItem>> {
+    sqlx::query_as::<_, AuditLogItem>(
+        r#"
+        SELECT a.id, a.action, a.entity_type, a.entity_id, a.user_id, u.full_name AS user_name,
+               a.metadata, a.before_json, a.after_json, a.created_at
+        FROM audit_log a
+        LEFT JOIN app_user u ON u.id = a.user_id
+        WHERE (a.entity_type = 'series' AND a.entity_id = ?1)
+           OR (a.entity_type = 'event' AND a.entity_id IN (SELECT id FROM event WHERE series_id = ?1))
+        ORDER BY a.created_at DESC, a.id DESC
+        LIMIT ?2
+        "#
+    )
+    .bind(series_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+/// Historial de un evento para la hoja "Event Logs": el evento, sus corridas, equipos (scratch
+/// incluido), tipos de penalización y ediciones del draw (`entity_type = 'draw'`, `entity_id` =
+/// evento).
+pub async fn event_logs(pool: &SqlitePool, event_id: i64) -> AppResult<Vec<AuditLogItem>> {
+    sqlx::query_as::<_, AuditLogItem>(
+        r#"
+        SELECT a.id, a.action, a.entity_type, a.entity_id, a.user_id, u.full_name AS user_name,
+               a.metadata, a.before_json, a.after_json, a.created_at
+        FROM audit_log a
+        LEFT JOIN app_user u ON u.id = a.user_id
+        WHERE (a.entity_type IN ('event', 'draw') AND a.entity_id = ?1)
+           OR (a.entity_type = 'run' AND a.entity_id IN (SELECT id FROM run WHERE event_id = ?1))
+           OR (a.entity_type = 'team' AND a.entity_id IN (SELECT id FROM team WHERE event_id = ?1))
+           OR (a.entity_type = 'penalty_type' AND a.entity_id IN (SELECT id FROM penalty_type WHERE event_id = ?1))
+        ORDER BY a.created_at DESC, a.id DESC
+        "#
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
@@ -0,0 +1,107 @@
+use sqlx::SqlitePool;
+
+use crate::error::AppResult;
+
+#[derive(serde::Serialize)]
+pub struct DashboardStats {
+    pub total_series: i64,
+    pub active_series: i64,
+    pub total_events: i64,
+    pub active_events: i64,
+    pub completed_events: i64,
+    pub upcoming_events: i64,
+    pub locked_events: i64,
+    pub total_teams: i64,
+    pub total_pot: f64,
+    pub upcoming_events_30d: i64,
+    pub global_progress: f64,
+}
+
+pub async fn stats(pool: &SqlitePool) -> AppResult<DashboardStats> {
+    let total_series: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM series WHERE is_deleted = 0")
+        .fetch_one(pool).await?;
+    
+    let active_series: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM series WHERE is_deleted = 0 AND status = 'active'")
+        .fetch_one(pool).await?;
+
+    let total_events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event WHERE is_deleted = 0")
+        .fetch_one(pool).await?;
+
+    let active_events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event WHERE is_deleted = 0 AND status = 'active'")
+        .fetch_one(pool).await?;
+
+    let completed_events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event WHERE is_deleted = 0 AND (status = 'completed' OR status = 'locked')")
+        .fetch_one(pool).await?;
+
+    let upcoming_events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event WHERE is_deleted = 0 AND status = 'upcoming'")
+        .fetch_one(pool).await?;
+
+    let locked_events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM event WHERE is_deleted = 0 AND status = 'locked'")
+        .fetch_one(pool).await?;
+
+    let total_teams: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM team WHERE status = 'active'")
+        .fetch_one(pool).await?;
+
+    // Calculate Total Pot: Sum of (entry_fee * unique_ropers) + prize_pool for all active/completed events
+    let pot_opt: Option<f64> = sqlx::query_scalar(
+        r#"
+        SELECT SUM(
+            COALESCE(e.prize_pool, 0) + 
+            (COALESCE(e.entry_fee, 0) * (
+                SELECT COUNT(DISTINCT roper_id) FROM (
+                    SELECT header_id AS roper_id FROM team WHERE event_id = e.id AND status = 'active'
+                    UNION
+                    SELECT heeler_id AS roper_id FROM team WHERE event_id = e.id AND status = 'active'
+                )
+            ))
+        )
+        FROM event e
+        WHERE e.is_deleted = 0 AND e.status IN ('active', 'completed', 'locked')
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+    
+    let total_pot = pot_opt.unwrap_or(0.0);
+
+    // Upcoming events in next 30 days
+    let upcoming_events_30d: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM event 
+        WHERE is_deleted = 0 
+          AND date >= date('now', 'localtime') 
+          AND date <= date('now', '+30 days', 'localtime')
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    // Calculate Global Progress (Runs)
+    let global_progress: f64 = sqlx::query_scalar(
+        r#"
+        SELECT 
+            CASE WHEN COUNT(r.id) = 0 THEN 0.0
+            ELSE CAST(SUM(CASE WHEN r.status = 'completed' THEN 1 ELSE 0 END) AS REAL) / COUNT(r.id) * 100.0
+            END
+        FROM run r
+        JOIN event e ON r.event_id = e.id
+        WHERE e.is_deleted = 0
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(DashboardStats {
+        total_series,
+        active_series,
+        total_events,
+        active_events,
+        completed_events,
+        upcoming_events,
+        locked_events,
+        total_teams,
+        total_pot,
+        upcoming_events_30d,
+        global_progress,
+    })
+}
@@ -0,0 +1,1144 @@
+use std::collections::HashMap;
+
+use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sqlx::{Sqlite, SqliteExecutor, SqlitePool, Transaction};
+
+use crate::error::{AppError, AppResult};
+use super::audit::{acting_user, log_audit, log_change};
+use super::events::{
+    require_pin, CALLBACK_ALL, CALLBACK_TOP_N, CALLBACK_TOP_PERCENT, FINAL_AVERAGE, FINAL_RANDOM,
+    FINAL_ROUND_ONE, FINAL_STANDINGS, TIES_EXCLUDE, TIES_INCLUDE,
+};
+use super::standings::{self, StandingRow};
+
+#[derive(serde::Deserialize)]
+pub struct GenerateDrawOptions {
+    pub event_id: i64,
+    pub round: i64,
+    pub reseed: Option<bool>,
+    pub seed_runs: Option<bool>,
+    /// Corridas mínimas entre dos salidas del mismo roper (`DEFAULT_MIN_SPACING` si falta).
+    pub min_spacing: Option<i64>,
+    /// PIN del evento; necesario si la ronda ya tiene sorteo.
+    pub pin: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct GenerateBatchDrawOptions {
+    pub event_id: i64,
+    pub rounds: i64,
+    pub shuffle: bool,
+    /// Corridas mínimas entre dos salidas del mismo roper (`DEFAULT_MIN_SPACING` si falta).
+    pub min_spacing: Option<i64>,
+    /// PIN del evento; necesario si ya hay sorteos.
+    pub pin: Option<String>,
+}
+
+/// Resultado de generar: equipos por ronda y separaciones que no se pudieron cumplir.
+#[derive(Debug, serde::Serialize)]
+pub struct DrawSummary {
+    pub teams: i64,
+    pub rounds: i64,
+    pub min_spacing: i64,
+    /// Salidas (sumando todas las rondas generadas) con un roper a menos de `min_spacing` corridas
+    /// de su salida anterior. Cero salvo que las inscripciones no den para separarlos.
+    pub violations: i64,
+    pub seeds: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct DrawRow {
+    pub id: i64,
+    pub event_id: i64,
+    pub round: i64,
+    pub position: i64,
+    pub team_id: i64,
+    pub header_id: i64,
+    pub heeler_id: i64,
+}
+
+/// Generador de los sorteos aleatorios: ChaCha8 sembrado con la semilla y un Fisher-Yates propio,
+/// así el mismo orden sale con cualquier versión de `rand`. Si algún día cambia, se sube la versión
+/// y `verify` sigue usando la que quedó guardada en `draw_round.rng`.
+pub const DRAW_RNG: &str = "chacha8-fy-v1";
+
+/// Separación por defecto: corridas de otros equipos entre dos salidas del mismo roper.
+pub const DEFAULT_MIN_SPACING: i64 = 2;
+
+/// La separación más grande que tiene sentido en una ronda de `teams` corridas: dos salidas
+/// quedan a lo más en la primera y la última posición, con `teams - 2` corridas en medio.
+pub fn max_min_spacing(teams: usize) -> i64 {
+    teams.saturating_sub(2) as i64
+}
+
+/// Cómo se ordenó una ronda (`draw_round.method`). Los dos primeros pasan después por
+/// `space_ropers` con la separación de la ronda.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawMethod {
+    /// Barajado con la semilla.
+    Random,
+    /// Sin barajar: orden de inscripción (id de equipo).
+    EntryOrder,
+    /// Ronda final: mayor tiempo acumulado primero.
+    AccumulatedTime,
+    /// Ronda final: mayor promedio primero.
+    AverageTime,
+    /// Ronda final: inverso de la clasificación previa (el líder al último).
+    Standings,
+    /// Ronda final: el orden de la ronda 1; los que no estaban, al final.
+    RoundOne,
+    /// Ronda final con short-go: sólo los que pasaron el corte, del peor al mejor clasificado.
+    Callback,
+}
+
+impl DrawMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DrawMethod::Random => "random",
+            DrawMethod::EntryOrder => "entry_order",
+            DrawMethod::AccumulatedTime => "accumulated_time",
+            DrawMethod::AverageTime => "average_time",
+            DrawMethod::Standings => "standings",
+            DrawMethod::RoundOne => "round_one",
+            DrawMethod::Callback => "callback",
+        }
+    }
+
+    pub fn parse(s: &str) -> AppResult<Self> {
+        match s {
+            "random" => Ok(DrawMethod::Random),
+            "entry_order" => Ok(DrawMethod::EntryOrder),
+            "accumulated_time" => Ok(DrawMethod::AccumulatedTime),
+            "average_time" => Ok(DrawMethod::AverageTime),
+            "standings" => Ok(DrawMethod::Standings),
+            "round_one" => Ok(DrawMethod::RoundOne),
+            "callback" => Ok(DrawMethod::Callback),
+            other => Err(AppError::internal(format!("Método de sorteo desconocido: {}", other))),
+        }
+    }
+
+    fn uses_seed(self) -> bool {
+        self == DrawMethod::Random
+    }
+}
+
+/// Un equipo tal como entró al sorteo; la lista se guarda en `draw_round.entries_json`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DrawEntry {
+    pub team_id: i64,
+    pub header_id: i64,
+    pub heeler_id: i64,
+    /// Sólo ronda final: tiempo acumulado en las rondas anteriores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accumulated_sec: Option<f64>,
+    /// Sólo final por promedio: promedio de las rondas anteriores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_sec: Option<f64>,
+    /// Sólo final por clasificación o short-go: lugar en la clasificación previa (1 = líder).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standing: Option<i64>,
+    /// Sólo final como la ronda 1: posición que tuvo en la ronda 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_round_position: Option<i64>,
+}
+
+/// Semilla registrada de una ronda.
+#[derive(Debug, serde::Serialize, sqlx::FromRow)]
+pub struct DrawRound {
+    pub event_id: i64,
+    pub round: i64,
+    pub method: String,
+    /// 16 dígitos hex; `None` si el método no es aleatorio.
+    pub seed: Option<String>,
+    pub rng: String,
+    pub entries_json: String,
+    pub min_spacing: i64,
+    pub violations: i64,
+    pub created_by: Option<i64>,
+    pub created_at: String,
+}
+
+/// Resultado de `verify`: el orden que da la semilla contra el que está guardado.
+#[derive(Debug, serde::Serialize)]
+pub struct DrawVerification {
+    pub event_id: i64,
+    pub round: i64,
+    pub method: DrawMethod,
+    pub seed: Option<String>,
+    pub rng: String,
+    pub generated_at: String,
+    pub min_spacing: i64,
+    pub violations: i64,
+    pub entries: Vec<DrawEntry>,
+    pub expected_order: Vec<i64>,
+    pub actual_order: Vec<i64>,
+    pub matches: bool,
+    pub message: String,
+}
+
+/// Semilla nueva del generador del sistema operativo.
+pub fn new_seed() -> String {
+    format!("{:016x}", OsRng.next_u64())
+}
+
+pub fn parse_seed(seed: &str) -> AppResult<u64> {
+    u64::from_str_radix(seed.trim(), 16)
+        .map_err(|_| AppError::validation("seed", format!("Semilla inválida: {} (16 dígitos hex).", seed)))
+}
+
+/// Fisher-Yates con ChaCha8 (`DRAW_RNG`): misma semilla y misma lista, mismo orden.
+pub fn seeded_shuffle<T>(items: &mut [T], seed: u64) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    for i in (1..items.len()).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Orden de una ronda y cuántas salidas quedaron sin la separación pedida.
+#[derive(Debug, PartialEq)]
+pub struct Arrangement {
+    pub order: Vec<i64>,
+    pub violations: i64,
+}
+
+/// Motor de sorteo de `generate` y `generate_batch` (y de `verify`). Las entradas se ordenan por
+/// `team_id` antes de barajar, así el resultado no depende del orden en que llegaron. Los
+/// órdenes de final (tiempos, clasificación, ronda 1) no se reacomodan; sólo se cuentan sus violaciones.
+pub fn derive_order(method: DrawMethod, seed: Option<&str>, entries: &[DrawEntry], min_spacing: i64) -> AppResult<Arrangement> {
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|e| e.team_id);
+
+    if method.uses_seed() {
+        let seed = seed.ok_or_else(|| AppError::validation("seed", "El sorteo aleatorio necesita semilla."))?;
+        seeded_shuffle(&mut entries, parse_seed(seed)?);
+    }
+
+    let min_spacing = min_spacing.max(0) as usize;
+    let teams: Vec<(i64, i64, i64)> = entries.iter().map(|e| (e.team_id, e.header_id, e.heeler_id)).collect();
+    let ordered = match method {
+        DrawMethod::Random | DrawMethod::EntryOrder => space_ropers(teams, min_spacing),
+        DrawMethod::AccumulatedTime => {
+            let times: Vec<(i64, Option<f64>)> = entries.iter().map(|e| (e.team_id, e.accumulated_sec)).collect();
+            let by_id: HashMap<i64, (i64, i64, i64)> = teams.iter().map(|t| (t.0, *t)).collect();
+            order_by_accumulated_time(&times).into_iter().map(|id| by_id[&id]).collect()
+        }
+        DrawMethod::AverageTime => {
+            let times: Vec<(i64, Option<f64>)> = entries.iter().map(|e| (e.team_id, e.average_sec)).collect();
+            let by_id: HashMap<i64, (i64, i64, i64)> = teams.iter().map(|t| (t.0, *t)).collect();
+            order_by_accumulated_time(&times).into_iter().map(|id| by_id[&id]).collect()
+        }
+        DrawMethod::Standings | DrawMethod::Callback => {
+            let mut ranked: Vec<(&DrawEntry, (i64, i64, i64))> = entries.iter().zip(teams).collect();
+            ranked.sort_by_key(|(e, _)| std::cmp::Reverse(e.standing.unwrap_or(i64::MAX)));
+            ranked.into_iter().map(|(_, t)| t).collect()
+        }
+        DrawMethod::RoundOne => {
+            let mut ranked: Vec<(&DrawEntry, (i64, i64, i64))> = entries.iter().zip(teams).collect();
+            ranked.sort_by_key(|(e, _)| e.first_round_position.unwrap_or(i64::MAX));
+            ranked.into_iter().map(|(_, t)| t).collect()
+        }
+    };
+
+    Ok(Arrangement {
+        violations: count_violations(&ordered, min_spacing) as i64,
+        order: ordered.into_iter().map(|(team_id, _, _)| team_id).collect(),
+    })
+}
+
+/// Valida la separación pedida y la recorta a lo que permite la ronda (`max_min_spacing`): pedir
+/// más no separa más, sólo marcaría como violación cada salida repetida.
+fn check_min_spacing(min_spacing: Option<i64>, teams: usize) -> AppResult<i64> {
+    let value = min_spacing.unwrap_or(DEFAULT_MIN_SPACING);
+    if value < 0 {
+        return Err(AppError::validation("min_spacing", "La separación mínima no puede ser negativa."));
+    }
+    Ok(value.min(max_min_spacing(teams)))
+}
+
+/// Lo que se guarda en `draw_round` al sortear una ronda.
+struct RoundRecord<'a> {
+    method: DrawMethod,
+    seed: Option<&'a str>,
+    entries: &'a [DrawEntry],
+    min_spacing: i64,
+    violations: i64,
+}
+
+/// Guarda (o reemplaza, si se regenera) la semilla y las inscripciones de la ronda.
+async fn record_round(tx: &mut Transaction<'_, Sqlite>, event_id: i64, round: i64, record: RoundRecord<'_>) -> AppResult<()> {
+    let entries_json = serde_json::to_string(record.entries).map_err(|e| AppError::internal(e.to_string()))?;
+    sqlx::query(
+        r#"
+        INSERT INTO draw_round (event_id, round, method, seed, rng, entries_json, min_spacing, violations, created_by)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        ON CONFLICT(event_id, round) DO UPDATE SET
+          method       = excluded.method,
+          seed         = excluded.seed,
+          rng          = excluded.rng,
+          entries_json = excluded.entries_json,
+          min_spacing  = excluded.min_spacing,
+          violations   = excluded.violations,
+          created_by   = excluded.created_by,
+          created_at   = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+        "#,
+    )
+    .bind(event_id)
+    .bind(round)
+    .bind(record.method.as_str())
+    .bind(record.seed)
+    .bind(DRAW_RNG)
+    .bind(entries_json)
+    .bind(record.min_spacing)
+    .bind(record.violations)
+    .bind(acting_user())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Clasificación de las rondas anteriores a `round` (una sola consulta, la misma de
+/// `get_standings`) reducida a los equipos que entran a la final; los que no tienen corridas
+/// van al fondo en orden de inscripción.
+async fn prior_standings(pool: &SqlitePool, event_id: i64, round: i64, entries: &[DrawEntry]) -> AppResult<Vec<StandingRow>> {
+    let mut ranked: Vec<StandingRow> = standings::before_round(pool, event_id, round)
+        .await?
+        .into_iter()
+        .filter(|r| entries.iter().any(|e| e.team_id == r.team_id))
+        .collect();
+    let missing: Vec<i64> = entries
+        .iter()
+        .map(|e| e.team_id)
+        .filter(|id| !ranked.iter().any(|r| r.team_id == *id))
+        .collect();
+    for team_id in missing {
+        ranked.push(StandingRow {
+            rank: 0,
+            team_id,
+            header_name: String::new(),
+            heeler_name: String::new(),
+            total_time: None,
+            completed_runs: 0,
+            nt_cnt: 0,
+            dq_cnt: 0,
+            avg_time: None,
+            best_time: None,
+            called_back: None,
+            penalty_total: 0.0,
+            penalties: Vec::new(),
+        });
+    }
+    Ok(ranked)
+}
+
+/// Corte del short-go sobre la clasificación previa (ya ordenada, líder primero): devuelve los
+/// `team_id` que pasan, en ese mismo orden. Dos equipos empatan si llevan las mismas corridas
+/// válidas y el mismo tiempo total; qué pasa con los empatados en la línea lo decide `ties`.
+pub fn callback_cut(ranked: &[StandingRow], mode: &str, value: Option<i64>, ties: &str) -> AppResult<Vec<i64>> {
+    let total = ranked.len();
+    let value = value.unwrap_or(0).max(0) as usize;
+    let mut cut = match mode {
+        CALLBACK_TOP_N => value,
+        CALLBACK_TOP_PERCENT => (total * value).div_ceil(100),
+        _ => total,
+    }
+    .min(total);
+
+    let tied = |a: &StandingRow, b: &StandingRow| {
+        a.completed_runs == b.completed_runs
+            && match (a.total_time, b.total_time) {
+                (Some(x), Some(y)) => (x - y).abs() < 1e-6,
+                (None, None) => true,
+                _ => false,
+            }
+    };
+    if cut > 0 && cut < total && tied(&ranked[cut - 1], &ranked[cut]) {
+        if ties == TIES_INCLUDE {
+            while cut < total && tied(&ranked[cut - 1], &ranked[cut]) {
+                cut += 1;
+            }
+        } else if ties == TIES_EXCLUDE {
+            while cut > 0 && tied(&ranked[cut - 1], &ranked[cut]) {
+                cut -= 1;
+            }
+        }
+    }
+    if cut == 0 {
+        return Err(AppError::validation("callback_value", "Con ese corte (y sus empates) ningún equipo pasa a la final."));
+    }
+    Ok(ranked[..cut].iter().map(|r| r.team_id).collect())
+}
+
+/// Final-round order: highest accumulated time first, teams without a time last.
+pub fn order_by_accumulated_time(team_times: &[(i64, Option<f64>)]) -> Vec<i64> {
+    let mut teams_with_times: Vec<(i64, f64)> = team_times
+        .iter()
+        .filter_map(|(id, time)| time.map(|t| (*id, t)))
+        .collect();
+
+    // Sort by time descending (highest first)
+    teams_with_times.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut result: Vec<i64> = teams_with_times.iter().map(|(id, _)| *id).collect();
+
+    // Add teams without times at the end
+    result.extend(
+        team_times
+            .iter()
+            .filter(|(_, time)| time.is_none())
+            .map(|(id, _)| *id),
+    );
+    result
+}
+
+/// Separa las salidas de un mismo roper: en cada posición toma, de los equipos que cumplen
+/// `min_spacing` (corridas de otros equipos desde la última salida de sus dos ropers), el de
+/// ropers con más salidas pendientes; así los que entran muchas veces se reparten desde el
+/// principio. Si ninguno cumple, el que más se aleja. Empates: el orden recibido (el barajado).
+/// La última posición de cada roper y sus salidas pendientes viven en mapas, así revisar un
+/// candidato es O(1) y nunca se vuelve a recorrer lo ya colocado.
+/// Teams are `(team_id, header_id, heeler_id)`.
+pub fn space_ropers(teams: Vec<(i64, i64, i64)>, min_spacing: usize) -> Vec<(i64, i64, i64)> {
+    if min_spacing == 0 {
+        return teams;
+    }
+
+    let mut pending: HashMap<i64, usize> = HashMap::new();
+    for &(_, header, heeler) in &teams {
+        *pending.entry(header).or_default() += 1;
+        *pending.entry(heeler).or_default() += 1;
+    }
+    // roper -> índice de su última salida colocada
+    let mut last_placed: HashMap<i64, usize> = HashMap::with_capacity(pending.len());
+    let mut ordered: Vec<(i64, i64, i64)> = Vec::with_capacity(teams.len());
+    let mut remaining = teams;
+
+    while !remaining.is_empty() {
+        let position = ordered.len();
+        let gap = |roper: i64| last_placed.get(&roper).map_or(usize::MAX, |&p| position - p - 1);
+
+        let mut best_idx = 0;
+        let mut best_key = (false, 0, 0);
+        for (i, &(_, header, heeler)) in remaining.iter().enumerate() {
+            let min_gap = gap(header).min(gap(heeler));
+            let load = pending[&header] + pending[&heeler];
+            let key = if min_gap >= min_spacing { (true, load, 0) } else { (false, min_gap, load) };
+            if i == 0 || key > best_key {
+                best_key = key;
+                best_idx = i;
+            }
+        }
+
+        let team = remaining.remove(best_idx);
+        for roper in [team.1, team.2] {
+            last_placed.insert(roper, position);
+            *pending.get_mut(&roper).unwrap() -= 1;
+        }
+        ordered.push(team);
+    }
+    ordered
+}
+
+/// Salidas con algún roper a menos de `min_spacing` corridas de su salida anterior.
+pub fn count_violations(order: &[(i64, i64, i64)], min_spacing: usize) -> usize {
+    let mut last_seen: HashMap<i64, usize> = HashMap::new();
+    let mut violations = 0;
+    for (position, &(_, header, heeler)) in order.iter().enumerate() {
+        let too_close = [header, heeler]
+            .iter()
+            .any(|r| last_seen.get(r).is_some_and(|&p| position - p - 1 < min_spacing));
+        if too_close {
+            violations += 1;
+        }
+        last_seen.insert(header, position);
+        last_seen.insert(heeler, position);
+    }
+    violations
+}
+
+/// Las rondas se pueden sortear o editar mientras el evento no esté finalizado o archivado
+/// (a diferencia de `ensure_event_unlocked`, `locked` no lo impide).
+pub(crate) async fn ensure_rounds_open<'e>(executor: impl SqliteExecutor<'e>, event_id: i64) -> AppResult<()> {
+    let event_status: Option<String> = sqlx::query_scalar("SELECT status FROM event WHERE id = ?1")
+        .bind(event_id)
+        .fetch_optional(executor)
+        .await?
+        .flatten();
+
+    if let Some(s) = event_status {
+        if s == "completed" || s == "finalized" || s == "archived" {
+            return Err(AppError::event_locked("El evento está finalizado o archivado. No se pueden modificar rondas."));
+        }
+    }
+    Ok(())
+}
+
+async fn ensure_round_not_started<'e>(executor: impl SqliteExecutor<'e>, event_id: i64, round: i64) -> AppResult<()> {
+    let round_started: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM run WHERE event_id = ?1 AND round = ?2 AND status = 'completed')"
+    )
+    .bind(event_id)
+    .bind(round)
+    .fetch_one(executor)
+    .await?;
+
+    if round_started {
+        return Err(AppError::conflict(format!("La ronda {} ya ha comenzado (tiene tiempos capturados). No se puede regenerar.", round)));
+    }
+    Ok(())
+}
+
+pub async fn generate(pool: &SqlitePool, opts: GenerateDrawOptions) -> AppResult<DrawSummary> {
+    // 1) Relaxed check: Only block if event is fully finalized/completed, OR if THIS specific round is started.
+    // We do NOT use ensure_event_unlocked because that blocks 'locked'/'active' events which are exactly where we want to generate next rounds.
+    ensure_rounds_open(pool, opts.event_id).await?;
+
+    // Check if THIS round has started (any completed runs)
+    ensure_round_not_started(pool, opts.event_id, opts.round).await?;
+
+    let has_draw: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM draw WHERE event_id = ?1 AND round = ?2)")
+        .bind(opts.event_id)
+        .bind(opts.round)
+        .fetch_one(pool)
+        .await?;
+    if has_draw {
+        require_pin(pool, opts.event_id, opts.pin.as_deref()).await?;
+    }
+
+    // Get the total number of rounds for this event to check if this is the final round
+    let total_rounds: i64 = sqlx::query_scalar("SELECT rounds FROM event WHERE id = ?1")
+        .bind(opts.event_id)
+        .fetch_one(pool)
+        .await?;
+
+    let is_final_round = opts.round == total_rounds;
+
+    // 2) obtener teams activos del evento que NO estén eliminados (NT o DQ previos)
+    let teams: Vec<(i64, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, header_id, heeler_id FROM team 
+        WHERE event_id = ?1 AND status = 'active'
+          AND id NOT IN (
+            SELECT team_id FROM run 
+            WHERE event_id = ?1 AND (no_time = 1 OR dq = 1)
+          )
+        ORDER BY id ASC
+        "#,
+    )
+    .bind(opts.event_id)
+    .fetch_all(pool)
+    .await?;
+
+    if teams.is_empty() {
+        return Err(AppError::validation("event_id", "No hay equipos activos para generar el draw."));
+    }
+
+    let mut entries: Vec<DrawEntry> = teams
+        .iter()
+        .map(|&(team_id, header_id, heeler_id)| DrawEntry { team_id, header_id, heeler_id, ..Default::default() })
+        .collect();
+
+    let (callback_mode, callback_value, callback_ties, final_order): (String, Option<i64>, String, String) =
+        sqlx::query_as("SELECT callback_mode, callback_value, callback_ties, final_order FROM event WHERE id = ?1")
+            .bind(opts.event_id)
+            .fetch_one(pool)
+            .await?;
+    // Short-go: (todos los candidatos, los que pasan el corte)
+    let mut callback: Option<(Vec<i64>, Vec<i64>)> = None;
+
+    // 3) Ronda final: con corte de short-go, los que pasan del peor al mejor clasificado;
+    // sin corte, el orden que tenga configurado el evento (`event.final_order`)
+    let method = if is_final_round {
+        let ranked = prior_standings(pool, opts.event_id, opts.round, &entries).await?;
+        let standing_of = |team_id: i64| ranked.iter().position(|r| r.team_id == team_id).map(|i| i as i64 + 1);
+        if callback_mode != CALLBACK_ALL {
+            let qualified = callback_cut(&ranked, &callback_mode, callback_value, &callback_ties)?;
+            let candidates: Vec<i64> = entries.iter().map(|e| e.team_id).collect();
+            entries.retain(|e| qualified.contains(&e.team_id));
+            for entry in entries.iter_mut() {
+                entry.standing = standing_of(entry.team_id);
+            }
+            callback = Some((candidates, qualified));
+            DrawMethod::Callback
+        } else {
+            match final_order.as_str() {
+                FINAL_RANDOM => DrawMethod::Random,
+                FINAL_ROUND_ONE => {
+                    let first: HashMap<i64, i64> = sqlx::query_as("SELECT team_id, position FROM draw WHERE event_id = ?1 AND round = 1")
+                        .bind(opts.event_id)
+                        .fetch_all(pool)
+                        .await?
+                        .into_iter()
+                        .collect();
+                    for entry in entries.iter_mut() {
+                        entry.first_round_position = first.get(&entry.team_id).copied();
+                    }
+                    DrawMethod::RoundOne
+                }
+                FINAL_STANDINGS => {
+                    for entry in entries.iter_mut() {
+                        entry.standing = standing_of(entry.team_id);
+                    }
+                    DrawMethod::Standings
+                }
+                FINAL_AVERAGE => {
+                    for entry in entries.iter_mut() {
+                        entry.average_sec = ranked.iter().find(|r| r.team_id == entry.team_id).and_then(|r| r.avg_time);
+                    }
+                    DrawMethod::AverageTime
+                }
+                _ => {
+                    for entry in entries.iter_mut() {
+                        entry.accumulated_sec = ranked.iter().find(|r| r.team_id == entry.team_id).and_then(|r| r.total_time);
+                    }
+                    DrawMethod::AccumulatedTime
+                }
+            }
+        }
+    } else if opts.reseed.unwrap_or(true) {
+        // 3) Normal rounds: reseed or keep order
+        DrawMethod::Random
+    } else {
+        DrawMethod::EntryOrder
+    };
+
+    let min_spacing = check_min_spacing(opts.min_spacing, entries.len())?;
+    let seed = method.uses_seed().then(new_seed);
+    let arrangement = derive_order(method, seed.as_deref(), &entries, min_spacing)?;
+    let teams = &arrangement.order;
+
+    let seed_runs = opts.seed_runs.unwrap_or(true);
+
+    // 4) transacción: LIMPIAR ronda actual (si es seguro) y luego insertar
+    let mut tx: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    // Borramos runs y draw de esta ronda para asegurar que no queden "restos" de equipos eliminados (posiciones altas antiguas).
+    // Es la primera escritura: con el lock tomado se vuelve a revisar que nadie capturó ni cerró
+    // el evento desde las validaciones de arriba; una corrida completada nunca se borra.
+    sqlx::query("DELETE FROM run WHERE event_id = ?1 AND round = ?2 AND status <> 'completed'")
+        .bind(opts.event_id)
+        .bind(opts.round)
+        .execute(&mut *tx)
+        .await?;
+    ensure_rounds_open(&mut *tx, opts.event_id).await?;
+    ensure_round_not_started(&mut *tx, opts.event_id, opts.round).await?;
+
+    sqlx::query("DELETE FROM draw WHERE event_id = ?1 AND round = ?2")
+        .bind(opts.event_id)
+        .bind(opts.round)
+        .execute(&mut *tx)
+        .await?;
+
+    for (idx, team_id) in teams.iter().enumerate() {
+        let position = (idx as i64) + 1;
+
+        // draw insert (ya limpiamos, así que insert es seguro)
+        sqlx::query(
+            r#"
+            INSERT INTO draw (event_id, round, position, team_id)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(opts.event_id)
+        .bind(opts.round)
+        .bind(position)
+        .bind(team_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if seed_runs {
+            sqlx::query(
+                r#"
+                INSERT INTO run (event_id, team_id, round, position, time_sec, penalty, total_sec, no_time, dq, status)
+                VALUES (?1, ?2, ?3, ?4, NULL, 0.0, NULL, 0, 0, 'pending')
+                "#
+            )
+            .bind(opts.event_id)
+            .bind(team_id)
+            .bind(opts.round)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    if is_final_round {
+        // un re-sorteo de la final recalcula el corte desde cero
+        sqlx::query("UPDATE team SET called_back = NULL WHERE event_id = ?1")
+            .bind(opts.event_id)
+            .execute(&mut *tx)
+            .await?;
+        if let Some((candidates, qualified)) = &callback {
+            for team_id in candidates {
+                sqlx::query("UPDATE team SET called_back = ?2 WHERE id = ?1")
+                    .bind(team_id)
+                    .bind(qualified.contains(team_id) as i64)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+    }
+
+    record_round(
+        &mut tx,
+        opts.event_id,
+        opts.round,
+        RoundRecord { method, seed: seed.as_deref(), entries: &entries, min_spacing, violations: arrangement.violations },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    log_audit(pool, "generate_draw", "draw", Some(opts.event_id), Some(draw_details(opts.event_id, opts.round, method, seed.as_deref(), arrangement.violations))).await?;
+    Ok(DrawSummary {
+        teams: teams.len() as i64,
+        rounds: 1,
+        min_spacing,
+        violations: arrangement.violations,
+        seeds: seed.into_iter().collect(),
+    })
+}
+
+pub async fn generate_batch(pool: &SqlitePool, opts: GenerateBatchDrawOptions) -> AppResult<DrawSummary> {
+    // misma regla que `generate`: un evento `locked` se puede seguir sorteando
+    ensure_rounds_open(pool, opts.event_id).await?;
+
+    let has_draw: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM draw WHERE event_id = ?1)")
+        .bind(opts.event_id)
+        .fetch_one(pool)
+        .await?;
+    if has_draw {
+        require_pin(pool, opts.event_id, opts.pin.as_deref()).await?;
+    }
+
+    // Get active teams with composition for smart shuffling (filtering eliminated)
+    let teams: Vec<(i64, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, header_id, heeler_id FROM team 
+        WHERE event_id = ?1 AND status = 'active'
+          AND id NOT IN (
+            SELECT team_id FROM run 
+            WHERE event_id = ?1 AND (no_time = 1 OR dq = 1)
+          )
+        ORDER BY id ASC
+        "#,
+    )
+    .bind(opts.event_id)
+    .fetch_all(pool)
+    .await?;
+
+    if teams.is_empty() {
+        return Err(AppError::validation("event_id", "No hay equipos activos para generar el draw."));
+    }
+
+    let entries: Vec<DrawEntry> = teams
+        .iter()
+        .map(|&(team_id, header_id, heeler_id)| DrawEntry { team_id, header_id, heeler_id, ..Default::default() })
+        .collect();
+    let min_spacing = check_min_spacing(opts.min_spacing, entries.len())?;
+    let method = if opts.shuffle { DrawMethod::Random } else { DrawMethod::EntryOrder };
+    let mut seeds: Vec<String> = Vec::new();
+    let mut violations = 0;
+
+    let mut tx: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    // For each round EXCEPT THE LAST ONE
+    // The last round should be generated separately after all intermediate rounds are completed
+    // so that ropers can be sorted by accumulated time (highest to lowest)
+    let rounds_to_generate = if opts.rounds > 1 { opts.rounds - 1 } else { opts.rounds };
+    
+    for r in 1..=rounds_to_generate {
+        // Shuffle if requested (with smart spacing logic): cada ronda con su propia semilla
+        let seed = method.uses_seed().then(new_seed);
+        let arrangement = derive_order(method, seed.as_deref(), &entries, min_spacing)?;
+        violations += arrangement.violations;
+
+        for (idx, &team_id) in arrangement.order.iter().enumerate() {
+            let position = (idx as i64) + 1;
+
+            // Insert into draw
+            sqlx::query(
+                r#"
+                INSERT INTO draw (event_id, round, position, team_id)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(event_id, round, position) DO UPDATE SET
+                  team_id = excluded.team_id
+                "#,
+            )
+            .bind(opts.event_id)
+            .bind(r)
+            .bind(position)
+            .bind(team_id)
+            .execute(&mut *tx)
+            .await?;
+
+            // Insert into run (pending)
+            sqlx::query(
+                r#"
+                INSERT INTO run (event_id, team_id, round, position, time_sec, penalty, total_sec, no_time, dq, status)
+                VALUES (?1, ?2, ?3, ?4, NULL, 0.0, NULL, 0, 0, 'pending')
+                ON CONFLICT(event_id, round, team_id) DO UPDATE SET
+                  position   = excluded.position,
+                  updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+                "#
+            )
+            .bind(opts.event_id)
+            .bind(team_id)
+            .bind(r)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        record_round(
+            &mut tx,
+            opts.event_id,
+            r,
+            RoundRecord { method, seed: seed.as_deref(), entries: &entries, min_spacing, violations: arrangement.violations },
+        )
+        .await?;
+        seeds.extend(seed);
+    }
+
+    tx.commit().await?;
+
+    let seed_list = if seeds.is_empty() { String::new() } else { format!(" seeds {}", seeds.join(",")) };
+    log_audit(pool, "generate_draw_batch", "draw", Some(opts.event_id), Some(format!("Event {} Rounds 1-{} (Final round {} to be generated separately){}, spacing violations {}", opts.event_id, rounds_to_generate, opts.rounds, seed_list, violations))).await?;
+    Ok(DrawSummary {
+        teams: teams.len() as i64,
+        rounds: rounds_to_generate,
+        min_spacing,
+        violations,
+        seeds,
+    })
+}
+
+fn draw_details(event_id: i64, round: i64, method: DrawMethod, seed: Option<&str>, violations: i64) -> String {
+    match seed {
+        Some(seed) => format!("Event {} Round {} ({}, seed {}, spacing violations {})", event_id, round, method.as_str(), seed, violations),
+        None => format!("Event {} Round {} ({}, spacing violations {})", event_id, round, method.as_str(), violations),
+    }
+}
+
+pub async fn get_round(pool: &SqlitePool, event_id: i64, round: i64) -> AppResult<Option<DrawRound>> {
+    sqlx::query_as::<_, DrawRound>(
+        r#"
+        SELECT event_id, round, method, seed, rng, entries_json, min_spacing, violations, created_by, created_at
+        FROM draw_round
+        WHERE event_id = ?1 AND round = ?2
+        "#,
+    )
+    .bind(event_id)
+    .bind(round)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+/// Vuelve a calcular el orden con la semilla y las inscripciones guardadas y lo compara con el
+/// draw actual. No coincide si el draw se editó a mano después de generarlo.
+pub async fn verify(pool: &SqlitePool, event_id: i64, round: i64) -> AppResult<DrawVerification> {
+    let record = get_round(pool, event_id, round).await?.ok_or_else(|| {
+        AppError::not_found(format!("La ronda {} no tiene semilla registrada (sorteo anterior a esta versión o sin generar).", round))
+    })?;
+    if record.rng != DRAW_RNG {
+        return Err(AppError::validation("rng", format!("Generador no soportado: {}", record.rng)));
+    }
+    let method = DrawMethod::parse(&record.method)?;
+    let entries: Vec<DrawEntry> =
+        serde_json::from_str(&record.entries_json).map_err(|e| AppError::internal(e.to_string()))?;
+
+    let expected_order = derive_order(method, record.seed.as_deref(), &entries, record.min_spacing)?.order;
+    let actual_order: Vec<i64> = get(pool, event_id, round).await?.into_iter().map(|d| d.team_id).collect();
+    let matches = expected_order == actual_order;
+    let message = if matches {
+        match &record.seed {
+            Some(seed) => format!("El orden coincide con el que produce la semilla {}.", seed),
+            None => "El orden coincide con el método registrado.".to_string(),
+        }
+    } else {
+        "El orden actual no es el que produce la semilla: el draw se modificó después de generarlo.".to_string()
+    };
+
+    Ok(DrawVerification {
+        event_id,
+        round,
+        method,
+        seed: record.seed,
+        rng: record.rng,
+        generated_at: record.created_at,
+        min_spacing: record.min_spacing,
+        violations: record.violations,
+        entries,
+        expected_order,
+        actual_order,
+        matches,
+        message,
+    })
+}
+
+pub async fn get(pool: &SqlitePool, event_id: i64, round: i64) -> AppResult<Vec<DrawRow>> {
+    sqlx::query_as::<_, DrawRow>(
+        r#"
+        SELECT 
+          d.id               AS id,
+          d.event_id         AS event_id,
+          d.round            AS round,
+          d.position         AS position,
+          d.team_id          AS team_id,
+          t.header_id        AS header_id,
+          t.heeler_id        AS heeler_id
+        FROM draw d
+        JOIN team t ON t.id = d.team_id
+        WHERE d.event_id = ?1 AND d.round = ?2
+        ORDER BY d.position ASC
+        "#,
+    )
+    .bind(event_id)
+    .bind(round)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+/* ---- Edición manual del draw ---- */
+
+#[derive(serde::Deserialize)]
+pub struct SwapDrawOptions {
+    pub event_id: i64,
+    pub round: i64,
+    pub position_a: i64,
+    pub position_b: i64,
+    /// PIN del evento (la ronda ya tiene sorteo).
+    pub pin: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct MoveDrawOptions {
+    pub event_id: i64,
+    pub round: i64,
+    pub team_id: i64,
+    pub to_position: i64,
+    pub pin: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct InsertDrawOptions {
+    pub event_id: i64,
+    pub round: i64,
+    pub team_id: i64,
+    /// `None`: al final de la ronda.
+    pub position: Option<i64>,
+    pub pin: Option<String>,
+}
+
+/// Intercambia los equipos de dos posiciones de la ronda.
+pub async fn swap_positions(pool: &SqlitePool, opts: SwapDrawOptions) -> AppResult<Vec<DrawRow>> {
+    let before = editable_order(pool, opts.event_id, opts.round, opts.pin.as_deref()).await?;
+    let a = position_index(&before, opts.position_a, "position_a")?;
+    let b = position_index(&before, opts.position_b, "position_b")?;
+    let mut after = before.clone();
+    after.swap(a, b);
+    let details = format!("Event {} Round {}: posiciones {} y {} intercambiadas", opts.event_id, opts.round, opts.position_a, opts.position_b);
+    apply_order(pool, opts.event_id, opts.round, &before, &after, "swap_draw", details).await
+}
+
+/// Lleva un equipo a otra posición; los del medio se corren un lugar.
+pub async fn move_team(pool: &SqlitePool, opts: MoveDrawOptions) -> AppResult<Vec<DrawRow>> {
+    let before = editable_order(pool, opts.event_id, opts.round, opts.pin.as_deref()).await?;
+    let from = before
+        .iter()
+        .position(|&t| t == opts.team_id)
+        .ok_or_else(|| AppError::validation("team_id", "El equipo no está en el draw de esta ronda."))?;
+    let to = position_index(&before, opts.to_position, "to_position")?;
+    let mut after = before.clone();
+    let team = after.remove(from);
+    after.insert(to, team);
+    let details = format!("Event {} Round {}: equipo {} de la posición {} a la {}", opts.event_id, opts.round, opts.team_id, from + 1, to + 1);
+    apply_order(pool, opts.event_id, opts.round, &before, &after, "move_draw_team", details).await
+}
+
+/// Inscripción tardía: mete un equipo activo del evento en la ronda (al final o en `position`)
+/// y crea su corrida pendiente.
+pub async fn insert_team(pool: &SqlitePool, opts: InsertDrawOptions) -> AppResult<Vec<DrawRow>> {
+    let before = editable_order(pool, opts.event_id, opts.round, opts.pin.as_deref()).await?;
+    if before.contains(&opts.team_id) {
+        return Err(AppError::validation("team_id", "El equipo ya está en el draw de esta ronda."));
+    }
+    let team_status: Option<String> = sqlx::query_scalar("SELECT status FROM team WHERE id = ?1 AND event_id = ?2")
+        .bind(opts.team_id)
+        .bind(opts.event_id)
+        .fetch_optional(pool)
+        .await?;
+    match team_status.as_deref() {
+        Some("active") => {}
+        Some(_) => return Err(AppError::validation("team_id", "El equipo no está activo.")),
+        None => return Err(AppError::not_found("Equipo no encontrado en este evento.")),
+    }
+
+    let at = match opts.position {
+        Some(p) if p >= 1 && p <= before.len() as i64 + 1 => (p - 1) as usize,
+        Some(_) => return Err(AppError::validation("position", format!("La posición debe estar entre 1 y {}.", before.len() + 1))),
+        None => before.len(),
+    };
+    let mut after = before.clone();
+    after.insert(at, opts.team_id);
+    let details = format!("Event {} Round {}: equipo {} agregado en la posición {}", opts.event_id, opts.round, opts.team_id, at + 1);
+    apply_order(pool, opts.event_id, opts.round, &before, &after, "insert_draw_team", details).await
+}
+
+/// Orden actual de la ronda (team_id por posición) después de comprobar evento y PIN.
+async fn editable_order(pool: &SqlitePool, event_id: i64, round: i64, pin: Option<&str>) -> AppResult<Vec<i64>> {
+    ensure_rounds_open(pool, event_id).await?;
+    let order: Vec<i64> = get(pool, event_id, round).await?.into_iter().map(|d| d.team_id).collect();
+    if order.is_empty() {
+        return Err(AppError::not_found(format!("La ronda {} no tiene draw.", round)));
+    }
+    require_pin(pool, event_id, pin).await?;
+    Ok(order)
+}
+
+fn position_index(order: &[i64], position: i64, field: &str) -> AppResult<usize> {
+    if position < 1 || position > order.len() as i64 {
+        return Err(AppError::validation(field, format!("La posición debe estar entre 1 y {}.", order.len())));
+    }
+    Ok((position - 1) as usize)
+}
+
+/// Renumera `draw` y las corridas de la ronda con el orden nuevo. Se niega si algún equipo que
+/// cambia de posición ya corrió o si un equipo que entra a la ronda quedó eliminado (NT/DQ) en
+/// una ronda anterior. Todo se comprueba dentro de la transacción que escribe.
+async fn apply_order(
+    pool: &SqlitePool,
+    event_id: i64,
+    round: i64,
+    before: &[i64],
+    after: &[i64],
+    action: &str,
+    details: String,
+) -> AppResult<Vec<DrawRow>> {
+    let moved: Vec<(usize, i64)> = after
+        .iter()
+        .enumerate()
+        .filter(|&(idx, team)| before.get(idx) != Some(team))
+        .map(|(idx, &team)| (idx, team))
+        .collect();
+
+    let mut tx: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    // fuera del rango para no chocar con UNIQUE(event_id, round, position) mientras se renumera;
+    // es la primera escritura: desde aquí ninguna otra ventana puede capturar ni editar la ronda
+    sqlx::query("UPDATE draw SET position = position + 100000 WHERE event_id = ?1 AND round = ?2")
+        .bind(event_id)
+        .bind(round)
+        .execute(&mut *tx)
+        .await?;
+
+    let current: Vec<i64> = sqlx::query_scalar("SELECT team_id FROM draw WHERE event_id = ?1 AND round = ?2 ORDER BY position")
+        .bind(event_id)
+        .bind(round)
+        .fetch_all(&mut *tx)
+        .await?;
+    if current != before {
+        return Err(AppError::conflict("El draw de la ronda cambió mientras tanto; vuelve a cargarlo."));
+    }
+
+    let completed: Vec<i64> = sqlx::query_scalar(
+        "SELECT team_id FROM run WHERE event_id = ?1 AND round = ?2 AND status = 'completed'",
+    )
+    .bind(event_id)
+    .bind(round)
+    .fetch_all(&mut *tx)
+    .await?;
+    let blocked: Vec<String> = moved
+        .iter()
+        .filter(|(_, team)| completed.contains(team))
+        .map(|(_, team)| format!("#{}", before.iter().position(|t| t == team).unwrap_or(0) + 1))
+        .collect();
+    if !blocked.is_empty() {
+        return Err(AppError::conflict(format!(
+            "Las posiciones {} ya tienen tiempo capturado; no se pueden mover.",
+            blocked.join(", ")
+        )));
+    }
+
+    for &team_id in after.iter().filter(|t| !before.contains(t)) {
+        let eliminated_in: Option<i64> = sqlx::query_scalar(
+            "SELECT MIN(round) FROM run WHERE event_id = ?1 AND team_id = ?2 AND round < ?3 AND (no_time = 1 OR dq = 1)",
+        )
+        .bind(event_id)
+        .bind(team_id)
+        .bind(round)
+        .fetch_one(&mut *tx)
+        .await?;
+        if let Some(r) = eliminated_in {
+            return Err(AppError::validation(
+                "team_id",
+                format!("El equipo quedó eliminado (NT/DQ) en la ronda {}; no puede entrar a la ronda {}.", r, round),
+            ));
+        }
+    }
+
+    // rondas sorteadas sin `seed_runs` no tienen corridas todavía: no se crean aquí
+    let has_runs: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM run WHERE event_id = ?1 AND round = ?2)")
+        .bind(event_id)
+        .bind(round)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    for (idx, &team_id) in after.iter().enumerate() {
+        let position = (idx as i64) + 1;
+        let updated = sqlx::query("UPDATE draw SET position = ?4 WHERE event_id = ?1 AND round = ?2 AND team_id = ?3")
+            .bind(event_id)
+            .bind(round)
+            .bind(team_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+        if updated.rows_affected() == 0 {
+            sqlx::query("INSERT INTO draw (event_id, round, position, team_id) VALUES (?1, ?2, ?3, ?4)")
+                .bind(event_id)
+                .bind(round)
+                .bind(position)
+                .bind(team_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        if has_runs {
+            sqlx::query(
+                r#"
+                INSERT INTO run (event_id, team_id, round, position, time_sec, penalty, total_sec, no_time, dq, status)
+                VALUES (?1, ?2, ?3, ?4, NULL, 0.0, NULL, 0, 0, 'pending')
+                ON CONFLICT(event_id, round, team_id) DO UPDATE SET
+                  position   = excluded.position,
+                  updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+                WHERE run.position <> excluded.position
+                "#,
+            )
+            .bind(event_id)
+            .bind(team_id)
+            .bind(round)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    log_change(
+        pool,
+        action,
+        "draw",
+        Some(event_id),
+        Some(details),
+        Some(serde_json::json!({ "round": round, "order": before }).to_string()),
+        Some(serde_json::json!({ "round": round, "order": after }).to_string()),
+    )
+    .await?;
+    get(pool, event_id, round).await
+}
@@ -0,0 +1,349 @@
+//! Revisión del draw antes de la competencia: cruza cada ronda de `draw` con `team`/`run` y
+//! resume qué tan parejas quedaron las posiciones entre rondas. Sólo lee; nada se corrige aquí.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use super::draw::{DrawMethod, DEFAULT_MIN_SPACING};
+
+/// Dos salidas del mismo roper con menos corridas de por medio que la separación de la ronda.
+#[derive(Debug, serde::Serialize)]
+pub struct SpacingIssue {
+    pub roper_id: i64,
+    pub roper_name: String,
+    pub first_position: i64,
+    pub second_position: i64,
+    /// Corridas de otros equipos entre las dos salidas.
+    pub gap: i64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RoundReport {
+    pub round: i64,
+    pub teams: i64,
+    /// `draw_round.method`; `None` si la ronda se sorteó antes de guardarlo.
+    pub method: Option<String>,
+    pub min_spacing: i64,
+    /// Posiciones que ocupan dos o más corridas de la ronda.
+    pub duplicate_positions: Vec<i64>,
+    /// Posiciones que faltan entre 1 y la última del draw.
+    pub position_gaps: Vec<i64>,
+    /// Equipos en el draw cuya corrida tiene otra posición.
+    pub run_mismatches: Vec<i64>,
+    /// Equipos activos que deberían correr la ronda y no están en el draw.
+    pub missing_teams: Vec<i64>,
+    /// Equipos con NT/DQ en una ronda anterior que todavía serían llamados (corrida no `skipped`).
+    pub eliminated_teams: Vec<i64>,
+    pub spacing: Vec<SpacingIssue>,
+}
+
+impl RoundReport {
+    fn issues(&self) -> usize {
+        self.duplicate_positions.len()
+            + self.position_gaps.len()
+            + self.run_mismatches.len()
+            + self.missing_teams.len()
+            + self.eliminated_teams.len()
+            + self.spacing.len()
+    }
+}
+
+/// Posición relativa: 0 = primero de la ronda, 1 = último.
+#[derive(Debug, serde::Serialize)]
+pub struct TeamFairness {
+    pub team_id: i64,
+    pub header_name: String,
+    pub heeler_name: String,
+    pub rounds: i64,
+    pub mean_position: f64,
+    pub mean_relative: f64,
+}
+
+/// Salidas de un roper por tercio de la ronda (temprano / medio / tarde).
+#[derive(Debug, serde::Serialize)]
+pub struct RoperFairness {
+    pub roper_id: i64,
+    pub roper_name: String,
+    pub runs: i64,
+    pub early: i64,
+    pub middle: i64,
+    pub late: i64,
+    pub mean_relative: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DrawReport {
+    pub event_id: i64,
+    pub rounds: Vec<RoundReport>,
+    /// Rondas que entran al resumen de posiciones: las sorteadas al azar o en orden de
+    /// inscripción. La final ordenada por tiempos o clasificación no cuenta.
+    pub fairness_rounds: Vec<i64>,
+    pub teams: Vec<TeamFairness>,
+    pub ropers: Vec<RoperFairness>,
+    pub issues: i64,
+    /// Sin observaciones: el draw se puede firmar.
+    pub clean: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct Slot {
+    round: i64,
+    position: i64,
+    team_id: i64,
+    header_id: i64,
+    heeler_id: i64,
+    run_position: Option<i64>,
+    run_status: Option<String>,
+}
+
+pub async fn get(pool: &SqlitePool, event_id: i64) -> AppResult<DrawReport> {
+    let total_rounds: i64 = sqlx::query_scalar("SELECT rounds FROM event WHERE id = ?1")
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Evento no encontrado."))?;
+
+    let slots: Vec<Slot> = sqlx::query_as(
+        r#"
+        SELECT d.round, d.position, d.team_id, t.header_id, t.heeler_id,
+               r.position AS run_position, r.status AS run_status
+        FROM draw d
+        JOIN team t ON t.id = d.team_id
+        LEFT JOIN run r ON r.event_id = d.event_id AND r.round = d.round AND r.team_id = d.team_id
+        WHERE d.event_id = ?1
+        ORDER BY d.round, d.position
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    let rounds_meta: HashMap<i64, (String, i64)> =
+        sqlx::query_as::<_, (i64, String, i64)>("SELECT round, method, min_spacing FROM draw_round WHERE event_id = ?1")
+            .bind(event_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|(round, method, spacing)| (round, (method, spacing)))
+            .collect();
+
+    // (round, position) de todas las corridas, para ver posiciones repetidas
+    let run_positions: Vec<(i64, i64)> = sqlx::query_as("SELECT round, position FROM run WHERE event_id = ?1")
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+
+    // equipos que pueden correr: activos (ni borrados ni scratch), con su marca de short-go
+    let active: Vec<(i64, Option<i64>)> =
+        sqlx::query_as("SELECT id, called_back FROM team WHERE event_id = ?1 AND status = 'active'")
+            .bind(event_id)
+            .fetch_all(pool)
+            .await?;
+
+    // primera ronda con NT/DQ de cada equipo
+    let eliminated_in: HashMap<i64, i64> = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT team_id, MIN(round) FROM run WHERE event_id = ?1 AND (no_time = 1 OR dq = 1) GROUP BY team_id",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let names = names(pool, event_id).await?;
+
+    let mut by_round: BTreeMap<i64, Vec<&Slot>> = BTreeMap::new();
+    for slot in &slots {
+        by_round.entry(slot.round).or_default().push(slot);
+    }
+
+    let mut rounds = Vec::new();
+    let mut fairness_rounds = Vec::new();
+    for (&round, slots) in &by_round {
+        let (method, min_spacing) = match rounds_meta.get(&round) {
+            Some((method, spacing)) => (Some(method.clone()), *spacing),
+            None => (None, DEFAULT_MIN_SPACING),
+        };
+
+        let mut seen: HashMap<i64, usize> = HashMap::new();
+        for &(r, position) in &run_positions {
+            if r == round {
+                *seen.entry(position).or_default() += 1;
+            }
+        }
+        let mut duplicate_positions: Vec<i64> = seen.into_iter().filter(|&(_, n)| n > 1).map(|(p, _)| p).collect();
+        duplicate_positions.sort();
+
+        let positions: HashSet<i64> = slots.iter().map(|s| s.position).collect();
+        let last = slots.iter().map(|s| s.position).max().unwrap_or(0);
+        let position_gaps: Vec<i64> = (1..=last).filter(|p| !positions.contains(p)).collect();
+
+        let run_mismatches: Vec<i64> = slots
+            .iter()
+            .filter(|s| s.run_position.is_some_and(|p| p != s.position))
+            .map(|s| s.team_id)
+            .collect();
+
+        let drawn: HashSet<i64> = slots.iter().map(|s| s.team_id).collect();
+        let eliminated_before = |team_id: &i64| eliminated_in.get(team_id).is_some_and(|&r| r < round);
+        let is_final = round == total_rounds;
+        let missing_teams: Vec<i64> = active
+            .iter()
+            .filter(|(id, called_back)| {
+                // no le toca: eliminado antes o fuera del corte del short-go
+                let excused = eliminated_before(id) || (is_final && *called_back == Some(0));
+                !(excused || drawn.contains(id))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        let eliminated_teams: Vec<i64> = slots
+            .iter()
+            .filter(|s| eliminated_before(&s.team_id) && s.run_status.as_deref() != Some("skipped"))
+            .map(|s| s.team_id)
+            .collect();
+
+        let spacing = spacing_issues(slots, min_spacing, &names);
+
+        let fair = match method.as_deref() {
+            Some(m) => matches!(DrawMethod::parse(m), Ok(DrawMethod::Random | DrawMethod::EntryOrder)),
+            None => !is_final,
+        };
+        if fair {
+            fairness_rounds.push(round);
+        }
+
+        rounds.push(RoundReport {
+            round,
+            teams: slots.len() as i64,
+            method,
+            min_spacing,
+            duplicate_positions,
+            position_gaps,
+            run_mismatches,
+            missing_teams,
+            eliminated_teams,
+            spacing,
+        });
+    }
+
+    let (teams, ropers) = fairness(&by_round, &fairness_rounds, &names);
+    let issues = rounds.iter().map(RoundReport::issues).sum::<usize>() as i64;
+    Ok(DrawReport { event_id, rounds, fairness_rounds, teams, ropers, issues, clean: issues == 0 })
+}
+
+/// Nombres de los ropers y de los equipos del evento.
+struct Names {
+    ropers: HashMap<i64, String>,
+    teams: HashMap<i64, (String, String)>,
+}
+
+async fn names(pool: &SqlitePool, event_id: i64) -> AppResult<Names> {
+    let rows: Vec<(i64, i64, String, i64, String)> = sqlx::query_as(
+        r#"
+        SELECT t.id, t.header_id, (rh.first_name || ' ' || rh.last_name), t.heeler_id, (rhe.first_name || ' ' || rhe.last_name)
+        FROM team t
+        JOIN roper rh ON rh.id = t.header_id
+        JOIN roper rhe ON rhe.id = t.heeler_id
+        WHERE t.event_id = ?1
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut names = Names { ropers: HashMap::new(), teams: HashMap::new() };
+    for (team_id, header_id, header, heeler_id, heeler) in rows {
+        names.ropers.insert(header_id, header.clone());
+        names.ropers.insert(heeler_id, heeler.clone());
+        names.teams.insert(team_id, (header, heeler));
+    }
+    Ok(names)
+}
+
+fn spacing_issues(slots: &[&Slot], min_spacing: i64, names: &Names) -> Vec<SpacingIssue> {
+    let mut last_seen: HashMap<i64, i64> = HashMap::new();
+    let mut issues = Vec::new();
+    for slot in slots {
+        for roper_id in [slot.header_id, slot.heeler_id] {
+            if let Some(&previous) = last_seen.get(&roper_id) {
+                let gap = slot.position - previous - 1;
+                if gap < min_spacing {
+                    issues.push(SpacingIssue {
+                        roper_id,
+                        roper_name: names.ropers.get(&roper_id).cloned().unwrap_or_default(),
+                        first_position: previous,
+                        second_position: slot.position,
+                        gap,
+                    });
+                }
+            }
+            last_seen.insert(roper_id, slot.position);
+        }
+    }
+    issues
+}
+
+fn fairness(by_round: &BTreeMap<i64, Vec<&Slot>>, rounds: &[i64], names: &Names) -> (Vec<TeamFairness>, Vec<RoperFairness>) {
+    // (suma de posiciones, suma relativa, rondas)
+    let mut teams: BTreeMap<i64, (i64, f64, i64)> = BTreeMap::new();
+    // (temprano, medio, tarde, suma relativa)
+    let mut ropers: BTreeMap<i64, (i64, i64, i64, f64)> = BTreeMap::new();
+
+    for round in rounds {
+        let slots = &by_round[round];
+        let n = slots.len() as i64;
+        for slot in slots {
+            let relative = if n > 1 { (slot.position - 1) as f64 / (n - 1) as f64 } else { 0.0 };
+            let team = teams.entry(slot.team_id).or_default();
+            team.0 += slot.position;
+            team.1 += relative;
+            team.2 += 1;
+
+            for roper_id in [slot.header_id, slot.heeler_id] {
+                let roper = ropers.entry(roper_id).or_default();
+                if relative < 1.0 / 3.0 {
+                    roper.0 += 1;
+                } else if relative <= 2.0 / 3.0 {
+                    roper.1 += 1;
+                } else {
+                    roper.2 += 1;
+                }
+                roper.3 += relative;
+            }
+        }
+    }
+
+    let teams = teams
+        .into_iter()
+        .map(|(team_id, (positions, relative, rounds))| {
+            let (header_name, heeler_name) = names.teams.get(&team_id).cloned().unwrap_or_default();
+            TeamFairness {
+                team_id,
+                header_name,
+                heeler_name,
+                rounds,
+                mean_position: positions as f64 / rounds as f64,
+                mean_relative: relative / rounds as f64,
+            }
+        })
+        .collect();
+    let ropers = ropers
+        .into_iter()
+        .map(|(roper_id, (early, middle, late, relative))| {
+            let runs = early + middle + late;
+            RoperFairness {
+                roper_id,
+                roper_name: names.ropers.get(&roper_id).cloned().unwrap_or_default(),
+                runs,
+                early,
+                middle,
+                late,
+                mean_relative: relative / runs as f64,
+            }
+        })
+        .collect();
+    (teams, ropers)
+}
@@ -0,0 +1,699 @@
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use super::activity;
+use super::audit::{acting_user, log_audit, log_change, snapshot};
+use super::penalties;
+use super::users::{hash_password, verify_password};
+
+#[derive(serde::Deserialize)]
+pub struct NewEvent {
+    pub series_id: i64,
+    pub name: String,
+    pub date: String,
+    pub rounds: i64,
+    pub status: Option<String>,
+    pub location: Option<String>,
+    pub entry_fee: Option<f64>,
+    pub prize_pool: Option<f64>,
+    pub max_team_rating: Option<f64>,
+    pub payoff_allocation: Option<String>,
+    /// `close_gap` (por defecto) o `keep_slot`; ver `teams::scratch`.
+    pub scratch_policy: Option<String>,
+    /// Short-go: `all` (por defecto), `top_n` o `top_percent`; ver `check_callback`.
+    pub callback_mode: Option<String>,
+    pub callback_value: Option<i64>,
+    pub callback_ties: Option<String>,
+    /// Orden de la ronda final; ver `check_final_order`. Por defecto `aggregate`.
+    pub final_order: Option<String>,
+    pub admin_pin: Option<String>,
+}
+
+#[derive(serde::Serialize, FromRow)]
+pub struct EventRow {
+    pub id: i64,
+    pub series_id: i64,
+    pub name: String,
+    pub date: String,
+    pub status: Option<String>,
+    pub rounds: i64,
+    pub location: Option<String>,
+    pub entry_fee: Option<f64>,
+    pub prize_pool: Option<f64>,
+    pub max_team_rating: Option<f64>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Sube con cada cambio de la fila (ver `EventPatch::expected_version`).
+    pub version: i64,
+    pub payoff_allocation: Option<String>,
+    pub scratch_policy: String,
+    pub callback_mode: String,
+    pub callback_value: Option<i64>,
+    pub callback_ties: String,
+    pub final_order: String,
+    /// El PIN nunca sale del backend; la UI sólo sabe si hay que pedirlo.
+    pub has_admin_pin: bool,
+    pub teams_count: i64,
+    pub pot: f64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct EventPatch {
+    pub name: Option<String>,
+    pub date: Option<String>,
+    pub rounds: Option<i64>,
+    pub status: Option<String>,
+    pub entry_fee: Option<f64>,
+    pub prize_pool: Option<f64>,
+    pub location: Option<String>,
+    pub max_team_rating: Option<f64>,
+    pub payoff_allocation: Option<String>,
+    pub scratch_policy: Option<String>,
+    pub callback_mode: Option<String>,
+    pub callback_value: Option<i64>,
+    pub callback_ties: Option<String>,
+    pub final_order: Option<String>,
+    /// PIN nuevo (4 dígitos).
+    pub admin_pin: Option<String>,
+    /// PIN actual: necesario para desbloquear el evento o cambiar su PIN.
+    pub pin: Option<String>,
+    /// `version` del evento que vio el cliente; si cambió desde entonces, Conflict.
+    pub expected_version: Option<i64>,
+}
+
+/// Normalize status values coming from the frontend. DB CHECK allows
+/// only ('active','upcoming','completed','locked'). Map common FE values
+/// to the canonical set to avoid constraint errors (e.g. 'draft' -> 'upcoming').
+pub fn normalize_status(raw: &str) -> String {
+    match raw {
+        "draft" => "upcoming".to_string(),
+        "finalized" => "completed".to_string(),
+        "active" | "upcoming" | "completed" | "locked" => raw.to_string(),
+        _ => "upcoming".to_string(),
+    }
+}
+
+pub async fn ensure_event_unlocked(pool: &SqlitePool, event_id: i64) -> AppResult<()> {
+    let status: Option<String> =
+        sqlx::query_scalar("SELECT status FROM event WHERE id = ?1 AND is_deleted = 0")
+            .bind(event_id)
+            .fetch_optional(pool)
+            .await?;
+
+    match status.as_deref() {
+        Some("locked") => Err(AppError::event_locked("El evento está bloqueado; no se permiten cambios.")),
+        Some(_) => Ok(()),
+        None => Err(AppError::not_found("Evento no encontrado.")),
+    }
+}
+
+pub const SCRATCH_CLOSE_GAP: &str = "close_gap";
+pub const SCRATCH_KEEP_SLOT: &str = "keep_slot";
+
+fn check_scratch_policy(policy: &str) -> AppResult<()> {
+    if policy != SCRATCH_CLOSE_GAP && policy != SCRATCH_KEEP_SLOT {
+        return Err(AppError::validation("scratch_policy", "Política de scratch inválida: usa 'close_gap' o 'keep_slot'."));
+    }
+    Ok(())
+}
+
+/// Short-go: todos los equipos vivos corren la final.
+pub const CALLBACK_ALL: &str = "all";
+/// Los mejores `callback_value` equipos.
+pub const CALLBACK_TOP_N: &str = "top_n";
+/// El `callback_value` % de los equipos (redondeado hacia arriba).
+pub const CALLBACK_TOP_PERCENT: &str = "top_percent";
+/// Empate en la línea de corte: pasan todos los empatados.
+pub const TIES_INCLUDE: &str = "include";
+/// Empate en la línea de corte: no pasa ninguno si no caben todos.
+pub const TIES_EXCLUDE: &str = "exclude";
+/// Empate en la línea de corte: se desempata como la clasificación (mejor tiempo, inscripción).
+pub const TIES_TIEBREAK: &str = "tiebreak";
+
+/// Orden de la final: mayor tiempo acumulado primero.
+pub const FINAL_AGGREGATE: &str = "aggregate";
+/// Mayor promedio primero (no castiga tanto a quien tiene una corrida menos).
+pub const FINAL_AVERAGE: &str = "average";
+/// Inverso de la clasificación: el líder corre al último.
+pub const FINAL_STANDINGS: &str = "standings";
+/// Sorteo con semilla, como una ronda normal.
+pub const FINAL_RANDOM: &str = "random";
+/// El mismo orden que la ronda 1.
+pub const FINAL_ROUND_ONE: &str = "round_one";
+
+fn check_final_order(order: &str) -> AppResult<()> {
+    if ![FINAL_AGGREGATE, FINAL_AVERAGE, FINAL_STANDINGS, FINAL_RANDOM, FINAL_ROUND_ONE].contains(&order) {
+        return Err(AppError::validation(
+            "final_order",
+            "Orden de final inválido: usa 'aggregate', 'average', 'standings', 'random' o 'round_one'.",
+        ));
+    }
+    Ok(())
+}
+
+fn check_callback(mode: &str, value: Option<i64>, ties: &str) -> AppResult<()> {
+    match mode {
+        CALLBACK_ALL => {}
+        CALLBACK_TOP_N if value.is_some_and(|n| n >= 1) => {}
+        CALLBACK_TOP_PERCENT if value.is_some_and(|p| (1..=100).contains(&p)) => {}
+        CALLBACK_TOP_N | CALLBACK_TOP_PERCENT => {
+            return Err(AppError::validation(
+                "callback_value",
+                "Indica cuántos equipos (top_n ≥ 1) o qué porcentaje (top_percent de 1 a 100) pasan a la final.",
+            ));
+        }
+        _ => {
+            return Err(AppError::validation("callback_mode", "Modo de short-go inválido: usa 'all', 'top_n' o 'top_percent'."));
+        }
+    }
+    if ![TIES_INCLUDE, TIES_EXCLUDE, TIES_TIEBREAK].contains(&ties) {
+        return Err(AppError::validation("callback_ties", "Empates inválidos: usa 'include', 'exclude' o 'tiebreak'."));
+    }
+    Ok(())
+}
+
+pub async fn list(pool: &SqlitePool, series_id: Option<i64>) -> AppResult<Vec<EventRow>> {
+    if let Some(sid) = series_id {
+        sqlx::query_as::<_, EventRow>(
+            r#"
+         SELECT 
+             e.id, e.series_id, e.name, e.date, e.status, e.rounds, e.location,
+             e.entry_fee, e.prize_pool, e.max_team_rating, e.created_at, e.updated_at, e.version,
+             e.payoff_allocation, e.scratch_policy, e.callback_mode, e.callback_value, e.callback_ties, e.final_order,
+             (COALESCE(e.admin_pin, '') <> '') AS has_admin_pin,
+             (SELECT COUNT(*) FROM team t WHERE t.event_id = e.id AND t.status = 'active') as teams_count,
+             (
+                COALESCE(e.prize_pool, 0.0) + 
+                (COALESCE(e.entry_fee, 0.0) * (
+                    SELECT COUNT(DISTINCT roper_id) FROM (
+                        SELECT header_id AS roper_id FROM team WHERE event_id = e.id AND status = 'active'
+                        UNION
+                        SELECT heeler_id AS roper_id FROM team WHERE event_id = e.id AND status = 'active'
+                    )
+                ))
+             ) as pot
+            FROM event e
+            WHERE e.is_deleted = 0 AND e.series_id = ?1
+            ORDER BY e.date ASC, e.id ASC
+            "#,
+        )
+        .bind(sid)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+    } else {
+        sqlx::query_as::<_, EventRow>(
+            r#"
+         SELECT 
+             e.id, e.series_id, e.name, e.date, e.status, e.rounds, e.location,
+             e.entry_fee, e.prize_pool, e.max_team_rating, e.created_at, e.updated_at, e.version,
+             e.payoff_allocation, e.scratch_policy, e.callback_mode, e.callback_value, e.callback_ties, e.final_order,
+             (COALESCE(e.admin_pin, '') <> '') AS has_admin_pin,
+             (SELECT COUNT(*) FROM team t WHERE t.event_id = e.id AND t.status = 'active') as teams_count,
+             (
+                COALESCE(e.prize_pool, 0.0) + 
+                (COALESCE(e.entry_fee, 0.0) * (
+                    SELECT COUNT(DISTINCT roper_id) FROM (
+                        SELECT header_id AS roper_id FROM team WHERE event_id = e.id AND status = 'active'
+                        UNION
+                        SELECT heeler_id AS roper_id FROM team WHERE event_id = e.id AND status = 'active'
+                    )
+                ))
+             ) as pot
+            FROM event e
+            WHERE e.is_deleted = 0
+            ORDER BY e.date ASC, e.id ASC
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+    }
+}
+
+pub async fn list_all_raw(pool: &SqlitePool) -> AppResult<Vec<EventRow>> {
+    tracing::info!("list_all_events_raw: returning all events without is_deleted filter");
+    sqlx::query_as::<_, EventRow>(
+        r#"
+        SELECT 
+             e.id, e.series_id, e.name, e.date, e.status, e.rounds, e.location,
+             e.entry_fee, e.prize_pool, e.max_team_rating, e.created_at, e.updated_at, e.version,
+             e.payoff_allocation, e.scratch_policy, e.callback_mode, e.callback_value, e.callback_ties, e.final_order,
+             (COALESCE(e.admin_pin, '') <> '') AS has_admin_pin,
+             (SELECT COUNT(*) FROM team t WHERE t.event_id = e.id AND t.status = 'active') as teams_count,
+             (
+                COALESCE(e.prize_pool, 0.0) + 
+                (COALESCE(e.entry_fee, 0.0) * (SELECT COUNT(*) FROM team t WHERE t.event_id = e.id AND t.status = 'active'))
+             ) as pot
+        FROM event e
+        ORDER BY e.date ASC, e.id ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "list_all_events_raw failed");
+        AppError::from(e)
+    })
+}
+
+/// Fila cruda del evento; `teams_count` y `pot` van en 0 porque quien la usa
+/// los calcula por su cuenta (payoffs, export).
+pub async fn get(pool: &SqlitePool, event_id: i64) -> AppResult<EventRow> {
+    sqlx::query_as::<_, EventRow>(
+        r#"
+        SELECT 
+            id, series_id, name, date, status, rounds, location, 
+            entry_fee, prize_pool, max_team_rating, created_at, updated_at, version,
+            payoff_allocation, scratch_policy, callback_mode, callback_value, callback_ties, final_order,
+            (COALESCE(admin_pin, '') <> '') AS has_admin_pin,
+            0 as teams_count,
+            0.0 as pot
+        FROM event 
+        WHERE id = ?1
+        "#
+    )
+    .bind(event_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::not_found("Evento no encontrado."))
+}
+
+pub async fn create(pool: &SqlitePool, payload: NewEvent) -> AppResult<i64> {
+    let status = normalize_status(payload.status.as_deref().unwrap_or("upcoming"));
+    let pin_hash = hash_pin(payload.admin_pin.as_deref())?;
+    let scratch_policy = payload.scratch_policy.as_deref().unwrap_or(SCRATCH_CLOSE_GAP);
+    check_scratch_policy(scratch_policy)?;
+    let callback_mode = payload.callback_mode.as_deref().unwrap_or(CALLBACK_ALL);
+    let callback_ties = payload.callback_ties.as_deref().unwrap_or(TIES_INCLUDE);
+    check_callback(callback_mode, payload.callback_value, callback_ties)?;
+    let final_order = payload.final_order.as_deref().unwrap_or(FINAL_AGGREGATE);
+    check_final_order(final_order)?;
+
+    let res = sqlx::query(
+        r#"
+        INSERT INTO event (series_id, name, date, status, rounds, location, entry_fee, prize_pool, max_team_rating, payoff_allocation, admin_pin, scratch_policy,
+                           callback_mode, callback_value, callback_ties, final_order)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+        "#
+    )
+    .bind(payload.series_id)
+    .bind(&payload.name)
+    .bind(&payload.date)
+    .bind(&status)
+    .bind(payload.rounds)
+    .bind(&payload.location)
+    .bind(payload.entry_fee)
+    .bind(payload.prize_pool)
+    .bind(payload.max_team_rating)
+    .bind(&payload.payoff_allocation)
+    .bind(&pin_hash)
+    .bind(scratch_policy)
+    .bind(callback_mode)
+    .bind(payload.callback_value)
+    .bind(callback_ties)
+    .bind(final_order)
+    .execute(pool)
+    .await?;
+
+    let id = res.last_insert_rowid();
+    penalties::seed_defaults(pool, id).await?;
+    let after = snapshot(pool, "event", id).await?;
+    log_change(pool, "create_event", "event", Some(id), Some(payload.name), None, after).await?;
+    Ok(id)
+}
+
+/// Sacar un evento de `locked` es desbloquearlo y pide el PIN del evento (si tiene).
+pub async fn update_status(pool: &SqlitePool, id: i64, status: &str, pin: Option<&str>) -> AppResult<()> {
+    let normalized_status = normalize_status(status);
+    if normalized_status != "locked" && current_status(pool, id).await?.as_deref() == Some("locked") {
+        require_pin(pool, id, pin).await?;
+    }
+
+    let before = snapshot(pool, "event", id).await?;
+    sqlx::query("UPDATE event SET status = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?2")
+        .bind(&normalized_status)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    
+    let after = snapshot(pool, "event", id).await?;
+    if normalized_status == "completed" {
+        activity::end_session(pool, activity::EVENT_DAY, id, None).await?;
+    }
+    log_change(pool, "update_event_status", "event", Some(id), Some(normalized_status), before, after).await?;
+    Ok(())
+}
+
+const STALE_EVENT: &str = "El evento cambió desde que lo abriste (otra ventana u otro usuario).";
+
+pub async fn update(pool: &SqlitePool, id: i64, patch: EventPatch) -> AppResult<()> {
+    // comprobar existencia
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT version FROM event WHERE id = ?1 AND is_deleted = 0")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    let Some(version) = version else {
+        return Err(AppError::not_found("Evento no encontrado."));
+    };
+
+    // impedir cambios si está locked
+    // ensure_event_unlocked(pool, id).await?;
+
+    // construir UPDATE dinámico usando QueryBuilder
+    let mut builder = QueryBuilder::<Sqlite>::new("UPDATE event SET ");
+    let mut has_any = false;
+
+    if let Some(name) = patch.name {
+        builder.push("name = ").push_bind(name).push(", ");
+        has_any = true;
+    }
+    if let Some(date) = patch.date {
+        builder.push("date = ").push_bind(date).push(", ");
+        has_any = true;
+    }
+    if let Some(rounds) = patch.rounds {
+        builder.push("rounds = ").push_bind(rounds).push(", ");
+        has_any = true;
+    }
+    let mut completing = false;
+    if let Some(raw_status) = patch.status {
+        let status = normalize_status(&raw_status);
+        completing = status == "completed";
+        if status != "locked" && current_status(pool, id).await?.as_deref() == Some("locked") {
+            require_pin(pool, id, patch.pin.as_deref()).await?;
+        }
+        builder.push("status = ").push_bind(status).push(", ");
+        has_any = true;
+    }
+    if let Some(entry) = patch.entry_fee {
+        builder.push("entry_fee = ").push_bind(entry).push(", ");
+        has_any = true;
+    }
+    if let Some(prize) = patch.prize_pool {
+        builder.push("prize_pool = ").push_bind(prize).push(", ");
+        has_any = true;
+    }
+    if let Some(loc) = patch.location {
+        builder.push("location = ").push_bind(loc).push(", ");
+        has_any = true;
+    }
+    if let Some(mtr) = patch.max_team_rating {
+        builder.push("max_team_rating = ").push_bind(mtr).push(", ");
+        has_any = true;
+    }
+    if let Some(pa) = patch.payoff_allocation {
+        builder.push("payoff_allocation = ").push_bind(pa).push(", ");
+        has_any = true;
+    }
+    if let Some(policy) = patch.scratch_policy {
+        check_scratch_policy(&policy)?;
+        builder.push("scratch_policy = ").push_bind(policy).push(", ");
+        has_any = true;
+    }
+    if patch.callback_mode.is_some() || patch.callback_value.is_some() || patch.callback_ties.is_some() {
+        // se valida la combinación final, no sólo los campos que llegan
+        let (mode, value, ties): (String, Option<i64>, String) =
+            sqlx::query_as("SELECT callback_mode, callback_value, callback_ties FROM event WHERE id = ?1")
+                .bind(id)
+                .fetch_one(pool)
+                .await?;
+        let mode = patch.callback_mode.unwrap_or(mode);
+        let value = patch.callback_value.or(value);
+        let ties = patch.callback_ties.unwrap_or(ties);
+        check_callback(&mode, value, &ties)?;
+        builder.push("callback_mode = ").push_bind(mode).push(", ");
+        builder.push("callback_value = ").push_bind(value).push(", ");
+        builder.push("callback_ties = ").push_bind(ties).push(", ");
+        has_any = true;
+    }
+    if let Some(order) = patch.final_order {
+        check_final_order(&order)?;
+        builder.push("final_order = ").push_bind(order).push(", ");
+        has_any = true;
+    }
+    if let Some(new_pin) = patch.admin_pin.as_deref().filter(|p| !p.trim().is_empty()) {
+        require_pin(pool, id, patch.pin.as_deref()).await?;
+        builder.push("admin_pin = ").push_bind(hash_pin(Some(new_pin))?).push(", ");
+        has_any = true;
+    }
+
+    // Sin cambios no hay UPDATE, pero una versión vieja sigue siendo Conflict
+    if !has_any {
+        if patch.expected_version.is_some_and(|v| v != version) {
+            return Err(AppError::stale(STALE_EVENT, get(pool, id).await?));
+        }
+        return Ok(());
+    }
+
+    let before = snapshot(pool, "event", id).await?;
+    builder
+        .push("updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ")
+        .push_bind(id);
+    if let Some(expected) = patch.expected_version {
+        builder.push(" AND version = ").push_bind(expected);
+    }
+
+    let res = builder
+        .build()
+        .execute(pool)
+        .await?;
+    if res.rows_affected() == 0 {
+        return Err(AppError::stale(STALE_EVENT, get(pool, id).await?));
+    }
+    
+    if completing {
+        activity::end_session(pool, activity::EVENT_DAY, id, None).await?;
+    }
+    let after = snapshot(pool, "event", id).await?;
+    log_change(pool, "update_event", "event", Some(id), None, before, after).await?;
+    Ok(())
+}
+
+pub async fn delete(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    // Verificar existencia y estado
+    let status_opt: Option<String> =
+        sqlx::query_scalar("SELECT status FROM event WHERE id = ?1 AND is_deleted = 0")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+
+    let Some(_status) = status_opt else {
+        return Err(AppError::not_found("Evento no encontrado."));
+    };
+
+    // if status == "locked" {
+    //     return Err("El evento está bloqueado; no se puede eliminar.".into());
+    // }
+
+    let before = snapshot(pool, "event", id).await?;
+    // Soft-delete: marcar is_deleted = 1. No cambiamos status a 'archived' porque el CHECK constraint no lo permite.
+    let res = sqlx::query("UPDATE event SET is_deleted = 1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 1 {
+        let after = snapshot(pool, "event", id).await?;
+        log_change(pool, "delete_event", "event", Some(id), None, before, after).await?;
+        Ok(())
+    } else {
+        Err(AppError::not_found("Evento no encontrado."))
+    }
+}
+
+pub async fn duplicate(pool: &SqlitePool, id: i64) -> AppResult<i64> {
+    let row = sqlx::query(
+        r#"SELECT series_id, name, date, status, rounds, entry_fee, prize_pool, location, max_team_rating, payoff_allocation, scratch_policy,
+                  callback_mode, callback_value, callback_ties, final_order
+           FROM event WHERE id = ?1"#,
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    let series_id: i64 = row.try_get("series_id")?;
+    let name_opt: Option<String> = row.try_get("name").ok();
+    let date_opt: Option<String> = row.try_get("date").ok();
+    let status_opt: Option<String> = row.try_get("status").ok();
+    let rounds_opt: Option<i64> = row.try_get("rounds").ok();
+    let entry_fee_opt: Option<f64> = row.try_get("entry_fee").ok();
+    let prize_pool_opt: Option<f64> = row.try_get("prize_pool").ok();
+    let location_opt: Option<String> = row.try_get("location").ok();
+    let max_team_rating_opt: Option<f64> = row.try_get("max_team_rating").ok();
+    let payoff_allocation_opt: Option<String> = row.try_get("payoff_allocation").ok();
+    let scratch_policy: String = row.try_get("scratch_policy")?;
+    let callback_mode: String = row.try_get("callback_mode")?;
+    let callback_value: Option<i64> = row.try_get("callback_value")?;
+    let callback_ties: String = row.try_get("callback_ties")?;
+    let final_order: String = row.try_get("final_order")?;
+
+    // bloquear duplicado si está locked
+    if let Some(st) = status_opt.as_ref() {
+        if st == "locked" {
+            return Err(AppError::event_locked("Evento bloqueado; no se puede duplicar."));
+        }
+    }
+
+    let base_name = name_opt.unwrap_or_default();
+    let new_name = format!("{} (Copy)", base_name);
+
+    let res = sqlx::query(
+        r#"INSERT INTO event (series_id, name, date, status, rounds, entry_fee, prize_pool, location, max_team_rating, payoff_allocation, scratch_policy,
+                            callback_mode, callback_value, callback_ties, final_order, created_at, updated_at)
+           VALUES (?1, ?2, ?3, 'upcoming', ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, strftime('%Y-%m-%dT%H:%M:%SZ','now'), strftime('%Y-%m-%dT%H:%M:%SZ','now'))"#)
+        .bind(series_id)
+        .bind(new_name)
+        .bind(date_opt)
+        .bind(rounds_opt)
+        .bind(entry_fee_opt)
+        .bind(prize_pool_opt)
+        .bind(location_opt)
+        .bind(max_team_rating_opt)
+        .bind(payoff_allocation_opt)
+        .bind(scratch_policy)
+        .bind(callback_mode)
+        .bind(callback_value)
+        .bind(callback_ties)
+        .bind(final_order)
+        .execute(pool)
+        .await?;
+
+    let new_id = res.last_insert_rowid();
+    penalties::copy_types(pool, id, new_id).await?;
+    let after = snapshot(pool, "event", new_id).await?;
+    log_change(pool, "duplicate_event", "event", Some(new_id), Some(format!("Copied from {}", id)), None, after).await?;
+    Ok(new_id)
+}
+
+pub async fn lock(pool: &SqlitePool, event_id: i64) -> AppResult<()> {
+    let before = snapshot(pool, "event", event_id).await?;
+    sqlx::query(
+        "UPDATE event SET status = 'locked', updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?1"
+    )
+    .bind(event_id)
+    .execute(pool)
+    .await?;
+    
+    let after = snapshot(pool, "event", event_id).await?;
+    log_change(pool, "lock_event", "event", Some(event_id), None, before, after).await?;
+    Ok(())
+}
+
+/* ---- PIN del evento ---- */
+
+/// Hash argon2 del PIN; `None` si no se indicó ninguno.
+fn hash_pin(pin: Option<&str>) -> AppResult<Option<String>> {
+    let Some(pin) = pin.map(str::trim).filter(|p| !p.is_empty()) else {
+        return Ok(None);
+    };
+    if pin.len() != 4 || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::validation("admin_pin", "El PIN de administrador debe ser de 4 dígitos numéricos."));
+    }
+    hash_password(pin).map(Some)
+}
+
+/// Versiones anteriores guardaban el PIN en claro.
+fn is_hashed(stored: &str) -> bool {
+    stored.starts_with("$argon2")
+}
+
+async fn current_status(pool: &SqlitePool, event_id: i64) -> AppResult<Option<String>> {
+    sqlx::query_scalar::<_, Option<String>>("SELECT status FROM event WHERE id = ?1 AND is_deleted = 0")
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Evento no encontrado."))
+}
+
+/// Intentos fallidos de PIN por evento antes de bloquear la verificación un rato.
+pub const PIN_MAX_ATTEMPTS: i64 = 5;
+pub const PIN_LOCKOUT_MINUTES: i64 = 15;
+
+/// `true` si el evento no tiene PIN o si `pin` coincide. Sin registro ni límite de intentos:
+/// sólo para uso interno, los comandos pasan por `verify_pin`/`require_pin`.
+async fn pin_matches(pool: &SqlitePool, event_id: i64, pin: &str) -> AppResult<bool> {
+    let stored: Option<String> = sqlx::query_scalar::<_, Option<String>>("SELECT admin_pin FROM event WHERE id = ?1")
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Evento no encontrado."))?;
+
+    Ok(match stored.as_deref().filter(|s| !s.is_empty()) {
+        None => true,
+        Some(hash) if is_hashed(hash) => verify_password(hash, pin.trim()),
+        Some(plain) => plain == pin.trim(),
+    })
+}
+
+/// Un PIN de 4 dígitos se adivina probando: tras `PIN_MAX_ATTEMPTS` PIN incorrectos
+/// (`pin_rejected` en el audit; no cuentan los pedidos sin PIN) en `PIN_LOCKOUT_MINUTES` minutos,
+/// el evento no acepta más intentos de ese usuario hasta que pase la ventana, ni siquiera el
+/// PIN correcto. Se cuenta por usuario (`acting_user`) para que un operador que se equivoca no
+/// deje al administrador sin poder corregir.
+async fn check_pin_attempts(pool: &SqlitePool, event_id: i64) -> AppResult<()> {
+    let rejected: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(1) FROM audit_log
+        WHERE action = 'pin_rejected' AND entity_type = 'event' AND entity_id = ?1 AND metadata IS NULL
+          AND user_id IS ?3
+          AND created_at >= strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?2)
+        "#,
+    )
+    .bind(event_id)
+    .bind(format!("-{} minutes", PIN_LOCKOUT_MINUTES))
+    .bind(acting_user())
+    .fetch_one(pool)
+    .await?;
+    if rejected >= PIN_MAX_ATTEMPTS {
+        return Err(AppError::conflict(format!(
+            "Demasiados intentos de PIN incorrectos para este evento desde tu usuario; espera {} minutos.",
+            PIN_LOCKOUT_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// `true` si el evento no tiene PIN o si `pin` coincide. Cada fallo queda como `pin_rejected`
+/// y cuenta para el bloqueo por intentos (`check_pin_attempts`).
+pub async fn verify_pin(pool: &SqlitePool, event_id: i64, pin: &str) -> AppResult<bool> {
+    check_pin_attempts(pool, event_id).await?;
+    if pin_matches(pool, event_id, pin).await? {
+        return Ok(true);
+    }
+    let missing = pin.trim().is_empty().then(|| "sin PIN".to_string());
+    log_audit(pool, "pin_rejected", "event", Some(event_id), missing).await?;
+    Ok(false)
+}
+
+/// Para acciones protegidas (desbloquear, editar corridas completadas, regenerar sorteos):
+/// error de validación en `pin` si el evento tiene PIN y no se indicó el correcto.
+pub async fn require_pin(pool: &SqlitePool, event_id: i64, pin: Option<&str>) -> AppResult<()> {
+    if verify_pin(pool, event_id, pin.unwrap_or("")).await? {
+        return Ok(());
+    }
+    Err(AppError::validation(
+        "pin",
+        if pin.is_some() { "PIN de administrador incorrecto." } else { "Esta acción requiere el PIN de administrador del evento." },
+    ))
+}
+
+/// Hashea los PIN que quedaron en claro de versiones anteriores. Devuelve cuántos cambió.
+pub async fn hash_plaintext_pins(pool: &SqlitePool) -> AppResult<u64> {
+    let rows: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, admin_pin FROM event WHERE admin_pin IS NOT NULL AND admin_pin <> '' AND admin_pin NOT LIKE '$argon2%'")
+            .fetch_all(pool)
+            .await?;
+    for (id, plain) in &rows {
+        sqlx::query("UPDATE event SET admin_pin = ?1 WHERE id = ?2")
+            .bind(hash_password(plain.trim())?)
+            .bind(id)
+            .execute(pool)
+            .await?;
+    }
+    if !rows.is_empty() {
+        log_audit(pool, "hash_event_pins", "event", None, Some(format!("{} PIN(s) en claro hasheados", rows.len()))).await?;
+    }
+    Ok(rows.len() as u64)
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use rust_xlsxwriter::*;
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use super::audit::{self, log_audit};
+use super::{events, payoffs, penalties, runs, standings};
+
+#[derive(serde::Deserialize)]
+pub struct ExportOptions {
+    pub overview: bool,
+    pub teams: bool,
+    pub run_order: bool,
+    pub standings: bool,
+    pub payoffs: bool,
+    pub event_logs: bool,
+    pub file_path: String,
+}
+
+pub async fn event_to_excel(pool: &SqlitePool, event_id: i64, options: ExportOptions) -> AppResult<()> {
+    let mut workbook = Workbook::new();
+
+    // 1. Overview
+    if options.overview {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Overview")?;
+        // Fetch event info
+        let event = events::get(pool, event_id).await?;
+        
+        worksheet.write_string(0, 0, "Event Name")?;
+        worksheet.write_string(0, 1, &event.name)?;
+        worksheet.write_string(1, 0, "Date")?;
+        worksheet.write_string(1, 1, &event.date)?;
+        worksheet.write_string(2, 0, "Status")?;
+        worksheet.write_string(2, 1, event.status.as_deref().unwrap_or(""))?;
+        worksheet.write_string(3, 0, "Location")?;
+        worksheet.write_string(3, 1, event.location.as_deref().unwrap_or(""))?;
+        worksheet.write_string(4, 0, "Final Round Order")?;
+        worksheet.write_string(4, 1, &event.final_order)?;
+    }
+
+    // 2. Teams
+    if options.teams {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Teams")?;
+        worksheet.write_string(0, 0, "ID")?;
+        worksheet.write_string(0, 1, "Header")?;
+        worksheet.write_string(0, 2, "Heeler")?;
+        worksheet.write_string(0, 3, "Rating")?;
+        worksheet.write_string(0, 4, "Status")?;
+
+        let teams_expanded: Vec<(i64, String, String, f64, String)> = sqlx::query_as(
+            r#"
+            SELECT t.id, 
+                   (rh.first_name || ' ' || rh.last_name),
+                   (rhe.first_name || ' ' || rhe.last_name),
+                   t.rating, CASE WHEN t.scratched_at IS NOT NULL THEN 'scratched' ELSE t.status END
+            FROM team t
+            JOIN roper rh ON t.header_id = rh.id
+            JOIN roper rhe ON t.heeler_id = rhe.id
+            WHERE t.event_id = ?1
+            ORDER BY t.id
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+
+        for (i, (id, header, heeler, rating, status)) in teams_expanded.iter().enumerate() {
+            let row = (i + 1) as u32;
+            worksheet.write_number(row, 0, *id as f64)?;
+            worksheet.write_string(row, 1, header)?;
+            worksheet.write_string(row, 2, heeler)?;
+            worksheet.write_number(row, 3, *rating)?;
+            worksheet.write_string(row, 4, status)?;
+        }
+    }
+
+    // 3. Run Order
+    if options.run_order {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Run Order")?;
+        let runs = runs::list_expanded(pool, event_id, None).await?;
+        worksheet.write_string(0, 0, "Round")?;
+        worksheet.write_string(0, 1, "Position")?;
+        worksheet.write_string(0, 2, "Header")?;
+        worksheet.write_string(0, 3, "Heeler")?;
+        worksheet.write_string(0, 4, "Time")?;
+        worksheet.write_string(0, 5, "Penalty")?;
+        worksheet.write_string(0, 6, "Total")?;
+        worksheet.write_string(0, 7, "Status")?;
+        worksheet.write_string(0, 8, "Draw Method")?;
+        worksheet.write_string(0, 9, "Penalties")?;
+        let methods = draw_methods(pool, event_id).await?;
+
+        for (i, run) in runs.iter().enumerate() {
+            let row = (i + 1) as u32;
+            worksheet.write_number(row, 0, run.round as f64)?;
+            worksheet.write_number(row, 1, run.position as f64)?;
+            worksheet.write_string(row, 2, &run.header_name)?;
+            worksheet.write_string(row, 3, &run.heeler_name)?;
+            if let Some(t) = run.time_sec { worksheet.write_number(row, 4, t)?; }
+            worksheet.write_number(row, 5, run.penalty)?;
+            if let Some(t) = run.total_sec { worksheet.write_number(row, 6, t)?; }
+            worksheet.write_string(row, 7, run_status(run))?;
+            worksheet.write_string(row, 8, methods.get(&run.round).map(String::as_str).unwrap_or(""))?;
+            worksheet.write_string(row, 9, run_penalties(run))?;
+        }
+    }
+
+    // 4. Standings
+    if options.standings {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Standings")?;
+        let standings = standings::get(pool, event_id).await?;
+        worksheet.write_string(0, 0, "Rank")?;
+        worksheet.write_string(0, 1, "Header")?;
+        worksheet.write_string(0, 2, "Heeler")?;
+        worksheet.write_string(0, 3, "Total Time")?;
+        worksheet.write_string(0, 4, "Caught")?;
+        worksheet.write_string(0, 5, "Avg Time")?;
+        worksheet.write_string(0, 6, "Penalty Total")?;
+        worksheet.write_string(0, 7, "Penalties")?;
+
+        for (i, s) in standings.iter().enumerate() {
+            let row = (i + 1) as u32;
+            worksheet.write_number(row, 0, s.rank as f64)?;
+            worksheet.write_string(row, 1, &s.header_name)?;
+            worksheet.write_string(row, 2, &s.heeler_name)?;
+            if let Some(t) = s.total_time { worksheet.write_number(row, 3, t)?; }
+            worksheet.write_number(row, 4, s.completed_runs as f64)?;
+            if let Some(t) = s.avg_time { worksheet.write_number(row, 5, t)?; }
+            worksheet.write_number(row, 6, s.penalty_total)?;
+            worksheet.write_string(row, 7, standing_penalties(s))?;
+        }
+    }
+
+    // 5. Payoffs
+    if options.payoffs {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Payoffs")?;
+        let breakdown = payoffs::payout_breakdown(pool, event_id).await?;
+        
+        worksheet.write_string(0, 0, "Total Pot")?;
+        worksheet.write_number(0, 1, breakdown.total_pot)?;
+        worksheet.write_string(1, 0, "Deductions")?;
+        worksheet.write_number(1, 1, breakdown.deductions)?;
+        worksheet.write_string(2, 0, "Net Pot")?;
+        worksheet.write_number(2, 1, breakdown.net_pot)?;
+
+        worksheet.write_string(4, 0, "Place")?;
+        worksheet.write_string(4, 1, "Percentage")?;
+        worksheet.write_string(4, 2, "Amount")?;
+        worksheet.write_string(4, 3, "Per Person")?;
+
+        for (i, p) in breakdown.payouts.iter().enumerate() {
+            let row = (i + 5) as u32;
+            worksheet.write_number(row, 0, p.place as f64)?;
+            worksheet.write_number(row, 1, p.percentage)?;
+            worksheet.write_number(row, 2, p.amount)?;
+            worksheet.write_number(row, 3, p.amount / 2.0)?;
+        }
+    }
+
+    // 6. Event Logs
+    if options.event_logs {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Event Logs")?;
+        worksheet.write_string(0, 0, "Date")?;
+        worksheet.write_string(0, 1, "Action")?;
+        worksheet.write_string(0, 2, "User")?;
+        worksheet.write_string(0, 3, "Details")?;
+        worksheet.write_string(0, 4, "Before")?;
+        worksheet.write_string(0, 5, "After")?;
+
+        // Todo lo del evento (ver `audit::event_logs`); el usuario por nombre, no por id.
+        let logs = audit::event_logs(pool, event_id).await?;
+
+        for (i, log) in logs.iter().enumerate() {
+            let row = (i + 1) as u32;
+            worksheet.write_string(row, 0, &log.created_at)?;
+            worksheet.write_string(row, 1, &log.action)?;
+            worksheet.write_string(row, 2, log.user_name.as_deref().unwrap_or("Sistema"))?;
+            worksheet.write_string(row, 3, log.metadata.as_deref().unwrap_or(""))?;
+            worksheet.write_string(row, 4, log.before_json.as_deref().unwrap_or(""))?;
+            worksheet.write_string(row, 5, log.after_json.as_deref().unwrap_or(""))?;
+        }
+    }
+
+    workbook.save(&options.file_path)?;
+    log_audit(pool, "export_event", "event", Some(event_id), Some("Exported to Excel".into())).await?;
+    Ok(())
+}
+
+/* ---- CSV (roping-admin) ---- */
+
+pub async fn standings_to_csv(pool: &SqlitePool, event_id: i64, file_path: &str) -> AppResult<()> {
+    let standings = standings::get(pool, event_id).await?;
+    let mut wtr = csv::Writer::from_path(file_path)?;
+    wtr.write_record(["Rank", "Header", "Heeler", "Total Time", "Caught", "NT", "DQ", "Avg Time", "Best Time", "Penalty Total", "Penalties"])?;
+    for s in &standings {
+        wtr.write_record([
+            s.rank.to_string(),
+            s.header_name.clone(),
+            s.heeler_name.clone(),
+            opt_num(s.total_time),
+            s.completed_runs.to_string(),
+            s.nt_cnt.to_string(),
+            s.dq_cnt.to_string(),
+            opt_num(s.avg_time),
+            opt_num(s.best_time),
+            s.penalty_total.to_string(),
+            standing_penalties(s),
+        ])?;
+    }
+    wtr.flush().map_err(|e| AppError::internal(e.to_string()))?;
+    log_audit(pool, "export_event", "event", Some(event_id), Some("Exported standings to CSV".into())).await?;
+    Ok(())
+}
+
+/// Método con que se sorteó cada ronda (`draw_round.method`); vacío si se sorteó antes de guardarlo.
+async fn draw_methods(pool: &SqlitePool, event_id: i64) -> AppResult<HashMap<i64, String>> {
+    let rows: Vec<(i64, String)> = sqlx::query_as("SELECT round, method FROM draw_round WHERE event_id = ?1")
+        .bind(event_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Estado a exportar: las corridas retiradas con `keep_slot` salen como "scratch".
+fn run_status(run: &runs::RunExpandedRow) -> &str {
+    if run.scratched == 1 {
+        "scratch"
+    } else {
+        &run.status
+    }
+}
+
+/// Desglose de la penalización de una corrida ("One-leg catch +5; Broken barrier +10").
+fn run_penalties(run: &runs::RunExpandedRow) -> String {
+    penalties::describe(run.penalties.iter().map(|p| (p.label.as_str(), p.seconds)))
+}
+
+/// Desglose por tipo en la clasificación ("One-leg catch x2 +10").
+fn standing_penalties(s: &standings::StandingRow) -> String {
+    s.penalties
+        .iter()
+        .map(|p| format!("{} x{} +{}", p.label, p.count, p.seconds))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+pub async fn runs_to_csv(pool: &SqlitePool, event_id: i64, file_path: &str) -> AppResult<()> {
+    let runs = runs::list_expanded(pool, event_id, None).await?;
+    let methods = draw_methods(pool, event_id).await?;
+    let mut wtr = csv::Writer::from_path(file_path)?;
+    wtr.write_record(["Round", "Position", "Header", "Heeler", "Time", "Penalty", "Total", "NT", "DQ", "Status", "Draw Method", "Penalties"])?;
+    for r in &runs {
+        wtr.write_record([
+            r.round.to_string(),
+            r.position.to_string(),
+            r.header_name.clone(),
+            r.heeler_name.clone(),
+            opt_num(r.time_sec),
+            r.penalty.to_string(),
+            opt_num(r.total_sec),
+            r.no_time.to_string(),
+            r.dq.to_string(),
+            run_status(r).to_string(),
+            methods.get(&r.round).cloned().unwrap_or_default(),
+            run_penalties(r),
+        ])?;
+    }
+    wtr.flush().map_err(|e| AppError::internal(e.to_string()))?;
+    log_audit(pool, "export_event", "event", Some(event_id), Some("Exported runs to CSV".into())).await?;
+    Ok(())
+}
+
+fn opt_num(v: Option<f64>) -> String {
+    v.map(|t| format!("{:.2}", t)).unwrap_or_default()
+}
@@ -0,0 +1,188 @@
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{Connection, Sqlite, SqlitePool, Transaction};
+use std::path::Path;
+
+use crate::db::MIGRATOR;
+use crate::error::{AppError, AppResult};
+use super::audit::log_audit;
+
+#[derive(Debug, serde::Serialize)]
+pub struct PurgeReport {
+    pub series: u64,
+    pub events: u64,
+    pub teams: u64,
+    pub ropers: u64,
+    pub payoff_rules: u64,
+    /// Ropers inactivos que no se borraron porque aún aparecen en algún equipo.
+    pub ropers_kept: i64,
+}
+
+/// `PRAGMA integrity_check` + `PRAGMA foreign_key_check`. Devuelve los problemas
+/// encontrados; vacío si la base está sana.
+pub async fn integrity_check(pool: &SqlitePool) -> AppResult<Vec<String>> {
+    let mut problems: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter(|line: &String| line != "ok")
+        .collect();
+
+    let fk: Vec<(String, Option<i64>, String, i64)> = sqlx::query_as("PRAGMA foreign_key_check")
+        .fetch_all(pool)
+        .await?;
+    for (table, rowid, parent, _fkid) in fk {
+        problems.push(format!(
+            "{} rowid {} referencia una fila inexistente en {}",
+            table,
+            rowid.map(|r| r.to_string()).unwrap_or_else(|| "?".into()),
+            parent
+        ));
+    }
+    Ok(problems)
+}
+
+/// Copia consistente de la base con `VACUUM INTO` (funciona con la app abierta en WAL).
+pub async fn backup_to(pool: &SqlitePool, dest: &Path) -> AppResult<()> {
+    if dest.exists() {
+        return Err(AppError::validation("path", "El archivo de destino ya existe."));
+    }
+    let dest_str = dest
+        .to_str()
+        .ok_or_else(|| AppError::validation("path", "Ruta de destino inválida."))?;
+
+    sqlx::query("VACUUM INTO ?1").bind(dest_str).execute(pool).await?;
+    log_audit(pool, "backup_database", "database", None, Some(dest_str.to_string())).await?;
+    Ok(())
+}
+
+/// Borra definitivamente las filas marcadas como eliminadas por la política de soft-delete
+/// (series/eventos `is_deleted = 1`, equipos `inactive`, ropers y reglas de payoff inactivos).
+/// Los equipos de eventos bloqueados se conservan y los ropers que todavía aparecen en un
+/// equipo también (FK RESTRICT).
+pub async fn purge_soft_deleted(pool: &SqlitePool) -> AppResult<PurgeReport> {
+    let mut tx: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    let payoff_rules = sqlx::query("DELETE FROM payoff_rule WHERE is_active = 0")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let teams = sqlx::query(
+        r#"
+        DELETE FROM team
+        WHERE status = 'inactive'
+          AND event_id NOT IN (SELECT id FROM event WHERE status = 'locked' AND is_deleted = 0)
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    // team/draw/run/payoff caen por ON DELETE CASCADE
+    let events = sqlx::query("DELETE FROM event WHERE is_deleted = 1")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let series = sqlx::query("DELETE FROM series WHERE is_deleted = 1")
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    let ropers = sqlx::query(
+        r#"
+        DELETE FROM roper
+        WHERE is_active = 0
+          AND NOT EXISTS (SELECT 1 FROM team t WHERE t.header_id = roper.id OR t.heeler_id = roper.id)
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let ropers_kept = sqlx::query_scalar("SELECT COUNT(1) FROM roper WHERE is_active = 0")
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    let report = PurgeReport {
+        series,
+        events,
+        teams,
+        ropers,
+        payoff_rules,
+        ropers_kept,
+    };
+
+    log_audit(
+        pool,
+        "purge_soft_deleted",
+        "database",
+        None,
+        serde_json::to_string(&report).ok(),
+    )
+    .await?;
+    Ok(report)
+}
+
+/// Valida un archivo de respaldo antes de restaurarlo: SQLite legible, `quick_check` ok y
+/// migraciones conocidas por esta versión (mismo checksum). Devuelve la última versión aplicada;
+/// las pendientes se aplican al abrirlo.
+pub async fn inspect_backup(path: &Path) -> AppResult<i64> {
+    if !path.is_file() {
+        return Err(AppError::validation("src", "El archivo de respaldo no existe."));
+    }
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .map_err(|_| AppError::validation("src", "El archivo no es una base de datos SQLite válida."))?;
+
+    let check: String = sqlx::query_scalar("PRAGMA quick_check")
+        .fetch_one(&mut conn)
+        .await
+        .map_err(|_| AppError::validation("src", "El archivo no es una base de datos SQLite válida."))?;
+    if check != "ok" {
+        return Err(AppError::validation("src", format!("El respaldo está dañado: {}", check)));
+    }
+
+    let has_migrations: i64 =
+        sqlx::query_scalar("SELECT COUNT(1) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'")
+            .fetch_one(&mut conn)
+            .await?;
+    if has_migrations == 0 {
+        return Err(AppError::validation("src", "El archivo no es una base de Roping Manager (no tiene migraciones)."));
+    }
+
+    let applied: Vec<(i64, Vec<u8>, bool)> =
+        sqlx::query_as("SELECT version, checksum, success FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(&mut conn)
+            .await?;
+    conn.close().await.ok();
+
+    let mut latest = 0;
+    for (version, checksum, success) in applied {
+        if !success {
+            return Err(AppError::validation("src", format!("La migración {} quedó a medias en el respaldo.", version)));
+        }
+        match MIGRATOR.iter().find(|m| m.version == version) {
+            None => {
+                return Err(AppError::validation(
+                    "src",
+                    format!("El respaldo es de una versión más nueva de la aplicación (migración {}).", version),
+                ))
+            }
+            Some(m) if m.checksum.as_ref() != checksum.as_slice() => {
+                return Err(AppError::validation(
+                    "src",
+                    format!("La migración {} del respaldo no coincide con la de esta versión.", version),
+                ))
+            }
+            Some(_) => latest = version,
+        }
+    }
+    if latest == 0 {
+        return Err(AppError::validation("src", "El respaldo no tiene migraciones aplicadas."));
+    }
+    Ok(latest)
+}
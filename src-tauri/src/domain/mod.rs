@@ -0,0 +1,35 @@
+//! Reglas de negocio sin dependencia de Tauri.
+//!
+//! Cada función recibe un `&SqlitePool`, así que la misma lógica se puede usar
+//! desde los comandos Tauri (`crate::commands`), desde tests o desde un CLI.
+
+pub mod activity;
+pub mod audit;
+pub mod dashboard;
+pub mod draw;
+pub mod draw_report;
+pub mod events;
+pub mod export;
+pub mod maintenance;
+pub mod payoffs;
+pub mod penalties;
+pub mod revisions;
+pub mod ropers;
+pub mod runs;
+pub mod series;
+pub mod settings;
+pub mod standings;
+pub mod teams;
+pub mod users;
+
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+
+pub async fn health_check(pool: &SqlitePool) -> AppResult<String> {
+    sqlx::query_scalar::<_, i64>("SELECT 1")
+        .fetch_one(pool)
+        .await
+        .map(|_| "ok".to_string())
+        .map_err(AppError::from)
+}
@@ -0,0 +1,193 @@
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use super::audit::{log_change, snapshot};
+use super::events;
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct PayoffRuleRow {
+    pub id: i64,
+    pub event_id: i64,
+    pub position: i64,
+    pub percentage: f64,
+    pub is_active: i64,
+    pub created_at: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct NewPayoffRule {
+    pub event_id: i64,
+    pub position: i64,
+    pub percentage: f64,
+}
+
+#[derive(serde::Serialize)]
+pub struct PayoutBreakdown {
+    pub total_pot: f64,
+    pub deductions: f64,
+    pub net_pot: f64,
+    pub payouts: Vec<PayoutAllocation>,
+}
+
+#[derive(serde::Serialize)]
+pub struct PayoutAllocation {
+    pub place: i64,
+    pub percentage: f64,
+    pub amount: f64,
+}
+
+pub async fn list_rules(pool: &SqlitePool, event_id: Option<i64>) -> AppResult<Vec<PayoffRuleRow>> {
+    if let Some(eid) = event_id {
+        sqlx::query_as::<_, PayoffRuleRow>(
+            r#"
+            SELECT id, event_id, position, percentage, is_active, created_at
+            FROM payoff_rule
+            WHERE event_id = ?1 AND is_active = 1
+            ORDER BY position ASC
+            "#,
+        )
+        .bind(eid)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+    } else {
+        sqlx::query_as::<_, PayoffRuleRow>(
+            r#"
+            SELECT id, event_id, position, percentage, is_active, created_at
+            FROM payoff_rule
+            WHERE is_active = 1
+            ORDER BY event_id ASC, position ASC
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+    }
+}
+
+pub async fn delete_rule(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    let before = snapshot(pool, "payoff_rule", id).await?;
+    let res = sqlx::query("UPDATE payoff_rule SET is_active = 0 WHERE id = ?1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::not_found("Payoff rule no encontrada."));
+    }
+    let after = snapshot(pool, "payoff_rule", id).await?;
+    log_change(pool, "delete_payoff_rule", "payoff_rule", Some(id), None, before, after).await?;
+    Ok(())
+}
+
+pub async fn create_rule(pool: &SqlitePool, rule: NewPayoffRule) -> AppResult<i64> {
+    // Validate percentage (0.0 - 1.0)
+    if rule.percentage < 0.0 || rule.percentage > 1.0 {
+        return Err(AppError::validation("percentage", "Percentage must be between 0.0 and 1.0"));
+    }
+
+    // Check if rule for this position already exists for this event (active or inactive)
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM payoff_rule WHERE event_id = ?1 AND position = ?2")
+            .bind(rule.event_id)
+            .bind(rule.position)
+            .fetch_optional(pool)
+            .await?;
+
+    if let Some(id) = exists {
+        // Update existing rule (and reactivate it if it was deleted)
+        let before = snapshot(pool, "payoff_rule", id).await?;
+        sqlx::query("UPDATE payoff_rule SET percentage = ?1, is_active = 1 WHERE id = ?2")
+            .bind(rule.percentage)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        let after = snapshot(pool, "payoff_rule", id).await?;
+        log_change(pool, "update_payoff_rule", "payoff_rule", Some(id), None, before, after).await?;
+        Ok(id)
+    } else {
+        // Create new rule
+        let res = sqlx::query(
+            r#"
+            INSERT INTO payoff_rule (event_id, position, percentage, is_active)
+            VALUES (?1, ?2, ?3, 1)
+            "#,
+        )
+        .bind(rule.event_id)
+        .bind(rule.position)
+        .bind(rule.percentage)
+        .execute(pool)
+        .await?;
+        let new_id = res.last_insert_rowid();
+        let after = snapshot(pool, "payoff_rule", new_id).await?;
+        log_change(pool, "create_payoff_rule", "payoff_rule", Some(new_id), None, None, after).await?;
+        Ok(new_id)
+    }
+}
+
+/// Pot math: each unique roper pays the entry fee once, plus any added
+/// prize money, split among the active payoff rules.
+pub fn compute_breakdown(
+    unique_ropers: i64,
+    entry_fee: f64,
+    prize_pool: f64,
+    rules: &[PayoffRuleRow],
+) -> PayoutBreakdown {
+    let total_pot = (unique_ropers as f64 * entry_fee) + prize_pool;
+
+    // Deductions (Placeholder: 0% for now, or make it configurable later)
+    let deduction_pct = 0.0;
+    let deductions = total_pot * deduction_pct;
+    let net_pot = total_pot - deductions;
+
+    let payouts = rules
+        .iter()
+        .map(|r| PayoutAllocation {
+            place: r.position,
+            percentage: r.percentage,
+            amount: net_pot * r.percentage,
+        })
+        .collect();
+
+    PayoutBreakdown {
+        total_pot,
+        deductions,
+        net_pot,
+        payouts,
+    }
+}
+
+pub async fn payout_breakdown(pool: &SqlitePool, event_id: i64) -> AppResult<PayoutBreakdown> {
+    // 1. Get Event Details (Entry Fee, Prize Pool)
+    let event = events::get(pool, event_id).await?;
+
+    // 2. Count unique ropers in the event (each roper pays once, regardless of how many teams they're in)
+    let unique_ropers: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(DISTINCT roper_id) FROM (
+            SELECT header_id AS roper_id FROM team WHERE event_id = ?1 AND status = 'active'
+            UNION
+            SELECT heeler_id AS roper_id FROM team WHERE event_id = ?1 AND status = 'active'
+        )
+        "#
+    )
+    .bind(event_id)
+    .fetch_one(pool)
+    .await?;
+
+    // 3. Get Payoff Rules
+    let rules: Vec<PayoffRuleRow> = sqlx::query_as(
+        "SELECT id, event_id, position, percentage, is_active, created_at FROM payoff_rule WHERE event_id = ?1 AND is_active = 1 ORDER BY position ASC"
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    // 4. Calculate Pot and Allocations
+    Ok(compute_breakdown(
+        unique_ropers,
+        event.entry_fee.unwrap_or(0.0),
+        event.prize_pool.unwrap_or(0.0),
+        &rules,
+    ))
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use super::audit::{log_change, snapshot};
+use super::events;
+
+/// Tipos estándar con los que nace todo evento (`code`, etiqueta, segundos).
+pub const DEFAULT_TYPES: [(&str, &str, f64); 2] = [("leg", "One-leg catch", 5.0), ("barrier", "Broken barrier", 10.0)];
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct PenaltyTypeRow {
+    pub id: i64,
+    pub event_id: i64,
+    pub code: String,
+    pub label: String,
+    pub seconds: f64,
+    pub is_active: i64,
+    pub created_at: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct NewPenaltyType {
+    pub event_id: i64,
+    pub code: String,
+    pub label: String,
+    pub seconds: f64,
+}
+
+#[derive(serde::Deserialize, Default)]
+pub struct PenaltyTypePatch {
+    pub label: Option<String>,
+    pub seconds: Option<f64>,
+    pub is_active: Option<bool>,
+}
+
+/// Renglón de penalización de una corrida. `label`/`seconds` se copian del tipo al capturar;
+/// `penalty_type_id` es nulo en las penalizaciones sueltas de antes de los tipos.
+#[derive(serde::Serialize, sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct RunPenaltyRow {
+    pub id: i64,
+    pub run_id: i64,
+    pub penalty_type_id: Option<i64>,
+    pub label: String,
+    pub seconds: f64,
+}
+
+/// Renglón ya resuelto contra el catálogo del evento, listo para guardarse.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct PenaltyItem {
+    pub penalty_type_id: Option<i64>,
+    pub label: String,
+    pub seconds: f64,
+}
+
+impl From<RunPenaltyRow> for PenaltyItem {
+    fn from(row: RunPenaltyRow) -> Self {
+        PenaltyItem { penalty_type_id: row.penalty_type_id, label: row.label, seconds: row.seconds }
+    }
+}
+
+fn check_seconds(seconds: f64) -> AppResult<()> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(AppError::validation("seconds", "Los segundos de penalización no pueden ser negativos."));
+    }
+    Ok(())
+}
+
+fn check_label(label: &str) -> AppResult<()> {
+    if label.trim().is_empty() {
+        return Err(AppError::validation("label", "La penalización necesita un nombre."));
+    }
+    Ok(())
+}
+
+pub async fn list_types(pool: &SqlitePool, event_id: i64, include_inactive: bool) -> AppResult<Vec<PenaltyTypeRow>> {
+    sqlx::query_as::<_, PenaltyTypeRow>(
+        r#"
+        SELECT id, event_id, code, label, seconds, is_active, created_at
+        FROM penalty_type
+        WHERE event_id = ?1 AND (?2 = 1 OR is_active = 1)
+        ORDER BY seconds ASC, id ASC
+        "#,
+    )
+    .bind(event_id)
+    .bind(include_inactive as i32)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+/// Da de alta los tipos estándar de un evento recién creado.
+pub async fn seed_defaults(pool: &SqlitePool, event_id: i64) -> AppResult<()> {
+    for (code, label, seconds) in DEFAULT_TYPES {
+        sqlx::query("INSERT OR IGNORE INTO penalty_type (event_id, code, label, seconds) VALUES (?1, ?2, ?3, ?4)")
+            .bind(event_id)
+            .bind(code)
+            .bind(label)
+            .bind(seconds)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Copia el catálogo (activos e inactivos) de un evento a otro; lo usa `events::duplicate`.
+pub async fn copy_types(pool: &SqlitePool, from_event: i64, to_event: i64) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO penalty_type (event_id, code, label, seconds, is_active)
+        SELECT ?2, code, label, seconds, is_active FROM penalty_type WHERE event_id = ?1
+        "#,
+    )
+    .bind(from_event)
+    .bind(to_event)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn create_type(pool: &SqlitePool, payload: NewPenaltyType) -> AppResult<i64> {
+    events::ensure_event_unlocked(pool, payload.event_id).await?;
+    let code = payload.code.trim().to_lowercase();
+    if code.is_empty() {
+        return Err(AppError::validation("code", "La penalización necesita un código."));
+    }
+    check_label(&payload.label)?;
+    check_seconds(payload.seconds)?;
+
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM penalty_type WHERE event_id = ?1 AND code = ?2")
+        .bind(payload.event_id)
+        .bind(&code)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_some() {
+        return Err(AppError::validation("code", "Ya existe una penalización con ese código en el evento."));
+    }
+
+    let res = sqlx::query("INSERT INTO penalty_type (event_id, code, label, seconds) VALUES (?1, ?2, ?3, ?4)")
+        .bind(payload.event_id)
+        .bind(&code)
+        .bind(payload.label.trim())
+        .bind(payload.seconds)
+        .execute(pool)
+        .await?;
+    let id = res.last_insert_rowid();
+    let after = snapshot(pool, "penalty_type", id).await?;
+    log_change(pool, "create_penalty_type", "penalty_type", Some(id), Some(code), None, after).await?;
+    Ok(id)
+}
+
+/// Los tipos no se borran: se desactivan, así las corridas que ya los usan siguen apuntando a algo.
+/// Cambiar segundos o etiqueta sólo afecta a las capturas siguientes.
+pub async fn update_type(pool: &SqlitePool, id: i64, patch: PenaltyTypePatch) -> AppResult<()> {
+    let event_id: i64 = sqlx::query_scalar("SELECT event_id FROM penalty_type WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Tipo de penalización no encontrado."))?;
+    events::ensure_event_unlocked(pool, event_id).await?;
+    if let Some(label) = patch.label.as_deref() {
+        check_label(label)?;
+    }
+    if let Some(seconds) = patch.seconds {
+        check_seconds(seconds)?;
+    }
+
+    let before = snapshot(pool, "penalty_type", id).await?;
+    sqlx::query(
+        r#"
+        UPDATE penalty_type SET
+          label     = COALESCE(?1, label),
+          seconds   = COALESCE(?2, seconds),
+          is_active = COALESCE(?3, is_active)
+        WHERE id = ?4
+        "#,
+    )
+    .bind(patch.label.as_deref().map(str::trim))
+    .bind(patch.seconds)
+    .bind(patch.is_active.map(|a| a as i32))
+    .bind(id)
+    .execute(pool)
+    .await?;
+    let after = snapshot(pool, "penalty_type", id).await?;
+    log_change(pool, "update_penalty_type", "penalty_type", Some(id), None, before, after).await?;
+    Ok(())
+}
+
+/// Convierte los tipos pedidos en renglones con la etiqueta y los segundos vigentes.
+/// Un tipo puede repetirse (dos barreras son dos renglones); debe ser del evento y estar activo.
+pub async fn resolve(pool: &SqlitePool, event_id: i64, type_ids: &[i64]) -> AppResult<Vec<PenaltyItem>> {
+    let types: HashMap<i64, PenaltyTypeRow> = list_types(pool, event_id, false)
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t))
+        .collect();
+    type_ids
+        .iter()
+        .map(|id| {
+            types
+                .get(id)
+                .map(|t| PenaltyItem { penalty_type_id: Some(t.id), label: t.label.clone(), seconds: t.seconds })
+                .ok_or_else(|| {
+                    AppError::validation("penalties", format!("La penalización {} no es un tipo activo de este evento.", id))
+                })
+        })
+        .collect()
+}
+
+/// Reemplaza los renglones de una corrida, dentro de la transacción que abrió quien llama
+/// (el guardado o el deshacer de la corrida).
+pub async fn replace_for_run(conn: &mut SqliteConnection, run_id: i64, items: &[PenaltyItem]) -> AppResult<()> {
+    sqlx::query("DELETE FROM run_penalty WHERE run_id = ?1")
+        .bind(run_id)
+        .execute(&mut *conn)
+        .await?;
+    for item in items {
+        sqlx::query("INSERT INTO run_penalty (run_id, penalty_type_id, label, seconds) VALUES (?1, ?2, ?3, ?4)")
+            .bind(run_id)
+            .bind(item.penalty_type_id)
+            .bind(&item.label)
+            .bind(item.seconds)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn for_run<'e>(executor: impl SqliteExecutor<'e>, run_id: i64) -> AppResult<Vec<RunPenaltyRow>> {
+    sqlx::query_as::<_, RunPenaltyRow>(
+        "SELECT id, run_id, penalty_type_id, label, seconds FROM run_penalty WHERE run_id = ?1 ORDER BY id ASC",
+    )
+    .bind(run_id)
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+/// Todos los renglones de un evento agrupados por corrida, en una sola consulta.
+pub async fn by_run(pool: &SqlitePool, event_id: i64) -> AppResult<HashMap<i64, Vec<RunPenaltyRow>>> {
+    let rows = sqlx::query_as::<_, RunPenaltyRow>(
+        r#"
+        SELECT p.id, p.run_id, p.penalty_type_id, p.label, p.seconds
+        FROM run_penalty p
+        JOIN run r ON r.id = p.run_id
+        WHERE r.event_id = ?1
+        ORDER BY p.run_id ASC, p.id ASC
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+    let mut map: HashMap<i64, Vec<RunPenaltyRow>> = HashMap::new();
+    for row in rows {
+        map.entry(row.run_id).or_default().push(row);
+    }
+    Ok(map)
+}
+
+/// "One-leg catch +5; Broken barrier +10" para exportes y auditoría.
+pub fn describe<'a>(items: impl IntoIterator<Item = (&'a str, f64)>) -> String {
+    items
+        .into_iter()
+        .map(|(label, seconds)| format!("{} +{}", label, seconds))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,510 @@
+//! Historial de corridas (`run_revision`): una fila por versión, sólo inserción.
+//!
+//! Cada captura o corrección guarda los valores que tenía la corrida y los que quedaron,
+//! con el autor (`acting_user`) y el motivo. Sirve para reconstruir una protesta.
+//! El historial es del equipo en la ronda (`event_id`, `team_id`, `round`): sobrevive a que se
+//! borre la fila de `run` al rehacer un sorteo (ahí `run_id` queda en NULL).
+//!
+//! Con los renglones de penalización y los cambios de status en rondas siguientes que también
+//! guarda, la última captura del evento se puede deshacer y rehacer (`undo_last`/`redo_last`):
+//! cada una agrega su propia revisión `undo`/`redo`, el historial nunca se reescribe.
+
+use sqlx::{SqliteExecutor, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use super::audit::{acting_user, log_audit};
+use super::events::require_pin;
+use super::penalties::{self, PenaltyItem};
+
+/// Valores de una corrida que se versionan.
+#[derive(serde::Serialize, sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct RunValues {
+    pub time_sec: Option<f64>,
+    pub penalty: f64,
+    pub total_sec: Option<f64>,
+    pub no_time: i64,
+    pub dq: i64,
+    pub status: String,
+}
+
+impl RunValues {
+    /// Corrida sin capturar, como la deja el sorteo.
+    pub fn pending() -> Self {
+        RunValues { time_sec: None, penalty: 0.0, total_sec: None, no_time: 0, dq: 0, status: "pending".into() }
+    }
+}
+
+#[derive(serde::Serialize, sqlx::FromRow, Debug)]
+pub struct RunRevisionRow {
+    pub id: i64,
+    /// `None` si la fila de `run` de esa versión ya no existe (sorteo rehecho).
+    pub run_id: Option<i64>,
+    /// 1, 2, 3… por equipo y ronda.
+    pub revision: i64,
+    /// `baseline` | `capture` | `correction` | `recompute` | `undo` | `redo`.
+    pub action: String,
+    pub old_time_sec: Option<f64>,
+    pub old_penalty: Option<f64>,
+    pub old_total_sec: Option<f64>,
+    pub old_no_time: Option<i64>,
+    pub old_dq: Option<i64>,
+    pub old_status: Option<String>,
+    pub new_time_sec: Option<f64>,
+    pub new_penalty: f64,
+    pub new_total_sec: Option<f64>,
+    pub new_no_time: i64,
+    pub new_dq: i64,
+    pub new_status: String,
+    /// Desglose de penalizaciones de la versión nueva (`penalties::describe`).
+    pub penalties: Option<String>,
+    pub reason: Option<String>,
+    /// En `undo`/`redo`: número de la revisión que revierte o vuelve a aplicar.
+    pub target_revision: Option<i64>,
+    pub user_id: Option<i64>,
+    pub user_name: Option<String>,
+    pub created_at: String,
+}
+
+/// Cambio de status que un guardado hizo en otra corrida del equipo (NT/DQ → `skipped`,
+/// tiempo válido → `pending`).
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct CascadeChange {
+    pub run_id: i64,
+    pub from: String,
+    pub to: String,
+}
+
+/// Revisión por agregar; `old` es lo que había y los valores nuevos se leen de la fila tal
+/// como quedó.
+#[derive(Default)]
+pub struct NewRevision<'a> {
+    pub run_id: i64,
+    pub action: &'a str,
+    pub old: Option<&'a RunValues>,
+    /// Desglose legible de las penalizaciones nuevas.
+    pub penalties: Option<&'a str>,
+    pub reason: Option<&'a str>,
+    pub target_id: Option<i64>,
+    pub cascade: &'a [CascadeChange],
+    pub old_items: Option<&'a [PenaltyItem]>,
+    pub new_items: Option<&'a [PenaltyItem]>,
+}
+
+/// Valores actuales de la corrida `run_id`.
+pub async fn current<'e>(executor: impl SqliteExecutor<'e>, run_id: i64) -> AppResult<Option<RunValues>> {
+    sqlx::query_as::<_, RunValues>("SELECT time_sec, penalty, total_sec, no_time, dq, status FROM run WHERE id = ?1")
+        .bind(run_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> AppResult<String> {
+    serde_json::to_string(value).map_err(|e| AppError::internal(e.to_string()))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> AppResult<T> {
+    serde_json::from_str(json).map_err(|e| AppError::internal(format!("Historial ilegible: {}", e)))
+}
+
+/// Agrega la siguiente revisión del equipo en la ronda de la corrida. Devuelve el número de revisión.
+pub async fn record<'e>(executor: impl SqliteExecutor<'e>, rev: NewRevision<'_>) -> AppResult<i64> {
+    let reason = rev.reason.map(str::trim).filter(|r| !r.is_empty());
+    let cascade = if rev.cascade.is_empty() { None } else { Some(to_json(rev.cascade)?) };
+    let old_items = rev.old_items.map(to_json).transpose()?;
+    let new_items = rev.new_items.map(to_json).transpose()?;
+    let old = rev.old;
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO run_revision (run_id, event_id, team_id, round, revision, action,
+                                  old_time_sec, old_penalty, old_total_sec, old_no_time, old_dq, old_status,
+                                  new_time_sec, new_penalty, new_total_sec, new_no_time, new_dq, new_status,
+                                  penalties, reason, user_id, target_id, cascade_json, old_penalties_json, new_penalties_json)
+        SELECT r.id, r.event_id, r.team_id, r.round,
+               COALESCE((SELECT MAX(v.revision) FROM run_revision v
+                         WHERE v.event_id = r.event_id AND v.team_id = r.team_id AND v.round = r.round), 0) + 1,
+               ?2, ?3, ?4, ?5, ?6, ?7, ?8,
+               r.time_sec, r.penalty, r.total_sec, r.no_time, r.dq, r.status,
+               ?9, ?10, ?11, ?12, ?13, ?14, ?15
+        FROM run r WHERE r.id = ?1
+        RETURNING revision
+        "#,
+    )
+    .bind(rev.run_id)
+    .bind(rev.action)
+    .bind(old.and_then(|o| o.time_sec))
+    .bind(old.map(|o| o.penalty))
+    .bind(old.and_then(|o| o.total_sec))
+    .bind(old.map(|o| o.no_time))
+    .bind(old.map(|o| o.dq))
+    .bind(old.map(|o| o.status.as_str()))
+    .bind(rev.penalties)
+    .bind(reason)
+    .bind(acting_user())
+    .bind(rev.target_id)
+    .bind(cascade)
+    .bind(old_items)
+    .bind(new_items)
+    .fetch_optional(executor)
+    .await?
+    .ok_or_else(|| AppError::not_found("Corrida no encontrada."))
+}
+
+/// Historial de una corrida, de la primera versión a la última (también las de filas
+/// anteriores del mismo equipo en la ronda, si el sorteo se rehizo).
+pub async fn history(pool: &SqlitePool, run_id: i64) -> AppResult<Vec<RunRevisionRow>> {
+    let (event_id, team_id, round): (i64, i64, i64) =
+        sqlx::query_as("SELECT event_id, team_id, round FROM run WHERE id = ?1")
+            .bind(run_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::not_found("Corrida no encontrada."))?;
+    sqlx::query_as::<_, RunRevisionRow>(
+        r#"
+        SELECT v.id, v.run_id, v.revision, v.action,
+               v.old_time_sec, v.old_penalty, v.old_total_sec, v.old_no_time, v.old_dq, v.old_status,
+               v.new_time_sec, v.new_penalty, v.new_total_sec, v.new_no_time, v.new_dq, v.new_status,
+               v.penalties, v.reason,
+               (SELECT t.revision FROM run_revision t WHERE t.id = v.target_id) AS target_revision,
+               v.user_id, u.full_name AS user_name, v.created_at
+        FROM run_revision v
+        LEFT JOIN app_user u ON u.id = v.user_id
+        WHERE v.event_id = ?1 AND v.team_id = ?2 AND v.round = ?3
+        ORDER BY v.revision ASC
+        "#,
+    )
+    .bind(event_id)
+    .bind(team_id)
+    .bind(round)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+/* ---- deshacer / rehacer ---- */
+
+/// Captura que se puede deshacer o rehacer, para que la UI diga cuál es.
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct UndoTarget {
+    /// `run_revision.id` de la captura.
+    pub revision_id: i64,
+    pub revision: i64,
+    /// `capture` | `correction`.
+    pub action: String,
+    pub run_id: i64,
+    pub team_id: i64,
+    pub round: i64,
+    pub position: i64,
+    pub header_name: String,
+    pub heeler_name: String,
+    /// Resultado que deja la captura (sin tiempo si fue NT/DQ).
+    pub total_sec: Option<f64>,
+    pub no_time: i64,
+    pub dq: i64,
+    /// Deshacer o rehacer una corrección pide el PIN del evento, como corregir.
+    pub requires_pin: bool,
+}
+
+#[derive(serde::Serialize, Debug, Default)]
+pub struct UndoState {
+    pub undo: Option<UndoTarget>,
+    pub redo: Option<UndoTarget>,
+}
+
+#[derive(serde::Serialize, Debug)]
+pub struct UndoOutcome {
+    pub run_id: i64,
+    pub team_id: i64,
+    pub round: i64,
+    /// Revisión `undo`/`redo` que quedó en el historial.
+    pub revision: i64,
+    pub state: UndoState,
+}
+
+#[derive(sqlx::FromRow)]
+struct Entry {
+    id: i64,
+    run_id: i64,
+    revision: i64,
+    action: String,
+    old_time_sec: Option<f64>,
+    old_penalty: Option<f64>,
+    old_total_sec: Option<f64>,
+    old_no_time: Option<i64>,
+    old_dq: Option<i64>,
+    old_status: Option<String>,
+    new_time_sec: Option<f64>,
+    new_penalty: f64,
+    new_total_sec: Option<f64>,
+    new_no_time: i64,
+    new_dq: i64,
+    new_status: String,
+    user_id: Option<i64>,
+    cascade_json: Option<String>,
+    old_penalties_json: Option<String>,
+    new_penalties_json: Option<String>,
+    event_id: i64,
+    team_id: i64,
+    round: i64,
+    position: i64,
+    header_name: String,
+    heeler_name: String,
+}
+
+impl Entry {
+    fn old_values(&self) -> Option<RunValues> {
+        Some(RunValues {
+            time_sec: self.old_time_sec,
+            penalty: self.old_penalty?,
+            total_sec: self.old_total_sec,
+            no_time: self.old_no_time?,
+            dq: self.old_dq?,
+            status: self.old_status.clone()?,
+        })
+    }
+
+    fn new_values(&self) -> RunValues {
+        RunValues {
+            time_sec: self.new_time_sec,
+            penalty: self.new_penalty,
+            total_sec: self.new_total_sec,
+            no_time: self.new_no_time,
+            dq: self.new_dq,
+            status: self.new_status.clone(),
+        }
+    }
+
+    fn target(&self) -> UndoTarget {
+        UndoTarget {
+            revision_id: self.id,
+            revision: self.revision,
+            action: self.action.clone(),
+            run_id: self.run_id,
+            team_id: self.team_id,
+            round: self.round,
+            position: self.position,
+            header_name: self.header_name.clone(),
+            heeler_name: self.heeler_name.clone(),
+            total_sec: self.new_total_sec,
+            no_time: self.new_no_time,
+            dq: self.new_dq,
+            requires_pin: self.action == "correction",
+        }
+    }
+}
+
+const ENTRY_SELECT: &str = r#"
+    SELECT v.id, v.run_id, v.revision, v.action,
+           v.old_time_sec, v.old_penalty, v.old_total_sec, v.old_no_time, v.old_dq, v.old_status,
+           v.new_time_sec, v.new_penalty, v.new_total_sec, v.new_no_time, v.new_dq, v.new_status,
+           v.user_id, v.cascade_json, v.old_penalties_json, v.new_penalties_json,
+           r.event_id, r.team_id, r.round, r.position,
+           (rh.first_name || ' ' || rh.last_name) AS header_name,
+           (rhe.first_name || ' ' || rhe.last_name) AS heeler_name
+    FROM run_revision v
+    JOIN run r ON r.id = v.run_id
+    JOIN team t ON t.id = r.team_id
+    JOIN roper rh ON rh.id = t.header_id
+    JOIN roper rhe ON rhe.id = t.heeler_id
+"#;
+
+/// La captura más reciente del evento que sigue aplicada: nunca se deshizo, o su último
+/// `undo` ya se rehízo.
+async fn find_undo(pool: &SqlitePool, event_id: i64) -> AppResult<Option<Entry>> {
+    let sql = format!(
+        r#"{}
+        WHERE v.event_id = ?1 AND v.action IN ('capture', 'correction')
+          AND COALESCE((SELECT u.action FROM run_revision u WHERE u.target_id = v.id ORDER BY u.id DESC LIMIT 1), 'redo') = 'redo'
+        ORDER BY v.id DESC LIMIT 1
+        "#,
+        ENTRY_SELECT
+    );
+    sqlx::query_as::<_, Entry>(&sql)
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+/// La captura del `undo` más reciente que sigue vigente, siempre que no se haya capturado
+/// nada en el evento después de ese `undo` (una captura nueva descarta lo deshecho).
+async fn find_redo(pool: &SqlitePool, event_id: i64) -> AppResult<Option<Entry>> {
+    let sql = format!(
+        r#"{}
+        WHERE v.id = (
+          SELECT u.target_id
+          FROM run_revision u
+          WHERE u.event_id = ?1 AND u.action = 'undo'
+            AND u.id = (SELECT MAX(x.id) FROM run_revision x WHERE x.target_id = u.target_id)
+            AND NOT EXISTS (
+              SELECT 1 FROM run_revision c
+              WHERE c.event_id = ?1 AND c.action IN ('capture', 'correction') AND c.id > u.id
+            )
+          ORDER BY u.id DESC LIMIT 1
+        )
+        "#,
+        ENTRY_SELECT
+    );
+    sqlx::query_as::<_, Entry>(&sql)
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Qué harían ahora `undo_last` y `redo_last` en el evento.
+pub async fn undo_state(pool: &SqlitePool, event_id: i64) -> AppResult<UndoState> {
+    Ok(UndoState {
+        undo: find_undo(pool, event_id).await?.map(|e| e.target()),
+        redo: find_redo(pool, event_id).await?.map(|e| e.target()),
+    })
+}
+
+/// Revierte la última captura del evento: valores y penalizaciones de la corrida como estaban
+/// y los status que cambió en rondas siguientes, todo en una transacción.
+pub async fn undo_last(pool: &SqlitePool, event_id: i64, pin: Option<&str>) -> AppResult<UndoOutcome> {
+    let entry = find_undo(pool, event_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("No hay capturas para deshacer en este evento."))?;
+    apply(pool, entry, true, pin).await
+}
+
+/// Vuelve a aplicar la última captura deshecha.
+pub async fn redo_last(pool: &SqlitePool, event_id: i64, pin: Option<&str>) -> AppResult<UndoOutcome> {
+    let entry = find_redo(pool, event_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("No hay capturas para rehacer en este evento."))?;
+    apply(pool, entry, false, pin).await
+}
+
+async fn apply(pool: &SqlitePool, entry: Entry, undo: bool, pin: Option<&str>) -> AppResult<UndoOutcome> {
+    if entry.action == "correction" {
+        require_pin(pool, entry.event_id, pin).await?;
+    }
+    let (expected, restored, items_json) = if undo {
+        (Some(entry.new_values()), entry.old_values(), entry.old_penalties_json.as_deref())
+    } else {
+        (entry.old_values(), Some(entry.new_values()), entry.new_penalties_json.as_deref())
+    };
+    let (Some(expected), Some(restored), Some(items_json)) = (expected, restored, items_json) else {
+        return Err(AppError::conflict(
+            "Esta captura no guardó lo necesario para deshacerla (es anterior a esta versión); corrígela a mano.",
+        ));
+    };
+    let items: Vec<PenaltyItem> = from_json(items_json)?;
+    let cascade: Vec<CascadeChange> = entry.cascade_json.as_deref().map(from_json).transpose()?.unwrap_or_default();
+    if !undo && entry.action == "capture" {
+        let scratched: Option<String> = sqlx::query_scalar("SELECT scratched_at FROM team WHERE id = ?1")
+            .bind(entry.team_id)
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+        if scratched.is_some() {
+            return Err(AppError::conflict("El equipo se retiró (scratch); no se puede capturar su corrida."));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let current = current(&mut *tx, entry.run_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Corrida no encontrada."))?;
+    if current != expected {
+        return Err(AppError::conflict(
+            "La corrida cambió después de esa captura (otra corrección o un recálculo); corrígela a mano.",
+        ));
+    }
+
+    // Autoría: deshacer una captura la deja sin capturista; rehacerla le devuelve el original.
+    // Deshacer o rehacer una corrección es otra corrección de quien lo hace.
+    let (captured_by, corrected) = match (entry.action.as_str(), undo) {
+        ("capture", true) => (None, false),
+        ("capture", false) => (entry.user_id, false),
+        _ => (None, true),
+    };
+    sqlx::query(
+        r#"
+        UPDATE run SET
+          time_sec     = ?1,
+          penalty      = ?2,
+          total_sec    = ?3,
+          no_time      = ?4,
+          dq           = ?5,
+          status       = ?6,
+          captured_by  = CASE WHEN ?7 = 1 THEN captured_by ELSE ?8 END,
+          corrected_by = CASE WHEN ?7 = 1 THEN ?9 ELSE corrected_by END,
+          corrected_at = CASE WHEN ?7 = 1 THEN strftime('%Y-%m-%dT%H:%M:%SZ','now') ELSE corrected_at END,
+          updated_at   = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+        WHERE id = ?10
+        "#,
+    )
+    .bind(restored.time_sec)
+    .bind(restored.penalty)
+    .bind(restored.total_sec)
+    .bind(restored.no_time)
+    .bind(restored.dq)
+    .bind(&restored.status)
+    .bind(corrected as i32)
+    .bind(captured_by)
+    .bind(acting_user())
+    .bind(entry.run_id)
+    .execute(&mut *tx)
+    .await?;
+    penalties::replace_for_run(&mut tx, entry.run_id, &items).await?;
+
+    // Las rondas siguientes sólo se tocan si siguen como las dejó la captura
+    let mut applied = Vec::new();
+    for change in &cascade {
+        let (from, to) = if undo { (&change.to, &change.from) } else { (&change.from, &change.to) };
+        let done = sqlx::query("UPDATE run SET status = ?1 WHERE id = ?2 AND status = ?3")
+            .bind(to)
+            .bind(change.run_id)
+            .bind(from)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if done > 0 {
+            applied.push(CascadeChange { run_id: change.run_id, from: from.clone(), to: to.clone() });
+        }
+    }
+
+    let described = (!items.is_empty()).then(|| penalties::describe(items.iter().map(|i| (i.label.as_str(), i.seconds))));
+    let action = if undo { "undo" } else { "redo" };
+    let revision = record(
+        &mut *tx,
+        NewRevision {
+            run_id: entry.run_id,
+            action,
+            old: Some(&current),
+            penalties: described.as_deref(),
+            target_id: Some(entry.id),
+            cascade: &applied,
+            new_items: Some(&items),
+            ..Default::default()
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    log_audit(
+        pool,
+        if undo { "undo_run_change" } else { "redo_run_change" },
+        "run",
+        Some(entry.run_id),
+        Some(format!(
+            "Event {} Round {} Rev {} ({} v{})",
+            entry.event_id,
+            entry.round,
+            revision,
+            if undo { "deshace" } else { "rehace" },
+            entry.revision
+        )),
+    )
+    .await?;
+    Ok(UndoOutcome {
+        run_id: entry.run_id,
+        team_id: entry.team_id,
+        round: entry.round,
+        revision,
+        state: undo_state(pool, entry.event_id).await?,
+    })
+}
@@ -0,0 +1,230 @@
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use super::audit::{log_audit, log_change, snapshot};
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct RoperRow {
+    pub id: i64,
+    pub first_name: String,
+    pub last_name: String,
+    pub specialty: String,
+    pub rating: i64,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub level: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Sube con cada cambio de la fila (ver `UpdateRoper::expected_version`).
+    pub version: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct NewRoper {
+    pub first_name: String,
+    pub last_name: String,
+    pub specialty: String,
+    pub rating: i64,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub level: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct UpdateRoper {
+    pub id: i64,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub specialty: Option<String>,
+    pub rating: Option<i64>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub level: Option<String>,
+    /// `version` del roper que vio el cliente; si cambió desde entonces, Conflict.
+    pub expected_version: Option<i64>,
+}
+
+pub async fn list(pool: &SqlitePool) -> AppResult<Vec<RoperRow>> {
+    // Solo retornamos ropers activos (is_active = 1) como parte de la política de soft-delete.
+    sqlx::query_as::<_, RoperRow>(
+        r#"
+        SELECT id, first_name, last_name, specialty, CAST(rating AS INTEGER) AS rating, phone, email, level, created_at, updated_at, version
+        FROM roper
+        WHERE is_active = 1
+        ORDER BY last_name, first_name
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> AppResult<RoperRow> {
+    sqlx::query_as::<_, RoperRow>(
+        r#"
+        SELECT id, first_name, last_name, specialty, CAST(rating AS INTEGER) AS rating, phone, email, level, created_at, updated_at, version
+        FROM roper
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::not_found("Roper no encontrado."))
+}
+
+pub async fn create(pool: &SqlitePool, r: NewRoper) -> AppResult<i64> {
+    // Validar specialty
+    if r.specialty != "header" && r.specialty != "heeler" && r.specialty != "both" {
+        return Err(AppError::validation("specialty", "Specialty inválida: usa 'header', 'heeler' o 'both'."));
+    }
+    if r.rating < 0 {
+        return Err(AppError::validation("rating", "Rating inválido: debe ser >= 0."));
+    }
+
+    // validar nivel
+    let level = r.level.unwrap_or_else(|| "amateur".to_string());
+    let level_l = level.to_lowercase();
+    if level_l != "pro" && level_l != "amateur" && level_l != "principiante" {
+        return Err(AppError::validation("level", "Nivel inválido: use 'pro', 'amateur' o 'principiante'."));
+    }
+
+    let res = sqlx::query(
+        r#"
+        INSERT INTO roper (first_name, last_name, specialty, rating, phone, email, level)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+    )
+    .bind(&r.first_name)
+    .bind(&r.last_name)
+    .bind(&r.specialty)
+    .bind(r.rating)
+    .bind(&r.phone)
+    .bind(&r.email)
+    .bind(level_l)
+    .execute(pool)
+    .await?;
+
+    let id = res.last_insert_rowid();
+    let after = snapshot(pool, "roper", id).await?;
+    log_change(pool, "create_roper", "roper", Some(id), Some(format!("{} {}", r.first_name, r.last_name)), None, after).await?;
+    Ok(id)
+}
+
+const STALE_ROPER: &str = "El roper cambió desde que lo abriste (otra ventana u otro usuario).";
+
+pub async fn update(pool: &SqlitePool, r: UpdateRoper) -> AppResult<()> {
+    // verificar existencia
+    let version: Option<i64> = sqlx::query_scalar("SELECT version FROM roper WHERE id = ?1")
+        .bind(r.id)
+        .fetch_optional(pool)
+        .await?;
+    let Some(version) = version else {
+        return Err(AppError::not_found("Roper no encontrado."));
+    };
+    let before = snapshot(pool, "roper", r.id).await?;
+
+    let mut builder = QueryBuilder::<Sqlite>::new("UPDATE roper SET ");
+    let mut has_any = false;
+
+    if let Some(first) = r.first_name {
+        builder.push("first_name = ").push_bind(first).push(", ");
+        has_any = true;
+    }
+    if let Some(last) = r.last_name {
+        builder.push("last_name = ").push_bind(last).push(", ");
+        has_any = true;
+    }
+    if let Some(spec) = r.specialty {
+        if spec != "header" && spec != "heeler" && spec != "both" {
+            return Err(AppError::validation("specialty", "Specialty inválida: usa 'header', 'heeler' o 'both'."));
+        }
+        builder.push("specialty = ").push_bind(spec).push(", ");
+        has_any = true;
+    }
+    if let Some(rating) = r.rating {
+        if rating < 0 {
+            return Err(AppError::validation("rating", "Rating inválido: debe ser >= 0."));
+        }
+        builder.push("rating = ").push_bind(rating).push(", ");
+        has_any = true;
+    }
+    if let Some(phone) = r.phone {
+        builder.push("phone = ").push_bind(phone).push(", ");
+        has_any = true;
+    }
+    if let Some(email) = r.email {
+        builder.push("email = ").push_bind(email).push(", ");
+        has_any = true;
+    }
+    if let Some(level) = r.level {
+        let lvl = level.to_lowercase();
+        if lvl != "pro" && lvl != "amateur" && lvl != "principiante" {
+            return Err(AppError::validation("level", "Nivel inválido: use 'pro', 'amateur' o 'principiante'."));
+        }
+        builder.push("level = ").push_bind(lvl).push(", ");
+        has_any = true;
+    }
+
+    // Sin cambios no hay UPDATE, pero una versión vieja sigue siendo Conflict
+    if !has_any {
+        if r.expected_version.is_some_and(|v| v != version) {
+            return Err(AppError::stale(STALE_ROPER, get(pool, r.id).await?));
+        }
+        return Ok(());
+    }
+
+    builder
+        .push("updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ")
+        .push_bind(r.id);
+    if let Some(expected) = r.expected_version {
+        builder.push(" AND version = ").push_bind(expected);
+    }
+    let res = builder
+        .build()
+        .execute(pool)
+        .await?;
+    if res.rows_affected() == 0 {
+        return Err(AppError::stale(STALE_ROPER, get(pool, r.id).await?));
+    }
+    
+    let after = snapshot(pool, "roper", r.id).await?;
+    log_change(pool, "update_roper", "roper", Some(r.id), None, before, after).await?;
+    Ok(())
+}
+
+pub async fn delete(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    let before = snapshot(pool, "roper", id).await?;
+    // Política: soft-delete para ropers. Marcamos `is_active = 0`.
+    let res = sqlx::query("UPDATE roper SET is_active = 0, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::not_found("Roper no encontrado."));
+    }
+
+    let after = snapshot(pool, "roper", id).await?;
+    log_change(pool, "delete_roper", "roper", Some(id), None, before, after).await?;
+    Ok(())
+}
+
+pub async fn delete_all(pool: &SqlitePool) -> AppResult<i64> {
+    // Hard-delete: primero elimina todos los equipos, luego elimina todos los ropers
+    
+    // Paso 1: Eliminar todos los equipos
+    sqlx::query("DELETE FROM team")
+        .execute(pool)
+        .await?;
+    
+    // Paso 2: Eliminar todos los ropers
+    let res = sqlx::query("DELETE FROM roper")
+        .execute(pool)
+        .await?;
+
+    let count = res.rows_affected() as i64;
+    
+    log_audit(pool, "delete_all_ropers", "roper", None, Some(format!("Deleted {} ropers and all teams", count))).await?;
+    Ok(count)
+}
@@ -0,0 +1,447 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::error::{AppError, AppResult};
+use super::audit::{acting_user, log_audit, log_change, snapshot};
+use super::activity;
+use super::events::require_pin;
+use super::penalties::{self, PenaltyItem, RunPenaltyRow};
+use super::revisions::{self, CascadeChange, NewRevision, RunValues};
+
+#[derive(serde::Deserialize)]
+pub struct SaveRun {
+    pub event_id: i64,
+    pub team_id: i64,
+    pub round: i64,
+    pub position: i64,
+    pub time_sec: Option<f64>, // null si NT/DQ
+    /// Penalización suelta (clientes viejos); se ignora si llegan `penalties`.
+    #[serde(default)]
+    pub penalty: f64,
+    /// Ids de `penalty_type` del evento, uno por penalización (un tipo puede repetirse).
+    #[serde(default)]
+    pub penalties: Option<Vec<i64>>,
+    pub no_time: bool,
+    pub dq: bool,
+    /// PIN del evento; sólo se pide para sobrescribir una corrida ya completada.
+    pub pin: Option<String>,
+    /// Motivo de la corrección (protesta, error de captura…); queda en `run_revision`.
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// `version` de la corrida que vio el cliente; si cambió desde entonces, Conflict.
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct RunRow {
+    pub id: i64,
+    pub event_id: i64,
+    pub team_id: i64,
+    pub round: i64,
+    pub position: i64,
+    pub time_sec: Option<f64>,
+    pub penalty: f64,
+    pub total_sec: Option<f64>,
+    pub no_time: i64,
+    pub dq: i64,
+    pub status: String,
+    /// 1 si la corrida quedó `skipped` por un scratch con `keep_slot`.
+    pub scratched: i64,
+    pub captured_by: Option<i64>,
+    pub corrected_by: Option<i64>,
+    pub corrected_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Versiones guardadas en `run_revision` (más de 1 = se corrigió).
+    pub revisions: i64,
+    /// Sube con cada cambio de la fila (concurrencia optimista, ver `SaveRun::expected_version`).
+    pub version: i64,
+}
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct RunExpandedRow {
+    pub id: i64,
+    pub event_id: i64,
+    pub team_id: i64,
+    pub round: i64,
+    pub position: i64,
+    pub header_name: String,
+    pub heeler_name: String,
+    pub time_sec: Option<f64>,
+    pub penalty: f64,
+    pub total_sec: Option<f64>,
+    pub no_time: i64,
+    pub dq: i64,
+    pub status: String,
+    pub scratched: i64,
+    pub revisions: i64,
+    pub version: i64,
+    /// Desglose de `penalty`, renglón por renglón.
+    #[sqlx(skip)]
+    pub penalties: Vec<RunPenaltyRow>,
+}
+
+/// `total_sec` de una corrida: tiempo + penalización, o nada si fue NT/DQ.
+pub fn total_time(time_sec: Option<f64>, penalty: f64, no_time: bool, dq: bool) -> Option<f64> {
+    if no_time || dq {
+        None
+    } else {
+        time_sec.map(|t| t + penalty)
+    }
+}
+
+async fn stale_run(pool: &SqlitePool, id: i64) -> AppError {
+    match get(pool, id).await {
+        Ok(current) => AppError::stale("La corrida cambió desde que la abriste (otra ventana u otro operador).", current),
+        Err(e) => e,
+    }
+}
+
+/// Quién captura es siempre el usuario de la sesión (`acting_user`), nunca un id del cliente.
+/// Si la corrida ya estaba completada, el guardado es una corrección: se conserva
+/// `captured_by` y se registra `corrected_by`/`corrected_at`. Cada guardado agrega una
+/// revisión al historial de la corrida (`revisions::record`). Con `expected_version`, si la
+/// corrida cambió desde que el cliente la leyó responde Conflict con la fila actual.
+///
+/// La corrida, la cascada a rondas siguientes, las penalizaciones, la revisión y el audit se
+/// escriben en una sola transacción: o queda todo o nada.
+pub async fn save(pool: &SqlitePool, payload: SaveRun) -> AppResult<i64> {
+    let seen: Option<(i64, String, i64)> =
+        sqlx::query_as("SELECT id, status, version FROM run WHERE event_id = ?1 AND round = ?2 AND team_id = ?3")
+            .bind(payload.event_id)
+            .bind(payload.round)
+            .bind(payload.team_id)
+            .fetch_optional(pool)
+            .await?;
+    if let (Some((id, _, version)), Some(expected)) = (&seen, payload.expected_version) {
+        if *version != expected {
+            return Err(stale_run(pool, *id).await);
+        }
+    }
+    let seen_status = seen.as_ref().map(|(_, status, _)| status.clone());
+    // El PIN va antes de la transacción: un intento fallido se registra aunque no se guarde nada
+    if seen_status.as_deref() == Some("completed") {
+        require_pin(pool, payload.event_id, payload.pin.as_deref()).await?;
+    }
+
+    // `run.penalty` queda como la suma de los renglones
+    let items: Vec<PenaltyItem> = match payload.penalties.as_deref() {
+        Some(type_ids) => penalties::resolve(pool, payload.event_id, type_ids).await?,
+        None if payload.penalty < 0.0 => {
+            return Err(AppError::validation("penalty", "La penalización no puede ser negativa."));
+        }
+        None if payload.penalty > 0.0 => vec![PenaltyItem {
+            penalty_type_id: None,
+            label: "Penalización (sin tipo)".into(),
+            seconds: payload.penalty,
+        }],
+        None => Vec::new(),
+    };
+    let penalty: f64 = items.iter().map(|i| i.seconds).sum();
+    let total = total_time(payload.time_sec, penalty, payload.no_time, payload.dq);
+
+    let mut tx = pool.begin().await?;
+    // Releer dentro de la transacción: si otra ventana la guardó entre tanto, lo que se
+    // validó arriba (versión, PIN) ya no vale
+    let previous: Option<(i64, String, i64)> =
+        sqlx::query_as("SELECT id, status, version FROM run WHERE event_id = ?1 AND round = ?2 AND team_id = ?3")
+            .bind(payload.event_id)
+            .bind(payload.round)
+            .bind(payload.team_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let previous_id = previous.as_ref().map(|(id, _, _)| *id);
+    let previous_status = previous.as_ref().map(|(_, status, _)| status.clone());
+    if let Some((id, _, version)) = &previous {
+        if seen_status != previous_status || payload.expected_version.is_some_and(|e| e != *version) {
+            drop(tx);
+            return Err(stale_run(pool, *id).await);
+        }
+    }
+    // Un equipo retirado ya no corre; sólo se pueden corregir las corridas que sí completó
+    if previous_status.as_deref() != Some("completed") {
+        let scratched: Option<String> = sqlx::query_scalar("SELECT scratched_at FROM team WHERE id = ?1")
+            .bind(payload.team_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .flatten();
+        if scratched.is_some() {
+            return Err(AppError::conflict("El equipo se retiró (scratch); no se puede capturar su corrida."));
+        }
+    }
+    let before = match previous_id {
+        Some(id) => snapshot(&mut *tx, "run", id).await?,
+        None => None,
+    };
+    // Sin fila previa (sorteo sin `seed_runs`) la corrida parte de pendiente y sin
+    // penalizaciones: es lo que deja deshacer esa captura
+    let (old_values, old_items): (Option<RunValues>, Vec<PenaltyItem>) = match previous_id {
+        Some(id) => (
+            revisions::current(&mut *tx, id).await?,
+            penalties::for_run(&mut *tx, id).await?.into_iter().map(PenaltyItem::from).collect(),
+        ),
+        None => (Some(RunValues::pending()), Vec::new()),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO run (event_id, team_id, round, position, time_sec, penalty, total_sec, no_time, dq, status, captured_by)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'completed', ?10)
+        ON CONFLICT(event_id, round, team_id) DO UPDATE SET
+          position   = excluded.position,
+          time_sec   = excluded.time_sec,
+          penalty    = excluded.penalty,
+          total_sec  = excluded.total_sec,
+          no_time    = excluded.no_time,
+          dq         = excluded.dq,
+          captured_by  = CASE WHEN run.status = 'completed' THEN run.captured_by ELSE excluded.captured_by END,
+          corrected_by = CASE WHEN run.status = 'completed' THEN excluded.captured_by ELSE run.corrected_by END,
+          corrected_at = CASE WHEN run.status = 'completed' THEN strftime('%Y-%m-%dT%H:%M:%SZ','now') ELSE run.corrected_at END,
+          status     = 'completed',
+          updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+        "#
+    )
+    .bind(payload.event_id)
+    .bind(payload.team_id)
+    .bind(payload.round)
+    .bind(payload.position)
+    .bind(payload.time_sec)
+    .bind(penalty)
+    .bind(total)
+    .bind(payload.no_time as i32)
+    .bind(payload.dq as i32)
+    .bind(acting_user())
+    .execute(&mut *tx)
+    .await?;
+
+    // Si es NT o DQ, sacar al equipo de las rondas siguientes (status='skipped'); si se corrige
+    // y es tiempo válido, restaurar a 'pending' las que estaban 'skipped'. Lo que cambie queda
+    // en la revisión para poder deshacerlo.
+    let (cascade_filter, cascade_to) = if payload.no_time || payload.dq {
+        ("status <> 'skipped'", "skipped")
+    } else {
+        ("status = 'skipped' AND scratched = 0", "pending")
+    };
+    let affected: Vec<(i64, String)> = sqlx::query_as(&format!(
+        "SELECT id, status FROM run WHERE event_id = ?1 AND team_id = ?2 AND round > ?3 AND {} ORDER BY round",
+        cascade_filter
+    ))
+    .bind(payload.event_id)
+    .bind(payload.team_id)
+    .bind(payload.round)
+    .fetch_all(&mut *tx)
+    .await?;
+    for (id, _) in &affected {
+        sqlx::query("UPDATE run SET status = ?1 WHERE id = ?2")
+            .bind(cascade_to)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let cascade: Vec<CascadeChange> = affected
+        .into_iter()
+        .map(|(run_id, from)| CascadeChange { run_id, from, to: cascade_to.into() })
+        .collect();
+
+    let run_id: i64 = sqlx::query_scalar("SELECT id FROM run WHERE event_id = ?1 AND round = ?2 AND team_id = ?3")
+        .bind(payload.event_id)
+        .bind(payload.round)
+        .bind(payload.team_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    penalties::replace_for_run(&mut tx, run_id, &items).await?;
+    let described = (!items.is_empty()).then(|| penalties::describe(items.iter().map(|i| (i.label.as_str(), i.seconds))));
+    let action = if previous_status.as_deref() == Some("completed") { "correction" } else { "capture" };
+    let revision = revisions::record(
+        &mut *tx,
+        NewRevision {
+            run_id,
+            action,
+            old: old_values.as_ref(),
+            penalties: described.as_deref(),
+            reason: payload.reason.as_deref(),
+            cascade: &cascade,
+            old_items: Some(&old_items),
+            new_items: Some(&items),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let after = snapshot(&mut *tx, "run", run_id).await?;
+    let mut metadata = format!("Event {} Round {} Rev {}", payload.event_id, payload.round, revision);
+    if let Some(described) = &described {
+        metadata.push_str(&format!(" | {}", described));
+    }
+    if let Some(reason) = payload.reason.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        metadata.push_str(&format!(" | {}", reason));
+    }
+    log_change(
+        &mut *tx,
+        "save_run",
+        "run",
+        Some(run_id),
+        Some(metadata),
+        before,
+        after,
+    )
+    .await?;
+    tx.commit().await?;
+    activity::track_capture(pool, payload.event_id, payload.round).await?;
+    Ok(run_id)
+}
+
+pub async fn list(pool: &SqlitePool, event_id: i64, round: Option<i64>) -> AppResult<Vec<RunRow>> {
+    if let Some(r) = round {
+        sqlx::query_as::<_, RunRow>(
+            r#"
+            SELECT id, event_id, team_id, round, position, time_sec, penalty, total_sec,
+                   no_time, dq, status, scratched, captured_by, corrected_by, corrected_at, created_at, updated_at,
+                   (SELECT COUNT(1) FROM run_revision v WHERE v.event_id = run.event_id AND v.team_id = run.team_id AND v.round = run.round) AS revisions, version
+            FROM run
+            WHERE event_id = ?1 AND round = ?2
+            ORDER BY position ASC, id ASC
+            "#,
+        )
+        .bind(event_id)
+        .bind(r)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+    } else {
+        sqlx::query_as::<_, RunRow>(
+            r#"
+            SELECT id, event_id, team_id, round, position, time_sec, penalty, total_sec,
+                   no_time, dq, status, scratched, captured_by, corrected_by, corrected_at, created_at, updated_at,
+                   (SELECT COUNT(1) FROM run_revision v WHERE v.event_id = run.event_id AND v.team_id = run.team_id AND v.round = run.round) AS revisions, version
+            FROM run
+            WHERE event_id = ?1
+            ORDER BY round ASC, position ASC, id ASC
+            "#,
+        )
+        .bind(event_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+    }
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> AppResult<RunRow> {
+    sqlx::query_as::<_, RunRow>(
+        r#"
+        SELECT id, event_id, team_id, round, position, time_sec, penalty, total_sec,
+               no_time, dq, status, scratched, captured_by, corrected_by, corrected_at, created_at, updated_at,
+               (SELECT COUNT(1) FROM run_revision v WHERE v.event_id = run.event_id AND v.team_id = run.team_id AND v.round = run.round) AS revisions, version
+        FROM run
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::not_found("Corrida no encontrada."))
+}
+
+pub async fn list_expanded(pool: &SqlitePool, event_id: i64, round: Option<i64>) -> AppResult<Vec<RunExpandedRow>> {
+    let base_query = r#"
+        SELECT
+          r.id, r.event_id, r.team_id, r.round, r.position,
+          r.time_sec, r.penalty, r.total_sec, r.status, r.scratched, r.no_time, r.dq,
+          (SELECT COUNT(1) FROM run_revision v WHERE v.event_id = r.event_id AND v.team_id = r.team_id AND v.round = r.round) AS revisions, r.version,
+          (rh.first_name || ' ' || rh.last_name) as header_name,
+          (rhe.first_name || ' ' || rhe.last_name) as heeler_name
+        FROM run r
+        JOIN team t ON r.team_id = t.id
+        JOIN roper rh ON t.header_id = rh.id
+        JOIN roper rhe ON t.heeler_id = rhe.id
+    "#;
+
+    let mut rows = if let Some(r) = round {
+        let q = format!(
+            "{} WHERE r.event_id = ?1 AND r.round = ?2 ORDER BY r.position ASC",
+            base_query
+        );
+        sqlx::query_as::<_, RunExpandedRow>(&q)
+            .bind(event_id)
+            .bind(r)
+            .fetch_all(pool)
+            .await?
+    } else {
+        let q = format!(
+            "{} WHERE r.event_id = ?1 ORDER BY r.round ASC, r.position ASC",
+            base_query
+        );
+        sqlx::query_as::<_, RunExpandedRow>(&q)
+            .bind(event_id)
+            .fetch_all(pool)
+            .await?
+    };
+
+    let mut breakdown = penalties::by_run(pool, event_id).await?;
+    for row in &mut rows {
+        row.penalties = breakdown.remove(&row.id).unwrap_or_default();
+    }
+    Ok(rows)
+}
+
+/// La corrida que sigue: la primera `pending` de la ronda (o del evento, si no se dice ronda)
+/// por ronda y posición. Es a la que la captura le ofrece el tiempo del timer.
+pub async fn next_pending(pool: &SqlitePool, event_id: i64, round: Option<i64>) -> AppResult<Option<RunExpandedRow>> {
+    Ok(list_expanded(pool, event_id, round)
+        .await?
+        .into_iter()
+        .find(|r| r.status == "pending" && r.scratched == 0))
+}
+
+/// Recalcula `penalty` (suma de sus renglones, si los tiene) y `total_sec` de las corridas completadas del
+/// evento a partir de `time_sec`/NT/DQ (mismas reglas que `total_time`). Devuelve cuántas cambiaron;
+/// cada una queda en su historial como `recompute`.
+pub async fn recompute_totals(pool: &SqlitePool, event_id: i64) -> AppResult<u64> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, time_sec, penalty, no_time, dq, total_sec,
+               (SELECT SUM(p.seconds) FROM run_penalty p WHERE p.run_id = run.id) AS items_sum
+        FROM run WHERE event_id = ?1 AND status = 'completed'
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    let mut changed = 0u64;
+    for row in rows {
+        let id: i64 = row.get("id");
+        let penalty: f64 = row.get::<Option<f64>, _>("items_sum").unwrap_or_else(|| row.get("penalty"));
+        let total = total_time(
+            row.get("time_sec"),
+            penalty,
+            row.get::<i64, _>("no_time") != 0,
+            row.get::<i64, _>("dq") != 0,
+        );
+        if total != row.get::<Option<f64>, _>("total_sec") || penalty != row.get::<f64, _>("penalty") {
+            let old = RunValues {
+                time_sec: row.get("time_sec"),
+                penalty: row.get("penalty"),
+                total_sec: row.get("total_sec"),
+                no_time: row.get("no_time"),
+                dq: row.get("dq"),
+                status: "completed".into(),
+            };
+            sqlx::query("UPDATE run SET penalty = ?1, total_sec = ?2, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?3")
+                .bind(penalty)
+                .bind(total)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            let revision = NewRevision { run_id: id, action: "recompute", old: Some(&old), reason: Some("Recálculo de totales"), ..Default::default() };
+            revisions::record(&mut *tx, revision).await?;
+            changed += 1;
+        }
+    }
+    tx.commit().await?;
+
+    if changed > 0 {
+        log_audit(pool, "recompute_totals", "event", Some(event_id), Some(format!("{} runs", changed))).await?;
+    }
+    Ok(changed)
+}
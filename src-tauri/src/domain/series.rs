@@ -0,0 +1,195 @@
+use sqlx::{FromRow, Sqlite, SqlitePool, Transaction};
+
+use crate::error::{AppError, AppResult};
+use super::audit::{log_change, snapshot};
+
+#[derive(serde::Deserialize)]
+pub struct NewSeries {
+    pub name: String,
+    pub season: String,
+    pub status: String, // 'active' | 'upcoming' | 'archived'
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+#[derive(serde::Serialize, FromRow)]
+pub struct SeriesRow {
+    pub id: i64,
+    pub name: String,
+    pub season: String,
+    pub status: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub events_count: i64,
+    pub progress: f64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct UpdateSeries {
+    pub name: Option<String>,
+    pub season: Option<String>,
+    pub status: Option<String>, // 'active' | 'upcoming' | 'archived'
+    pub start_date: Option<Option<String>>,
+    pub end_date: Option<Option<String>>,
+}
+
+pub async fn list(pool: &SqlitePool) -> AppResult<Vec<SeriesRow>> {
+    sqlx::query_as::<_, SeriesRow>(
+        r#"
+        SELECT 
+            s.id, s.name, s.season, s.status,
+            s.start_date, s.end_date, s.created_at, s.updated_at,
+            (SELECT COUNT(*) FROM event e WHERE e.series_id = s.id AND e.is_deleted = 0) as events_count,
+            COALESCE(
+                (
+                    SELECT 
+                        CASE WHEN COUNT(r.id) = 0 THEN 0.0
+                        ELSE CAST(SUM(CASE WHEN r.status = 'completed' THEN 1 ELSE 0 END) AS REAL) / COUNT(r.id) * 100.0
+                        END
+                    FROM run r
+                    JOIN event e ON r.event_id = e.id
+                    WHERE e.series_id = s.id AND e.is_deleted = 0
+                ), 
+                0.0
+            ) as progress
+        FROM series s
+        WHERE s.is_deleted = 0
+        ORDER BY s.created_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+pub async fn create(pool: &SqlitePool, payload: NewSeries) -> AppResult<i64> {
+    let res = sqlx::query(
+        r#"
+        INSERT INTO series (name, season, status, start_date, end_date)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+    )
+    .bind(&payload.name)
+    .bind(&payload.season)
+    .bind(&payload.status)
+    .bind(&payload.start_date)
+    .bind(&payload.end_date)
+    .execute(pool)
+    .await?;
+
+    let id = res.last_insert_rowid();
+    let after = snapshot(pool, "series", id).await?;
+    log_change(pool, "create_series", "series", Some(id), Some(payload.name), None, after).await?;
+    Ok(id)
+}
+
+pub async fn update(pool: &SqlitePool, id: i64, patch: UpdateSeries) -> AppResult<()> {
+    // verify series exists
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM series WHERE id = ?1 AND is_deleted = 0")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    let Some(_exists) = exists else {
+        return Err(AppError::not_found("Serie no encontrada."));
+    };
+    let before = snapshot(pool, "series", id).await?;
+
+    // build update within transaction
+    let mut tx: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    if let Some(name) = patch.name {
+        sqlx::query("UPDATE series SET name = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?2")
+            .bind(name)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    if let Some(season) = patch.season {
+        sqlx::query("UPDATE series SET season = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?2")
+            .bind(season)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    if let Some(status) = patch.status {
+        if status != "active" && status != "upcoming" && status != "archived" {
+            return Err(AppError::validation("status", "Status inválido: usa 'active', 'upcoming' o 'archived'."));
+        }
+        sqlx::query("UPDATE series SET status = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?2")
+            .bind(status)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // start_date/end_date are Option<Option<String>> to allow explicit null
+    if let Some(start_opt) = patch.start_date {
+        sqlx::query("UPDATE series SET start_date = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?2")
+            .bind(start_opt)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    if let Some(end_opt) = patch.end_date {
+        sqlx::query("UPDATE series SET end_date = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?2")
+            .bind(end_opt)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    let after = snapshot(pool, "series", id).await?;
+    log_change(pool, "update_series", "series", Some(id), None, before, after).await?;
+    Ok(())
+}
+
+pub async fn delete(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    // verificar que la serie exista
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM series WHERE id = ?1 AND is_deleted = 0")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    let Some(_exists) = exists else {
+        return Err(AppError::not_found("Serie no encontrada."));
+    };
+
+    // impedir borrado si hay eventos locked
+    let locked_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(1) FROM event WHERE series_id = ?1 AND status = 'locked' AND is_deleted = 0",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+    if locked_count > 0 {
+        return Err(AppError::event_locked("Hay eventos bloqueados en la serie; desbloquea los eventos antes de eliminar la serie."));
+    }
+
+    let before = snapshot(pool, "series", id).await?;
+
+    // soft-delete series y eventos asociados en una transacción
+    let mut tx: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    sqlx::query("UPDATE series SET is_deleted = 1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    // además de marcar is_deleted, no cambiamos status a 'archived' para evitar error de constraint.
+    sqlx::query("UPDATE event SET is_deleted = 1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE series_id = ?1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    let after = snapshot(pool, "series", id).await?;
+    log_change(pool, "delete_series", "series", Some(id), None, before, after).await?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use super::audit::log_change;
+
+pub async fn get(pool: &SqlitePool, key: &str) -> AppResult<Option<String>> {
+    sqlx::query_scalar("SELECT value FROM app_setting WHERE key = ?1")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn set(pool: &SqlitePool, key: &str, value: &str) -> AppResult<()> {
+    let before = get(pool, key).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO app_setting (key, value, updated_at)
+        VALUES (?1, ?2, strftime('%Y-%m-%dT%H:%M:%SZ','now'))
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+    log_change(
+        pool,
+        "update_setting",
+        "app_setting",
+        None,
+        Some(format!("{} = {}", key, value)),
+        before.map(|v| serde_json::json!({ key: v }).to_string()),
+        Some(serde_json::json!({ key: value }).to_string()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Valor entero de un setting; `default` si no existe o no se puede leer.
+pub async fn get_i64(pool: &SqlitePool, key: &str, default: i64) -> AppResult<i64> {
+    Ok(get(pool, key)
+        .await?
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default))
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use sqlx::SqlitePool;
+
+use crate::error::AppResult;
+
+#[derive(serde::Serialize)]
+pub struct StandingRow {
+    pub rank: i64,
+    pub team_id: i64,
+    pub header_name: String,
+    pub heeler_name: String,
+    pub total_time: Option<f64>,
+    pub completed_runs: i64,
+    pub nt_cnt: i64,
+    pub dq_cnt: i64,
+    pub avg_time: Option<f64>,
+    pub best_time: Option<f64>,
+    /// Short-go: 1 pasó el corte, 0 no fue llamado a la final, `None` sin corte.
+    pub called_back: Option<i64>,
+    /// Segundos de penalización ya incluidos en `total_time`.
+    pub penalty_total: f64,
+    /// `penalty_total` desglosado por tipo.
+    pub penalties: Vec<PenaltyTally>,
+}
+
+/// Cuántas veces cayó una penalización en las corridas que cuentan y cuánto sumó.
+#[derive(serde::Serialize, sqlx::FromRow, Clone, Debug, PartialEq)]
+pub struct PenaltyTally {
+    #[serde(skip)]
+    pub team_id: i64,
+    pub label: String,
+    pub count: i64,
+    pub seconds: f64,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct StandingAgg {
+    pub team_id: i64,
+    pub header_name: String,
+    pub heeler_name: String,
+    pub total_time: Option<f64>,
+    pub completed_runs: i64,
+    pub nt_cnt: i64,
+    pub dq_cnt: i64,
+    pub avg_time: Option<f64>,
+    pub best_time: Option<f64>,
+    pub called_back: Option<i64>,
+    pub penalty_total: f64,
+}
+
+/// Ordena los agregados y asigna rank (1-based).
+pub fn rank(mut rows: Vec<StandingAgg>) -> Vec<StandingRow> {
+    // Ordenar: completed_runs desc (pero 0 al final), luego total_time asc (nulos al final),
+    // luego best_time asc (nulos al final), y por último team_id asc.
+    rows.sort_by(|a, b| {
+        use std::cmp::Ordering;
+        // completed desc
+        let cr = b.completed_runs.cmp(&a.completed_runs);
+        if cr != Ordering::Equal {
+            return cr;
+        }
+
+        // total_time asc (None al final)
+        match (&a.total_time, &b.total_time) {
+            (Some(ta), Some(tb)) => {
+                let ot = ta.partial_cmp(tb).unwrap_or(Ordering::Equal);
+                if ot != Ordering::Equal {
+                    return ot;
+                }
+            }
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => {}
+        }
+
+        // best_time asc (None al final)
+        match (&a.best_time, &b.best_time) {
+            (Some(ta), Some(tb)) => {
+                let ob = ta.partial_cmp(tb).unwrap_or(Ordering::Equal);
+                if ob != Ordering::Equal {
+                    return ob;
+                }
+            }
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => {}
+        }
+
+        // último desempate: team_id
+        a.team_id.cmp(&b.team_id)
+    });
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, r)| StandingRow {
+            rank: (i as i64) + 1,
+            team_id: r.team_id,
+            header_name: r.header_name,
+            heeler_name: r.heeler_name,
+            total_time: r.total_time,
+            completed_runs: r.completed_runs,
+            nt_cnt: r.nt_cnt,
+            dq_cnt: r.dq_cnt,
+            avg_time: r.avg_time,
+            best_time: r.best_time,
+            called_back: r.called_back,
+            penalty_total: r.penalty_total,
+            penalties: Vec::new(),
+        })
+        .collect()
+}
+
+pub async fn get(pool: &SqlitePool, event_id: i64) -> AppResult<Vec<StandingRow>> {
+    aggregate(pool, event_id, None).await
+}
+
+/// Clasificación con sólo las rondas anteriores a `round` (la que manda en el corte del short-go).
+pub async fn before_round(pool: &SqlitePool, event_id: i64, round: i64) -> AppResult<Vec<StandingRow>> {
+    aggregate(pool, event_id, Some(round)).await
+}
+
+async fn aggregate(pool: &SqlitePool, event_id: i64, before_round: Option<i64>) -> AppResult<Vec<StandingRow>> {
+    // Agregados por equipo para el evento
+    let rows: Vec<StandingAgg> = sqlx::query_as::<_, StandingAgg>(
+        r#"
+        SELECT
+          r.team_id                                        AS team_id,
+          (rh.first_name || ' ' || rh.last_name)           AS header_name,
+          (rhe.first_name || ' ' || rhe.last_name)         AS heeler_name,
+          SUM(CASE WHEN r.status='completed' AND r.no_time=0 AND r.dq=0 THEN r.total_sec END) AS total_time,
+          SUM(CASE WHEN r.status='completed' AND r.no_time=0 AND r.dq=0 THEN 1 ELSE 0 END)    AS completed_runs,
+          SUM(CASE WHEN r.no_time=1 THEN 1 ELSE 0 END)                                       AS nt_cnt,
+          SUM(CASE WHEN r.dq=1 THEN 1 ELSE 0 END)                                            AS dq_cnt,
+          AVG(CASE WHEN r.status='completed' AND r.no_time=0 AND r.dq=0 THEN r.total_sec END) AS avg_time,
+          MIN(CASE WHEN r.status='completed' AND r.no_time=0 AND r.dq=0 THEN r.total_sec END) AS best_time,
+          t.called_back                                    AS called_back,
+          COALESCE(SUM(CASE WHEN r.status='completed' AND r.no_time=0 AND r.dq=0 THEN r.penalty END), 0.0) AS penalty_total
+        FROM run r
+        JOIN team t ON r.team_id = t.id
+        JOIN roper rh ON t.header_id = rh.id
+        JOIN roper rhe ON t.heeler_id = rhe.id
+        WHERE r.event_id = ?1 AND (?2 IS NULL OR r.round < ?2)
+        GROUP BY r.team_id, header_name, heeler_name, t.called_back
+        "#
+    )
+    .bind(event_id)
+    .bind(before_round)
+    .fetch_all(pool)
+    .await?;
+
+    // Si no hay runs, regresamos vacío
+    if rows.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Desglose por tipo, con las mismas corridas que suman al total
+    let tallies: Vec<PenaltyTally> = sqlx::query_as::<_, PenaltyTally>(
+        r#"
+        SELECT r.team_id AS team_id, p.label AS label, COUNT(*) AS count, SUM(p.seconds) AS seconds
+        FROM run_penalty p
+        JOIN run r ON r.id = p.run_id
+        WHERE r.event_id = ?1 AND (?2 IS NULL OR r.round < ?2)
+          AND r.status='completed' AND r.no_time=0 AND r.dq=0
+        GROUP BY r.team_id, p.label
+        ORDER BY r.team_id ASC, MIN(p.id) ASC
+        "#
+    )
+    .bind(event_id)
+    .bind(before_round)
+    .fetch_all(pool)
+    .await?;
+    let mut by_team: HashMap<i64, Vec<PenaltyTally>> = HashMap::new();
+    for tally in tallies {
+        by_team.entry(tally.team_id).or_default().push(tally);
+    }
+
+    let mut ranked = rank(rows);
+    for row in &mut ranked {
+        row.penalties = by_team.remove(&row.team_id).unwrap_or_default();
+    }
+    Ok(ranked)
+}
@@ -0,0 +1,419 @@
+use sqlx::{Sqlite, SqlitePool, Transaction};
+
+use crate::error::{AppError, AppResult};
+use super::audit::{log_audit, log_change, snapshot};
+use super::draw::ensure_rounds_open;
+use super::events::{ensure_event_unlocked, SCRATCH_KEEP_SLOT};
+
+#[derive(serde::Serialize, sqlx::FromRow)]
+pub struct TeamRow {
+    pub id: i64,
+    pub event_id: i64,
+    pub header_id: i64,
+    pub heeler_id: i64,
+    pub rating: f64,
+    pub status: String,
+    /// Fecha del scratch; un equipo retirado no se puede reactivar.
+    pub scratched_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Sube con cada cambio de la fila (ver `UpdateTeam::expected_version`).
+    pub version: i64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct NewTeam {
+    pub event_id: i64,
+    pub header_id: i64,
+    pub heeler_id: i64,
+    pub rating: f64,
+}
+
+#[derive(serde::Deserialize)]
+pub struct UpdateTeam {
+    pub id: i64,
+    pub rating: Option<f64>,
+    pub status: Option<String>, // 'active' | 'inactive'
+    /// `version` del equipo que vio el cliente; si cambió desde entonces, Conflict.
+    pub expected_version: Option<i64>,
+}
+
+pub async fn list(pool: &SqlitePool, event_id: i64) -> AppResult<Vec<TeamRow>> {
+    tracing::info!(event_id, "list_teams: called");
+
+    let rows = sqlx::query_as::<_, TeamRow>(
+        r#"
+        SELECT id, event_id, header_id, heeler_id, rating, status, scratched_at, created_at, updated_at, version
+        FROM team
+        WHERE event_id = ?1 AND status = 'active'
+        ORDER BY id ASC
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, event_id, "list_teams failed");
+        AppError::from(e)
+    })?;
+
+    tracing::info!(event_id, count = rows.len(), "list_teams: returning rows");
+    Ok(rows)
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> AppResult<TeamRow> {
+    sqlx::query_as::<_, TeamRow>(
+        "SELECT id, event_id, header_id, heeler_id, rating, status, scratched_at, created_at, updated_at, version FROM team WHERE id = ?1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::not_found("Team no encontrado."))
+}
+
+pub async fn create(pool: &SqlitePool, t: NewTeam) -> AppResult<i64> {
+    // log intent
+    tracing::info!(
+        event_id = t.event_id,
+        header_id = t.header_id,
+        heeler_id = t.heeler_id,
+        rating = t.rating,
+        "create_team: attempt"
+    );
+
+    ensure_event_unlocked(pool, t.event_id).await?;
+
+    // Validación básica: header != heeler
+    if t.header_id == t.heeler_id {
+        tracing::error!(
+            header_id = t.header_id,
+            heeler_id = t.heeler_id,
+            "create_team failed: same header and heeler"
+        );
+        return Err(AppError::validation(
+            "heeler_id",
+            "Header y Heeler no pueden ser la misma persona. Aún no clonamos vaqueros.",
+        ));
+    }
+
+    // Verifica que existan los ropers
+    let exist: (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT 
+          (SELECT COUNT(1) FROM roper WHERE id = ?1) AS h,
+          (SELECT COUNT(1) FROM roper WHERE id = ?2) AS he
+        "#,
+    )
+    .bind(t.header_id)
+    .bind(t.heeler_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "create_team: error checking ropers");
+        AppError::from(e)
+    })?;
+
+    if exist.0 == 0 || exist.1 == 0 {
+        tracing::error!(
+            header_exists = exist.0,
+            heeler_exists = exist.1,
+            "create_team failed: missing roper"
+        );
+        return Err(AppError::not_found("Header o Heeler no existen en la tabla roper."));
+    }
+
+    // Inserta respetando UNIQUE(event_id, header_id, heeler_id)
+    let res = sqlx::query(
+        r#"
+        INSERT INTO team (event_id, header_id, heeler_id, rating, status)
+        VALUES (?1, ?2, ?3, ?4, 'active')
+        "#,
+    )
+    .bind(t.event_id)
+    .bind(t.header_id)
+    .bind(t.heeler_id)
+    .bind(t.rating)
+    .execute(pool)
+    .await;
+
+    match res {
+        Ok(r) => {
+            let last_id = r.last_insert_rowid();
+            tracing::info!(
+                event_id = t.event_id,
+                header_id = t.header_id,
+                heeler_id = t.heeler_id,
+                rating = t.rating,
+                last_row = last_id,
+                "create_team: success"
+            );
+            let after = snapshot(pool, "team", last_id).await?;
+            log_change(pool, "create_team", "team", Some(last_id), Some(format!("Event {}", t.event_id)), None, after).await?;
+            Ok(last_id)
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "create_team failed: insert error");
+            let unique = e
+                .as_database_error()
+                .is_some_and(|d| d.is_unique_violation());
+            if unique {
+                Err(AppError::conflict("Ya existe un equipo con ese header/heeler en este evento."))
+            } else {
+                Err(e.into())
+            }
+        }
+    }
+}
+
+pub async fn hard_delete_for_event(pool: &SqlitePool, event_id: i64) -> AppResult<()> {
+    tracing::info!(event_id, "hard_delete_teams_for_event: starting");
+    // verificar que el evento exista y no esté locked
+    ensure_event_unlocked(pool, event_id).await?;
+
+    let res = sqlx::query("DELETE FROM team WHERE event_id = ?1")
+        .bind(event_id)
+        .execute(pool)
+        .await;
+
+    match res {
+        Ok(r) => {
+            tracing::info!(
+                deleted = r.rows_affected(),
+                event_id,
+                "hard_delete_teams_for_event: completed"
+            );
+            log_audit(pool, "hard_delete_teams", "event", Some(event_id), None).await?;
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!(error = %e, event_id, "hard_delete_teams_for_event failed");
+            Err(e.into())
+        }
+    }
+}
+
+const SCRATCHED_TEAM: &str = "El equipo se retiró (scratch); no se puede reactivar.";
+const STALE_TEAM: &str = "El equipo cambió desde que lo abriste (otra ventana u otro usuario).";
+
+/// Un equipo retirado (`scratched_at`) no vuelve a `active`: el draw lo volvería a sortear y
+/// `runs::save` le negaría la captura.
+pub async fn update(pool: &SqlitePool, t: UpdateTeam) -> AppResult<()> {
+    // Lee event_id del team para validar lock
+    let team: Option<(i64, Option<String>, i64)> =
+        sqlx::query_as("SELECT event_id, scratched_at, version FROM team WHERE id = ?1")
+            .bind(t.id)
+            .fetch_optional(pool)
+            .await?;
+
+    let Some((event_id, scratched_at, version)) = team else {
+        return Err(AppError::not_found("Team no encontrado."));
+    };
+    ensure_event_unlocked(pool, event_id).await?;
+    let before = snapshot(pool, "team", t.id).await?;
+
+    if let Some(st) = t.status.as_deref() {
+        if st != "active" && st != "inactive" {
+            return Err(AppError::validation("status", "Status inválido: usa 'active' o 'inactive'."));
+        }
+        if st == "active" && scratched_at.is_some() {
+            return Err(AppError::conflict(SCRATCHED_TEAM));
+        }
+    }
+    // Sin cambios no hay UPDATE, pero una versión vieja sigue siendo Conflict
+    if t.rating.is_none() && t.status.is_none() {
+        if t.expected_version.is_some_and(|v| v != version) {
+            return Err(AppError::stale(STALE_TEAM, get(pool, t.id).await?));
+        }
+        return Ok(());
+    }
+
+    // Un solo UPDATE: la comparación de versión, el scratch y el cambio van juntos
+    let res = sqlx::query(
+        r#"
+        UPDATE team SET
+          rating     = COALESCE(?1, rating),
+          status     = COALESCE(?2, status),
+          updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+        WHERE id = ?3 AND (?4 IS NULL OR version = ?4)
+          AND NOT (COALESCE(?2, '') = 'active' AND scratched_at IS NOT NULL)
+        "#,
+    )
+    .bind(t.rating)
+    .bind(&t.status)
+    .bind(t.id)
+    .bind(t.expected_version)
+    .execute(pool)
+    .await?;
+    if res.rows_affected() == 0 {
+        let current = get(pool, t.id).await?;
+        if t.status.as_deref() == Some("active") && current.scratched_at.is_some() {
+            return Err(AppError::conflict(SCRATCHED_TEAM));
+        }
+        return Err(AppError::stale(STALE_TEAM, current));
+    }
+    let after = snapshot(pool, "team", t.id).await?;
+    log_change(pool, "update_team", "team", Some(t.id), None, before, after).await?;
+    Ok(())
+}
+
+pub async fn delete(pool: &SqlitePool, id: i64) -> AppResult<()> {
+    // Obtén event_id y valida lock
+    let event_id: Option<i64> = sqlx::query_scalar("SELECT event_id FROM team WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    let Some(event_id) = event_id else {
+        return Err(AppError::not_found("Team no encontrado."));
+    };
+    ensure_event_unlocked(pool, event_id).await?;
+    let before = snapshot(pool, "team", id).await?;
+
+    // Política: soft-delete para teams. Marcamos status = 'inactive'.
+    let res = sqlx::query("UPDATE team SET status = 'inactive', updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if res.rows_affected() == 0 {
+        return Err(AppError::not_found("Team no encontrado."));
+    }
+
+    let after = snapshot(pool, "team", id).await?;
+    log_change(pool, "delete_team", "team", Some(id), None, before, after).await?;
+    Ok(())
+}
+
+const ALREADY_SCRATCHED: &str = "El equipo ya fue retirado (scratch).";
+
+/// Resultado de `scratch`: rondas donde el equipo tenía lugar sin correr.
+#[derive(Debug, serde::Serialize)]
+pub struct ScratchOutcome {
+    pub team_id: i64,
+    pub event_id: i64,
+    /// Política aplicada (`event.scratch_policy`).
+    pub policy: String,
+    pub rounds: Vec<i64>,
+}
+
+/// Retira un equipo ya sorteado: queda `inactive` con `scratched_at` (los sorteos siguientes lo
+/// dejan fuera) y, en cada ronda donde todavía no corrió, según la política del evento:
+/// `close_gap` borra su lugar y recorre el orden (draw y corridas), `keep_slot` deja la corrida
+/// como `skipped` con `scratched = 1` (la crea si el sorteo no sembró corridas). Lo ya corrido
+/// no se toca.
+pub async fn scratch(pool: &SqlitePool, team_id: i64, reason: Option<String>) -> AppResult<ScratchOutcome> {
+    let team: Option<(i64, Option<String>)> = sqlx::query_as("SELECT event_id, scratched_at FROM team WHERE id = ?1")
+        .bind(team_id)
+        .fetch_optional(pool)
+        .await?;
+    let Some((event_id, scratched_at)) = team else {
+        return Err(AppError::not_found("Team no encontrado."));
+    };
+    if scratched_at.is_some() {
+        return Err(AppError::conflict(ALREADY_SCRATCHED));
+    }
+    ensure_rounds_open(pool, event_id).await?;
+    let policy: String = sqlx::query_scalar("SELECT scratch_policy FROM event WHERE id = ?1")
+        .bind(event_id)
+        .fetch_one(pool)
+        .await?;
+
+    let before = snapshot(pool, "team", team_id).await?;
+    let mut tx: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    // Primera escritura de la transacción: toma el lock y, si otro scratch del mismo equipo
+    // ganó la carrera, no se recorre el orden dos veces
+    let marked = sqlx::query(
+        r#"UPDATE team SET status = 'inactive', scratched_at = strftime('%Y-%m-%dT%H:%M:%SZ','now'), scratch_reason = ?2,
+                          updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+           WHERE id = ?1 AND scratched_at IS NULL"#,
+    )
+    .bind(team_id)
+    .bind(reason.as_deref().map(str::trim).filter(|r| !r.is_empty()))
+    .execute(&mut *tx)
+    .await?;
+    if marked.rows_affected() == 0 {
+        return Err(AppError::conflict(ALREADY_SCRATCHED));
+    }
+
+    // lugares del equipo en rondas donde no ha corrido, leídos ya con el lock
+    let slots: Vec<(i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT d.round, d.position
+        FROM draw d
+        LEFT JOIN run r ON r.event_id = d.event_id AND r.round = d.round AND r.team_id = d.team_id
+        WHERE d.event_id = ?1 AND d.team_id = ?2 AND COALESCE(r.status, 'pending') <> 'completed'
+        ORDER BY d.round
+        "#,
+    )
+    .bind(event_id)
+    .bind(team_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for &(round, position) in &slots {
+        if policy == SCRATCH_KEEP_SLOT {
+            // también si el sorteo no sembró corridas: el hueco queda marcado en `run`
+            sqlx::query(
+                r#"INSERT INTO run (event_id, team_id, round, position, status, scratched)
+                   VALUES (?1, ?3, ?2, ?4, 'skipped', 1)
+                   ON CONFLICT(event_id, round, team_id) DO UPDATE SET
+                     status = 'skipped', scratched = 1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now')
+                   WHERE run.status <> 'completed'"#,
+            )
+            .bind(event_id)
+            .bind(round)
+            .bind(team_id)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+            continue;
+        }
+
+        sqlx::query("DELETE FROM draw WHERE event_id = ?1 AND round = ?2 AND team_id = ?3")
+            .bind(event_id)
+            .bind(round)
+            .bind(team_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM run WHERE event_id = ?1 AND round = ?2 AND team_id = ?3 AND status <> 'completed'")
+            .bind(event_id)
+            .bind(round)
+            .bind(team_id)
+            .execute(&mut *tx)
+            .await?;
+        // el draw en dos pasos para no chocar con UNIQUE(event_id, round, position)
+        sqlx::query("UPDATE draw SET position = position + 100000 WHERE event_id = ?1 AND round = ?2 AND position > ?3")
+            .bind(event_id)
+            .bind(round)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE draw SET position = position - 100001 WHERE event_id = ?1 AND round = ?2 AND position > 100000")
+            .bind(event_id)
+            .bind(round)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE run SET position = position - 1 WHERE event_id = ?1 AND round = ?2 AND position > ?3")
+            .bind(event_id)
+            .bind(round)
+            .bind(position)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    let rounds: Vec<i64> = slots.iter().map(|&(round, _)| round).collect();
+    let after = snapshot(pool, "team", team_id).await?;
+    log_change(
+        pool,
+        "scratch_team",
+        "team",
+        Some(team_id),
+        Some(format!("Event {} rondas {:?} ({})", event_id, rounds, policy)),
+        before,
+        after,
+    )
+    .await?;
+    Ok(ScratchOutcome { team_id, event_id, policy, rounds })
+}
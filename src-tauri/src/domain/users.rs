@@ -0,0 +1,214 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::rngs::OsRng;
+use sqlx::SqlitePool;
+
+use crate::error::{AppError, AppResult};
+use super::activity;
+use super::audit::{as_user, log_audit};
+
+pub const ROLES: [&str; 3] = ["admin", "operator", "viewer"];
+
+/// Roles ordenados por privilegio: `Viewer < Operator < Admin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl Role {
+    /// Un nombre desconocido cuenta como `Viewer` (sólo lectura).
+    pub fn parse(name: &str) -> Role {
+        match name {
+            "admin" => Role::Admin,
+            "operator" => Role::Operator,
+            _ => Role::Viewer,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Role::Admin => "administrador",
+            Role::Operator => "operador",
+            Role::Viewer => "solo lectura",
+        }
+    }
+}
+const MIN_PASSWORD_LEN: usize = 8;
+
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct UserRow {
+    pub id: i64,
+    pub email: String,
+    pub full_name: String,
+    pub role: String,
+    pub is_active: i64,
+    pub created_at: String,
+}
+
+impl UserRow {
+    pub fn role(&self) -> Role {
+        Role::parse(&self.role)
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct NewUser {
+    pub email: String,
+    pub full_name: String,
+    pub password: String,
+    pub role: String,
+}
+
+// Un usuario puede tener varias filas en user_role; usamos la de más privilegio.
+const SELECT_USER: &str = r#"
+    SELECT u.id, u.email, u.full_name, u.is_active, u.created_at,
+           COALESCE((
+             SELECT r.name FROM user_role ur JOIN role r ON r.id = ur.role_id
+             WHERE ur.user_id = u.id
+             ORDER BY CASE r.name WHEN 'admin' THEN 0 WHEN 'operator' THEN 1 ELSE 2 END
+             LIMIT 1
+           ), 'viewer') AS role
+    FROM app_user u
+"#;
+
+pub fn hash_password(password: &str) -> AppResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| AppError::internal(format!("No se pudo hashear la contraseña: {}", e)))
+}
+
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+fn validate_password(password: &str, field: &str) -> AppResult<()> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(AppError::validation(
+            field,
+            format!("La contraseña debe tener al menos {} caracteres.", MIN_PASSWORD_LEN),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn count(pool: &SqlitePool) -> AppResult<i64> {
+    sqlx::query_scalar("SELECT COUNT(1) FROM app_user")
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn list(pool: &SqlitePool) -> AppResult<Vec<UserRow>> {
+    sqlx::query_as::<_, UserRow>(&format!("{} ORDER BY u.full_name COLLATE NOCASE", SELECT_USER))
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn get(pool: &SqlitePool, id: i64) -> AppResult<UserRow> {
+    sqlx::query_as::<_, UserRow>(&format!("{} WHERE u.id = ?1", SELECT_USER))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Usuario no encontrado."))
+}
+
+pub async fn create(pool: &SqlitePool, u: NewUser) -> AppResult<i64> {
+    let email = u.email.trim().to_lowercase();
+    let full_name = u.full_name.trim();
+    if email.is_empty() || !email.contains('@') {
+        return Err(AppError::validation("email", "Email inválido."));
+    }
+    if full_name.is_empty() {
+        return Err(AppError::validation("full_name", "El nombre es obligatorio."));
+    }
+    if !ROLES.contains(&u.role.as_str()) {
+        return Err(AppError::validation("role", "Rol inválido: use 'admin', 'operator' o 'viewer'."));
+    }
+    validate_password(&u.password, "password")?;
+    let hash = hash_password(&u.password)?;
+
+    let mut tx = pool.begin().await?;
+    let res = sqlx::query("INSERT INTO app_user (email, full_name, password_hash) VALUES (?1, ?2, ?3)")
+        .bind(&email)
+        .bind(full_name)
+        .bind(&hash)
+        .execute(&mut *tx)
+        .await;
+    let id = match res {
+        Ok(r) => r.last_insert_rowid(),
+        Err(e) => {
+            let unique = e.as_database_error().is_some_and(|d| d.is_unique_violation());
+            return Err(if unique {
+                AppError::conflict("Ya existe un usuario con ese email.")
+            } else {
+                e.into()
+            });
+        }
+    };
+    sqlx::query("INSERT INTO user_role (user_id, role_id) SELECT ?1, id FROM role WHERE name = ?2")
+        .bind(id)
+        .bind(&u.role)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    log_audit(pool, "create_user", "app_user", Some(id), Some(format!("{} ({})", email, u.role))).await?;
+    Ok(id)
+}
+
+/// Email + contraseña → usuario activo. El mismo error para email o contraseña incorrectos.
+pub async fn authenticate(pool: &SqlitePool, email: &str, password: &str) -> AppResult<UserRow> {
+    let row: Option<(i64, String, i64)> =
+        sqlx::query_as("SELECT id, password_hash, is_active FROM app_user WHERE email = ?1")
+            .bind(email.trim().to_lowercase())
+            .fetch_optional(pool)
+            .await?;
+
+    match row {
+        Some((id, hash, 1)) if verify_password(&hash, password) => {
+            let user = get(pool, id).await?;
+            as_user(Some(id), async {
+                log_audit(pool, "login", "app_user", Some(id), Some(user.email.clone())).await?;
+                activity::record(pool, activity::LOGIN, Some(user.email.clone())).await
+            })
+            .await?;
+            Ok(user)
+        }
+        Some((_, _, 0)) => Err(AppError::unauthorized("El usuario está desactivado.")),
+        _ => Err(AppError::unauthorized("Email o contraseña incorrectos.")),
+    }
+}
+
+/// Además cierra las capturas que el usuario tuviera abiertas.
+pub async fn record_logout(pool: &SqlitePool, user: &UserRow) -> AppResult<()> {
+    log_audit(pool, "logout", "app_user", Some(user.id), Some(user.email.clone())).await?;
+    activity::end_user_sessions(pool, user.id).await?;
+    activity::record(pool, activity::LOGOUT, Some(user.email.clone())).await
+}
+
+pub async fn change_password(pool: &SqlitePool, user_id: i64, current: &str, new: &str) -> AppResult<()> {
+    let hash: String = sqlx::query_scalar("SELECT password_hash FROM app_user WHERE id = ?1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Usuario no encontrado."))?;
+    if !verify_password(&hash, current) {
+        return Err(AppError::validation("current_password", "La contraseña actual no es correcta."));
+    }
+    validate_password(new, "new_password")?;
+
+    sqlx::query("UPDATE app_user SET password_hash = ?1, updated_at = strftime('%Y-%m-%dT%H:%M:%SZ','now') WHERE id = ?2")
+        .bind(hash_password(new)?)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    log_audit(pool, "change_password", "app_user", Some(user_id), None).await?;
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use serde::ser::SerializeStruct;
+
+/// Error devuelto por el dominio y por todos los comandos Tauri.
+///
+/// Se serializa como `{ code, message, field, details }`: la UI decide con `code`
+/// (y puede traducir el mensaje) en lugar de comparar textos.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("{message}")]
+    NotFound { message: String },
+    #[error("{message}")]
+    EventLocked { message: String },
+    #[error("{message}")]
+    Validation { field: String, message: String },
+    /// Sin sesión o credenciales inválidas.
+    #[error("{message}")]
+    Unauthorized { message: String },
+    /// Hay sesión pero el rol no alcanza.
+    #[error("{message}")]
+    Forbidden { message: String },
+    #[error("{message}")]
+    Conflict {
+        message: String,
+        details: Option<serde_json::Value>,
+    },
+    #[error("{0}")]
+    Database(sqlx::Error),
+    #[error("{0}")]
+    Internal(String),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        AppError::NotFound { message: message.into() }
+    }
+
+    pub fn event_locked(message: impl Into<String>) -> Self {
+        AppError::EventLocked { message: message.into() }
+    }
+
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::Validation {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        AppError::Unauthorized { message: message.into() }
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        AppError::Forbidden { message: message.into() }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Concurrencia optimista: la fila cambió desde que el cliente la leyó. `details.current`
+    /// lleva la fila como está ahora, para que la UI la muestre o la recargue.
+    pub fn stale(message: impl Into<String>, current: impl serde::Serialize) -> Self {
+        AppError::Conflict {
+            message: message.into(),
+            details: serde_json::to_value(current).ok().map(|row| serde_json::json!({ "current": row })),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::Internal(message.into())
+    }
+
+    /// Código estable que ve el frontend.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "NotFound",
+            AppError::EventLocked { .. } => "EventLocked",
+            AppError::Validation { .. } => "Validation",
+            AppError::Unauthorized { .. } => "Unauthorized",
+            AppError::Forbidden { .. } => "Forbidden",
+            AppError::Conflict { .. } => "Conflict",
+            AppError::Database(_) => "Database",
+            AppError::Internal(_) => "Internal",
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        // Las violaciones de UNIQUE son conflictos de datos, no fallos de la BD.
+        let unique = e
+            .as_database_error()
+            .is_some_and(|d| d.is_unique_violation());
+        if unique {
+            AppError::conflict(e.to_string())
+        } else {
+            AppError::Database(e)
+        }
+    }
+}
+
+impl From<rust_xlsxwriter::XlsxError> for AppError {
+    fn from(e: rust_xlsxwriter::XlsxError) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl From<csv::Error> for AppError {
+    fn from(e: csv::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl serde::Serialize for AppError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let field = match self {
+            AppError::Validation { field, .. } => Some(field.as_str()),
+            _ => None,
+        };
+        let details = match self {
+            AppError::Conflict { details, .. } => details.as_ref(),
+            _ => None,
+        };
+
+        let mut s = serializer.serialize_struct("AppError", 4)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("field", &field)?;
+        s.serialize_field("details", &details)?;
+        s.end()
+    }
+}
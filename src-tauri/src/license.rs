@@ -0,0 +1,479 @@
+//! Licencia offline firmada con ed25519.
+//!
+//! La clave de licencia es `<payload>.<firma>`, ambos en base64url: el payload es el JSON de
+//! `LicensePayload` y la firma la hace quien vende la app con su clave privada
+//! (`roping-admin issue-license`). La app sólo trae la clave pública (`PUBLIC_KEY_B64`),
+//! así que verifica sin red. Cada licencia va atada al `device_id` del equipo
+//! (`device_fingerprint`).
+//!
+//! Vencida, la app sigue funcionando `GRACE_DAYS` días con aviso; después queda en modo
+//! sólo lectura (consultas, exportaciones y backups siguen disponibles).
+//!
+//! Sin licencia, la app es editable `TRIAL_DAYS` días desde el primer arranque de una versión
+//! con licencias (instalación nueva o actualización de una que ya tenía datos), con aviso de
+//! cuándo pasa a sólo lectura. El inicio queda en `app_setting` y en el archivo `TRIAL_FILE`
+//! junto a la base (`trial_start`); vale el más antiguo, así que restaurar un respaldo o borrar
+//! el renglón no reinicia la prueba.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::{Duration, NaiveDate};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Signer, Verifier};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::domain::audit::log_audit;
+use crate::error::{AppError, AppResult};
+
+/// Clave pública con la que se firman las licencias.
+pub const PUBLIC_KEY_B64: &str = "Xz5JwoHG9z6SOE4oraBjvDDRhOK9PvwCuYVjWYvJkQE=";
+
+/// Días que la app sigue editable después de `valid_until`.
+pub const GRACE_DAYS: i64 = 14;
+
+/// Días editables sin licencia desde el primer arranque (`trial_start`).
+pub const TRIAL_DAYS: i64 = 30;
+
+/// Clave de `app_setting` con la fecha del primer arranque con licencias.
+pub const TRIAL_SETTING: &str = "license_trial_started";
+
+/// Archivo (en el directorio de datos, junto a la base) con la misma fecha, fuera de lo que
+/// se restaura.
+pub const TRIAL_FILE: &str = "license_trial";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LicensePayload {
+    pub customer: String,
+    pub device_id: String,
+    /// `YYYY-MM-DD`; sin valor, válida desde que se emite.
+    pub valid_from: Option<String>,
+    /// `YYYY-MM-DD`, inclusive.
+    pub valid_until: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseState {
+    Unlicensed,
+    /// Sin licencia, dentro de los días de prueba.
+    Trial,
+    Active,
+    /// Vencida, dentro de los días de gracia.
+    Grace,
+    Expired,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LicenseStatus {
+    pub state: LicenseState,
+    /// Huella de este equipo: es lo que hay que mandar para pedir una licencia.
+    pub device_id: String,
+    pub customer: Option<String>,
+    pub valid_until: Option<String>,
+    /// Último día editable: fin de la gracia o, sin licencia, fin de la prueba.
+    pub grace_until: Option<String>,
+    /// Días hasta `valid_until` (negativos si ya venció); en prueba, días que le quedan.
+    pub days_left: Option<i64>,
+    pub read_only: bool,
+    pub message: String,
+}
+
+/* ---- claves y firma ---- */
+
+pub fn embedded_public_key() -> PublicKey {
+    public_key_from_b64(PUBLIC_KEY_B64).expect("PUBLIC_KEY_B64 inválida")
+}
+
+pub fn public_key_from_b64(b64: &str) -> AppResult<PublicKey> {
+    let bytes = STANDARD
+        .decode(b64.trim())
+        .map_err(|e| AppError::internal(format!("Clave pública inválida: {}", e)))?;
+    PublicKey::from_bytes(&bytes).map_err(|e| AppError::internal(format!("Clave pública inválida: {}", e)))
+}
+
+/// Firma `payload` con la clave privada (32 bytes en base64). Sólo lo usa quien emite licencias.
+pub fn issue(secret_b64: &str, payload: &LicensePayload) -> AppResult<String> {
+    parse_date("valid_until", &payload.valid_until)?;
+    if let Some(from) = payload.valid_from.as_deref() {
+        parse_date("valid_from", from)?;
+    }
+    let seed = STANDARD
+        .decode(secret_b64.trim())
+        .map_err(|e| AppError::validation("secret", format!("Clave privada inválida: {}", e)))?;
+    let secret = SecretKey::from_bytes(&seed)
+        .map_err(|e| AppError::validation("secret", format!("Clave privada inválida: {}", e)))?;
+    let public: PublicKey = (&secret).into();
+    let keypair = Keypair { secret, public };
+
+    let json = serde_json::to_string(payload).map_err(|e| AppError::internal(e.to_string()))?;
+    let signature = keypair.sign(json.as_bytes());
+    Ok(format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(json.as_bytes()),
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    ))
+}
+
+/// Separa y verifica una clave de licencia. Devuelve el payload y el JSON/firma tal como se guardan.
+pub fn verify_key(public: &PublicKey, key: &str) -> AppResult<(LicensePayload, String, String)> {
+    let invalid = || AppError::validation("license", "La clave de licencia no es válida.");
+    let (payload_part, sig_part) = key.trim().split_once('.').ok_or_else(invalid)?;
+    let json = URL_SAFE_NO_PAD
+        .decode(payload_part)
+        .ok()
+        .and_then(|b| String::from_utf8(b).ok())
+        .ok_or_else(invalid)?;
+    let sig = URL_SAFE_NO_PAD.decode(sig_part).map_err(|_| invalid())?;
+    let sig_b64 = STANDARD.encode(&sig);
+    let payload = verify_stored(public, &json, &sig_b64)?;
+    Ok((payload, json, sig_b64))
+}
+
+/// Verifica un payload ya guardado en `license_info` (la firma se vuelve a comprobar al arrancar).
+pub fn verify_stored(public: &PublicKey, payload_json: &str, signature_b64: &str) -> AppResult<LicensePayload> {
+    let invalid = || AppError::validation("license", "La clave de licencia no es válida.");
+    let sig_bytes = STANDARD.decode(signature_b64).map_err(|_| invalid())?;
+    let signature = Signature::from_bytes(&sig_bytes).map_err(|_| invalid())?;
+    public
+        .verify(payload_json.as_bytes(), &signature)
+        .map_err(|_| AppError::validation("license", "La firma de la licencia no es válida."))?;
+    let payload: LicensePayload = serde_json::from_str(payload_json).map_err(|_| invalid())?;
+    parse_date("valid_until", &payload.valid_until)?;
+    if let Some(from) = payload.valid_from.as_deref() {
+        parse_date("valid_from", from)?;
+    }
+    Ok(payload)
+}
+
+fn parse_date(field: &str, value: &str) -> AppResult<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .map_err(|_| AppError::validation(field, format!("Fecha inválida: {} (usa AAAA-MM-DD).", value)))
+}
+
+/* ---- huella del equipo ---- */
+
+/// Identificador estable del equipo: hash del id de máquina del sistema operativo
+/// (o del hostname si no hay), en grupos `XXXX-XXXX-XXXX-XXXX`.
+pub fn device_fingerprint() -> String {
+    let source = machine_id()
+        .or_else(hostname)
+        .unwrap_or_else(|| "unknown".to_string());
+    fingerprint_of(&source)
+}
+
+pub fn fingerprint_of(source: &str) -> String {
+    let digest = Sha256::digest(format!("roping-manager|{}", source.trim()).as_bytes());
+    let hex: String = digest[..8].iter().map(|b| format!("{:02X}", b)).collect();
+    hex.as_bytes()
+        .chunks(4)
+        .map(|c| std::str::from_utf8(c).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(target_os = "linux")]
+fn machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .map(|s| s.trim().to_string())
+        .find(|s| !s.is_empty())
+}
+
+#[cfg(target_os = "windows")]
+fn machine_id() -> Option<String> {
+    let out = std::process::Command::new("reg")
+        .args(["query", r"HKLM\SOFTWARE\Microsoft\Cryptography", "/v", "MachineGuid"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .find(|l| l.contains("MachineGuid"))
+        .and_then(|l| l.split_whitespace().last())
+        .map(str::to_string)
+}
+
+#[cfg(target_os = "macos")]
+fn machine_id() -> Option<String> {
+    let out = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .find(|l| l.contains("IOPlatformUUID"))
+        .and_then(|l| l.split('"').nth(3))
+        .map(str::to_string)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn machine_id() -> Option<String> {
+    None
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/* ---- estado ---- */
+
+/// Estado de `payload` para `device_id` en la fecha `today`. `trial_started` es el primer
+/// arranque con licencias; sólo cuenta si no hay ninguna licencia guardada.
+pub fn evaluate(
+    payload: Option<&LicensePayload>,
+    device_id: &str,
+    today: NaiveDate,
+    trial_started: Option<NaiveDate>,
+) -> LicenseStatus {
+    let mut status = LicenseStatus {
+        state: LicenseState::Unlicensed,
+        device_id: device_id.to_string(),
+        customer: None,
+        valid_until: None,
+        grace_until: None,
+        days_left: None,
+        read_only: true,
+        message: "Sin licencia: la app está en modo sólo lectura.".to_string(),
+    };
+    let Some(p) = payload else {
+        if let Some(started) = trial_started {
+            let trial_until = started + Duration::days(TRIAL_DAYS);
+            status.grace_until = Some(trial_until.format(DATE_FORMAT).to_string());
+            status.days_left = Some((trial_until - today).num_days());
+            if today <= trial_until {
+                status.state = LicenseState::Trial;
+                status.read_only = false;
+                status.message = format!(
+                    "Sin licencia: periodo de prueba hasta el {}. Después la app quedará en sólo lectura.",
+                    trial_until.format(DATE_FORMAT)
+                );
+            } else {
+                status.message = format!(
+                    "Sin licencia: el periodo de prueba terminó el {}; la app está en modo sólo lectura.",
+                    trial_until.format(DATE_FORMAT)
+                );
+            }
+        }
+        return status;
+    };
+    if p.device_id != device_id {
+        status.message = format!(
+            "La licencia guardada es de otro equipo ({}); este equipo es {}.",
+            p.device_id, device_id
+        );
+        return status;
+    }
+    let (Ok(until), from) = (
+        parse_date("valid_until", &p.valid_until),
+        p.valid_from.as_deref().map(|f| parse_date("valid_from", f)),
+    ) else {
+        return status;
+    };
+
+    status.customer = Some(p.customer.clone());
+    status.valid_until = Some(p.valid_until.clone());
+    if let Some(Ok(from)) = from {
+        if today < from {
+            status.message = format!("La licencia empieza el {}.", from.format(DATE_FORMAT));
+            return status;
+        }
+    }
+
+    let grace_until = until + Duration::days(GRACE_DAYS);
+    status.grace_until = Some(grace_until.format(DATE_FORMAT).to_string());
+    status.days_left = Some((until - today).num_days());
+    if today <= until {
+        status.state = LicenseState::Active;
+        status.read_only = false;
+        status.message = format!("Licencia de {} válida hasta el {}.", p.customer, p.valid_until);
+    } else if today <= grace_until {
+        status.state = LicenseState::Grace;
+        status.read_only = false;
+        status.message = format!(
+            "La licencia venció el {}. Renuévala antes del {} o la app quedará en sólo lectura.",
+            p.valid_until,
+            grace_until.format(DATE_FORMAT)
+        );
+    } else {
+        status.state = LicenseState::Expired;
+        status.message = format!("La licencia venció el {}: la app está en modo sólo lectura.", p.valid_until);
+    }
+    status
+}
+
+fn today() -> NaiveDate {
+    chrono::Utc::now().date_naive()
+}
+
+/// Verifica `key`, comprueba que sea para `device_id` y no esté vencida, y la guarda como la licencia vigente.
+pub async fn activate(pool: &SqlitePool, public: &PublicKey, device_id: &str, key: &str) -> AppResult<LicensePayload> {
+    let (payload, json, sig_b64) = verify_key(public, key)?;
+    if payload.device_id != device_id {
+        return Err(AppError::validation(
+            "license",
+            format!("La licencia es para otro equipo ({}); este equipo es {}.", payload.device_id, device_id),
+        ));
+    }
+    let status = evaluate(Some(&payload), device_id, today(), None);
+    if status.state != LicenseState::Active {
+        return Err(AppError::validation("license", status.message));
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE license_info SET is_valid = 0 WHERE is_valid = 1")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"INSERT INTO license_info (customer, device_id, payload_json, signature_b64, valid_from, valid_until, is_valid)
+           VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1)"#,
+    )
+    .bind(&payload.customer)
+    .bind(&payload.device_id)
+    .bind(&json)
+    .bind(&sig_b64)
+    .bind(&payload.valid_from)
+    .bind(&payload.valid_until)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    log_audit(
+        pool,
+        "activate_license",
+        "license",
+        None,
+        Some(format!("{} hasta {}", payload.customer, payload.valid_until)),
+    )
+    .await?;
+    Ok(payload)
+}
+
+/// Licencia vigente guardada, con la firma verificada otra vez (una fila editada a mano no cuenta).
+pub async fn load(pool: &SqlitePool, public: &PublicKey) -> AppResult<Option<LicensePayload>> {
+    let row: Option<(String, String)> = sqlx::query_as(
+        "SELECT payload_json, signature_b64 FROM license_info WHERE is_valid = 1 ORDER BY id DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+    let Some((json, sig)) = row else {
+        return Ok(None);
+    };
+    match verify_stored(public, &json, &sig) {
+        Ok(payload) => Ok(Some(payload)),
+        Err(e) => {
+            tracing::warn!(error = %e, "licencia guardada con firma inválida; se ignora");
+            Ok(None)
+        }
+    }
+}
+
+/// `TRIAL_FILE` junto a la base `db_path`.
+pub fn trial_marker(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(TRIAL_FILE)
+}
+
+/// Fecha del primer arranque con licencias: la más antigua entre `app_setting` y `marker`.
+/// La primera vez la guarda en los dos; después deja ambos con esa fecha (un renglón borrado o
+/// un respaldo restaurado con una fecha posterior no reinician la prueba).
+pub async fn trial_start(pool: &SqlitePool, marker: &Path) -> AppResult<NaiveDate> {
+    let from_db = setting_trial_start(pool).await?;
+    let from_file = file_trial_start(marker);
+    let started = from_db.into_iter().chain(from_file).min().unwrap_or_else(today);
+    if from_db.is_none() && from_file.is_none() {
+        log_audit(pool, "start_license_trial", "license", None, Some(format!("{} días", TRIAL_DAYS))).await?;
+    }
+    if from_db != Some(started) {
+        sqlx::query(
+            r#"INSERT INTO app_setting (key, value, updated_at) VALUES (?1, ?2, strftime('%Y-%m-%dT%H:%M:%SZ','now'))
+               ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"#,
+        )
+        .bind(TRIAL_SETTING)
+        .bind(started.format(DATE_FORMAT).to_string())
+        .execute(pool)
+        .await?;
+    }
+    if from_file != Some(started) {
+        // sin el archivo la prueba sigue contando desde la base; no impide arrancar
+        if let Err(e) = std::fs::write(marker, started.format(DATE_FORMAT).to_string()) {
+            tracing::warn!(error = %e, path = %marker.display(), "no se pudo guardar el inicio de la prueba");
+        }
+    }
+    Ok(started)
+}
+
+/// Inicio de la prueba ya guardado (el más antiguo de los dos), sin registrarlo si falta (CLI).
+/// Un valor ilegible (editado a mano) no regala días: cuenta como prueba terminada.
+pub async fn stored_trial_start(pool: &SqlitePool, marker: &Path) -> AppResult<Option<NaiveDate>> {
+    let from_db = setting_trial_start(pool).await?;
+    Ok(from_db.into_iter().chain(file_trial_start(marker)).min())
+}
+
+async fn setting_trial_start(pool: &SqlitePool) -> AppResult<Option<NaiveDate>> {
+    let stored: Option<String> = sqlx::query_scalar("SELECT value FROM app_setting WHERE key = ?1")
+        .bind(TRIAL_SETTING)
+        .fetch_optional(pool)
+        .await?;
+    Ok(stored.map(|v| parse_date(TRIAL_SETTING, &v).unwrap_or(NaiveDate::MIN)))
+}
+
+fn file_trial_start(marker: &Path) -> Option<NaiveDate> {
+    let stored = std::fs::read_to_string(marker).ok()?;
+    Some(parse_date(TRIAL_FILE, stored.trim()).unwrap_or(NaiveDate::MIN))
+}
+
+/// Licencia de este proceso (estado Tauri). El estado se recalcula con la fecha del
+/// momento, así que una app abierta varios días pasa sola a gracia o a sólo lectura.
+pub struct License {
+    device_id: String,
+    payload: RwLock<Option<LicensePayload>>,
+    trial_started: RwLock<Option<NaiveDate>>,
+}
+
+impl License {
+    pub fn new(device_id: String, payload: Option<LicensePayload>, trial_started: Option<NaiveDate>) -> Self {
+        License {
+            device_id,
+            payload: RwLock::new(payload),
+            trial_started: RwLock::new(trial_started),
+        }
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    pub fn set(&self, payload: Option<LicensePayload>) {
+        *self.payload.write().unwrap_or_else(|e| e.into_inner()) = payload;
+    }
+
+    /// Tras restaurar un respaldo (ver `trial_start`).
+    pub fn set_trial_started(&self, trial_started: Option<NaiveDate>) {
+        *self.trial_started.write().unwrap_or_else(|e| e.into_inner()) = trial_started;
+    }
+
+    pub fn status(&self) -> LicenseStatus {
+        let payload = self.payload.read().unwrap_or_else(|e| e.into_inner());
+        let trial_started = *self.trial_started.read().unwrap_or_else(|e| e.into_inner());
+        evaluate(payload.as_ref(), &self.device_id, today(), trial_started)
+    }
+
+    /// `Forbidden` si la app está en sólo lectura (sin licencia fuera de la prueba, o vencida
+    /// fuera de la gracia).
+    pub fn require_writable(&self) -> AppResult<()> {
+        let status = self.status();
+        if status.read_only {
+            return Err(AppError::forbidden(status.message));
+        }
+        Ok(())
+    }
+}
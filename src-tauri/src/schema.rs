@@ -0,0 +1,319 @@
+//! Capa sobre `sqlx::migrate!` para bases antiguas en campo.
+//!
+//! - Antes de aplicar una migración mira `pragma_table_info`: si un `ALTER TABLE ... ADD COLUMN`
+//!   apunta a una columna que ya existe, ejecuta la migración sin esa sentencia y la registra
+//!   en `_sqlx_migrations` con el checksum original (la reparación queda en `audit_log`).
+//! - Comprueba que las migraciones aplicadas coincidan con las del binario y que el esquema
+//!   final tenga todas las tablas/columnas esperadas. Si no, devuelve un `SchemaError`
+//!   legible en lugar de abortar.
+//! - Arreglos de datos que SQL no puede hacer solo: hashear los `event.admin_pin` que
+//!   versiones anteriores guardaban en claro.
+
+use sqlx::migrate::{Migrate, MigrateError, Migration};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Connection, Executor, SqlitePool};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use crate::db::MIGRATOR;
+use crate::domain::audit::log_audit;
+use crate::domain::events;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ColumnRepair {
+    pub version: i64,
+    pub table: String,
+    pub column: String,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct MigrationReport {
+    /// Versiones aplicadas en esta ejecución (reparadas o no).
+    pub applied: Vec<i64>,
+    /// `ADD COLUMN` omitidos porque la columna ya existía.
+    pub repaired: Vec<ColumnRepair>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error("La base de datos tiene aplicada la migración {version}, que esta versión de la aplicación no conoce. Probablemente la creó una versión más nueva: actualiza la aplicación o restaura un backup.")]
+    NewerDatabase { version: i64 },
+    #[error("La migración {version} aplicada en esta base no coincide con la incluida en la aplicación (checksum distinto). El archivo .sql se modificó después de aplicarse o la base proviene de otra compilación.")]
+    ChecksumMismatch { version: i64 },
+    #[error("La migración {version} quedó aplicada a medias. Restaura un backup antes de continuar.")]
+    Dirty { version: i64 },
+    #[error("La migración {version} ({description}) falló: {message}")]
+    MigrationFailed {
+        version: i64,
+        description: String,
+        message: String,
+    },
+    #[error("El esquema de la base no coincide con el esperado. Falta: {}", missing.join(", "))]
+    Drift { missing: Vec<String> },
+    #[error("Error de base de datos al verificar el esquema: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<MigrateError> for SchemaError {
+    fn from(e: MigrateError) -> Self {
+        match e {
+            MigrateError::Execute(e) => SchemaError::Database(e),
+            MigrateError::VersionMissing(version) => SchemaError::NewerDatabase { version },
+            MigrateError::VersionMismatch(version) => SchemaError::ChecksumMismatch { version },
+            MigrateError::Dirty(version) => SchemaError::Dirty { version },
+            other => SchemaError::MigrationFailed {
+                version: 0,
+                description: String::new(),
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+/// Aplica las migraciones pendientes (reparando `ADD COLUMN` duplicados) y verifica el esquema.
+pub async fn migrate(pool: &SqlitePool) -> Result<MigrationReport, SchemaError> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+
+    if let Some(version) = conn.dirty_version().await? {
+        return Err(SchemaError::Dirty { version });
+    }
+
+    let applied: BTreeMap<i64, Vec<u8>> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m.checksum.into_owned()))
+        .collect();
+
+    for (version, checksum) in &applied {
+        match MIGRATOR.iter().find(|m| m.version == *version) {
+            None => return Err(SchemaError::NewerDatabase { version: *version }),
+            Some(m) if m.checksum.as_ref() != checksum.as_slice() => {
+                return Err(SchemaError::ChecksumMismatch { version: *version })
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut report = MigrationReport::default();
+    for migration in MIGRATOR.iter() {
+        if migration.migration_type.is_down_migration() || applied.contains_key(&migration.version) {
+            continue;
+        }
+
+        let mut existing = Vec::new();
+        for (table, column) in added_columns(&migration.sql) {
+            if column_exists(&mut conn, &table, &column).await? {
+                existing.push((table, column));
+            }
+        }
+
+        if existing.is_empty() {
+            conn.apply(migration).await.map_err(|e| failed(migration, e))?;
+        } else {
+            apply_without(&mut conn, migration, &existing).await?;
+            for (table, column) in existing {
+                report.repaired.push(ColumnRepair {
+                    version: migration.version,
+                    table,
+                    column,
+                });
+            }
+        }
+        report.applied.push(migration.version);
+    }
+    drop(conn);
+
+    for repair in &report.repaired {
+        tracing::warn!(version = repair.version, table = %repair.table, column = %repair.column, "schema repair: ADD COLUMN omitido, la columna ya existía");
+        log_audit(
+            pool,
+            "schema_repair",
+            "migration",
+            Some(repair.version),
+            Some(format!("{}.{} ya existía; se omitió el ALTER TABLE", repair.table, repair.column)),
+        )
+        .await
+        .ok();
+    }
+
+    check_drift(pool).await?;
+
+    if let Err(e) = events::hash_plaintext_pins(pool).await {
+        tracing::error!(error = %e, "no se pudieron hashear los PIN de evento en claro");
+    }
+    Ok(report)
+}
+
+/// Compara tablas/columnas con las de una base en memoria migrada desde cero.
+/// Las columnas de más se toleran; las que faltan son drift.
+pub async fn check_drift(pool: &SqlitePool) -> Result<(), SchemaError> {
+    let reference = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::from_str("sqlite::memory:")?)
+        .await?;
+    MIGRATOR.run(&reference).await?;
+
+    let expected = columns_by_table(&reference).await?;
+    reference.close().await;
+    let actual = columns_by_table(pool).await?;
+
+    let mut missing = Vec::new();
+    for (table, columns) in &expected {
+        match actual.get(table) {
+            None => missing.push(format!("tabla {}", table)),
+            Some(have) => missing.extend(columns.difference(have).map(|c| format!("{}.{}", table, c))),
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::Drift { missing })
+    }
+}
+
+/* ---- helpers ---- */
+
+fn failed(migration: &Migration, e: MigrateError) -> SchemaError {
+    SchemaError::MigrationFailed {
+        version: migration.version,
+        description: migration.description.to_string(),
+        message: e.to_string(),
+    }
+}
+
+async fn column_exists(conn: &mut sqlx::SqliteConnection, table: &str, column: &str) -> Result<bool, SchemaError> {
+    let n: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM pragma_table_info(?1) WHERE name = ?2 COLLATE NOCASE")
+        .bind(table)
+        .bind(column)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(n > 0)
+}
+
+async fn columns_by_table(pool: &SqlitePool) -> Result<BTreeMap<String, BTreeSet<String>>, SchemaError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT m.name, p.name
+        FROM sqlite_master m
+        JOIN pragma_table_info(m.name) p
+        WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' AND m.name <> '_sqlx_migrations'
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut map: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (table, column) in rows {
+        map.entry(table).or_default().insert(column);
+    }
+    Ok(map)
+}
+
+/// Igual que `Migrate::apply`, pero sin los `ADD COLUMN` de columnas que ya existen.
+/// Se registra con el checksum original para que sqlx la dé por aplicada.
+async fn apply_without(
+    conn: &mut sqlx::SqliteConnection,
+    migration: &Migration,
+    existing: &[(String, String)],
+) -> Result<(), SchemaError> {
+    let sql: Vec<String> = split_statements(&strip_comments(&migration.sql))
+        .into_iter()
+        .filter(|stmt| match parse_add_column(stmt) {
+            Some(col) => !existing.contains(&col),
+            None => true,
+        })
+        .collect();
+
+    let mut tx = conn.begin().await?;
+    tx.execute(sql.join(";").as_str()).await.map_err(|e| SchemaError::MigrationFailed {
+        version: migration.version,
+        description: migration.description.to_string(),
+        message: e.to_string(),
+    })?;
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?1, ?2, TRUE, ?3, -1)",
+    )
+    .bind(migration.version)
+    .bind(&*migration.description)
+    .bind(&*migration.checksum)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// `(tabla, columna)` de cada `ALTER TABLE ... ADD [COLUMN] ...` del script.
+pub fn added_columns(sql: &str) -> Vec<(String, String)> {
+    split_statements(&strip_comments(sql))
+        .iter()
+        .filter_map(|stmt| parse_add_column(stmt))
+        .collect()
+}
+
+fn parse_add_column(stmt: &str) -> Option<(String, String)> {
+    let tokens: Vec<&str> = stmt.split_whitespace().collect();
+    let is = |i: usize, kw: &str| tokens.get(i).is_some_and(|t| t.eq_ignore_ascii_case(kw));
+    if !(is(0, "ALTER") && is(1, "TABLE") && is(3, "ADD")) {
+        return None;
+    }
+    let column_at = if is(4, "COLUMN") { 5 } else { 4 };
+    let unquote = |s: &str| s.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']')).to_string();
+    Some((unquote(tokens[2]), unquote(tokens.get(column_at)?)))
+}
+
+/// Quita comentarios `--` (fuera de literales).
+fn strip_comments(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                out.push(c);
+            }
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            None if c == '-' && chars.peek() == Some(&'-') => {
+                for skipped in chars.by_ref() {
+                    if skipped == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+/// Parte el script por `;` fuera de literales. Los cuerpos de trigger quedan partidos,
+/// pero volver a unir con `;` reproduce el script original.
+fn split_statements(sql: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for c in sql.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == ';' => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            None => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+}
@@ -0,0 +1,39 @@
+//! Sesión del usuario que está usando la app (una por proceso: la app corre en una sola máquina
+//! y se pasan el teclado entre secretaría, cronometraje y locución).
+
+use std::sync::RwLock;
+
+use crate::domain::users::{Role, UserRow};
+use crate::error::{AppError, AppResult};
+
+#[derive(Default)]
+pub struct Session(RwLock<Option<UserRow>>);
+
+impl Session {
+    pub fn current(&self) -> Option<UserRow> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set(&self, user: Option<UserRow>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = user;
+    }
+
+    /// Usuario logueado o `Unauthorized`.
+    pub fn require(&self) -> AppResult<UserRow> {
+        self.current()
+            .ok_or_else(|| AppError::unauthorized("Inicia sesión para continuar."))
+    }
+
+    /// Usuario logueado con al menos el rol `min`; si no alcanza, `Forbidden`.
+    pub fn require_role(&self, min: Role) -> AppResult<UserRow> {
+        let user = self.require()?;
+        if user.role() < min {
+            return Err(AppError::forbidden(format!(
+                "Tu usuario ({}) no tiene permiso para esta acción; requiere rol {}.",
+                user.role().label(),
+                min.label()
+            )));
+        }
+        Ok(user)
+    }
+}
@@ -0,0 +1,175 @@
+//! Snapshots automáticos de la base (`VACUUM INTO`) en `<dir de la DB>/snapshots/`.
+//!
+//! - `auto`: cada `snapshot_interval_minutes` mientras haya algún evento `active` (ver `tick`).
+//! - `lock` / `completed`: después de `lock_event` y de pasar un evento a `completed`.
+//!
+//! Cada tipo conserva sus últimas `snapshot_keep` generaciones; las más viejas se borran.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::db::Db;
+use crate::domain::settings;
+use crate::error::{AppError, AppResult};
+
+/// Cada cuánto se despierta el planificador para ver si toca snapshot.
+pub const TICK: Duration = Duration::from_secs(30);
+
+const PREFIX: &str = "roping_manager-";
+const KEY_INTERVAL: &str = "snapshot_interval_minutes";
+const KEY_KEEP: &str = "snapshot_keep";
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotSettings {
+    pub interval_minutes: i64,
+    pub keep: i64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SnapshotInfo {
+    pub path: String,
+    pub file_name: String,
+    pub reason: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+pub async fn load_settings(db: &Db) -> AppResult<SnapshotSettings> {
+    let pool = db.pool();
+    Ok(SnapshotSettings {
+        interval_minutes: settings::get_i64(&pool, KEY_INTERVAL, 5).await?,
+        keep: settings::get_i64(&pool, KEY_KEEP, 24).await?,
+    })
+}
+
+pub async fn save_settings(db: &Db, s: SnapshotSettings) -> AppResult<()> {
+    if !(1..=240).contains(&s.interval_minutes) {
+        return Err(AppError::validation("interval_minutes", "El intervalo debe estar entre 1 y 240 minutos."));
+    }
+    if !(1..=500).contains(&s.keep) {
+        return Err(AppError::validation("keep", "Las generaciones a conservar deben estar entre 1 y 500."));
+    }
+    let pool = db.pool();
+    settings::set(&pool, KEY_INTERVAL, &s.interval_minutes.to_string()).await?;
+    settings::set(&pool, KEY_KEEP, &s.keep.to_string()).await?;
+    Ok(())
+}
+
+pub fn snapshot_dir(db: &Db) -> PathBuf {
+    db.path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+        .join("snapshots")
+}
+
+/// Toma un snapshot y rota los del mismo tipo.
+pub async fn take(db: &Db, reason: &str) -> AppResult<SnapshotInfo> {
+    let dir = snapshot_dir(db);
+    std::fs::create_dir_all(&dir).map_err(io_err)?;
+
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S-%3f");
+    let path = dir.join(format!("{}{}-{}.db", PREFIX, stamp, reason));
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| AppError::internal("Ruta de snapshot inválida."))?
+        .to_string();
+
+    // Sin log_audit: los automáticos llenarían la actividad reciente.
+    sqlx::query("VACUUM INTO ?1").bind(&path_str).execute(&db.pool()).await?;
+
+    let keep = load_settings(db).await?.keep.max(1) as usize;
+    rotate(&dir, reason, keep)?;
+
+    info(&path).ok_or_else(|| AppError::internal("No se pudo leer el snapshot recién creado."))
+}
+
+/// Snapshots existentes, del más nuevo al más viejo.
+pub fn list(db: &Db) -> AppResult<Vec<SnapshotInfo>> {
+    let dir = snapshot_dir(db);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+    let mut out: Vec<SnapshotInfo> = std::fs::read_dir(&dir)
+        .map_err(io_err)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| info(&e.path()))
+        .collect();
+    out.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(out)
+}
+
+/// Para llamar después de un cambio importante; un fallo se registra pero no
+/// debe tumbar el comando que ya se ejecutó.
+pub async fn after_change(db: &Db, reason: &str) {
+    if let Err(e) = take(db, reason).await {
+        tracing::error!(error = %e, reason, "snapshot failed");
+    }
+}
+
+/// Paso del planificador: snapshot `auto` si pasó el intervalo y hay algún evento activo.
+pub async fn tick(db: &Db, last: &mut Option<Instant>) {
+    let settings = match load_settings(db).await {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!(error = %e, "snapshot settings unavailable");
+            return;
+        }
+    };
+    let interval = Duration::from_secs(settings.interval_minutes.max(1) as u64 * 60);
+    if last.is_some_and(|t| t.elapsed() < interval) {
+        return;
+    }
+
+    let active: Result<i64, _> =
+        sqlx::query_scalar("SELECT COUNT(1) FROM event WHERE status = 'active' AND is_deleted = 0")
+            .fetch_one(&db.pool())
+            .await;
+    match active {
+        Ok(n) if n > 0 => {
+            after_change(db, "auto").await;
+            *last = Some(Instant::now());
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!(error = %e, "snapshot check failed"),
+    }
+}
+
+/* ---- helpers ---- */
+
+fn rotate(dir: &Path, reason: &str, keep: usize) -> AppResult<()> {
+    let suffix = format!("-{}.db", reason);
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .map_err(io_err)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|n| n.starts_with(PREFIX) && n.ends_with(&suffix))
+        .collect();
+    // el timestamp en el nombre ordena cronológicamente
+    names.sort();
+    let excess = names.len().saturating_sub(keep);
+    for name in &names[..excess] {
+        std::fs::remove_file(dir.join(name)).map_err(io_err)?;
+    }
+    Ok(())
+}
+
+/// `roping_manager-20251114-153000-123-auto.db` → info; `None` si no es un snapshot.
+fn info(path: &Path) -> Option<SnapshotInfo> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let rest = file_name.strip_prefix(PREFIX)?.strip_suffix(".db")?;
+    let mut parts = rest.splitn(4, '-');
+    let (date, time, _millis, reason) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    let created = chrono::NaiveDateTime::parse_from_str(&format!("{}{}", date, time), "%Y%m%d%H%M%S").ok()?;
+    Some(SnapshotInfo {
+        path: path.display().to_string(),
+        reason: reason.to_string(),
+        created_at: created.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        size_bytes: std::fs::metadata(path).ok()?.len(),
+        file_name,
+    })
+}
+
+fn io_err(e: std::io::Error) -> AppError {
+    AppError::internal(e.to_string())
+}
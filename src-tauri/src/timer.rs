@@ -0,0 +1,439 @@
+//! Tiempos del timer de rodeo leídos por puerto serie o por TCP.
+//!
+//! El timer manda una trama por línea (ver `parse_frame`). Cada `FINISH` queda como lectura
+//! por confirmar; la captura la ofrece para la siguiente corrida `pending` de la ronda y el
+//! operador la acepta con una tecla (`confirm`, que guarda con `runs::save`).
+//! `timer_sim` emite el mismo protocolo por un pseudo-terminal o un socket local.
+
+use std::io::{ErrorKind, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+
+use crate::domain::audit::log_audit;
+use crate::domain::runs::{self, RunExpandedRow, SaveRun};
+use crate::error::{AppError, AppResult};
+
+/// Velocidad del puerto serie si no se indica otra (8N1).
+pub const DEFAULT_BAUD: u32 = 9600;
+/// Un tiempo fuera de (0, 600) segundos es ruido en la línea, no una corrida.
+const MAX_SECONDS: f64 = 600.0;
+/// Cada cuánto despierta el lector sin datos para ver si lo desconectaron.
+const POLL: Duration = Duration::from_millis(500);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimerFrame {
+    Start,
+    Finish { seconds: f64 },
+    Reset,
+    Heartbeat,
+}
+
+impl TimerFrame {
+    /// Trama canónica con checksum, terminada en CRLF (la que emite el simulador).
+    pub fn encode(&self) -> String {
+        let body = match self {
+            TimerFrame::Start => "START".to_string(),
+            TimerFrame::Finish { seconds } => format!("FINISH {:.3}", seconds),
+            TimerFrame::Reset => "RESET".to_string(),
+            TimerFrame::Heartbeat => "HB".to_string(),
+        };
+        format!("{}*{:02X}\r\n", body, checksum(&body))
+    }
+}
+
+fn checksum(body: &str) -> u8 {
+    body.bytes().fold(0, |acc, b| acc ^ b)
+}
+
+/// Interpreta una línea del timer. Sin distinguir mayúsculas, ignorando espacios y STX/ETX:
+/// - `START` o `S`: arrancó la corrida.
+/// - `FINISH 8.456`, `F 8.456`, `F,8.456` o sólo el tiempo (`8.456`, `0:08.456`): tiempo final.
+/// - `RESET` o `R`: el timer se reinició.
+/// - `HB`: latido, sin efecto.
+///
+/// Un sufijo `*XX` es el XOR (hex) de los bytes antes del `*`; si viene, tiene que cuadrar.
+/// Las líneas vacías dan `Ok(None)`.
+pub fn parse_frame(line: &str) -> Result<Option<TimerFrame>, String> {
+    let line = line.trim_matches(|c: char| c.is_whitespace() || c == '\u{2}' || c == '\u{3}');
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let body = match line.rsplit_once('*') {
+        Some((body, sum)) => {
+            let expected = u8::from_str_radix(sum.trim(), 16).map_err(|_| format!("Checksum ilegible: {}", line))?;
+            if checksum(body) != expected {
+                return Err(format!("Checksum no cuadra: {}", line));
+            }
+            body.trim()
+        }
+        None => line,
+    };
+
+    let upper = body.to_ascii_uppercase();
+    let (word, rest) = match upper.split_once([' ', ',']) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (upper.as_str(), ""),
+    };
+    let frame = match (word, rest) {
+        ("START" | "S", "") => TimerFrame::Start,
+        ("RESET" | "R", "") => TimerFrame::Reset,
+        ("HB", "") => TimerFrame::Heartbeat,
+        ("FINISH" | "F", value) => TimerFrame::Finish { seconds: parse_seconds(value)? },
+        _ => TimerFrame::Finish { seconds: parse_seconds(&upper)? },
+    };
+    Ok(Some(frame))
+}
+
+fn parse_seconds(value: &str) -> Result<f64, String> {
+    let parsed = match value.split_once(':') {
+        Some((min, sec)) => min
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .zip(sec.trim().parse::<f64>().ok())
+            .map(|(m, s)| m as f64 * 60.0 + s),
+        None => value.trim().parse::<f64>().ok(),
+    };
+    match parsed {
+        Some(s) if s.is_finite() && s > 0.0 && s < MAX_SECONDS => Ok(s),
+        _ => Err(format!("Trama no reconocida: {}", value)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimerSource {
+    /// Puerto serie (`/dev/ttyUSB0`, `COM3`); `baud` por defecto `DEFAULT_BAUD`.
+    Serial { path: String, baud: Option<u32> },
+    /// Timer (o convertidor serie-ethernet) que escucha en `host:puerto`.
+    Tcp { addr: String },
+}
+
+/// Un `FINISH` todavía sin confirmar. `seq` cambia con cada tiempo nuevo.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TimerReading {
+    pub seq: u64,
+    pub seconds: f64,
+    pub raw: String,
+    pub received_at: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct TimerStatus {
+    pub source: Option<TimerSource>,
+    pub connected: bool,
+    /// Llegó `START` y todavía no el `FINISH`/`RESET`.
+    pub running: bool,
+    pub reading: Option<TimerReading>,
+    pub frames: u64,
+    /// Líneas que no se entendieron (ruido, checksum malo).
+    pub rejected: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    status: TimerStatus,
+    seq: u64,
+}
+
+/// Estado del timer (uno por app, en el estado de Tauri). La lectura corre en un hilo propio.
+#[derive(Default)]
+pub struct Timer {
+    inner: Arc<Mutex<Inner>>,
+    /// Sube en cada `connect`/`disconnect`; el hilo lector que ve otra generación termina.
+    generation: Arc<AtomicU64>,
+}
+
+impl Timer {
+    pub fn status(&self) -> TimerStatus {
+        lock(&self.inner).status.clone()
+    }
+
+    /// Abre la fuente (los errores de conexión salen aquí) y deja un hilo leyendo tramas.
+    pub fn connect(&self, source: TimerSource) -> AppResult<()> {
+        let (stream, eof_is_idle) = open(&source)?;
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        {
+            let mut inner = lock(&self.inner);
+            inner.status = TimerStatus { source: Some(source), connected: true, ..Default::default() };
+        }
+        let inner = self.inner.clone();
+        let current = self.generation.clone();
+        std::thread::Builder::new()
+            .name("timer-reader".into())
+            .spawn(move || read_loop(stream, eof_is_idle, &inner, &current, generation))
+            .map_err(|e| AppError::internal(e.to_string()))?;
+        Ok(())
+    }
+
+    pub fn disconnect(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let mut inner = lock(&self.inner);
+        inner.status.connected = false;
+        inner.status.running = false;
+    }
+
+    /// Procesa una línea como si llegara del timer.
+    pub fn ingest_line(&self, line: &str) {
+        ingest(&self.inner, line);
+    }
+
+    /// Descarta la lectura `seq` (tiempo falso, prueba del timer). `false` si ya no estaba.
+    pub fn dismiss(&self, seq: u64) -> bool {
+        self.take(seq).is_some()
+    }
+
+    fn take(&self, seq: u64) -> Option<TimerReading> {
+        let mut inner = lock(&self.inner);
+        match &inner.status.reading {
+            Some(r) if r.seq == seq => inner.status.reading.take(),
+            _ => None,
+        }
+    }
+
+    /// Devuelve una lectura que no se pudo guardar, salvo que ya haya llegado otra.
+    fn restore(&self, reading: TimerReading) {
+        let mut inner = lock(&self.inner);
+        if inner.status.reading.is_none() {
+            inner.status.reading = Some(reading);
+        }
+    }
+}
+
+fn lock(inner: &Mutex<Inner>) -> std::sync::MutexGuard<'_, Inner> {
+    inner.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn ingest(inner: &Mutex<Inner>, line: &str) {
+    let parsed = parse_frame(line);
+    let mut inner = lock(inner);
+    match parsed {
+        Ok(None) => {}
+        Ok(Some(frame)) => {
+            inner.status.frames += 1;
+            match frame {
+                TimerFrame::Start => inner.status.running = true,
+                TimerFrame::Reset => inner.status.running = false,
+                TimerFrame::Heartbeat => {}
+                // Un tiempo nuevo reemplaza al anterior sin confirmar; el `seq` evita confirmar el equivocado
+                TimerFrame::Finish { seconds } => {
+                    inner.seq += 1;
+                    inner.status.running = false;
+                    inner.status.reading = Some(TimerReading {
+                        seq: inner.seq,
+                        seconds,
+                        raw: line.trim().to_string(),
+                        received_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                    });
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "trama del timer descartada");
+            inner.status.rejected += 1;
+            inner.status.last_error = Some(e);
+        }
+    }
+}
+
+/// Lee hasta que cambie la generación o se caiga la fuente. Las tramas se separan por CR o LF.
+/// En serie, una lectura vacía sólo significa que pasó `VTIME` sin datos.
+fn read_loop(mut stream: Box<dyn Read + Send>, eof_is_idle: bool, inner: &Mutex<Inner>, current: &AtomicU64, generation: u64) {
+    let mut chunk = [0u8; 256];
+    let mut line = Vec::new();
+    let closed = loop {
+        if current.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        match stream.read(&mut chunk) {
+            Ok(0) if eof_is_idle => continue,
+            Ok(0) => break "El timer cerró la conexión.".to_string(),
+            Ok(n) => {
+                for &b in &chunk[..n] {
+                    if b == b'\r' || b == b'\n' {
+                        if !line.is_empty() {
+                            ingest(inner, &String::from_utf8_lossy(&line));
+                            line.clear();
+                        }
+                    } else {
+                        line.push(b);
+                    }
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => continue,
+            Err(e) => break format!("Se perdió la conexión con el timer: {}", e),
+        }
+    };
+    if current.load(Ordering::SeqCst) == generation {
+        tracing::warn!(reason = %closed, "timer desconectado");
+        let mut inner = lock(inner);
+        inner.status.connected = false;
+        inner.status.running = false;
+        inner.status.last_error = Some(closed);
+    }
+}
+
+fn open(source: &TimerSource) -> AppResult<(Box<dyn Read + Send>, bool)> {
+    match source {
+        TimerSource::Tcp { addr } => {
+            let target = addr
+                .to_socket_addrs()
+                .ok()
+                .and_then(|mut a| a.next())
+                .ok_or_else(|| AppError::validation("addr", format!("Dirección inválida: {} (usa host:puerto).", addr)))?;
+            let stream = TcpStream::connect_timeout(&target, CONNECT_TIMEOUT)
+                .map_err(|e| AppError::validation("addr", format!("No se pudo conectar con el timer en {}: {}", addr, e)))?;
+            stream.set_read_timeout(Some(POLL)).map_err(|e| AppError::internal(e.to_string()))?;
+            Ok((Box::new(stream), false))
+        }
+        TimerSource::Serial { path, baud } => {
+            let baud = baud.unwrap_or(DEFAULT_BAUD);
+            let file = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .map_err(|e| AppError::validation("path", format!("No se pudo abrir {}: {}", path, e)))?;
+            #[cfg(unix)]
+            configure_serial(&file, baud)?;
+            #[cfg(not(unix))]
+            let _ = baud; // en Windows la velocidad se ajusta en el administrador de dispositivos
+            Ok((Box::new(file), true))
+        }
+    }
+}
+
+/// Modo raw 8N1 a `baud`, con lecturas que regresan vacías tras `POLL` sin datos (VMIN=0, VTIME=5).
+#[cfg(unix)]
+fn configure_serial(file: &std::fs::File, baud: u32) -> AppResult<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let speed = match baud {
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        other => return Err(AppError::validation("baud", format!("Velocidad no soportada: {}", other))),
+    };
+    let fd = file.as_raw_fd();
+    // SAFETY: `fd` es válido mientras viva `file` y `tio` es un termios propio.
+    unsafe {
+        let mut tio: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut tio) != 0 {
+            return Err(AppError::validation(
+                "path",
+                format!("No es un puerto serie: {}", std::io::Error::last_os_error()),
+            ));
+        }
+        libc::cfmakeraw(&mut tio);
+        tio.c_cflag |= libc::CLOCAL | libc::CREAD;
+        tio.c_cc[libc::VMIN] = 0;
+        tio.c_cc[libc::VTIME] = (POLL.as_millis() / 100) as libc::cc_t;
+        libc::cfsetispeed(&mut tio, speed);
+        libc::cfsetospeed(&mut tio, speed);
+        if libc::tcsetattr(fd, libc::TCSANOW, &tio) != 0 {
+            return Err(AppError::internal(format!(
+                "No se pudo configurar el puerto serie: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+pub struct TimerOffer {
+    pub status: TimerStatus,
+    /// Corrida a la que se aplicaría la lectura (la siguiente `pending`).
+    pub run: Option<RunExpandedRow>,
+}
+
+pub async fn offer(pool: &SqlitePool, timer: &Timer, event_id: i64, round: Option<i64>) -> AppResult<TimerOffer> {
+    Ok(TimerOffer {
+        status: timer.status(),
+        run: runs::next_pending(pool, event_id, round).await?,
+    })
+}
+
+#[derive(serde::Deserialize)]
+pub struct ConfirmTimerTime {
+    pub event_id: i64,
+    /// Corrida de la oferta que vio el operador (`TimerOffer::run`) y su `version`.
+    pub run_id: i64,
+    pub expected_version: i64,
+    /// `seq` de la lectura que vio el operador; si llegó otra, no se guarda nada.
+    pub seq: u64,
+    pub penalties: Option<Vec<i64>>,
+}
+
+/// Guarda la lectura `seq` en la corrida que se le ofreció al operador y la consume. Si esa
+/// corrida ya no está pendiente o cambió (otra ventana la capturó), Conflict con la corrida
+/// actual: el tiempo nunca cae en otro equipo que el que estaba en pantalla.
+pub async fn confirm(pool: &SqlitePool, timer: &Timer, payload: ConfirmTimerTime) -> AppResult<i64> {
+    let current = timer
+        .status()
+        .reading
+        .ok_or_else(|| AppError::not_found("No hay tiempo del timer por confirmar."))?;
+    if current.seq != payload.seq {
+        return Err(AppError::conflict("El timer mandó otro tiempo; revisa la lectura antes de confirmar."));
+    }
+    let run = runs::get(pool, payload.run_id).await?;
+    if run.event_id != payload.event_id {
+        return Err(AppError::not_found("Corrida no encontrada en este evento."));
+    }
+    if run.status != "pending" || run.version != payload.expected_version {
+        return Err(AppError::stale(
+            "La corrida en pantalla ya se capturó o cambió; revisa a qué equipo va el tiempo.",
+            run,
+        ));
+    }
+    let reading = timer
+        .take(payload.seq)
+        .ok_or_else(|| AppError::conflict("La lectura ya se confirmó o se descartó."))?;
+
+    let saved = runs::save(
+        pool,
+        SaveRun {
+            event_id: payload.event_id,
+            team_id: run.team_id,
+            round: run.round,
+            position: run.position,
+            time_sec: Some(reading.seconds),
+            penalty: 0.0,
+            penalties: payload.penalties,
+            no_time: false,
+            dq: false,
+            pin: None,
+            reason: None,
+            // si otra ventana la capturó mientras tanto, no se pisa
+            expected_version: Some(payload.expected_version),
+        },
+    )
+    .await;
+    let run_id = match saved {
+        Ok(id) => id,
+        Err(e) => {
+            timer.restore(reading);
+            return Err(e);
+        }
+    };
+    log_audit(
+        pool,
+        "timer_capture",
+        "run",
+        Some(run_id),
+        Some(format!("{:.3}s del timer ({})", reading.seconds, reading.raw)),
+    )
+    .await?;
+    Ok(run_id)
+}
@@ -0,0 +1,126 @@
+//! Simulador de timer para probar sin hardware: emite las mismas tramas que entiende
+//! `timer::parse_frame` por un socket local (el simulador escucha y la app se conecta, como con
+//! un convertidor serie-ethernet) o por un pseudo-terminal (la app abre el lado esclavo como si
+//! fuera el puerto serie). Lo usan los tests y `roping-admin timer-sim`.
+
+use std::io::{ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::{AppError, AppResult};
+use crate::timer::TimerFrame;
+
+/// Algo que emite tramas del timer.
+pub trait TimerEmitter {
+    /// Manda una línea tal cual (útil para probar ruido o formatos de otros timers).
+    fn send_raw(&mut self, line: &str) -> AppResult<()>;
+
+    fn send(&mut self, frame: TimerFrame) -> AppResult<()> {
+        self.send_raw(&frame.encode())
+    }
+}
+
+/// Servidor TCP en `addr` que manda cada trama a todos los clientes conectados.
+pub struct TcpSimulator {
+    addr: SocketAddr,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl TcpSimulator {
+    /// `127.0.0.1:0` toma un puerto libre (ver `addr`).
+    pub fn bind(addr: &str) -> AppResult<Self> {
+        let listener = TcpListener::bind(addr).map_err(|e| AppError::internal(format!("No se pudo escuchar en {}: {}", addr, e)))?;
+        listener.set_nonblocking(true).map_err(|e| AppError::internal(e.to_string()))?;
+        let addr = listener.local_addr().map_err(|e| AppError::internal(e.to_string()))?;
+        let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::default();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (accepted, stopped) = (clients.clone(), stop.clone());
+        std::thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, _)) => accepted.lock().unwrap_or_else(|e| e.into_inner()).push(stream),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(Duration::from_millis(20)),
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(TcpSimulator { addr, clients, stop })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn clients(&self) -> usize {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+impl TimerEmitter for TcpSimulator {
+    /// Los clientes que ya se fueron se descartan al escribirles.
+    fn send_raw(&mut self, line: &str) -> AppResult<()> {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        clients.retain_mut(|c| c.write_all(line.as_bytes()).and_then(|_| c.flush()).is_ok());
+        Ok(())
+    }
+}
+
+impl Drop for TcpSimulator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Pseudo-terminal: el simulador escribe en el maestro y la app abre `path` (el esclavo) como
+/// puerto serie.
+#[cfg(unix)]
+pub struct PtySimulator {
+    master: std::fs::File,
+    path: String,
+}
+
+#[cfg(unix)]
+impl PtySimulator {
+    pub fn open() -> AppResult<Self> {
+        use std::ffi::CStr;
+        use std::os::unix::io::FromRawFd;
+
+        let os_error = |what: &str| AppError::internal(format!("{}: {}", what, std::io::Error::last_os_error()));
+        // SAFETY: llamadas POSIX sobre un fd recién abierto; si algo falla se cierra antes de salir.
+        unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            if fd < 0 {
+                return Err(os_error("posix_openpt"));
+            }
+            let master = std::fs::File::from_raw_fd(fd);
+            if libc::grantpt(fd) != 0 || libc::unlockpt(fd) != 0 {
+                return Err(os_error("grantpt/unlockpt"));
+            }
+            let name = libc::ptsname(fd);
+            if name.is_null() {
+                return Err(os_error("ptsname"));
+            }
+            let path = CStr::from_ptr(name).to_string_lossy().into_owned();
+            Ok(PtySimulator { master, path })
+        }
+    }
+
+    /// Ruta del lado esclavo (`/dev/pts/N`), la que se le da a la app como puerto serie.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+#[cfg(unix)]
+impl TimerEmitter for PtySimulator {
+    fn send_raw(&mut self, line: &str) -> AppResult<()> {
+        self.master
+            .write_all(line.as_bytes())
+            .and_then(|_| self.master.flush())
+            .map_err(|e| AppError::internal(e.to_string()))
+    }
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::activity::{self, ActivityFilter};
+use roping_manager_tauri_lib::domain::{audit, draw, events, users};
+use roping_manager_tauri_lib::error::AppError;
+use sqlx::SqlitePool;
+
+async fn insert_user(pool: &SqlitePool, email: &str, full_name: &str) -> i64 {
+    sqlx::query("INSERT INTO app_user (email, full_name, password_hash) VALUES (?1, ?2, 'x')")
+        .bind(email)
+        .bind(full_name)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+}
+
+async fn event_with_draw(pool: &SqlitePool, rounds: i64, n_teams: usize) -> (i64, Vec<i64>) {
+    let event_id = create_event(pool, rounds).await;
+    let teams = create_teams(pool, event_id, n_teams).await;
+    draw::generate_batch(
+        pool,
+        draw::GenerateBatchDrawOptions {
+            event_id,
+            rounds,
+            shuffle: false,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+    (event_id, teams)
+}
+
+fn open(rows: &[activity::ActivityRow], kind: &str) -> usize {
+    rows.iter().filter(|r| r.kind == kind && r.ended_at.is_none()).count()
+}
+
+#[tokio::test]
+async fn capture_sessions_follow_the_rounds_and_the_timer_operator() {
+    let pool = memory_pool().await;
+    let (event_id, teams) = event_with_draw(&pool, 3, 2).await;
+    let ana = insert_user(&pool, "ana@example.com", "Ana").await;
+
+    audit::as_user(Some(ana), capture(&pool, event_id, 1, teams[0], Some(8.0), false, false)).await;
+    let rows = activity::event_sessions(&pool, event_id).await.unwrap();
+    assert_eq!(open(&rows, activity::EVENT_DAY), 1);
+    assert_eq!(open(&rows, activity::CAPTURE), 1);
+    let capture_row = rows.iter().find(|r| r.kind == activity::CAPTURE).unwrap();
+    assert_eq!(capture_row.round, Some(1));
+    assert_eq!(capture_row.user_name.as_deref(), Some("Ana"));
+
+    // la última corrida de la ronda cierra su captura; la jornada sigue abierta
+    audit::as_user(Some(ana), capture(&pool, event_id, 1, teams[1], Some(9.0), false, false)).await;
+    let rows = activity::event_sessions(&pool, event_id).await.unwrap();
+    assert_eq!(rows.iter().filter(|r| r.kind == activity::CAPTURE).count(), 1);
+    assert_eq!(open(&rows, activity::CAPTURE), 0);
+    assert!(rows.iter().find(|r| r.kind == activity::CAPTURE).unwrap().duration_sec.is_some());
+    assert_eq!(open(&rows, activity::EVENT_DAY), 1);
+
+    // otro usuario toma la ronda 2
+    let luis = insert_user(&pool, "luis@example.com", "Luis").await;
+    audit::as_user(Some(luis), capture(&pool, event_id, 2, teams[0], Some(7.5), false, false)).await;
+    let user = users::get(&pool, luis).await.unwrap();
+    audit::as_user(Some(luis), users::record_logout(&pool, &user)).await.unwrap();
+    let rows = activity::event_sessions(&pool, event_id).await.unwrap();
+    let round2 = rows.iter().find(|r| r.round == Some(2)).unwrap();
+    assert_eq!(round2.user_id, Some(luis));
+    assert!(round2.ended_at.is_some(), "el logout cierra su captura");
+
+    events::update_status(&pool, event_id, "completed", None).await.unwrap();
+    let rows = activity::event_sessions(&pool, event_id).await.unwrap();
+    assert_eq!(open(&rows, activity::EVENT_DAY), 0);
+}
+
+#[tokio::test]
+async fn explicit_sessions_and_dangling_ones_are_closed() {
+    let pool = memory_pool().await;
+    let (event_id, _) = event_with_draw(&pool, 2, 1).await;
+
+    let id = activity::start_session(&pool, activity::CAPTURE, event_id, Some(1)).await.unwrap();
+    assert_eq!(activity::start_session(&pool, activity::CAPTURE, event_id, Some(1)).await.unwrap(), id);
+    // cambiar de ronda cierra la anterior del mismo usuario
+    activity::start_session(&pool, activity::CAPTURE, event_id, Some(2)).await.unwrap();
+    let rows = activity::event_sessions(&pool, event_id).await.unwrap();
+    assert_eq!(open(&rows, activity::CAPTURE), 1);
+    assert!(rows.iter().find(|r| r.id == id).unwrap().ended_at.is_some());
+
+    // la app se cerró de golpe: al arrancar se cierran
+    assert_eq!(activity::close_dangling(&pool).await.unwrap(), 2);
+    let rows = activity::event_sessions(&pool, event_id).await.unwrap();
+    assert!(rows.iter().all(|r| r.ended_at.is_some()));
+
+    let err = activity::start_session(&pool, "coffee_break", event_id, None).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "kind"));
+}
+
+#[tokio::test]
+async fn list_filters_by_date_kind_and_user() {
+    let pool = memory_pool().await;
+    let ana = insert_user(&pool, "ana@example.com", "Ana").await;
+    activity::record(&pool, activity::APP_START, None).await.unwrap();
+    audit::as_user(Some(ana), activity::record(&pool, activity::LOGIN, Some("ana@example.com".into())))
+        .await
+        .unwrap();
+    sqlx::query("UPDATE activity SET created_at = '2020-01-15T10:00:00Z' WHERE kind = 'app_start'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let all = activity::list(&pool, ActivityFilter::default()).await.unwrap();
+    assert_eq!(all.len(), 2);
+
+    let old = activity::list(
+        &pool,
+        ActivityFilter {
+            from: Some("2020-01-15".into()),
+            to: Some("2020-01-15".into()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(old.len(), 1);
+    assert_eq!(old[0].kind, activity::APP_START);
+
+    let logins = activity::list(
+        &pool,
+        ActivityFilter {
+            kind: Some(activity::LOGIN.into()),
+            user_id: Some(ana),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(logins.len(), 1);
+    assert_eq!(logins[0].user_name.as_deref(), Some("Ana"));
+
+    let err = activity::list(
+        &pool,
+        ActivityFilter {
+            from: Some("15/01/2020".into()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "from"));
+}
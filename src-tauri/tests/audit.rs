@@ -0,0 +1,120 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::{audit, draw, events, penalties, teams};
+use sqlx::SqlitePool;
+
+/// Usuario directo en la tabla: estos tests no necesitan hashear contraseñas.
+async fn insert_user(pool: &SqlitePool, email: &str, full_name: &str) -> i64 {
+    sqlx::query("INSERT INTO app_user (email, full_name, password_hash) VALUES (?1, ?2, 'x')")
+        .bind(email)
+        .bind(full_name)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+}
+
+#[tokio::test]
+async fn audit_rows_carry_the_acting_user_and_before_after() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let ana = insert_user(&pool, "ana@example.com", "Ana Pérez").await;
+
+    let patch: events::EventPatch =
+        serde_json::from_value(serde_json::json!({ "name": "Renombrado", "admin_pin": "4321" })).unwrap();
+    audit::as_user(Some(ana), events::update(&pool, event_id, patch)).await.unwrap();
+
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.action, "update_event");
+    assert_eq!(log.user_id, Some(ana));
+    assert_eq!(log.user_name.as_deref(), Some("Ana Pérez"));
+
+    let before: serde_json::Value = serde_json::from_str(log.before_json.as_deref().unwrap()).unwrap();
+    let after: serde_json::Value = serde_json::from_str(log.after_json.as_deref().unwrap()).unwrap();
+    assert_ne!(before["name"], "Renombrado");
+    assert_eq!(after["name"], "Renombrado");
+    // el PIN (aunque hasheado) no se copia al audit
+    assert!(after.get("admin_pin").is_none());
+
+    // fuera de `as_user` el autor queda vacío
+    events::lock(&pool, event_id).await.unwrap();
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.action, "lock_event");
+    assert_eq!(log.user_id, None);
+}
+
+#[tokio::test]
+async fn run_keeps_capturer_and_records_corrector() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let teams = create_teams(&pool, event_id, 1).await;
+    draw::generate_batch(
+        &pool,
+        draw::GenerateBatchDrawOptions {
+            event_id,
+            rounds: 2,
+            shuffle: false,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+    let ana = insert_user(&pool, "ana@example.com", "Ana").await;
+    let luis = insert_user(&pool, "luis@example.com", "Luis").await;
+
+    audit::as_user(Some(ana), capture(&pool, event_id, 1, teams[0], Some(8.0), false, false)).await;
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!(run.captured_by, Some(ana));
+    assert_eq!(run.corrected_by, None);
+
+    let run_id = audit::as_user(Some(luis), capture(&pool, event_id, 1, teams[0], Some(9.5), false, false)).await;
+    assert_eq!(run_id, run.id);
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!(run.captured_by, Some(ana));
+    assert_eq!(run.corrected_by, Some(luis));
+    assert!(run.corrected_at.is_some());
+
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.action, "save_run");
+    assert_eq!(log.entity_id, Some(run.id));
+    assert_eq!(log.user_id, Some(luis));
+    let before: serde_json::Value = serde_json::from_str(log.before_json.as_deref().unwrap()).unwrap();
+    let after: serde_json::Value = serde_json::from_str(log.after_json.as_deref().unwrap()).unwrap();
+    assert_eq!(before["time_sec"], 8.0);
+    assert_eq!(after["time_sec"], 9.5);
+}
+
+#[tokio::test]
+async fn event_logs_include_draw_scratch_and_penalty_changes() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let team_ids = create_teams(&pool, event_id, 3).await;
+    draw::generate_batch(
+        &pool,
+        draw::GenerateBatchDrawOptions { event_id, rounds: 2, shuffle: false, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    draw::swap_positions(&pool, draw::SwapDrawOptions { event_id, round: 1, position_a: 1, position_b: 2, pin: None })
+        .await
+        .unwrap();
+    penalties::create_type(
+        &pool,
+        penalties::NewPenaltyType { event_id, code: "flag".into(), label: "Bandera".into(), seconds: 3.0 },
+    )
+    .await
+    .unwrap();
+    capture(&pool, event_id, 1, team_ids[0], Some(8.0), false, false).await;
+    teams::scratch(&pool, team_ids[2], Some("Caballo lesionado".into())).await.unwrap();
+    // de otro evento: no entra
+    audit::log_audit(&pool, "generate_draw", "draw", Some(event_id + 1), None).await.unwrap();
+
+    let logs = audit::event_logs(&pool, event_id).await.unwrap();
+    let actions: Vec<&str> = logs.iter().map(|l| l.action.as_str()).collect();
+    for expected in ["create_event", "generate_draw_batch", "swap_draw", "create_penalty_type", "save_run", "scratch_team"] {
+        assert!(actions.contains(&expected), "falta {} en {:?}", expected, actions);
+    }
+    assert_eq!(actions.iter().filter(|a| **a == "generate_draw").count(), 0);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::db;
+use roping_manager_tauri_lib::domain::{events, maintenance};
+use roping_manager_tauri_lib::error::AppError;
+
+#[tokio::test]
+async fn restore_swaps_the_live_pool_and_keeps_a_safety_copy() {
+    let dir = TempDir::new("restore");
+    let db = file_db(&dir).await;
+    create_event(&db.pool(), 2).await;
+
+    let backup = dir.0.join("backup.db");
+    maintenance::backup_to(&db.pool(), &backup).await.unwrap();
+    events::delete(&db.pool(), 1).await.unwrap();
+    assert!(events::list(&db.pool(), None).await.unwrap().is_empty());
+
+    let report = db.restore_from(&backup).await.unwrap();
+
+    assert_eq!(events::list(&db.pool(), None).await.unwrap().len(), 1);
+    assert_eq!(report.migrations_applied, 0);
+    assert!(report.requires_login);
+    let safety = db::open_pool(std::path::Path::new(&report.safety_backup), false).await.unwrap();
+    assert!(events::list(&safety, None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn backup_refuses_to_overwrite_an_existing_file() {
+    let dir = TempDir::new("overwrite");
+    let db = file_db(&dir).await;
+    let dest = dir.0.join("exists.db");
+    std::fs::write(&dest, b"x").unwrap();
+
+    let err = maintenance::backup_to(&db.pool(), &dest).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "path"), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn restore_rejects_files_that_are_not_roping_databases() {
+    let dir = TempDir::new("reject");
+    let db = file_db(&dir).await;
+    create_event(&db.pool(), 2).await;
+
+    let junk = dir.0.join("junk.db");
+    std::fs::write(&junk, b"definitely not sqlite").unwrap();
+    assert_eq!(db.restore_from(&junk).await.unwrap_err().code(), "Validation");
+
+    let newer = dir.0.join("newer.db");
+    maintenance::backup_to(&db.pool(), &newer).await.unwrap();
+    let other = db::open_pool(&newer, false).await.unwrap();
+    sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (999, 'future', 1, x'00', 0)")
+        .execute(&other)
+        .await
+        .unwrap();
+    other.close().await;
+    let err = db.restore_from(&newer).await.unwrap_err();
+    assert!(err.to_string().contains("más nueva"), "got {}", err);
+
+    // la base en uso sigue intacta
+    assert_eq!(events::list(&db.pool(), None).await.unwrap().len(), 1);
+}
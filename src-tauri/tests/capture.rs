@@ -0,0 +1,107 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::{draw, runs};
+
+async fn event_with_draw(rounds: i64, n_teams: usize) -> (sqlx::SqlitePool, i64, Vec<i64>) {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, rounds).await;
+    let team_ids = create_teams(&pool, event_id, n_teams).await;
+    draw::generate_batch(
+        &pool,
+        draw::GenerateBatchDrawOptions {
+            event_id,
+            rounds,
+            shuffle: false,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+    (pool, event_id, team_ids)
+}
+
+#[tokio::test]
+async fn no_time_skips_later_rounds_and_valid_correction_restores_them() {
+    let (pool, event_id, teams) = event_with_draw(4, 2).await;
+    let team = teams[0];
+
+    capture(&pool, event_id, 1, team, None, true, false).await;
+    assert_eq!(run_for(&pool, event_id, 2, team).await.status, "skipped");
+    assert_eq!(run_for(&pool, event_id, 3, team).await.status, "skipped");
+    // el otro equipo no se toca
+    assert_eq!(run_for(&pool, event_id, 2, teams[1]).await.status, "pending");
+
+    capture(&pool, event_id, 1, team, Some(8.5), false, false).await;
+    assert_eq!(run_for(&pool, event_id, 2, team).await.status, "pending");
+    assert_eq!(run_for(&pool, event_id, 3, team).await.status, "pending");
+}
+
+#[tokio::test]
+async fn dq_skips_only_rounds_after_the_captured_one() {
+    let (pool, event_id, teams) = event_with_draw(4, 2).await;
+    let team = teams[1];
+
+    capture(&pool, event_id, 1, team, Some(7.0), false, false).await;
+    capture(&pool, event_id, 2, team, None, false, true).await;
+
+    let r1 = run_for(&pool, event_id, 1, team).await;
+    assert_eq!(r1.status, "completed");
+    assert_eq!(r1.total_sec, Some(7.0));
+    let r2 = run_for(&pool, event_id, 2, team).await;
+    assert_eq!(r2.status, "completed");
+    assert_eq!(r2.dq, 1);
+    assert_eq!(r2.total_sec, None);
+    assert_eq!(run_for(&pool, event_id, 3, team).await.status, "skipped");
+}
+
+#[tokio::test]
+async fn valid_correction_does_not_reopen_completed_later_rounds() {
+    let (pool, event_id, teams) = event_with_draw(4, 1).await;
+    let team = teams[0];
+
+    capture(&pool, event_id, 1, team, Some(9.0), false, false).await;
+    capture(&pool, event_id, 2, team, Some(8.0), false, false).await;
+    // corrección del round 1: el round 2 ya capturado sigue completed
+    capture(&pool, event_id, 1, team, Some(9.5), false, false).await;
+
+    let r2 = run_for(&pool, event_id, 2, team).await;
+    assert_eq!(r2.status, "completed");
+    assert_eq!(r2.total_sec, Some(8.0));
+}
+
+#[tokio::test]
+async fn correction_updates_the_same_row_and_adds_penalty() {
+    let (pool, event_id, teams) = event_with_draw(2, 1).await;
+    let team = teams[0];
+    let before = run_for(&pool, event_id, 1, team).await;
+
+    for penalty in [0.0, 5.0] {
+        runs::save(
+            &pool,
+            runs::SaveRun {
+                event_id,
+                team_id: team,
+                round: 1,
+                position: before.position,
+                time_sec: Some(10.0),
+                penalty,
+                penalties: None,
+                no_time: false,
+                dq: false,
+                pin: None,
+                reason: None,
+                expected_version: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    let rows = runs::list(&pool, event_id, Some(1)).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].id, before.id);
+    assert_eq!(rows[0].penalty, 5.0);
+    assert_eq!(rows[0].total_sec, Some(15.0));
+}
@@ -0,0 +1,194 @@
+#![allow(dead_code)]
+
+use std::str::FromStr;
+
+use std::path::PathBuf;
+
+use roping_manager_tauri_lib::db::{self, Db};
+use roping_manager_tauri_lib::domain::{events, ropers, runs, series, teams};
+use roping_manager_tauri_lib::schema;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+
+/// SQLite en memoria vacía (sin migraciones).
+///
+/// Una sola conexión que nunca se recicla: cada conexión `:memory:` es una BD distinta.
+pub async fn empty_memory_pool() -> SqlitePool {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:")
+        .unwrap()
+        .foreign_keys(true);
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .expect("open in-memory sqlite")
+}
+
+/// SQLite en memoria con todas las migraciones aplicadas.
+pub async fn memory_pool() -> SqlitePool {
+    let pool = empty_memory_pool().await;
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    pool
+}
+
+/// Directorio temporal propio por test (se borra al final).
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("roping-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// `Db` sobre un archivo real en `dir` (backup/restore/snapshots necesitan archivo).
+pub async fn file_db(dir: &TempDir) -> Db {
+    let path = dir.0.join(db::DB_FILE_NAME);
+    let pool = db::open_pool(&path, true).await.unwrap();
+    schema::migrate(&pool).await.unwrap();
+    Db::new(pool, path)
+}
+
+pub async fn create_event(pool: &SqlitePool, rounds: i64) -> i64 {
+    let series_id = series::create(
+        pool,
+        series::NewSeries {
+            name: "Serie Test".into(),
+            season: "2025".into(),
+            status: "active".into(),
+            start_date: None,
+            end_date: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    events::create(
+        pool,
+        events::NewEvent {
+            series_id,
+            name: "Roping Test".into(),
+            date: "2025-06-01".into(),
+            rounds,
+            status: Some("active".into()),
+            location: Some("Arena".into()),
+            entry_fee: Some(100.0),
+            prize_pool: Some(500.0),
+            max_team_rating: None,
+            payoff_allocation: None,
+            scratch_policy: None,
+            callback_mode: None,
+            callback_value: None,
+            callback_ties: None,
+            final_order: None,
+            admin_pin: None,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+pub async fn create_roper(pool: &SqlitePool, first: &str, specialty: &str) -> i64 {
+    ropers::create(
+        pool,
+        ropers::NewRoper {
+            first_name: first.into(),
+            last_name: "Test".into(),
+            specialty: specialty.into(),
+            rating: 3,
+            phone: None,
+            email: None,
+            level: None,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+pub async fn create_team(pool: &SqlitePool, event_id: i64, header_id: i64, heeler_id: i64) -> i64 {
+    teams::create(
+        pool,
+        teams::NewTeam {
+            event_id,
+            header_id,
+            heeler_id,
+            rating: 6.0,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// Crea `n` equipos con ropers distintos (H1/E1, H2/E2, ...).
+pub async fn create_teams(pool: &SqlitePool, event_id: i64, n: usize) -> Vec<i64> {
+    let mut ids = Vec::with_capacity(n);
+    for i in 1..=n {
+        let header = create_roper(pool, &format!("H{}", i), "header").await;
+        let heeler = create_roper(pool, &format!("E{}", i), "heeler").await;
+        ids.push(create_team(pool, event_id, header, heeler).await);
+    }
+    ids
+}
+
+pub async fn run_for(pool: &SqlitePool, event_id: i64, round: i64, team_id: i64) -> runs::RunRow {
+    runs::list(pool, event_id, Some(round))
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|r| r.team_id == team_id)
+        .unwrap_or_else(|| panic!("no run for team {} in round {}", team_id, round))
+}
+
+/// Captura un run usando la posición que ya tiene en el draw.
+pub async fn capture(
+    pool: &SqlitePool,
+    event_id: i64,
+    round: i64,
+    team_id: i64,
+    time_sec: Option<f64>,
+    no_time: bool,
+    dq: bool,
+) -> i64 {
+    let position = run_for(pool, event_id, round, team_id).await.position;
+    runs::save(
+        pool,
+        runs::SaveRun {
+            event_id,
+            team_id,
+            round,
+            position,
+            time_sec,
+            penalty: 0.0,
+            penalties: None,
+            no_time,
+            dq,
+            pin: None,
+            reason: None,
+            expected_version: None,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+pub async fn draw_order(pool: &SqlitePool, event_id: i64, round: i64) -> Vec<i64> {
+    roping_manager_tauri_lib::domain::draw::get(pool, event_id, round)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|d| d.team_id)
+        .collect()
+}
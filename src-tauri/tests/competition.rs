@@ -0,0 +1,133 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::draw::{self, GenerateBatchDrawOptions, GenerateDrawOptions};
+use roping_manager_tauri_lib::domain::payoffs::{self, NewPayoffRule};
+use roping_manager_tauri_lib::domain::standings;
+
+#[tokio::test]
+async fn full_roping_from_entries_to_payouts() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+
+    // 6 ropers, 4 equipos; "Ana" y "Beto" lazean dos veces
+    let ana = create_roper(&pool, "Ana", "header").await;
+    let beto = create_roper(&pool, "Beto", "heeler").await;
+    let caro = create_roper(&pool, "Caro", "both").await;
+    let dani = create_roper(&pool, "Dani", "heeler").await;
+    let eli = create_roper(&pool, "Eli", "header").await;
+    let fer = create_roper(&pool, "Fer", "heeler").await;
+    let t_ab = create_team(&pool, event_id, ana, beto).await;
+    let t_ad = create_team(&pool, event_id, ana, dani).await;
+    let t_cb = create_team(&pool, event_id, caro, beto).await;
+    let t_ef = create_team(&pool, event_id, eli, fer).await;
+
+    draw::generate_batch(
+        &pool,
+        GenerateBatchDrawOptions {
+            event_id,
+            rounds: 3,
+            shuffle: true,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    // Round 1
+    capture(&pool, event_id, 1, t_ab, Some(7.2), false, false).await;
+    capture(&pool, event_id, 1, t_ad, Some(9.1), false, false).await;
+    capture(&pool, event_id, 1, t_cb, None, true, false).await;
+    capture(&pool, event_id, 1, t_ef, Some(8.0), false, false).await;
+    assert_eq!(run_for(&pool, event_id, 2, t_cb).await.status, "skipped");
+
+    // Round 2
+    capture(&pool, event_id, 2, t_ab, Some(7.8), false, false).await;
+    capture(&pool, event_id, 2, t_ad, None, false, true).await;
+    capture(&pool, event_id, 2, t_ef, Some(6.5), false, false).await;
+
+    // Short round: sólo quedan t_ab (15.0) y t_ef (14.5); el más lento primero
+    draw::generate(
+        &pool,
+        GenerateDrawOptions {
+            event_id,
+            round: 3,
+            reseed: None,
+            seed_runs: None,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(draw_order(&pool, event_id, 3).await, vec![t_ab, t_ef]);
+
+    capture(&pool, event_id, 3, t_ab, Some(6.9), false, false).await;
+    capture(&pool, event_id, 3, t_ef, Some(8.1), false, false).await;
+
+    let table = standings::get(&pool, event_id).await.unwrap();
+    let ranked: Vec<i64> = table.iter().map(|s| s.team_id).collect();
+    // 3 vueltas: t_ab 21.9 < t_ef 22.6; luego t_ad (1 vuelta) y t_cb (0)
+    assert_eq!(ranked, vec![t_ab, t_ef, t_ad, t_cb]);
+    assert_eq!(table[0].rank, 1);
+    assert_eq!(table[0].completed_runs, 3);
+    assert!((table[0].total_time.unwrap() - 21.9).abs() < 1e-9);
+    assert_eq!(table[0].best_time, Some(6.9));
+    assert_eq!(table[2].dq_cnt, 1);
+    assert_eq!(table[3].nt_cnt, 1);
+    assert_eq!(table[3].total_time, None);
+
+    for (position, percentage) in [(1, 0.6), (2, 0.4)] {
+        payoffs::create_rule(
+            &pool,
+            NewPayoffRule {
+                event_id,
+                position,
+                percentage,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    // 6 ropers únicos * 100 + 500 añadidos
+    let breakdown = payoffs::payout_breakdown(&pool, event_id).await.unwrap();
+    assert_eq!(breakdown.total_pot, 1100.0);
+    assert_eq!(breakdown.net_pot, 1100.0);
+    let amounts: Vec<f64> = breakdown.payouts.iter().map(|p| p.amount).collect();
+    assert_eq!(amounts, vec![660.0, 440.0]);
+}
+
+#[tokio::test]
+async fn standings_rank_more_runs_before_faster_totals() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    let teams = create_teams(&pool, event_id, 3).await;
+    draw::generate_batch(
+        &pool,
+        GenerateBatchDrawOptions {
+            event_id,
+            rounds: 3,
+            shuffle: false,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    capture(&pool, event_id, 1, teams[0], Some(12.0), false, false).await;
+    capture(&pool, event_id, 2, teams[0], Some(12.0), false, false).await;
+    capture(&pool, event_id, 1, teams[1], Some(5.0), false, false).await;
+    capture(&pool, event_id, 1, teams[2], Some(9.0), false, false).await;
+    capture(&pool, event_id, 2, teams[2], Some(9.0), false, false).await;
+
+    let ranked: Vec<i64> = standings::get(&pool, event_id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|s| s.team_id)
+        .collect();
+    assert_eq!(ranked, vec![teams[2], teams[0], teams[1]]);
+}
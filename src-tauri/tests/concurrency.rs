@@ -0,0 +1,130 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::{draw, events, ropers, runs, teams};
+use roping_manager_tauri_lib::error::AppError;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+/// Fila actual que trae el Conflict de una versión vieja.
+fn stale_row(err: AppError) -> Value {
+    match err {
+        AppError::Conflict { details: Some(details), .. } => details["current"].clone(),
+        other => panic!("se esperaba Conflict con la fila actual, llegó {:?}", other),
+    }
+}
+
+fn save(event_id: i64, run: &runs::RunRow, time_sec: f64, expected_version: Option<i64>) -> runs::SaveRun {
+    runs::SaveRun {
+        event_id,
+        team_id: run.team_id,
+        round: run.round,
+        position: run.position,
+        time_sec: Some(time_sec),
+        penalty: 0.0,
+        penalties: None,
+        no_time: false,
+        dq: false,
+        pin: None,
+        reason: None,
+        expected_version,
+    }
+}
+
+async fn event_with_draw(pool: &SqlitePool) -> (i64, Vec<i64>) {
+    let event_id = create_event(pool, 3).await;
+    let team_ids = create_teams(pool, event_id, 2).await;
+    draw::generate_batch(
+        pool,
+        draw::GenerateBatchDrawOptions { event_id, rounds: 3, shuffle: false, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    (event_id, team_ids)
+}
+
+#[tokio::test]
+async fn second_window_saving_a_run_gets_the_current_row() {
+    let pool = memory_pool().await;
+    let (event_id, teams) = event_with_draw(&pool).await;
+    // las dos ventanas abren la misma corrida
+    let seen = run_for(&pool, event_id, 1, teams[0]).await;
+
+    runs::save(&pool, save(event_id, &seen, 8.1, Some(seen.version))).await.unwrap();
+    let err = runs::save(&pool, save(event_id, &seen, 9.9, Some(seen.version))).await.unwrap_err();
+    let current = stale_row(err);
+    assert_eq!(current["time_sec"], 8.1);
+    assert!(current["version"].as_i64().unwrap() > seen.version);
+    assert_eq!(run_for(&pool, event_id, 1, teams[0]).await.time_sec, Some(8.1));
+
+    // con la versión que trajo el Conflict, la corrección pasa
+    let mut reloaded = run_for(&pool, event_id, 1, teams[0]).await;
+    reloaded.version = current["version"].as_i64().unwrap();
+    runs::save(&pool, save(event_id, &reloaded, 9.9, Some(reloaded.version))).await.unwrap();
+    assert_eq!(run_for(&pool, event_id, 1, teams[0]).await.time_sec, Some(9.9));
+
+    // la cascada de un NT también cuenta como cambio de las rondas siguientes
+    let later = run_for(&pool, event_id, 2, teams[1]).await;
+    capture(&pool, event_id, 1, teams[1], None, true, false).await;
+    let err = runs::save(&pool, save(event_id, &later, 7.0, Some(later.version))).await.unwrap_err();
+    assert_eq!(stale_row(err)["status"], "skipped");
+
+    // sin versión (clientes viejos) se guarda como antes
+    runs::save(&pool, save(event_id, &reloaded, 10.0, None)).await.unwrap();
+}
+
+#[tokio::test]
+async fn stale_event_team_and_roper_edits_are_refused() {
+    let pool = memory_pool().await;
+    let (event_id, team_ids) = event_with_draw(&pool).await;
+
+    let event = events::get(&pool, event_id).await.unwrap();
+    let patch = |name: &str, version: Option<i64>| -> events::EventPatch {
+        serde_json::from_value(json!({ "name": name, "expected_version": version })).unwrap()
+    };
+    events::update(&pool, event_id, patch("Ventana A", Some(event.version))).await.unwrap();
+    let err = events::update(&pool, event_id, patch("Ventana B", Some(event.version))).await.unwrap_err();
+    let current = stale_row(err);
+    assert_eq!((current["name"].as_str(), current["version"].as_i64()), (Some("Ventana A"), Some(event.version + 1)));
+    events::update(&pool, event_id, patch("Ventana B", Some(event.version + 1))).await.unwrap();
+    // un patch sin cambios también compara la versión
+    let noop = |version: i64| -> events::EventPatch { serde_json::from_value(json!({ "expected_version": version })).unwrap() };
+    let err = events::update(&pool, event_id, noop(event.version)).await.unwrap_err();
+    assert_eq!(stale_row(err)["name"], "Ventana B");
+    events::update(&pool, event_id, noop(event.version + 2)).await.unwrap();
+
+    let team = teams::get(&pool, team_ids[0]).await.unwrap();
+    let rate = |rating: f64, version: Option<i64>| teams::UpdateTeam {
+        id: team.id,
+        rating: Some(rating),
+        status: None,
+        expected_version: version,
+    };
+    teams::update(&pool, rate(5.0, Some(team.version))).await.unwrap();
+    let err = teams::update(&pool, rate(6.0, Some(team.version))).await.unwrap_err();
+    assert_eq!(stale_row(err)["rating"], 5.0);
+    assert_eq!(teams::get(&pool, team.id).await.unwrap().rating, 5.0);
+    let untouched = teams::UpdateTeam { id: team.id, rating: None, status: None, expected_version: Some(team.version) };
+    assert_eq!(stale_row(teams::update(&pool, untouched).await.unwrap_err())["rating"], 5.0);
+
+    let roper_id = team.header_id;
+    let roper = ropers::get(&pool, roper_id).await.unwrap();
+    let rename = |first: &str, version: Option<i64>| ropers::UpdateRoper {
+        id: roper_id,
+        first_name: Some(first.into()),
+        last_name: None,
+        specialty: None,
+        rating: None,
+        phone: None,
+        email: None,
+        level: None,
+        expected_version: version,
+    };
+    ropers::update(&pool, rename("Ana", Some(roper.version))).await.unwrap();
+    let err = ropers::update(&pool, rename("Luis", Some(roper.version))).await.unwrap_err();
+    assert_eq!(stale_row(err)["first_name"], "Ana");
+    let untouched = ropers::UpdateRoper { first_name: None, ..rename("", Some(roper.version)) };
+    assert_eq!(stale_row(ropers::update(&pool, untouched).await.unwrap_err())["first_name"], "Ana");
+    ropers::update(&pool, rename("Luis", None)).await.unwrap();
+    assert_eq!(ropers::get(&pool, roper_id).await.unwrap().first_name, "Luis");
+}
@@ -0,0 +1,525 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::draw::{
+    self, DrawEntry, DrawMethod, GenerateBatchDrawOptions, GenerateDrawOptions, InsertDrawOptions, MoveDrawOptions,
+    SwapDrawOptions,
+};
+use roping_manager_tauri_lib::domain::{audit, events, runs, standings};
+use roping_manager_tauri_lib::error::AppError;
+
+fn final_round(event_id: i64, round: i64) -> GenerateDrawOptions {
+    GenerateDrawOptions {
+        event_id,
+        round,
+        reseed: None,
+        seed_runs: None,
+        min_spacing: None,
+        pin: None,
+    }
+}
+
+#[tokio::test]
+async fn batch_draw_leaves_the_final_round_for_later() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    let teams = create_teams(&pool, event_id, 3).await;
+
+    let seeded = draw::generate_batch(
+        &pool,
+        GenerateBatchDrawOptions {
+            event_id,
+            rounds: 3,
+            shuffle: true,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!((seeded.teams, seeded.rounds), (3, 2));
+    assert_eq!(seeded.seeds.len(), 2);
+    for round in 1..=2 {
+        let mut order = draw_order(&pool, event_id, round).await;
+        order.sort();
+        assert_eq!(order, teams);
+    }
+    assert!(draw_order(&pool, event_id, 3).await.is_empty());
+    let pending = roping_manager_tauri_lib::domain::runs::list(&pool, event_id, None)
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 6);
+    assert!(pending.iter().all(|r| r.status == "pending"));
+}
+
+#[tokio::test]
+async fn final_round_runs_slowest_aggregate_first_and_drops_eliminated_teams() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    let teams = create_teams(&pool, event_id, 4).await;
+    draw::generate_batch(
+        &pool,
+        GenerateBatchDrawOptions {
+            event_id,
+            rounds: 3,
+            shuffle: false,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    // aggregates: t0 = 15, t1 = 19, t2 = NT (eliminado), t3 = 17
+    for (team, r1, r2) in [(teams[0], 7.0, 8.0), (teams[1], 9.0, 10.0), (teams[3], 8.5, 8.5)] {
+        capture(&pool, event_id, 1, team, Some(r1), false, false).await;
+        capture(&pool, event_id, 2, team, Some(r2), false, false).await;
+    }
+    capture(&pool, event_id, 1, teams[2], None, true, false).await;
+
+    // un equipo tardío sin tiempos va al final
+    let late_header = create_roper(&pool, "Late", "header").await;
+    let late_heeler = create_roper(&pool, "Comer", "heeler").await;
+    let late = create_team(&pool, event_id, late_header, late_heeler).await;
+
+    let count = draw::generate(&pool, final_round(event_id, 3)).await.unwrap();
+
+    assert_eq!(count.teams, 4);
+    assert_eq!(
+        draw_order(&pool, event_id, 3).await,
+        vec![teams[1], teams[3], teams[0], late]
+    );
+    assert_eq!(run_for(&pool, event_id, 3, teams[1]).await.position, 1);
+}
+
+async fn set_callback(pool: &sqlx::SqlitePool, event_id: i64, settings: serde_json::Value) {
+    let patch: events::EventPatch = serde_json::from_value(settings).unwrap();
+    events::update(pool, event_id, patch).await.unwrap();
+}
+
+/// Evento de 3 rondas con las dos primeras capturadas según `times` (None = NT en la ronda 1).
+async fn two_rounds_run(pool: &sqlx::SqlitePool, times: &[Option<(f64, f64)>]) -> (i64, Vec<i64>) {
+    let event_id = create_event(pool, 3).await;
+    let teams = create_teams(pool, event_id, times.len()).await;
+    draw::generate_batch(
+        pool,
+        GenerateBatchDrawOptions { event_id, rounds: 3, shuffle: false, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    for (&team, time) in teams.iter().zip(times) {
+        match time {
+            Some((r1, r2)) => {
+                capture(pool, event_id, 1, team, Some(*r1), false, false).await;
+                capture(pool, event_id, 2, team, Some(*r2), false, false).await;
+            }
+            None => {
+                capture(pool, event_id, 1, team, None, true, false).await;
+            }
+        }
+    }
+    (event_id, teams)
+}
+
+#[tokio::test]
+async fn short_go_calls_back_the_top_teams_in_reverse_standing() {
+    let pool = memory_pool().await;
+    // totales: t0 = 15, t1 = 19, t2 = 17, t3 = 16, t4 = NT
+    let (event_id, teams) =
+        two_rounds_run(&pool, &[Some((7.0, 8.0)), Some((9.0, 10.0)), Some((8.0, 9.0)), Some((8.0, 8.0)), None]).await;
+    set_callback(&pool, event_id, serde_json::json!({ "callback_mode": "top_n", "callback_value": 2 })).await;
+
+    let summary = draw::generate(&pool, final_round(event_id, 3)).await.unwrap();
+    assert_eq!(summary.teams, 2);
+    assert_eq!(draw_order(&pool, event_id, 3).await, vec![teams[3], teams[0]]);
+    assert_eq!(draw::get_round(&pool, event_id, 3).await.unwrap().unwrap().method, "callback");
+    assert!(draw::verify(&pool, event_id, 3).await.unwrap().matches);
+
+    let called: Vec<(i64, Option<i64>)> = standings::get(&pool, event_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| (s.team_id, s.called_back))
+        .collect();
+    assert!(called.contains(&(teams[0], Some(1))));
+    assert!(called.contains(&(teams[1], Some(0))));
+    assert!(called.contains(&(teams[2], Some(0))));
+    // el NT está eliminado, no "sin callback"
+    assert!(called.contains(&(teams[4], None)));
+
+    // 50% de 4 equipos vivos = 2; volver a 'all' limpia las marcas
+    set_callback(&pool, event_id, serde_json::json!({ "callback_mode": "top_percent", "callback_value": 50 })).await;
+    assert_eq!(draw::generate(&pool, final_round(event_id, 3)).await.unwrap().teams, 2);
+    set_callback(&pool, event_id, serde_json::json!({ "callback_mode": "all" })).await;
+    assert_eq!(draw::generate(&pool, final_round(event_id, 3)).await.unwrap().teams, 4);
+    assert!(standings::get(&pool, event_id).await.unwrap().iter().all(|s| s.called_back.is_none()));
+}
+
+#[tokio::test]
+async fn short_go_tie_handling_at_the_cut_line() {
+    let pool = memory_pool().await;
+    // t1 y t2 empatan en 16 (t1 tiene mejor corrida); el corte top 2 cae entre ellos
+    let (event_id, teams) = two_rounds_run(&pool, &[Some((7.0, 8.0)), Some((7.0, 9.0)), Some((8.0, 8.0)), Some((9.0, 9.0))]).await;
+
+    let mut drawn = Vec::new();
+    for ties in ["include", "exclude", "tiebreak"] {
+        set_callback(&pool, event_id, serde_json::json!({ "callback_mode": "top_n", "callback_value": 2, "callback_ties": ties })).await;
+        draw::generate(&pool, final_round(event_id, 3)).await.unwrap();
+        drawn.push(draw_order(&pool, event_id, 3).await);
+    }
+    assert_eq!(drawn[0], vec![teams[2], teams[1], teams[0]]);
+    assert_eq!(drawn[1], vec![teams[0]]);
+    assert_eq!(drawn[2], vec![teams[1], teams[0]]);
+
+    let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "callback_value": 0 })).unwrap();
+    let err = events::update(&pool, event_id, patch).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "callback_value"));
+}
+
+#[tokio::test]
+async fn final_order_follows_the_event_strategy() {
+    let pool = memory_pool().await;
+    // t0 = 7 + 8, t1 = 9 + 10, t2 = 8 + 9, t3 = 12 (sólo ronda 1)
+    let (event_id, teams) = two_rounds_run(&pool, &[Some((7.0, 8.0)), Some((9.0, 10.0)), Some((8.0, 9.0))]).await;
+    let (header, heeler) = (create_roper(&pool, "H4", "header").await, create_roper(&pool, "E4", "heeler").await);
+    let t3 = create_team(&pool, event_id, header, heeler).await;
+    draw::insert_team(&pool, InsertDrawOptions { event_id, round: 1, team_id: t3, position: None, pin: None })
+        .await
+        .unwrap();
+    capture(&pool, event_id, 1, t3, Some(12.0), false, false).await;
+
+    let mut drawn = Vec::new();
+    for order in ["aggregate", "average", "standings", "round_one", "random"] {
+        let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "final_order": order })).unwrap();
+        events::update(&pool, event_id, patch).await.unwrap();
+        draw::generate(&pool, final_round(event_id, 3)).await.unwrap();
+        assert!(draw::verify(&pool, event_id, 3).await.unwrap().matches, "{}", order);
+        let method = draw::get_round(&pool, event_id, 3).await.unwrap().unwrap().method;
+        drawn.push((method, draw_order(&pool, event_id, 3).await));
+    }
+
+    let (t0, t1, t2) = (teams[0], teams[1], teams[2]);
+    assert_eq!(drawn[0], ("accumulated_time".to_string(), vec![t1, t2, t0, t3]));
+    assert_eq!(drawn[1], ("average_time".to_string(), vec![t3, t1, t2, t0]));
+    // t3 lleva una corrida menos: último en la clasificación, primero en salir
+    assert_eq!(drawn[2], ("standings".to_string(), vec![t3, t1, t2, t0]));
+    assert_eq!(drawn[3], ("round_one".to_string(), draw_order(&pool, event_id, 1).await));
+    assert_eq!(drawn[4].0, "random");
+
+    let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "final_order": "fastest" })).unwrap();
+    let err = events::update(&pool, event_id, patch).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "final_order"));
+}
+
+#[tokio::test]
+async fn regenerating_a_started_round_is_refused() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    let teams = create_teams(&pool, event_id, 2).await;
+    draw::generate(&pool, final_round(event_id, 1)).await.unwrap();
+    capture(&pool, event_id, 1, teams[0], Some(9.0), false, false).await;
+
+    let err = draw::generate(&pool, final_round(event_id, 1)).await.unwrap_err();
+    assert!(matches!(err, AppError::Conflict { .. }), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn completed_events_cannot_be_redrawn() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    create_teams(&pool, event_id, 2).await;
+    events::update_status(&pool, event_id, "completed", None).await.unwrap();
+
+    let err = draw::generate(&pool, final_round(event_id, 1)).await.unwrap_err();
+    assert_eq!(err.code(), "EventLocked");
+    let batch = GenerateBatchDrawOptions { event_id, rounds: 2, shuffle: false, min_spacing: None, pin: None };
+    assert_eq!(draw::generate_batch(&pool, batch).await.unwrap_err().code(), "EventLocked");
+}
+
+#[tokio::test]
+async fn locked_events_can_still_be_drawn_in_both_modes() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    create_teams(&pool, event_id, 2).await;
+    events::lock(&pool, event_id).await.unwrap();
+
+    let batch = GenerateBatchDrawOptions { event_id, rounds: 3, shuffle: false, min_spacing: None, pin: None };
+    assert_eq!(draw::generate_batch(&pool, batch).await.unwrap().rounds, 2);
+    draw::generate(&pool, final_round(event_id, 3)).await.unwrap();
+}
+
+#[tokio::test]
+async fn seeded_draw_is_recorded_and_verifies_until_edited() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    create_teams(&pool, event_id, 6).await;
+    draw::generate(
+        &pool,
+        GenerateDrawOptions {
+            event_id,
+            round: 1,
+            reseed: Some(true),
+            seed_runs: Some(true),
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let record = draw::get_round(&pool, event_id, 1).await.unwrap().unwrap();
+    assert_eq!(record.method, "random");
+    assert_eq!(record.seed.as_deref().map(str::len), Some(16));
+
+    let check = draw::verify(&pool, event_id, 1).await.unwrap();
+    assert!(check.matches, "{}", check.message);
+    assert_eq!(check.actual_order, draw_order(&pool, event_id, 1).await);
+
+    // alguien cambia el orden directamente en la base
+    for (from, to) in [(1, 1000), (2, 1), (1000, 2)] {
+        sqlx::query("UPDATE draw SET position = ?3 WHERE event_id = ?1 AND round = 1 AND position = ?2")
+            .bind(event_id)
+            .bind(from)
+            .bind(to)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    assert!(!draw::verify(&pool, event_id, 1).await.unwrap().matches);
+
+    // la ronda 2 no se ha sorteado
+    assert_eq!(draw::verify(&pool, event_id, 2).await.unwrap_err().code(), "NotFound");
+}
+
+#[test]
+fn same_seed_and_entries_give_the_same_order() {
+    let entries: Vec<DrawEntry> = (1..=12)
+        .map(|i| DrawEntry { team_id: i, header_id: 100 + i % 4, heeler_id: 200 + i, ..Default::default() })
+        .collect();
+    let a = draw::derive_order(DrawMethod::Random, Some("00000000deadbeef"), &entries, 0).unwrap();
+    let mut reversed = entries.clone();
+    reversed.reverse();
+    // el orden de llegada de las inscripciones no importa
+    assert_eq!(draw::derive_order(DrawMethod::Random, Some("00000000deadbeef"), &reversed, 0).unwrap(), a);
+    assert_ne!(draw::derive_order(DrawMethod::Random, Some("00000000deadbeee"), &entries, 0).unwrap(), a);
+    // valor fijo: si cambia, los sorteos viejos dejan de verificarse (subir `DRAW_RNG`)
+    assert_eq!(a.order, vec![8, 12, 3, 4, 10, 1, 11, 7, 2, 9, 5, 6]);
+
+    let spaced = draw::derive_order(DrawMethod::Random, Some("00000000deadbeef"), &entries, 2).unwrap();
+    assert_eq!(spaced, draw::derive_order(DrawMethod::Random, Some("00000000deadbeef"), &reversed, 2).unwrap());
+    assert!(draw::derive_order(DrawMethod::Random, None, &entries, 2).is_err());
+}
+
+#[test]
+fn accumulated_time_order_puts_teams_without_time_last() {
+    let order = draw::order_by_accumulated_time(&[(1, Some(10.0)), (2, None), (3, Some(12.5)), (4, None)]);
+    assert_eq!(order, vec![3, 1, 2, 4]);
+}
+
+#[test]
+fn spacing_keeps_a_roper_apart_when_possible() {
+    // el roper 100 lazea en los equipos 1 y 2; hay otros tres equipos para separarlos
+    let teams = vec![(1, 100, 200), (2, 100, 201), (3, 101, 202), (4, 102, 203), (5, 103, 204)];
+    let ordered = draw::space_ropers(teams, 3);
+    let pos = |id: i64| ordered.iter().position(|t| t.0 == id).unwrap();
+    assert!(pos(1).abs_diff(pos(2)) > 3, "order: {:?}", ordered);
+    assert_eq!(draw::count_violations(&ordered, 3), 0);
+}
+
+#[test]
+fn spacing_spreads_a_roper_entered_many_times_and_reports_what_it_cannot() {
+    // el header 100 está en 4 de 12 equipos: con separación 2 cabe (posiciones 0, 3, 6, 9)
+    let entries: Vec<DrawEntry> = (1..=12)
+        .map(|i| DrawEntry { team_id: i, header_id: if i <= 4 { 100 } else { 100 + i }, heeler_id: 200 + i, ..Default::default() })
+        .collect();
+    for seed in ["0000000000000001", "00000000deadbeef", "ffffffffffffffff"] {
+        let arrangement = draw::derive_order(DrawMethod::Random, Some(seed), &entries, 2).unwrap();
+        assert_eq!(arrangement.violations, 0, "seed {}: {:?}", seed, arrangement.order);
+    }
+    let in_entry_order = draw::derive_order(DrawMethod::EntryOrder, None, &entries, 2).unwrap();
+    assert_eq!(in_entry_order.violations, 0);
+
+    // en 3 de 4 equipos: con separación 2 no hay forma, se reporta en lugar de fallar
+    let crowded: Vec<DrawEntry> = (1..=4)
+        .map(|i| DrawEntry { team_id: i, header_id: if i <= 3 { 100 } else { 104 }, heeler_id: 200 + i, ..Default::default() })
+        .collect();
+    let arrangement = draw::derive_order(DrawMethod::EntryOrder, None, &crowded, 2).unwrap();
+    assert!(arrangement.violations > 0);
+    assert_eq!(arrangement.order.len(), 4);
+}
+
+#[test]
+fn spacing_holds_on_a_large_roster_beyond_the_old_cap() {
+    // 60 headers con 10 equipos cada uno: separación 40, por encima del viejo tope de 20
+    let entries: Vec<DrawEntry> = (1..=600)
+        .map(|i| DrawEntry { team_id: i, header_id: 1000 + i % 60, heeler_id: 5000 + i, ..Default::default() })
+        .collect();
+    let arrangement = draw::derive_order(DrawMethod::Random, Some("00000000deadbeef"), &entries, 40).unwrap();
+    assert_eq!(arrangement.violations, 0);
+    assert_eq!(arrangement.order.len(), 600);
+}
+
+#[tokio::test]
+async fn single_round_draw_applies_the_spacing_and_rejects_bad_values() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    // un header que lazea con cuatro heelers distintos y cuatro equipos más
+    let busy = create_roper(&pool, "Busy", "header").await;
+    for i in 0..8 {
+        let header = if i < 4 { busy } else { create_roper(&pool, &format!("H{}", i), "header").await };
+        let heeler = create_roper(&pool, &format!("E{}", i), "heeler").await;
+        create_team(&pool, event_id, header, heeler).await;
+    }
+
+    let opts = |min_spacing: Option<i64>| GenerateDrawOptions {
+        event_id,
+        round: 1,
+        reseed: Some(true),
+        seed_runs: Some(true),
+        min_spacing,
+        pin: None,
+    };
+    let summary = draw::generate(&pool, opts(Some(1))).await.unwrap();
+    assert_eq!(summary.violations, 0);
+    let rows = draw::get(&pool, event_id, 1).await.unwrap();
+    let busy_positions: Vec<i64> = rows.iter().filter(|r| r.header_id == busy).map(|r| r.position).collect();
+    assert!(busy_positions.windows(2).all(|w| w[1] - w[0] >= 2), "positions {:?}", busy_positions);
+    assert_eq!(draw::get_round(&pool, event_id, 1).await.unwrap().unwrap().min_spacing, 1);
+    assert!(draw::verify(&pool, event_id, 1).await.unwrap().matches);
+
+    let err = draw::generate(&pool, opts(Some(-1))).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "min_spacing"));
+
+    // con 8 equipos dos salidas quedan a lo más a 6 corridas: pedir más se recorta a eso
+    assert_eq!(draw::max_min_spacing(8), 6);
+    let summary = draw::generate(&pool, opts(Some(50))).await.unwrap();
+    assert_eq!(summary.min_spacing, 6);
+    assert_eq!(draw::get_round(&pool, event_id, 1).await.unwrap().unwrap().min_spacing, 6);
+    assert!(draw::verify(&pool, event_id, 1).await.unwrap().matches);
+}
+
+async fn five_team_round(pool: &sqlx::SqlitePool) -> (i64, Vec<i64>) {
+    let event_id = create_event(pool, 3).await;
+    let teams = create_teams(pool, event_id, 5).await;
+    draw::generate(
+        pool,
+        GenerateDrawOptions {
+            event_id,
+            round: 1,
+            reseed: Some(false),
+            seed_runs: Some(true),
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+    (event_id, teams)
+}
+
+async fn run_positions(pool: &sqlx::SqlitePool, event_id: i64) -> Vec<i64> {
+    let mut runs = runs::list(pool, event_id, Some(1)).await.unwrap();
+    runs.sort_by_key(|r| r.position);
+    runs.into_iter().map(|r| r.team_id).collect()
+}
+
+#[tokio::test]
+async fn swap_move_and_insert_keep_draw_and_runs_in_sync() {
+    let pool = memory_pool().await;
+    let (event_id, teams) = five_team_round(&pool).await;
+    let [a, b, c, d, e] = teams[..] else { unreachable!() };
+    assert_eq!(draw_order(&pool, event_id, 1).await, vec![a, b, c, d, e]);
+
+    let swap = |pa: i64, pb: i64| SwapDrawOptions { event_id, round: 1, position_a: pa, position_b: pb, pin: None };
+    draw::swap_positions(&pool, swap(1, 4)).await.unwrap();
+    assert_eq!(draw_order(&pool, event_id, 1).await, vec![d, b, c, a, e]);
+
+    draw::move_team(&pool, MoveDrawOptions { event_id, round: 1, team_id: e, to_position: 2, pin: None })
+        .await
+        .unwrap();
+    assert_eq!(draw_order(&pool, event_id, 1).await, vec![d, e, b, c, a]);
+    assert_eq!(run_positions(&pool, event_id).await, vec![d, e, b, c, a]);
+
+    // inscripción tardía: el equipo existe en el evento pero no estaba en la ronda
+    let late = create_teams(&pool, event_id, 1).await[0];
+    let rows = draw::insert_team(&pool, InsertDrawOptions { event_id, round: 1, team_id: late, position: Some(3), pin: None })
+        .await
+        .unwrap();
+    assert_eq!(rows.iter().map(|r| r.team_id).collect::<Vec<_>>(), vec![d, e, late, b, c, a]);
+    assert_eq!(run_positions(&pool, event_id).await, vec![d, e, late, b, c, a]);
+    assert_eq!(run_for(&pool, event_id, 1, late).await.status, "pending");
+
+    let err = draw::insert_team(&pool, InsertDrawOptions { event_id, round: 1, team_id: late, position: None, pin: None })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "team_id"));
+    let err = draw::swap_positions(&pool, swap(1, 9)).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "position_b"));
+
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.action, "insert_draw_team");
+    let after: serde_json::Value = serde_json::from_str(log.after_json.as_deref().unwrap()).unwrap();
+    assert_eq!(after["order"][2], late);
+}
+
+#[tokio::test]
+async fn editing_refuses_completed_runs_but_allows_the_rest() {
+    let pool = memory_pool().await;
+    let (event_id, teams) = five_team_round(&pool).await;
+    capture(&pool, event_id, 1, teams[0], Some(8.0), false, false).await;
+
+    let err = draw::swap_positions(&pool, SwapDrawOptions { event_id, round: 1, position_a: 1, position_b: 3, pin: None })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+    // insertar delante de la corrida hecha la movería
+    let late = create_teams(&pool, event_id, 1).await[0];
+    let err = draw::insert_team(&pool, InsertDrawOptions { event_id, round: 1, team_id: late, position: Some(1), pin: None })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+    assert_eq!(draw_order(&pool, event_id, 1).await, teams);
+
+    // lo que queda detrás sí se puede mover, y al final siempre se puede agregar
+    draw::swap_positions(&pool, SwapDrawOptions { event_id, round: 1, position_a: 2, position_b: 5, pin: None })
+        .await
+        .unwrap();
+    draw::insert_team(&pool, InsertDrawOptions { event_id, round: 1, team_id: late, position: None, pin: None })
+        .await
+        .unwrap();
+    assert_eq!(draw_order(&pool, event_id, 1).await, vec![teams[0], teams[4], teams[2], teams[3], teams[1], late]);
+    assert_eq!(run_for(&pool, event_id, 1, teams[0]).await.status, "completed");
+
+    // con PIN en el evento, editar lo pide
+    let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "admin_pin": "4321" })).unwrap();
+    events::update(&pool, event_id, patch).await.unwrap();
+    let move_opts = |pin: Option<&str>| MoveDrawOptions { event_id, round: 1, team_id: late, to_position: 2, pin: pin.map(String::from) };
+    let err = draw::move_team(&pool, move_opts(None)).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "pin"));
+    draw::move_team(&pool, move_opts(Some("4321"))).await.unwrap();
+}
+
+#[tokio::test]
+async fn late_insert_refuses_a_team_eliminated_in_an_earlier_round() {
+    let pool = memory_pool().await;
+    let (event_id, teams) = five_team_round(&pool).await;
+    capture(&pool, event_id, 1, teams[0], None, true, false).await;
+    capture(&pool, event_id, 1, teams[1], Some(8.0), false, false).await;
+    draw::generate(
+        &pool,
+        GenerateDrawOptions { event_id, round: 2, reseed: Some(false), seed_runs: Some(true), min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    let order = draw_order(&pool, event_id, 2).await;
+    assert!(!order.contains(&teams[0]));
+
+    let err = draw::insert_team(&pool, InsertDrawOptions { event_id, round: 2, team_id: teams[0], position: None, pin: None })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "team_id"), "got {:?}", err);
+    // nada quedó a medias: ni el draw ni las corridas de la ronda
+    assert_eq!(draw_order(&pool, event_id, 2).await, order);
+    assert_eq!(runs::list(&pool, event_id, Some(2)).await.unwrap().len(), order.len());
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::draw::{self, GenerateBatchDrawOptions};
+use roping_manager_tauri_lib::domain::draw_report;
+use sqlx::SqlitePool;
+
+async fn batch(pool: &SqlitePool, event_id: i64, rounds: i64, min_spacing: i64) {
+    draw::generate_batch(
+        pool,
+        GenerateBatchDrawOptions { event_id, rounds, shuffle: false, min_spacing: Some(min_spacing), pin: None },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn a_fresh_draw_is_clean_and_positions_average_out() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    create_teams(&pool, event_id, 4).await;
+    batch(&pool, event_id, 3, 0).await;
+    // ronda 2 al revés: cada equipo termina con posición media 2.5
+    let order = draw_order(&pool, event_id, 1).await;
+    for (idx, team_id) in order.iter().rev().enumerate() {
+        sqlx::query("UPDATE draw SET position = ?1 + 100 WHERE event_id = ?2 AND round = 2 AND team_id = ?3")
+            .bind(idx as i64 + 1)
+            .bind(event_id)
+            .bind(team_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE run SET position = ?1 WHERE event_id = ?2 AND round = 2 AND team_id = ?3")
+            .bind(idx as i64 + 1)
+            .bind(event_id)
+            .bind(team_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    sqlx::query("UPDATE draw SET position = position - 100 WHERE event_id = ?1 AND round = 2")
+        .bind(event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let report = draw_report::get(&pool, event_id).await.unwrap();
+    assert!(report.clean, "{:?}", report.rounds);
+    assert_eq!(report.fairness_rounds, vec![1, 2]);
+    assert!(report.teams.iter().all(|t| t.rounds == 2 && t.mean_position == 2.5 && t.mean_relative == 0.5));
+    let first = report.ropers.iter().find(|r| r.roper_name == "H1 Test").unwrap();
+    assert_eq!((first.runs, first.early, first.middle, first.late), (2, 1, 0, 1));
+}
+
+#[tokio::test]
+async fn report_flags_broken_rounds() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    let teams = create_teams(&pool, event_id, 4).await;
+    // dos equipos con el mismo header: salen 1.º y 5.º, a 3 corridas con separación de 4
+    let shared = create_roper(&pool, "Compartido", "header").await;
+    let heeler = create_roper(&pool, "Otro", "heeler").await;
+    let twin = create_team(&pool, event_id, shared, heeler).await;
+    sqlx::query("UPDATE team SET header_id = ?1 WHERE id = ?2")
+        .bind(shared)
+        .bind(teams[0])
+        .execute(&pool)
+        .await
+        .unwrap();
+    batch(&pool, event_id, 3, 0).await;
+    sqlx::query("UPDATE draw_round SET min_spacing = 4 WHERE event_id = ?1")
+        .bind(event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // NT en la ronda 1 y la corrida de la 2 vuelta a pending a mano
+    capture(&pool, event_id, 1, teams[1], None, true, false).await;
+    sqlx::query("UPDATE run SET status = 'pending' WHERE event_id = ?1 AND round = 2 AND team_id = ?2")
+        .bind(event_id)
+        .bind(teams[1])
+        .execute(&pool)
+        .await
+        .unwrap();
+    // un hueco en la ronda 2 y dos corridas en la misma posición
+    let gap = run_for(&pool, event_id, 2, teams[2]).await.position;
+    assert_eq!(gap, 3);
+    sqlx::query("DELETE FROM draw WHERE event_id = ?1 AND round = 2 AND team_id = ?2")
+        .bind(event_id)
+        .bind(teams[2])
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE run SET position = ?1 WHERE event_id = ?2 AND round = 2 AND team_id = ?3")
+        .bind(gap)
+        .bind(event_id)
+        .bind(teams[3])
+        .execute(&pool)
+        .await
+        .unwrap();
+    // inscrito después del sorteo
+    let late = create_teams(&pool, event_id, 1).await[0];
+
+    let report = draw_report::get(&pool, event_id).await.unwrap();
+    assert!(!report.clean);
+    let round1 = &report.rounds[0];
+    assert_eq!(round1.missing_teams, vec![late]);
+    assert!(round1.spacing.iter().any(|s| s.roper_id == shared && s.gap == 3));
+
+    let round2 = &report.rounds[1];
+    assert_eq!(round2.eliminated_teams, vec![teams[1]]);
+    assert_eq!(round2.duplicate_positions, vec![gap]);
+    assert!(round2.missing_teams.contains(&teams[2]) && round2.missing_teams.contains(&late));
+    assert!(!round2.missing_teams.contains(&teams[1]));
+    assert_eq!(round2.run_mismatches, vec![teams[3]]);
+    assert_eq!(round2.position_gaps, vec![gap]);
+    assert!(!round2.missing_teams.contains(&twin));
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::{events, teams};
+use roping_manager_tauri_lib::error::AppError;
+
+#[tokio::test]
+async fn locked_event_rejects_new_teams() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let header = create_roper(&pool, "H", "header").await;
+    let heeler = create_roper(&pool, "E", "heeler").await;
+    events::lock(&pool, event_id).await.unwrap();
+
+    let err = teams::create(
+        &pool,
+        teams::NewTeam {
+            event_id,
+            header_id: header,
+            heeler_id: heeler,
+            rating: 5.0,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AppError::EventLocked { .. }), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn duplicate_team_is_a_conflict_and_same_roper_is_a_validation_error() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let header = create_roper(&pool, "H", "header").await;
+    let heeler = create_roper(&pool, "E", "heeler").await;
+    create_team(&pool, event_id, header, heeler).await;
+
+    let new_team = |header_id, heeler_id| teams::NewTeam {
+        event_id,
+        header_id,
+        heeler_id,
+        rating: 5.0,
+    };
+
+    let dup = teams::create(&pool, new_team(header, heeler)).await.unwrap_err();
+    assert_eq!(dup.code(), "Conflict");
+
+    let same = teams::create(&pool, new_team(header, header)).await.unwrap_err();
+    match same {
+        AppError::Validation { field, .. } => assert_eq!(field, "heeler_id"),
+        other => panic!("expected validation error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn missing_event_is_not_found() {
+    let pool = memory_pool().await;
+    let err = events::delete(&pool, 999).await.unwrap_err();
+    assert_eq!(err.code(), "NotFound");
+}
+
+#[test]
+fn errors_serialize_with_code_message_field_and_details() {
+    let json = serde_json::to_value(AppError::validation("status", "Status inválido")).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "code": "Validation",
+            "message": "Status inválido",
+            "field": "status",
+            "details": null,
+        })
+    );
+
+    let json = serde_json::to_value(AppError::event_locked("bloqueado")).unwrap();
+    assert_eq!(json["code"], "EventLocked");
+    assert_eq!(json["field"], serde_json::Value::Null);
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::{audit, draw, revisions, runs};
+use sqlx::SqlitePool;
+
+async fn insert_user(pool: &SqlitePool, email: &str, full_name: &str) -> i64 {
+    sqlx::query("INSERT INTO app_user (email, full_name, password_hash) VALUES (?1, ?2, 'x')")
+        .bind(email)
+        .bind(full_name)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+}
+
+async fn event_with_draw(rounds: i64, n_teams: usize) -> (SqlitePool, i64, Vec<i64>) {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, rounds).await;
+    let team_ids = create_teams(&pool, event_id, n_teams).await;
+    draw::generate_batch(
+        &pool,
+        draw::GenerateBatchDrawOptions { event_id, rounds, shuffle: false, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    (pool, event_id, team_ids)
+}
+
+async fn correct(pool: &SqlitePool, event_id: i64, team_id: i64, time_sec: Option<f64>, no_time: bool, reason: &str) {
+    let position = run_for(pool, event_id, 1, team_id).await.position;
+    runs::save(
+        pool,
+        runs::SaveRun {
+            event_id,
+            team_id,
+            round: 1,
+            position,
+            time_sec,
+            penalty: 0.0,
+            penalties: None,
+            no_time,
+            dq: false,
+            pin: None,
+            reason: Some(reason.into()),
+            expected_version: None,
+        },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn every_save_appends_a_revision_with_old_and_new_values() {
+    let (pool, event_id, teams) = event_with_draw(2, 2).await;
+    let ana = insert_user(&pool, "ana@example.com", "Ana").await;
+    let luis = insert_user(&pool, "luis@example.com", "Luis").await;
+
+    let run_id = audit::as_user(Some(ana), capture(&pool, event_id, 1, teams[0], Some(8.0), false, false)).await;
+    audit::as_user(Some(luis), correct(&pool, event_id, teams[0], Some(8.4), false, "Protesta: tiempo del juez")).await;
+    audit::as_user(Some(luis), correct(&pool, event_id, teams[0], None, true, "  ")).await;
+
+    let history = revisions::history(&pool, run_id).await.unwrap();
+    let summary: Vec<(i64, &str, Option<&str>, Option<&str>)> = history
+        .iter()
+        .map(|v| (v.revision, v.action.as_str(), v.user_name.as_deref(), v.reason.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (1, "capture", Some("Ana"), None),
+            (2, "correction", Some("Luis"), Some("Protesta: tiempo del juez")),
+            (3, "correction", Some("Luis"), None),
+        ]
+    );
+    // la primera captura parte de la corrida pendiente, sin valores capturados
+    assert_eq!((history[0].old_status.as_deref(), history[0].old_time_sec), (Some("pending"), None));
+    assert_eq!((history[1].old_time_sec, history[1].new_time_sec), (Some(8.0), Some(8.4)));
+    assert_eq!((history[2].old_total_sec, history[2].new_total_sec, history[2].new_no_time), (Some(8.4), None, 1));
+
+    let listed = runs::list(&pool, event_id, Some(1)).await.unwrap();
+    let counts: Vec<(i64, i64)> = listed.iter().map(|r| (r.team_id, r.revisions)).collect();
+    assert_eq!(counts, vec![(teams[0], 3), (teams[1], 0)]);
+    let expanded = runs::list_expanded(&pool, event_id, Some(1)).await.unwrap();
+    assert_eq!(expanded[0].revisions, 3);
+
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.metadata.as_deref(), Some(format!("Event {} Round 1 Rev 3", event_id).as_str()));
+
+    // el historial no se reescribe
+    assert!(sqlx::query("UPDATE run_revision SET reason = 'otra' WHERE run_id = ?1")
+        .bind(run_id)
+        .execute(&pool)
+        .await
+        .is_err());
+    assert_eq!(revisions::history(&pool, 9999).await.unwrap_err().code(), "NotFound");
+}
+
+#[tokio::test]
+async fn recompute_records_its_changes_in_the_history() {
+    let (pool, event_id, teams) = event_with_draw(2, 1).await;
+    let run_id = capture(&pool, event_id, 1, teams[0], Some(8.0), false, false).await;
+    sqlx::query("UPDATE run SET total_sec = 99 WHERE id = ?1").bind(run_id).execute(&pool).await.unwrap();
+
+    assert_eq!(runs::recompute_totals(&pool, event_id).await.unwrap(), 1);
+    let last = revisions::history(&pool, run_id).await.unwrap().pop().unwrap();
+    assert_eq!(last.revision, 2);
+    assert_eq!(last.action, "recompute");
+    assert_eq!((last.old_total_sec, last.new_total_sec), (Some(99.0), Some(8.0)));
+}
+
+#[tokio::test]
+async fn a_save_that_cannot_record_its_revision_leaves_nothing_behind() {
+    let (pool, event_id, teams) = event_with_draw(2, 1).await;
+    sqlx::query(
+        "CREATE TRIGGER test_no_revision BEFORE INSERT ON run_revision BEGIN SELECT RAISE(ABORT, 'sin historial'); END",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    let err = runs::save(
+        &pool,
+        runs::SaveRun {
+            event_id,
+            team_id: teams[0],
+            round: 1,
+            position: run.position,
+            time_sec: None,
+            penalty: 5.0,
+            penalties: None,
+            no_time: true,
+            dq: false,
+            pin: None,
+            reason: None,
+            expected_version: Some(run.version),
+        },
+    )
+    .await;
+    assert!(err.is_err());
+
+    let after = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!((after.status.as_str(), after.no_time, after.version), ("pending", 0, run.version));
+    let leftovers: i64 = sqlx::query_scalar(
+        "SELECT (SELECT COUNT(1) FROM run_penalty) + (SELECT COUNT(1) FROM audit_log WHERE action = 'save_run')",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(leftovers, 0);
+}
+
+#[tokio::test]
+async fn history_survives_a_redraw_that_deletes_the_run() {
+    let (pool, event_id, teams) = event_with_draw(2, 2).await;
+    // capturada y deshecha: la ronda vuelve a estar sin tiempos y se puede sortear de nuevo
+    let old_run = capture(&pool, event_id, 1, teams[0], Some(8.0), false, false).await;
+    revisions::undo_last(&pool, event_id, None).await.unwrap();
+
+    draw::generate(
+        &pool,
+        draw::GenerateDrawOptions { event_id, round: 1, reseed: Some(true), seed_runs: Some(true), min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_ne!(run.id, old_run);
+    assert_eq!((run.status.as_str(), run.revisions), ("pending", 2));
+
+    // la captura nueva sigue la numeración del equipo en la ronda
+    capture(&pool, event_id, 1, teams[0], Some(7.5), false, false).await;
+    let history = revisions::history(&pool, run.id).await.unwrap();
+    let summary: Vec<(i64, &str, Option<i64>)> = history.iter().map(|v| (v.revision, v.action.as_str(), v.run_id)).collect();
+    assert_eq!(summary, vec![(1, "capture", None), (2, "undo", None), (3, "capture", Some(run.id))]);
+
+    // el vínculo sólo se suelta al borrar la corrida, no se reasigna
+    assert!(sqlx::query("UPDATE run_revision SET run_id = ?1 WHERE run_id IS NULL")
+        .bind(run.id)
+        .execute(&pool)
+        .await
+        .is_err());
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::*;
+use ed25519_dalek::{PublicKey, SecretKey};
+use roping_manager_tauri_lib::error::AppError;
+use roping_manager_tauri_lib::license::{self, License, LicensePayload, LicenseState};
+
+/// Par de claves de prueba (la privada real no está en el repo).
+const TEST_SECRET_B64: &str = "AQIDBAUGBwgJCgsMDQ4PEBESExQVFhcYGRobHB0eHyA=";
+const DEVICE: &str = "AAAA-BBBB-CCCC-DDDD";
+
+fn test_public_key() -> PublicKey {
+    let seed: Vec<u8> = (1..=32).collect();
+    (&SecretKey::from_bytes(&seed).unwrap()).into()
+}
+
+fn payload(device_id: &str, valid_until: &str) -> LicensePayload {
+    LicensePayload {
+        customer: "Rodeo Los Pinos".into(),
+        device_id: device_id.into(),
+        valid_from: None,
+        valid_until: valid_until.into(),
+    }
+}
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn assert_license_error(err: AppError) {
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "license"), "got {:?}", err);
+}
+
+#[test]
+fn signed_key_verifies_and_tampering_is_rejected() {
+    let key = license::issue(TEST_SECRET_B64, &payload(DEVICE, "2099-12-31")).unwrap();
+    let (p, _, _) = license::verify_key(&test_public_key(), &key).unwrap();
+    assert_eq!(p.customer, "Rodeo Los Pinos");
+
+    // otro payload con la firma original
+    let forged = license::issue(TEST_SECRET_B64, &payload(DEVICE, "2199-12-31")).unwrap();
+    let tampered = format!("{}.{}", forged.split('.').next().unwrap(), key.split('.').nth(1).unwrap());
+    assert_license_error(license::verify_key(&test_public_key(), &tampered).unwrap_err());
+
+    // firmada con otra clave: la pública embebida no la acepta
+    assert_license_error(license::verify_key(&license::embedded_public_key(), &key).unwrap_err());
+    assert_license_error(license::verify_key(&test_public_key(), "basura").unwrap_err());
+}
+
+#[test]
+fn expired_license_gets_grace_then_read_only() {
+    let p = payload(DEVICE, "2026-03-31");
+
+    let s = license::evaluate(Some(&p), DEVICE, date("2026-03-31"), None);
+    assert_eq!(s.state, LicenseState::Active);
+    assert!(!s.read_only);
+    assert_eq!(s.days_left, Some(0));
+
+    let s = license::evaluate(Some(&p), DEVICE, date("2026-04-14"), None);
+    assert_eq!(s.state, LicenseState::Grace);
+    assert!(!s.read_only);
+    assert_eq!(s.grace_until.as_deref(), Some("2026-04-14"));
+
+    let s = license::evaluate(Some(&p), DEVICE, date("2026-04-15"), None);
+    assert_eq!(s.state, LicenseState::Expired);
+    assert!(s.read_only);
+
+    // sin licencia, o de otro equipo: sólo lectura
+    assert!(license::evaluate(None, DEVICE, date("2026-01-01"), None).read_only);
+    let other = license::evaluate(Some(&p), "1111-2222-3333-4444", date("2026-01-01"), None);
+    assert_eq!(other.state, LicenseState::Unlicensed);
+    assert!(other.read_only);
+
+    let state = License::new(DEVICE.into(), None, None);
+    assert!(matches!(state.require_writable().unwrap_err(), AppError::Forbidden { .. }));
+    state.set(Some(payload(DEVICE, "2099-12-31")));
+    state.require_writable().unwrap();
+}
+
+#[tokio::test]
+async fn unlicensed_install_gets_a_trial_from_its_first_start() {
+    let dir = TempDir::new("license-trial");
+    let marker = license::trial_marker(&dir.0.join("roping_manager.db"));
+    let pool = memory_pool().await;
+    assert_eq!(license::stored_trial_start(&pool, &marker).await.unwrap(), None);
+    let started = license::trial_start(&pool, &marker).await.unwrap();
+    assert_ne!(started, NaiveDate::MIN);
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), started.format("%Y-%m-%d").to_string());
+
+    // el segundo arranque no reinicia la prueba
+    set_trial_setting(&pool, "2026-01-01").await;
+    assert_eq!(license::trial_start(&pool, &marker).await.unwrap(), date("2026-01-01"));
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), "2026-01-01");
+
+    let s = license::evaluate(None, DEVICE, date("2026-01-31"), Some(date("2026-01-01")));
+    assert_eq!((s.state, s.read_only, s.days_left), (LicenseState::Trial, false, Some(0)));
+    assert_eq!(s.grace_until.as_deref(), Some("2026-01-31"));
+    let s = license::evaluate(None, DEVICE, date("2026-02-01"), Some(date("2026-01-01")));
+    assert_eq!((s.state, s.read_only), (LicenseState::Unlicensed, true));
+
+    // una licencia guardada manda sobre la prueba
+    let p = payload(DEVICE, "2025-12-01");
+    assert_eq!(license::evaluate(Some(&p), DEVICE, date("2026-01-10"), Some(date("2026-01-01"))).state, LicenseState::Expired);
+
+    // un valor ilegible no regala días
+    set_trial_setting(&pool, "nunca").await;
+    let state = License::new(DEVICE.into(), None, Some(license::trial_start(&pool, &marker).await.unwrap()));
+    assert!(matches!(state.require_writable().unwrap_err(), AppError::Forbidden { .. }));
+}
+
+#[tokio::test]
+async fn deleting_the_setting_or_restoring_an_older_base_does_not_restart_the_trial() {
+    let dir = TempDir::new("license-trial-restore");
+    let marker = license::trial_marker(&dir.0.join("roping_manager.db"));
+    let pool = memory_pool().await;
+    std::fs::write(&marker, "2026-01-01").unwrap();
+
+    // renglón borrado a mano: vuelve desde el archivo
+    assert_eq!(license::trial_start(&pool, &marker).await.unwrap(), date("2026-01-01"));
+    sqlx::query("DELETE FROM app_setting WHERE key = ?1").bind(license::TRIAL_SETTING).execute(&pool).await.unwrap();
+    assert_eq!(license::trial_start(&pool, &marker).await.unwrap(), date("2026-01-01"));
+    assert_eq!(license::stored_trial_start(&pool, &marker).await.unwrap(), Some(date("2026-01-01")));
+
+    // respaldo de una base que empezó después: manda la fecha más antigua
+    set_trial_setting(&pool, "2026-03-01").await;
+    let state = License::new(DEVICE.into(), None, None);
+    state.set_trial_started(Some(license::trial_start(&pool, &marker).await.unwrap()));
+    assert_eq!(state.status().grace_until.as_deref(), Some("2026-01-31"));
+
+    // borrar el archivo tampoco: la base lo vuelve a escribir
+    std::fs::remove_file(&marker).unwrap();
+    assert_eq!(license::trial_start(&pool, &marker).await.unwrap(), date("2026-01-01"));
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), "2026-01-01");
+}
+
+async fn set_trial_setting(pool: &sqlx::SqlitePool, value: &str) {
+    sqlx::query("UPDATE app_setting SET value = ?2 WHERE key = ?1")
+        .bind(license::TRIAL_SETTING)
+        .bind(value)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn activation_is_bound_to_the_device_and_reverified_on_load() {
+    let pool = memory_pool().await;
+    let public = test_public_key();
+
+    let foreign = license::issue(TEST_SECRET_B64, &payload("1111-2222-3333-4444", "2099-12-31")).unwrap();
+    assert_license_error(license::activate(&pool, &public, DEVICE, &foreign).await.unwrap_err());
+    let expired = license::issue(TEST_SECRET_B64, &payload(DEVICE, "2000-01-31")).unwrap();
+    assert_license_error(license::activate(&pool, &public, DEVICE, &expired).await.unwrap_err());
+    assert_eq!(license::load(&pool, &public).await.unwrap(), None);
+
+    let key = license::issue(TEST_SECRET_B64, &payload(DEVICE, "2099-12-31")).unwrap();
+    let activated = license::activate(&pool, &public, DEVICE, &key).await.unwrap();
+    assert_eq!(license::load(&pool, &public).await.unwrap(), Some(activated));
+
+    // alargar la fecha a mano en la base invalida la firma
+    sqlx::query("UPDATE license_info SET payload_json = replace(payload_json, '2099', '2199')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(license::load(&pool, &public).await.unwrap(), None);
+}
+
+#[test]
+fn fingerprint_is_stable_and_grouped() {
+    let a = license::fingerprint_of("machine-1");
+    assert_eq!(a, license::fingerprint_of(" machine-1\n"));
+    assert_ne!(a, license::fingerprint_of("machine-2"));
+    assert_eq!(a.len(), 19);
+    assert_eq!(a.split('-').count(), 4);
+}
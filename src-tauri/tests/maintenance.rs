@@ -0,0 +1,84 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::draw::{self, GenerateDrawOptions};
+use roping_manager_tauri_lib::domain::{events, maintenance, ropers, runs, series, teams};
+
+#[tokio::test]
+async fn purge_removes_soft_deleted_rows_but_keeps_referenced_ropers() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let ids = create_teams(&pool, event_id, 2).await;
+    let orphan = create_roper(&pool, "Sin", "both").await;
+
+    teams::delete(&pool, ids[0]).await.unwrap();
+    ropers::delete(&pool, orphan).await.unwrap();
+    let header_of_live_team = teams::list(&pool, event_id).await.unwrap()[0].header_id;
+    ropers::delete(&pool, header_of_live_team).await.unwrap();
+
+    let report = maintenance::purge_soft_deleted(&pool).await.unwrap();
+
+    assert_eq!(report.teams, 1);
+    assert_eq!(report.ropers, 1);
+    assert_eq!(report.ropers_kept, 1);
+    assert_eq!(report.events, 0);
+    let left: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM team").fetch_one(&pool).await.unwrap();
+    assert_eq!(left, 1);
+}
+
+#[tokio::test]
+async fn purge_cascades_deleted_series_and_events() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    create_teams(&pool, event_id, 2).await;
+    let series_id: i64 = sqlx::query_scalar("SELECT series_id FROM event WHERE id = ?1")
+        .bind(event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    series::delete(&pool, series_id).await.unwrap();
+
+    let report = maintenance::purge_soft_deleted(&pool).await.unwrap();
+
+    assert_eq!((report.series, report.events), (1, 1));
+    let teams_left: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM team").fetch_one(&pool).await.unwrap();
+    assert_eq!(teams_left, 0);
+    assert!(events::list_all_raw(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn recompute_totals_fixes_stale_run_totals() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 1).await;
+    let ids = create_teams(&pool, event_id, 1).await;
+    draw::generate(
+        &pool,
+        GenerateDrawOptions {
+            event_id,
+            round: 1,
+            reseed: None,
+            seed_runs: None,
+            min_spacing: None,
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+    capture(&pool, event_id, 1, ids[0], Some(8.0), false, false).await;
+    sqlx::query("UPDATE run SET penalty = 5, total_sec = 8 WHERE event_id = ?1")
+        .bind(event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(runs::recompute_totals(&pool, event_id).await.unwrap(), 1);
+    assert_eq!(run_for(&pool, event_id, 1, ids[0]).await.total_sec, Some(13.0));
+    assert_eq!(runs::recompute_totals(&pool, event_id).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn integrity_check_reports_a_healthy_database() {
+    let pool = memory_pool().await;
+    create_event(&pool, 1).await;
+    assert!(maintenance::integrity_check(&pool).await.unwrap().is_empty());
+}
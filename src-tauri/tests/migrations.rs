@@ -0,0 +1,93 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::db::MIGRATOR;
+use roping_manager_tauri_lib::schema::{self, SchemaError};
+use sqlx::migrate::Migrate;
+use sqlx::SqlitePool;
+
+/// Base "de campo": migraciones hasta `version` aplicadas con sqlx, como las dejaba la app antigua.
+async fn legacy_pool(version: i64) -> SqlitePool {
+    let pool = empty_memory_pool().await;
+    let mut conn = pool.acquire().await.unwrap();
+    conn.ensure_migrations_table().await.unwrap();
+    for m in MIGRATOR.iter().filter(|m| m.version <= version) {
+        conn.apply(m).await.unwrap();
+    }
+    drop(conn);
+    pool
+}
+
+#[tokio::test]
+async fn fresh_database_migrates_without_repairs() {
+    let pool = empty_memory_pool().await;
+
+    let report = schema::migrate(&pool).await.unwrap();
+
+    assert_eq!(report.applied, MIGRATOR.iter().map(|m| m.version).collect::<Vec<_>>());
+    assert!(report.repaired.is_empty());
+}
+
+#[tokio::test]
+async fn existing_columns_are_skipped_and_recorded() {
+    let pool = legacy_pool(3).await;
+    // columna añadida a mano siguiendo las instrucciones de 0004
+    sqlx::query("ALTER TABLE roper ADD COLUMN is_active INTEGER NOT NULL DEFAULT 1")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let report = schema::migrate(&pool).await.unwrap();
+
+    assert_eq!(report.repaired.len(), 1);
+    assert_eq!(report.repaired[0].version, 4);
+    assert_eq!((report.repaired[0].table.as_str(), report.repaired[0].column.as_str()), ("roper", "is_active"));
+    // el resto de 0004 sí se aplicó
+    let rule_col: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM pragma_table_info('payoff_rule') WHERE name = 'is_active'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rule_col, 1);
+    let audited: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM audit_log WHERE action = 'schema_repair' AND entity_id = 4")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(audited, 1);
+
+    // idempotente: la siguiente pasada no hace nada y sqlx acepta el checksum registrado
+    let again = schema::migrate(&pool).await.unwrap();
+    assert!(again.applied.is_empty());
+    MIGRATOR.run(&pool).await.unwrap();
+}
+
+#[tokio::test]
+async fn missing_columns_are_reported_as_drift() {
+    let pool = memory_pool().await;
+    sqlx::query("ALTER TABLE event DROP COLUMN admin_pin").execute(&pool).await.unwrap();
+
+    match schema::migrate(&pool).await {
+        Err(SchemaError::Drift { missing }) => assert_eq!(missing, vec!["event.admin_pin".to_string()]),
+        other => panic!("expected drift, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn databases_from_a_newer_build_are_refused() {
+    let pool = memory_pool().await;
+    sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (999, 'future', 1, x'00', 0)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let err = schema::migrate(&pool).await.unwrap_err();
+    assert!(matches!(err, SchemaError::NewerDatabase { version: 999 }), "got {:?}", err);
+}
+
+#[test]
+fn added_columns_ignores_comments_and_trigger_bodies() {
+    let sql = "-- ALTER TABLE x ADD COLUMN nope INTEGER;\nALTER TABLE \"roper\" ADD level TEXT;\nCREATE TRIGGER t BEFORE INSERT ON roper BEGIN SELECT RAISE(ABORT, 'a;b'); END;\nALTER TABLE event ADD COLUMN admin_pin TEXT;";
+    assert_eq!(
+        schema::added_columns(sql),
+        vec![("roper".to_string(), "level".to_string()), ("event".to_string(), "admin_pin".to_string())]
+    );
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::draw::{self, GenerateBatchDrawOptions};
+use roping_manager_tauri_lib::domain::penalties::{self, NewPenaltyType, PenaltyTypePatch};
+use roping_manager_tauri_lib::domain::{events, runs, standings};
+use roping_manager_tauri_lib::error::{AppError, AppResult};
+use sqlx::SqlitePool;
+
+async fn event_with_draw(rounds: i64, n_teams: usize) -> (SqlitePool, i64, Vec<i64>) {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, rounds).await;
+    let team_ids = create_teams(&pool, event_id, n_teams).await;
+    draw::generate_batch(
+        &pool,
+        GenerateBatchDrawOptions { event_id, rounds, shuffle: false, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    (pool, event_id, team_ids)
+}
+
+async fn type_id(pool: &SqlitePool, event_id: i64, code: &str) -> i64 {
+    penalties::list_types(pool, event_id, true)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|t| t.code == code)
+        .unwrap()
+        .id
+}
+
+async fn save_with(
+    pool: &SqlitePool,
+    event_id: i64,
+    round: i64,
+    team_id: i64,
+    penalty: f64,
+    items: Option<Vec<i64>>,
+) -> AppResult<i64> {
+    let position = run_for(pool, event_id, round, team_id).await.position;
+    runs::save(
+        pool,
+        runs::SaveRun {
+            event_id,
+            team_id,
+            round,
+            position,
+            time_sec: Some(8.0),
+            penalty,
+            penalties: items,
+            no_time: false,
+            dq: false,
+            pin: None,
+            reason: None,
+            expected_version: None,
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn itemized_penalties_drive_the_total_and_the_breakdown() {
+    let (pool, event_id, teams) = event_with_draw(3, 2).await;
+    let leg = type_id(&pool, event_id, "leg").await;
+    let barrier = type_id(&pool, event_id, "barrier").await;
+
+    save_with(&pool, event_id, 1, teams[0], 0.0, Some(vec![leg, barrier])).await.unwrap();
+    save_with(&pool, event_id, 2, teams[0], 0.0, Some(vec![leg])).await.unwrap();
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!((run.penalty, run.total_sec), (15.0, Some(23.0)));
+
+    let expanded = runs::list_expanded(&pool, event_id, Some(1)).await.unwrap();
+    let row = expanded.iter().find(|r| r.team_id == teams[0]).unwrap();
+    let labels: Vec<(&str, f64)> = row.penalties.iter().map(|p| (p.label.as_str(), p.seconds)).collect();
+    assert_eq!(labels, vec![("One-leg catch", 5.0), ("Broken barrier", 10.0)]);
+    assert!(expanded.iter().find(|r| r.team_id == teams[1]).unwrap().penalties.is_empty());
+
+    let standing = standings::get(&pool, event_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|s| s.team_id == teams[0])
+        .unwrap();
+    assert_eq!(standing.penalty_total, 20.0);
+    let tallies: Vec<(&str, i64, f64)> =
+        standing.penalties.iter().map(|p| (p.label.as_str(), p.count, p.seconds)).collect();
+    assert_eq!(tallies, vec![("One-leg catch", 2, 10.0), ("Broken barrier", 1, 10.0)]);
+
+    // una corrección sin penalizaciones borra los renglones
+    save_with(&pool, event_id, 1, teams[0], 0.0, Some(vec![])).await.unwrap();
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!((run.penalty, run.total_sec), (0.0, Some(8.0)));
+    assert!(penalties::for_run(&pool, run.id).await.unwrap().is_empty());
+
+    // los clientes viejos siguen mandando un número suelto: queda como renglón sin tipo
+    save_with(&pool, event_id, 1, teams[1], 3.0, None).await.unwrap();
+    let run = run_for(&pool, event_id, 1, teams[1]).await;
+    let items = penalties::for_run(&pool, run.id).await.unwrap();
+    assert_eq!((items.len(), items[0].penalty_type_id, items[0].seconds), (1, None, 3.0));
+    assert_eq!(run.total_sec, Some(11.0));
+}
+
+#[tokio::test]
+async fn custom_types_are_per_event_and_copied_on_duplicate() {
+    let (pool, event_id, teams) = event_with_draw(2, 1).await;
+    let other_event = events::duplicate(&pool, event_id).await.unwrap();
+    sqlx::query("UPDATE event SET name = 'Otro evento' WHERE id = ?1")
+        .bind(other_event)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let foreign = type_id(&pool, other_event, "leg").await;
+
+    let custom = penalties::create_type(
+        &pool,
+        NewPenaltyType { event_id, code: "Cross-fire".into(), label: "Cross-fire".into(), seconds: 7.5 },
+    )
+    .await
+    .unwrap();
+    let err = penalties::create_type(
+        &pool,
+        NewPenaltyType { event_id, code: "cross-fire".into(), label: "Otra".into(), seconds: 1.0 },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "code"));
+
+    let err = save_with(&pool, event_id, 1, teams[0], 0.0, Some(vec![foreign])).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "penalties"));
+
+    save_with(&pool, event_id, 1, teams[0], 0.0, Some(vec![custom])).await.unwrap();
+    // cambiar el tipo no reescribe lo ya capturado; desactivarlo lo saca de la captura
+    let patch = PenaltyTypePatch { seconds: Some(20.0), is_active: Some(false), ..Default::default() };
+    penalties::update_type(&pool, custom, patch).await.unwrap();
+    assert_eq!(run_for(&pool, event_id, 1, teams[0]).await.total_sec, Some(15.5));
+    let err = save_with(&pool, event_id, 1, teams[0], 0.0, Some(vec![custom])).await.unwrap_err();
+    assert_eq!(err.code(), "Validation");
+
+    let copy = events::duplicate(&pool, event_id).await.unwrap();
+    let codes: Vec<(String, i64)> = penalties::list_types(&pool, copy, true)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|t| (t.code, t.is_active))
+        .collect();
+    assert_eq!(codes, vec![("leg".into(), 1), ("barrier".into(), 1), ("cross-fire".into(), 0)]);
+}
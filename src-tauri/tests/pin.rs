@@ -0,0 +1,178 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::draw::{self, GenerateDrawOptions};
+use roping_manager_tauri_lib::domain::{audit, events, runs};
+use roping_manager_tauri_lib::error::AppError;
+use roping_manager_tauri_lib::schema;
+use sqlx::SqlitePool;
+
+async fn set_pin(pool: &SqlitePool, event_id: i64, pin: &str) {
+    let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "admin_pin": pin })).unwrap();
+    events::update(pool, event_id, patch).await.unwrap();
+}
+
+fn assert_pin_error(err: AppError) {
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "pin"), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn pin_is_hashed_and_never_serialized() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    set_pin(&pool, event_id, "4321").await;
+
+    let stored: String = sqlx::query_scalar("SELECT admin_pin FROM event WHERE id = ?1")
+        .bind(event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(stored.starts_with("$argon2"), "stored: {}", stored);
+
+    let rows = events::list(&pool, None).await.unwrap();
+    let json = serde_json::to_value(&rows).unwrap();
+    assert_eq!(json[0]["has_admin_pin"], true);
+    assert!(json[0].get("admin_pin").is_none());
+
+    assert!(events::verify_pin(&pool, event_id, "4321").await.unwrap());
+    assert!(!events::verify_pin(&pool, event_id, "1234").await.unwrap());
+
+    let bad: events::EventPatch = serde_json::from_value(serde_json::json!({ "admin_pin": "12ab" })).unwrap();
+    let err = events::update(&pool, event_id, bad).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "pin"), "cambiar el PIN pide el actual: {:?}", err);
+}
+
+#[tokio::test]
+async fn unlocking_requires_the_pin() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+
+    // sin PIN configurado no se pide nada
+    events::lock(&pool, event_id).await.unwrap();
+    events::update_status(&pool, event_id, "active", None).await.unwrap();
+
+    set_pin(&pool, event_id, "4321").await;
+    events::lock(&pool, event_id).await.unwrap();
+    assert_pin_error(events::update_status(&pool, event_id, "active", None).await.unwrap_err());
+    assert_pin_error(events::update_status(&pool, event_id, "active", Some("0000")).await.unwrap_err());
+    events::update_status(&pool, event_id, "active", Some("4321")).await.unwrap();
+}
+
+#[tokio::test]
+async fn overwriting_a_completed_run_and_redrawing_require_the_pin() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 3).await;
+    let teams = create_teams(&pool, event_id, 3).await;
+    let opts = |pin: Option<&str>| GenerateDrawOptions {
+        event_id,
+        round: 1,
+        reseed: Some(true),
+        seed_runs: Some(true),
+        min_spacing: None,
+        pin: pin.map(String::from),
+    };
+    draw::generate(&pool, opts(None)).await.unwrap();
+    set_pin(&pool, event_id, "4321").await;
+
+    assert_pin_error(draw::generate(&pool, opts(None)).await.unwrap_err());
+    draw::generate(&pool, opts(Some("4321"))).await.unwrap();
+
+    capture(&pool, event_id, 1, teams[0], Some(7.5), false, false).await;
+    let overwrite = |pin: Option<&str>| runs::SaveRun {
+        event_id,
+        team_id: teams[0],
+        round: 1,
+        position: 1,
+        time_sec: Some(6.9),
+        penalty: 0.0,
+        penalties: None,
+        no_time: false,
+        dq: false,
+        pin: pin.map(String::from),
+        reason: None,
+        expected_version: None,
+    };
+    assert_pin_error(runs::save(&pool, overwrite(Some("1111"))).await.unwrap_err());
+    runs::save(&pool, overwrite(Some("4321"))).await.unwrap();
+    assert_eq!(run_for(&pool, event_id, 1, teams[0]).await.time_sec, Some(6.9));
+}
+
+#[tokio::test]
+async fn migrate_hashes_plaintext_pins_from_older_versions() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    sqlx::query("UPDATE event SET admin_pin = '2468' WHERE id = ?1")
+        .bind(event_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    schema::migrate(&pool).await.unwrap();
+
+    let stored: String = sqlx::query_scalar("SELECT admin_pin FROM event WHERE id = ?1")
+        .bind(event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(stored.starts_with("$argon2"));
+    assert!(events::verify_pin(&pool, event_id, "2468").await.unwrap());
+}
+
+#[tokio::test]
+async fn repeated_wrong_pins_lock_the_event_for_a_while() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    set_pin(&pool, event_id, "4321").await;
+
+    // pedir la acción sin PIN no es un intento
+    for _ in 0..events::PIN_MAX_ATTEMPTS {
+        assert_pin_error(events::require_pin(&pool, event_id, None).await.unwrap_err());
+    }
+    for _ in 0..events::PIN_MAX_ATTEMPTS {
+        assert!(!events::verify_pin(&pool, event_id, "0000").await.unwrap());
+    }
+    let rejected: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM audit_log WHERE action = 'pin_rejected' AND entity_id = ?1")
+        .bind(event_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rejected, 2 * events::PIN_MAX_ATTEMPTS);
+
+    // bloqueado: ni el PIN correcto pasa, por ninguno de los dos caminos
+    assert_eq!(events::verify_pin(&pool, event_id, "4321").await.unwrap_err().code(), "Conflict");
+    assert_eq!(events::require_pin(&pool, event_id, Some("4321")).await.unwrap_err().code(), "Conflict");
+
+    // pasada la ventana vuelve a aceptar
+    sqlx::query("UPDATE audit_log SET created_at = strftime('%Y-%m-%dT%H:%M:%SZ','now','-1 hour') WHERE action = 'pin_rejected'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(events::verify_pin(&pool, event_id, "4321").await.unwrap());
+}
+
+#[tokio::test]
+async fn one_users_wrong_pins_do_not_lock_out_another_user() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    set_pin(&pool, event_id, "4321").await;
+    let mut users = Vec::new();
+    for email in ["operador@example.com", "admin@example.com"] {
+        let id = sqlx::query("INSERT INTO app_user (email, full_name, password_hash) VALUES (?1, ?1, 'x')")
+            .bind(email)
+            .execute(&pool)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        users.push(Some(id));
+    }
+    let (operator, admin) = (users[0], users[1]);
+
+    for _ in 0..events::PIN_MAX_ATTEMPTS {
+        assert!(!audit::as_user(operator, events::verify_pin(&pool, event_id, "0000")).await.unwrap());
+    }
+    let err = audit::as_user(operator, events::require_pin(&pool, event_id, Some("4321"))).await.unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+
+    // el administrador sigue pudiendo corregir con el PIN correcto
+    audit::as_user(admin, events::require_pin(&pool, event_id, Some("4321"))).await.unwrap();
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::draw::{self, GenerateBatchDrawOptions, GenerateDrawOptions};
+use roping_manager_tauri_lib::domain::{audit, events, runs, teams};
+use sqlx::SqlitePool;
+
+/// Evento de 3 rondas con 4 equipos sorteados en las rondas 1 y 2 (corridas pendientes).
+async fn drawn_event(pool: &SqlitePool, policy: &str) -> (i64, Vec<i64>) {
+    let event_id = create_event(pool, 3).await;
+    let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "scratch_policy": policy })).unwrap();
+    events::update(pool, event_id, patch).await.unwrap();
+    let teams = create_teams(pool, event_id, 4).await;
+    draw::generate_batch(
+        pool,
+        GenerateBatchDrawOptions {
+            event_id,
+            rounds: 3,
+            shuffle: false,
+            min_spacing: Some(0),
+            pin: None,
+        },
+    )
+    .await
+    .unwrap();
+    (event_id, teams)
+}
+
+async fn run_positions(pool: &SqlitePool, event_id: i64, round: i64) -> Vec<(i64, i64)> {
+    runs::list(pool, event_id, Some(round))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.position, r.team_id))
+        .collect()
+}
+
+#[tokio::test]
+async fn close_gap_removes_pending_slots_and_renumbers() {
+    let pool = memory_pool().await;
+    let (event_id, _) = drawn_event(&pool, "close_gap").await;
+    let out_team = draw_order(&pool, event_id, 2).await[1];
+    // ya corrió la ronda 1: esa corrida se queda
+    capture(&pool, event_id, 1, out_team, Some(7.5), false, false).await;
+
+    let outcome = teams::scratch(&pool, out_team, Some("Caballo lesionado".into())).await.unwrap();
+    assert_eq!(outcome.policy, "close_gap");
+    assert_eq!(outcome.rounds, vec![2]);
+
+    assert_eq!(run_for(&pool, event_id, 1, out_team).await.status, "completed");
+    let order = draw_order(&pool, event_id, 2).await;
+    assert_eq!(order.len(), 3);
+    assert!(!order.contains(&out_team));
+    let positions = run_positions(&pool, event_id, 2).await;
+    assert_eq!(positions, order.iter().enumerate().map(|(i, &t)| (i as i64 + 1, t)).collect::<Vec<_>>());
+
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.action, "scratch_team");
+    let after: serde_json::Value = serde_json::from_str(log.after_json.as_deref().unwrap()).unwrap();
+    assert_eq!(after["scratch_reason"], "Caballo lesionado");
+    assert_eq!(after["status"], "inactive");
+
+    // el sorteo de la final ya no lo considera
+    draw::generate(
+        &pool,
+        GenerateDrawOptions { event_id, round: 3, reseed: None, seed_runs: None, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    let final_order = draw_order(&pool, event_id, 3).await;
+    assert!(!final_order.is_empty());
+    assert!(!final_order.contains(&out_team));
+}
+
+#[tokio::test]
+async fn keep_slot_marks_runs_and_blocks_capture() {
+    let pool = memory_pool().await;
+    let (event_id, teams) = drawn_event(&pool, "keep_slot").await;
+    let out_team = teams[2];
+    let before = draw_order(&pool, event_id, 1).await;
+
+    let outcome = teams::scratch(&pool, out_team, None).await.unwrap();
+    assert_eq!(outcome.rounds, vec![1, 2]);
+    assert_eq!(draw_order(&pool, event_id, 1).await, before);
+    for round in 1..=2 {
+        let run = run_for(&pool, event_id, round, out_team).await;
+        assert_eq!((run.status.as_str(), run.scratched), ("skipped", 1));
+    }
+
+    let position = run_for(&pool, event_id, 1, out_team).await.position;
+    let err = runs::save(
+        &pool,
+        runs::SaveRun {
+            event_id,
+            team_id: out_team,
+            round: 1,
+            position,
+            time_sec: Some(8.0),
+            penalty: 0.0,
+            penalties: None,
+            no_time: false,
+            dq: false,
+            pin: None,
+            reason: None,
+            expected_version: None,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+
+    let err = teams::scratch(&pool, out_team, None).await.unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+
+    // reactivarlo lo volvería a meter al draw sin poder capturarlo
+    let reactivate = teams::UpdateTeam { id: out_team, rating: None, status: Some("active".into()), expected_version: None };
+    assert_eq!(teams::update(&pool, reactivate).await.unwrap_err().code(), "Conflict");
+    let team = teams::get(&pool, out_team).await.unwrap();
+    assert_eq!(team.status, "inactive");
+    assert!(team.scratched_at.is_some());
+    // el resto de la edición sigue permitida
+    teams::update(&pool, teams::UpdateTeam { id: out_team, rating: Some(4.5), status: None, expected_version: None })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn scratch_is_refused_once_the_event_is_closed() {
+    let pool = memory_pool().await;
+    let (event_id, teams) = drawn_event(&pool, "close_gap").await;
+    events::update_status(&pool, event_id, "completed", None).await.unwrap();
+
+    let err = teams::scratch(&pool, teams[0], None).await.unwrap_err();
+    assert_eq!(err.code(), "EventLocked");
+}
+
+#[tokio::test]
+async fn keep_slot_marks_the_slot_even_without_a_seeded_run() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "scratch_policy": "keep_slot" })).unwrap();
+    events::update(&pool, event_id, patch).await.unwrap();
+    create_teams(&pool, event_id, 3).await;
+    draw::generate(
+        &pool,
+        GenerateDrawOptions { event_id, round: 1, reseed: Some(false), seed_runs: Some(false), min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    let out_team = draw_order(&pool, event_id, 1).await[1];
+
+    let outcome = teams::scratch(&pool, out_team, None).await.unwrap();
+    assert_eq!(outcome.rounds, vec![1]);
+    let run = run_for(&pool, event_id, 1, out_team).await;
+    assert_eq!((run.position, run.status.as_str(), run.scratched), (2, "skipped", 1));
+    // el resto de la ronda sigue sin corridas hasta que se capturen
+    assert_eq!(run_positions(&pool, event_id, 1).await, vec![(2, out_team)]);
+
+    // un segundo scratch no vuelve a tocar el orden
+    assert_eq!(teams::scratch(&pool, out_team, None).await.unwrap_err().code(), "Conflict");
+    assert_eq!(draw_order(&pool, event_id, 1).await.len(), 3);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::events;
+use roping_manager_tauri_lib::snapshots::{self, SnapshotSettings};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn snapshots_rotate_per_reason() {
+    let dir = TempDir::new("snap-rotate");
+    let db = file_db(&dir).await;
+    snapshots::save_settings(&db, SnapshotSettings { interval_minutes: 5, keep: 2 })
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        snapshots::take(&db, "auto").await.unwrap();
+        // los nombres llevan milisegundos; evitamos colisiones
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let lock = snapshots::take(&db, "lock").await.unwrap();
+
+    let all = snapshots::list(&db).unwrap();
+    assert_eq!(all.iter().filter(|s| s.reason == "auto").count(), 2);
+    assert_eq!(all[0].file_name, lock.file_name);
+    assert!(all.iter().all(|s| s.size_bytes > 0));
+}
+
+#[tokio::test]
+async fn scheduler_only_snapshots_while_an_event_is_active() {
+    let dir = TempDir::new("snap-tick");
+    let db = file_db(&dir).await;
+    let mut last = None;
+
+    snapshots::tick(&db, &mut last).await;
+    assert!(snapshots::list(&db).unwrap().is_empty());
+    assert!(last.is_none());
+
+    let event_id = create_event(&db.pool(), 2).await; // status 'active'
+    snapshots::tick(&db, &mut last).await;
+    assert_eq!(snapshots::list(&db).unwrap().len(), 1);
+
+    // dentro del intervalo no se repite
+    snapshots::tick(&db, &mut last).await;
+    assert_eq!(snapshots::list(&db).unwrap().len(), 1);
+
+    // pasado el intervalo, pero sin eventos activos, tampoco
+    events::update_status(&db.pool(), event_id, "completed", None).await.unwrap();
+    last = Some(Instant::now() - Duration::from_secs(6 * 60));
+    snapshots::tick(&db, &mut last).await;
+    assert_eq!(snapshots::list(&db).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn settings_are_validated() {
+    let dir = TempDir::new("snap-settings");
+    let db = file_db(&dir).await;
+
+    assert_eq!(
+        snapshots::load_settings(&db).await.unwrap(),
+        SnapshotSettings { interval_minutes: 5, keep: 24 }
+    );
+    let err = snapshots::save_settings(&db, SnapshotSettings { interval_minutes: 0, keep: 3 })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "Validation");
+}
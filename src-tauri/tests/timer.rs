@@ -0,0 +1,132 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::*;
+use roping_manager_tauri_lib::domain::{audit, draw};
+use roping_manager_tauri_lib::timer::{self, parse_frame, ConfirmTimerTime, Timer, TimerFrame, TimerReading, TimerSource};
+use roping_manager_tauri_lib::timer_sim::{TcpSimulator, TimerEmitter};
+
+/// Espera (hasta 3 s) a que el hilo lector deje una lectura con `seq`.
+fn wait_reading(timer: &Timer, seq: u64) -> TimerReading {
+    let deadline = Instant::now() + Duration::from_secs(3);
+    loop {
+        if let Some(r) = timer.status().reading.filter(|r| r.seq == seq) {
+            return r;
+        }
+        assert!(Instant::now() < deadline, "no llegó la lectura {}: {:?}", seq, timer.status());
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn frames_parse_in_every_accepted_form() {
+    let finish = |s: f64| Ok(Some(TimerFrame::Finish { seconds: s }));
+    assert_eq!(parse_frame("FINISH 8.456"), finish(8.456));
+    assert_eq!(parse_frame("f,8.456\r"), finish(8.456));
+    assert_eq!(parse_frame("\u{2}  12.1\u{3}"), finish(12.1));
+    assert_eq!(parse_frame("1:02.500"), finish(62.5));
+    assert_eq!(parse_frame("S"), Ok(Some(TimerFrame::Start)));
+    assert_eq!(parse_frame("reset"), Ok(Some(TimerFrame::Reset)));
+    assert_eq!(parse_frame("   "), Ok(None));
+    // la trama del simulador lleva checksum y se lee igual
+    let encoded = TimerFrame::Finish { seconds: 7.25 }.encode();
+    assert_eq!(parse_frame(&encoded), finish(7.25));
+
+    assert!(parse_frame("FINISH 8.456*00").is_err());
+    assert!(parse_frame("FINISH 0").is_err());
+    assert!(parse_frame("F 999").is_err());
+    assert!(parse_frame("HELLO").is_err());
+}
+
+#[tokio::test]
+async fn tcp_reading_is_offered_to_the_next_pending_run_and_confirmed() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    create_teams(&pool, event_id, 3).await;
+    draw::generate_batch(
+        &pool,
+        draw::GenerateBatchDrawOptions { event_id, rounds: 1, shuffle: false, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    let order = draw_order(&pool, event_id, 1).await;
+    capture(&pool, event_id, 1, order[0], Some(9.0), false, false).await;
+
+    let mut sim = TcpSimulator::bind("127.0.0.1:0").unwrap();
+    let timer = Timer::default();
+    timer.connect(TimerSource::Tcp { addr: sim.addr().to_string() }).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(3);
+    while sim.clients() == 0 {
+        assert!(Instant::now() < deadline);
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    sim.send(TimerFrame::Start).unwrap();
+    sim.send_raw("basura\r\n").unwrap();
+    sim.send(TimerFrame::Finish { seconds: 7.891 }).unwrap();
+    let reading = wait_reading(&timer, 1);
+    assert_eq!(reading.seconds, 7.891);
+    let status = timer.status();
+    assert!(status.connected && !status.running);
+    assert_eq!((status.frames, status.rejected), (2, 1));
+
+    let offer = timer::offer(&pool, &timer, event_id, Some(1)).await.unwrap();
+    let run = offer.run.unwrap();
+    assert_eq!((run.team_id, run.position), (order[1], 2));
+
+    // llegó otro tiempo: confirmar el que ya no está en pantalla no guarda nada
+    sim.send(TimerFrame::Finish { seconds: 8.002 }).unwrap();
+    wait_reading(&timer, 2);
+    let confirm = |seq| ConfirmTimerTime { event_id, run_id: run.id, expected_version: run.version, seq, penalties: None };
+    let err = timer::confirm(&pool, &timer, confirm(1)).await.unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+
+    let run_id = timer::confirm(&pool, &timer, confirm(2)).await.unwrap();
+    let saved = run_for(&pool, event_id, 1, order[1]).await;
+    assert_eq!((saved.id, saved.status.as_str(), saved.total_sec), (run_id, "completed", Some(8.002)));
+    assert!(timer.status().reading.is_none());
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.action, "timer_capture");
+
+    // la siguiente oferta ya es el tercer equipo
+    let next = timer::offer(&pool, &timer, event_id, Some(1)).await.unwrap().run.unwrap();
+    assert_eq!(next.team_id, order[2]);
+
+    // otra ventana captura ese equipo a mano antes de confirmar: el tiempo no se va a otro equipo
+    sim.send(TimerFrame::Finish { seconds: 9.5 }).unwrap();
+    wait_reading(&timer, 3);
+    capture(&pool, event_id, 1, order[2], Some(10.0), false, false).await;
+    let stale = ConfirmTimerTime { event_id, run_id: next.id, expected_version: next.version, seq: 3, penalties: None };
+    let err = timer::confirm(&pool, &timer, stale).await.unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+    assert_eq!(run_for(&pool, event_id, 1, order[2]).await.total_sec, Some(10.0));
+    // la lectura sigue esperando: no se perdió
+    assert_eq!(timer.status().reading.map(|r| r.seq), Some(3));
+
+    timer.disconnect();
+    assert!(!timer.status().connected);
+}
+
+#[cfg(unix)]
+#[test]
+fn serial_reader_works_over_a_pseudo_terminal() {
+    use roping_manager_tauri_lib::timer_sim::PtySimulator;
+
+    let mut pty = PtySimulator::open().unwrap();
+    let timer = Timer::default();
+    timer.connect(TimerSource::Serial { path: pty.path().to_string(), baud: Some(9600) }).unwrap();
+
+    // un timer que sólo manda el tiempo terminado en CR
+    pty.send_raw("  10.250\r").unwrap();
+    assert_eq!(wait_reading(&timer, 1).seconds, 10.25);
+    pty.send(TimerFrame::Finish { seconds: 6.5 }).unwrap();
+    assert_eq!(wait_reading(&timer, 2).raw, TimerFrame::Finish { seconds: 6.5 }.encode().trim());
+
+    assert!(timer.dismiss(2));
+    assert!(!timer.dismiss(2));
+    timer.disconnect();
+
+    let err = timer.connect(TimerSource::Serial { path: pty.path().to_string(), baud: Some(1234) }).unwrap_err();
+    assert_eq!(err.code(), "Validation");
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::{audit, draw, events, penalties, revisions, runs};
+use roping_manager_tauri_lib::error::AppError;
+use sqlx::SqlitePool;
+
+async fn event_with_draw(rounds: i64, n_teams: usize) -> (SqlitePool, i64, Vec<i64>) {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, rounds).await;
+    let team_ids = create_teams(&pool, event_id, n_teams).await;
+    draw::generate_batch(
+        &pool,
+        draw::GenerateBatchDrawOptions { event_id, rounds, shuffle: false, min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    (pool, event_id, team_ids)
+}
+
+async fn save_with_penalties(
+    pool: &SqlitePool,
+    event_id: i64,
+    team_id: i64,
+    time_sec: f64,
+    items: Vec<i64>,
+    pin: Option<&str>,
+) {
+    let position = run_for(pool, event_id, 1, team_id).await.position;
+    runs::save(
+        pool,
+        runs::SaveRun {
+            event_id,
+            team_id,
+            round: 1,
+            position,
+            time_sec: Some(time_sec),
+            penalty: 0.0,
+            penalties: Some(items),
+            no_time: false,
+            dq: false,
+            pin: pin.map(String::from),
+            reason: None,
+            expected_version: None,
+        },
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn undo_reverts_a_capture_with_its_cascade_and_redo_reapplies_it() {
+    let (pool, event_id, teams) = event_with_draw(3, 2).await;
+    let (wrong, right) = (teams[0], teams[1]);
+    let err = revisions::undo_last(&pool, event_id, None).await.unwrap_err();
+    assert_eq!(err.code(), "NotFound");
+
+    // NT guardado en el equipo equivocado: saca al equipo de la ronda 2
+    let run_id = capture(&pool, event_id, 1, wrong, None, true, false).await;
+    assert_eq!(run_for(&pool, event_id, 2, wrong).await.status, "skipped");
+    let state = revisions::undo_state(&pool, event_id).await.unwrap();
+    let target = state.undo.unwrap();
+    assert_eq!(
+        (target.run_id, target.action.as_str(), target.no_time, target.requires_pin),
+        (run_id, "capture", 1, false)
+    );
+    assert!(state.redo.is_none());
+
+    let outcome = revisions::undo_last(&pool, event_id, None).await.unwrap();
+    assert_eq!((outcome.run_id, outcome.revision), (run_id, 2));
+    let run = run_for(&pool, event_id, 1, wrong).await;
+    assert_eq!((run.status.as_str(), run.no_time, run.time_sec, run.captured_by), ("pending", 0, None, None));
+    assert_eq!(run_for(&pool, event_id, 2, wrong).await.status, "pending");
+    assert!(outcome.state.undo.is_none());
+    assert_eq!(outcome.state.redo.as_ref().map(|t| t.run_id), Some(run_id));
+    let last = revisions::history(&pool, run_id).await.unwrap().pop().unwrap();
+    assert_eq!((last.action.as_str(), last.target_revision), ("undo", Some(1)));
+    let log = audit::recent_activity(&pool, 1, None).await.unwrap().remove(0);
+    assert_eq!(log.action, "undo_run_change");
+
+    // rehacer vuelve a dejar el NT y su cascada
+    revisions::redo_last(&pool, event_id, None).await.unwrap();
+    let run = run_for(&pool, event_id, 1, wrong).await;
+    assert_eq!((run.status.as_str(), run.no_time), ("completed", 1));
+    assert_eq!(run_for(&pool, event_id, 2, wrong).await.status, "skipped");
+
+    // deshacer y capturar otra cosa descarta el rehacer
+    revisions::undo_last(&pool, event_id, None).await.unwrap();
+    capture(&pool, event_id, 1, right, Some(7.5), false, false).await;
+    let state = revisions::undo_state(&pool, event_id).await.unwrap();
+    assert_eq!(state.undo.map(|t| t.team_id), Some(right));
+    assert!(state.redo.is_none());
+    assert_eq!(revisions::redo_last(&pool, event_id, None).await.unwrap_err().code(), "NotFound");
+}
+
+#[tokio::test]
+async fn undoing_a_correction_restores_penalties_and_needs_the_pin() {
+    let (pool, event_id, teams) = event_with_draw(2, 1).await;
+    let patch: events::EventPatch = serde_json::from_value(serde_json::json!({ "admin_pin": "4321" })).unwrap();
+    events::update(&pool, event_id, patch).await.unwrap();
+    let leg = penalties::list_types(&pool, event_id, false).await.unwrap()[0].id;
+
+    save_with_penalties(&pool, event_id, teams[0], 8.0, vec![leg], None).await;
+    save_with_penalties(&pool, event_id, teams[0], 9.0, vec![], Some("4321")).await;
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!(run.total_sec, Some(9.0));
+
+    let err = revisions::undo_last(&pool, event_id, None).await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "pin"), "got {:?}", err);
+    revisions::undo_last(&pool, event_id, Some("4321")).await.unwrap();
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!((run.time_sec, run.penalty, run.total_sec), (Some(8.0), 5.0, Some(13.0)));
+    let items = penalties::for_run(&pool, run.id).await.unwrap();
+    assert_eq!((items.len(), items[0].penalty_type_id), (1, Some(leg)));
+
+    // si la corrida cambió por fuera del historial, no se pisa: Conflict
+    sqlx::query("UPDATE run SET time_sec = 8.5 WHERE id = ?1").bind(run.id).execute(&pool).await.unwrap();
+    let err = revisions::undo_last(&pool, event_id, None).await.unwrap_err();
+    assert_eq!(err.code(), "Conflict");
+    assert_eq!(run_for(&pool, event_id, 1, teams[0]).await.time_sec, Some(8.5));
+}
+
+#[tokio::test]
+async fn undoing_a_capture_without_a_seeded_run_leaves_it_pending() {
+    let pool = memory_pool().await;
+    let event_id = create_event(&pool, 2).await;
+    let teams = create_teams(&pool, event_id, 2).await;
+    draw::generate(
+        &pool,
+        draw::GenerateDrawOptions { event_id, round: 1, reseed: Some(false), seed_runs: Some(false), min_spacing: None, pin: None },
+    )
+    .await
+    .unwrap();
+    assert!(runs::list(&pool, event_id, Some(1)).await.unwrap().is_empty());
+
+    // el capturista toma la posición del draw: la corrida nace con la captura
+    let slot = draw::get(&pool, event_id, 1).await.unwrap().into_iter().find(|d| d.team_id == teams[0]).unwrap();
+    let run_id = runs::save(
+        &pool,
+        runs::SaveRun {
+            event_id,
+            team_id: teams[0],
+            round: 1,
+            position: slot.position,
+            time_sec: Some(8.2),
+            penalty: 5.0,
+            penalties: None,
+            no_time: false,
+            dq: false,
+            pin: None,
+            reason: None,
+            expected_version: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    revisions::undo_last(&pool, event_id, None).await.unwrap();
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!(run.id, run_id);
+    assert_eq!((run.status.as_str(), run.time_sec, run.penalty, run.total_sec), ("pending", None, 0.0, None));
+    assert!(penalties::for_run(&pool, run_id).await.unwrap().is_empty());
+
+    revisions::redo_last(&pool, event_id, None).await.unwrap();
+    let run = run_for(&pool, event_id, 1, teams[0]).await;
+    assert_eq!((run.status.as_str(), run.total_sec), ("completed", Some(13.2)));
+    assert_eq!(penalties::for_run(&pool, run_id).await.unwrap().len(), 1);
+}
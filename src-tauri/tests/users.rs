@@ -0,0 +1,125 @@
+mod common;
+
+use common::*;
+use roping_manager_tauri_lib::domain::users::{self, NewUser, Role};
+use roping_manager_tauri_lib::error::AppError;
+use roping_manager_tauri_lib::session::Session;
+use sqlx::SqlitePool;
+
+async fn create_user(pool: &SqlitePool, email: &str, role: &str) -> i64 {
+    users::create(
+        pool,
+        NewUser {
+            email: email.into(),
+            full_name: "Usuario Test".into(),
+            password: "secreto123".into(),
+            role: role.into(),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn password_is_stored_as_argon2_hash() {
+    let pool = memory_pool().await;
+    let id = create_user(&pool, "Admin@Example.com ", "admin").await;
+
+    let (email, hash): (String, String) = sqlx::query_as("SELECT email, password_hash FROM app_user WHERE id = ?1")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(email, "admin@example.com");
+    assert!(hash.starts_with("$argon2"), "hash: {}", hash);
+    assert!(users::verify_password(&hash, "secreto123"));
+    assert!(!users::verify_password(&hash, "otra-cosa"));
+
+    let user = users::get(&pool, id).await.unwrap();
+    assert_eq!(user.role, "admin");
+}
+
+#[tokio::test]
+async fn login_with_wrong_password_or_unknown_email_is_unauthorized() {
+    let pool = memory_pool().await;
+    create_user(&pool, "op@example.com", "operator").await;
+
+    let user = users::authenticate(&pool, "OP@example.com", "secreto123").await.unwrap();
+    assert_eq!(user.role, "operator");
+
+    for (email, password) in [("op@example.com", "mala-clave"), ("nadie@example.com", "secreto123")] {
+        let err = users::authenticate(&pool, email, password).await.unwrap_err();
+        assert!(matches!(err, AppError::Unauthorized { .. }), "got {:?}", err);
+    }
+
+    sqlx::query("UPDATE app_user SET is_active = 0").execute(&pool).await.unwrap();
+    let err = users::authenticate(&pool, "op@example.com", "secreto123").await.unwrap_err();
+    assert!(matches!(err, AppError::Unauthorized { .. }), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn duplicate_email_and_short_password_are_rejected() {
+    let pool = memory_pool().await;
+    create_user(&pool, "a@example.com", "viewer").await;
+
+    let err = users::create(
+        &pool,
+        NewUser {
+            email: "A@example.com".into(),
+            full_name: "Otro".into(),
+            password: "secreto123".into(),
+            role: "viewer".into(),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AppError::Conflict { .. }), "got {:?}", err);
+
+    let err = users::create(
+        &pool,
+        NewUser {
+            email: "b@example.com".into(),
+            full_name: "Otro".into(),
+            password: "corta".into(),
+            role: "viewer".into(),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "password"), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn change_password_requires_the_current_one() {
+    let pool = memory_pool().await;
+    let id = create_user(&pool, "a@example.com", "admin").await;
+
+    let err = users::change_password(&pool, id, "incorrecta", "nueva-clave-1").await.unwrap_err();
+    assert!(matches!(err, AppError::Validation { ref field, .. } if field == "current_password"), "got {:?}", err);
+
+    users::change_password(&pool, id, "secreto123", "nueva-clave-1").await.unwrap();
+    assert!(users::authenticate(&pool, "a@example.com", "secreto123").await.is_err());
+    users::authenticate(&pool, "a@example.com", "nueva-clave-1").await.unwrap();
+}
+
+#[tokio::test]
+async fn session_enforces_role_hierarchy() {
+    let pool = memory_pool().await;
+    let session = Session::default();
+    let err = session.require_role(Role::Viewer).unwrap_err();
+    assert!(matches!(err, AppError::Unauthorized { .. }), "got {:?}", err);
+
+    create_user(&pool, "op@example.com", "operator").await;
+    session.set(Some(users::authenticate(&pool, "op@example.com", "secreto123").await.unwrap()));
+    session.require_role(Role::Viewer).unwrap();
+    session.require_role(Role::Operator).unwrap();
+    let err = session.require_role(Role::Admin).unwrap_err();
+    assert!(matches!(err, AppError::Forbidden { .. }), "got {:?}", err);
+    assert_eq!(err.code(), "Forbidden");
+
+    create_user(&pool, "v@example.com", "viewer").await;
+    session.set(Some(users::authenticate(&pool, "v@example.com", "secreto123").await.unwrap()));
+    assert!(session.require_role(Role::Operator).is_err());
+
+    assert_eq!(Role::parse("desconocido"), Role::Viewer);
+}
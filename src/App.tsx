@@ -9,8 +9,12 @@ import { ExportManagement } from './components/ExportManagement'
 import { SettingsManagement } from './components/SettingsManagement'
 import { CaptureManagement } from './components/CaptureManagement'
 import { ActivityLogView } from './components/ActivityLogView'
+import { LoginScreen } from './components/LoginScreen'
+import { LicenseBanner } from './components/LicenseBanner'
 import { Toaster } from './components/ui/sonner'
-import { useState } from 'react'
+import { useEffect, useState } from 'react'
+import { authStatus, licenseStatus, logout, type LicenseStatus, type User } from './lib/api'
+import { SessionContext } from './lib/session'
 
 /**
  * Roping Manager — Aplicación principal
@@ -28,9 +32,42 @@ import { useState } from 'react'
  * - payoffs: Cálculo de premios
  * - exportar: Exportación de reportes
  * - settings: Configuración general
+ *
+ * Sin sesión se muestra LoginScreen (o la creación del primer admin).
+ * Sin licencia vigente, LicenseBanner avisa que la app está en sólo lectura.
  */
 export default function App() {
   const [activeMenuItem, setActiveMenuItem] = useState('dashboard')
+  // undefined = todavía consultando al backend
+  const [user, setUser] = useState<User | null | undefined>(undefined)
+  const [hasUsers, setHasUsers] = useState(true)
+  const [license, setLicense] = useState<LicenseStatus | null>(null)
+
+  useEffect(() => {
+    licenseStatus().then(setLicense).catch(console.error)
+  }, [])
+
+  useEffect(() => {
+    authStatus()
+      .then((status) => {
+        setHasUsers(status.has_users)
+        setUser(status.user)
+      })
+      .catch((error) => {
+        console.error(error)
+        setUser(null)
+      })
+  }, [])
+
+  const signOut = async () => {
+    try {
+      await logout()
+    } finally {
+      setUser(null)
+      setHasUsers(true)
+      setActiveMenuItem('dashboard')
+    }
+  }
 
   // Maneja la navegación entre vistas principales
   const handleMenuItemClick = (item: string) => {
@@ -65,16 +102,34 @@ export default function App() {
     }
   }
 
+  if (user === undefined) {
+    return <div className="h-screen w-screen bg-background" />
+  }
+
+  if (user === null) {
+    return (
+      <div className="h-screen w-screen bg-background text-foreground">
+        <LoginScreen hasUsers={hasUsers} onLoggedIn={setUser} />
+        <Toaster richColors position="top-right" />
+      </div>
+    )
+  }
+
   return (
-    <div className="h-screen w-screen flex bg-background text-foreground">
-      {/* Sidebar de navegación principal */}
-      <Sidebar activeItem={activeMenuItem} onItemClick={handleMenuItemClick} />
+    <SessionContext.Provider value={{ user, signOut, license, setLicense }}>
+      <div className="h-screen w-screen flex bg-background text-foreground">
+        {/* Sidebar de navegación principal */}
+        <Sidebar activeItem={activeMenuItem} onItemClick={handleMenuItemClick} />
 
-  {/* Contenido principal: cada vista administra su propio scroll para centro y panel derecho */}
-  <main className="flex-1 min-h-0">{renderContent()}</main>
+    {/* Contenido principal: cada vista administra su propio scroll para centro y panel derecho */}
+    <main className="flex-1 min-h-0 flex flex-col">
+      {license && <LicenseBanner status={license} onActivate={() => setActiveMenuItem('settings')} />}
+      <div className="flex-1 min-h-0">{renderContent()}</div>
+    </main>
 
-      {/* Sistema de notificaciones toast */}
-      <Toaster richColors position="top-right" />
-    </div>
+        {/* Sistema de notificaciones toast */}
+        <Toaster richColors position="top-right" />
+      </div>
+    </SessionContext.Provider>
   )
 }
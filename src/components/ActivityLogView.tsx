@@ -1,6 +1,7 @@
 import React, { useEffect, useState } from 'react'
 import { ArrowLeft, CheckCircle2, Settings, Download, DollarSign, Trash2, Edit, Plus, Loader2 } from 'lucide-react'
 import { Button } from './ui/button'
+import { UsageActivityPanel } from './UsageActivityPanel'
 import { getRecentActivity } from '@/lib/api'
 import type { AuditLogItem } from '@/types'
 import { formatDistanceToNow } from 'date-fns'
@@ -37,6 +38,7 @@ export function ActivityLogView({ onBack }: ActivityLogViewProps) {
   const [loading, setLoading] = useState(true)
   const [offset, setOffset] = useState(0)
   const [hasMore, setHasMore] = useState(true)
+  const [view, setView] = useState<'changes' | 'usage'>('changes')
   const LIMIT = 50
 
   const loadMore = async () => {
@@ -69,11 +71,16 @@ export function ActivityLogView({ onBack }: ActivityLogViewProps) {
           <h1 className="text-2xl font-semibold text-foreground">Registro de Actividad</h1>
           <p className="text-sm text-muted-foreground">Historial completo de acciones en el sistema</p>
         </div>
+        <div className="ml-auto flex gap-2">
+          <Button variant={view === 'changes' ? 'default' : 'outline'} size="sm" onClick={() => setView('changes')}>Cambios</Button>
+          <Button variant={view === 'usage' ? 'default' : 'outline'} size="sm" onClick={() => setView('usage')}>Uso y sesiones</Button>
+        </div>
       </div>
 
       <div className="flex-1 overflow-y-auto p-6">
         <div className="max-w-3xl mx-auto space-y-4">
-          {items.map((a) => {
+          {view === 'usage' && <UsageActivityPanel />}
+          {view === 'changes' && items.map((a) => {
             const { icon, tone, message } = mapActionToIconAndTone(a.action)
             const displayMessage = a.metadata ? `${message}: ${a.metadata}` : `${message} ${a.entity_type}`
             
@@ -88,23 +95,32 @@ export function ActivityLogView({ onBack }: ActivityLogViewProps) {
                     </span>
                   </div>
                   <p className="text-sm text-muted-foreground mt-1">
-                    {formatDistanceToNow(new Date(a.created_at), { addSuffix: true, locale: es })}
+                    {a.user_name ?? 'Sistema'} · {formatDistanceToNow(new Date(a.created_at), { addSuffix: true, locale: es })}
                   </p>
                   <div className="mt-2 text-xs font-mono text-muted-foreground bg-muted/50 p-1 rounded w-fit">
                     ID: {a.id} • {a.action} • {a.entity_type} #{a.entity_id}
                   </div>
+                  {(a.before_json || a.after_json) && (
+                    <details className="mt-2 text-xs">
+                      <summary className="cursor-pointer text-muted-foreground">Ver cambios</summary>
+                      <div className="mt-1 grid grid-cols-2 gap-2 font-mono">
+                        <pre className="bg-muted/50 p-2 rounded whitespace-pre-wrap break-all">{a.before_json ?? '—'}</pre>
+                        <pre className="bg-muted/50 p-2 rounded whitespace-pre-wrap break-all">{a.after_json ?? '—'}</pre>
+                      </div>
+                    </details>
+                  )}
                 </div>
               </div>
             )
           })}
 
-          {loading && (
+          {view === 'changes' && loading && (
             <div className="flex justify-center py-8">
               <Loader2 className="size-8 animate-spin text-primary" />
             </div>
           )}
 
-          {!loading && hasMore && (
+          {view === 'changes' && !loading && hasMore && (
             <div className="flex justify-center pt-4">
               <Button onClick={loadMore} variant="outline">
                 Cargar más actividad
@@ -112,7 +128,7 @@ export function ActivityLogView({ onBack }: ActivityLogViewProps) {
             </div>
           )}
 
-          {!loading && items.length === 0 && (
+          {view === 'changes' && !loading && items.length === 0 && (
             <div className="text-center py-12 text-muted-foreground">
               No hay actividad registrada.
             </div>
@@ -1,7 +1,7 @@
 import { useState, useEffect, useCallback } from 'react'
 import {
   Play, Pause, RotateCcw, Save, ChevronLeft, ChevronRight, X, Clock,
-  CheckCircle2, Activity, Lock, Users,
+  CheckCircle2, Activity, Lock, Users, History,
 } from 'lucide-react'
 import { Button } from './ui/button'
 import { Input } from './ui/input'
@@ -17,7 +17,7 @@ import {
 } from './ui/table'
 import { Tabs, TabsContent, TabsList, TabsTrigger } from './ui/tabs'
 import { toast } from 'sonner'
-import { getRunsExpanded, saveRun, getStandings, updateEventStatus, generateDraw } from '../lib/api'
+import { getRunsExpanded, saveRun, getStandings, updateEventStatus, generateDraw, isCommandError, staleRow, listPenaltyTypes, type PenaltyType } from '../lib/api'
 import {
   AlertDialog,
   AlertDialogAction,
@@ -29,6 +29,9 @@ import {
   AlertDialogTitle,
 } from "./ui/alert-dialog"
 
+import { TimerPanel } from './TimerPanel'
+import { RunHistoryDialog } from './RunHistoryDialog'
+import { UndoRedoControls } from './UndoRedoControls'
 import type { Event, Team as TeamType, Run as RunType } from '../types'
 
 interface CaptureRunsTabProps {
@@ -43,6 +46,7 @@ interface RoundResult {
   team: TeamType
   time: number | null
   penalty: number
+  penalties: RunType['penalties']
   total: number | null
   status: 'valid' | 'penalty' | 'nt' | 'dq'
 }
@@ -62,7 +66,11 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
   const [selectedTeamIndex, setSelectedTeamIndex] = useState<number | null>(null)
   const [timerRunning, setTimerRunning] = useState(false)
   const [timerValue, setTimerValue] = useState(0)
-  const [penalty, setPenalty] = useState('0')
+  const [penaltyTypes, setPenaltyTypes] = useState<PenaltyType[]>([])
+  // Una entrada por penalización (el mismo tipo puede repetirse)
+  const [penaltyIds, setPenaltyIds] = useState<number[]>([])
+  // Penalización suelta (sin tipo) de una corrida vieja que se está corrigiendo
+  const [legacyPenalty, setLegacyPenalty] = useState(0)
   const [noTime, setNoTime] = useState(false)
   const [dq, setDq] = useState(false)
   const [isManualMode, setIsManualMode] = useState(false)
@@ -71,6 +79,8 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
   const [isConfirmOpen, setIsConfirmOpen] = useState(false)
   const [inputPin, setInputPin] = useState('')
   const [pinError, setPinError] = useState(false)
+  const [correctionReason, setCorrectionReason] = useState('')
+  const [historyRun, setHistoryRun] = useState<RunType | null>(null)
 
   const totalRounds = event?.rounds ?? 3
   const currentRun = selectedTeamIndex !== null ? runs[selectedTeamIndex] : null
@@ -80,7 +90,8 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
     try {
       const data = await getRunsExpanded(Number(event.id), Number(selectedRound))
       
-      const filtered = data.filter((r: any) => r.status !== 'skipped');
+      // Los scratch con 'keep_slot' se quedan a la vista: su lugar sigue en el orden
+      const filtered = data.filter((r: any) => r.status !== 'skipped' || r.scratched);
 
       const mapped: RunType[] = filtered.map((r: any) => ({
         id: String(r.id),
@@ -94,9 +105,13 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
         position: r.position,
         time: r.time_sec,
         penalty: r.penalty,
+        penalties: (r.penalties ?? []).map((p: any) => ({ typeId: p.penalty_type_id, label: p.label, seconds: p.seconds })),
         noTime: !!r.no_time,
         dq: !!r.dq,
-        status: r.status === 'completed' ? 'completed' : 'pending',
+        status: r.status === 'completed' ? 'completed' : r.scratched ? 'skipped' : 'pending',
+        scratched: !!r.scratched,
+        revisions: r.revisions ?? 0,
+        version: r.version,
       }))
       setRuns(mapped)
     } catch (error) {
@@ -105,6 +120,16 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
     }
   }, [event?.id, selectedRound])
 
+  useEffect(() => {
+    if (!event?.id) return
+    listPenaltyTypes(Number(event.id))
+      .then(setPenaltyTypes)
+      .catch((error) => console.error('Error fetching penalty types:', error))
+  }, [event?.id])
+
+  const penaltyTotal = (penaltyIds.length === 0 ? legacyPenalty : 0)
+    + penaltyIds.reduce((sum, id) => sum + (penaltyTypes.find((t) => t.id === id)?.seconds ?? 0), 0)
+
   const fetchStandingsData = useCallback(async () => {
     if (!event?.id) return
     try {
@@ -205,7 +230,7 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
     }
     window.addEventListener('keydown', handleKey)
     return () => window.removeEventListener('keydown', handleKey)
-  }, [currentRun, timerRunning, timerValue, penalty, noTime, dq])
+  }, [currentRun, timerRunning, timerValue, penaltyIds, legacyPenalty, noTime, dq])
 
   const formatTime = (ms: number) => {
     const m = Math.floor(ms / 60000)
@@ -249,6 +274,7 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
   }
 
   const handleSelectTeam = (index: number) => {
+    if (runs[index]?.scratched) return
     setSelectedTeamIndex(index)
     const run = runs[index]
     // If run is completed, load its data? 
@@ -262,7 +288,9 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
           setTimerValue(run.time * 1000)
           setManualTimeInput(run.time.toFixed(3))
         }
-        setPenalty(String(run.penalty))
+        const items = run.penalties ?? []
+        setPenaltyIds(items.flatMap((p) => (p.typeId !== null ? [p.typeId] : [])))
+        setLegacyPenalty(items.filter((p) => p.typeId === null).reduce((sum, p) => sum + p.seconds, 0))
         setNoTime(run.noTime)
         setDq(run.dq)
     }
@@ -273,7 +301,8 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
   const handleReset = () => {
     setTimerRunning(false)
     setTimerValue(0)
-    setPenalty('0')
+    setPenaltyIds([])
+    setLegacyPenalty(0)
     setNoTime(false)
     setDq(false)
     setManualTimeInput('')
@@ -290,6 +319,7 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
     if (currentRun.status === 'completed') {
       setInputPin('')
       setPinError(false)
+      setCorrectionReason('')
       setIsConfirmOpen(true)
       return
     }
@@ -297,7 +327,8 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
     await performSave()
   }
 
-  const performSave = async () => {
+  // `pin` sólo al sobrescribir una corrida completada; lo valida el backend
+  const performSave = async (pin?: string) => {
     if (!currentRun || !event?.id) return
     
     // Calcular tiempo según el modo
@@ -317,7 +348,8 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
       timeInSeconds = timerValue / 1000
     }
     
-    const penaltyValue = parseFloat(penalty) || 0
+    // Una corrida vieja con penalización suelta la conserva mientras no se le pongan tipos
+    const keepLegacy = legacyPenalty > 0 && penaltyIds.length === 0
 
     try {
         await saveRun({
@@ -326,10 +358,12 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
             round: currentRun.round,
             position: currentRun.position,
             time_sec: (noTime || dq) ? null : timeInSeconds,
-            penalty: penaltyValue,
+            ...(keepLegacy ? { penalty: legacyPenalty } : { penalties: penaltyIds }),
             no_time: noTime,
             dq: dq,
-            captured_by: null // TODO: Add user ID if auth exists
+            pin: pin ?? null,
+            reason: currentRun.status === 'completed' ? correctionReason.trim() || null : null,
+            expected_version: currentRun.version ?? null,
         })
 
         // Lock event if not locked yet
@@ -352,21 +386,28 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
         
         setIsConfirmOpen(false)
     } catch (error) {
+        if (isCommandError(error, 'Validation') && error.field === 'pin') {
+          setPinError(true)
+          return
+        }
+        // Otra ventana (o el cronómetro) guardó esta corrida mientras tanto: mostrar lo que quedó
+        const current = staleRow<{ status: string; total_sec: number | null; no_time: number; dq: number }>(error)
+        if (current) {
+          const result = current.dq ? 'DQ' : current.no_time ? 'NT' : current.total_sec !== null ? `${current.total_sec.toFixed(3)}s` : 'pendiente'
+          toast.error('La corrida cambió antes de guardar', { description: `Ahora está ${result}. Revisa y vuelve a guardar.` })
+          setIsConfirmOpen(false)
+          await fetchRuns()
+          return
+        }
         console.error('Error saving run:', error)
         toast.error('Error al guardar el run')
     }
   }
 
   const handleConfirmOverwrite = (e: React.MouseEvent) => {
-    // If PIN is required
-    if (event.adminPin) {
-      if (inputPin !== event.adminPin) {
-        e.preventDefault()
-        setPinError(true)
-        return
-      }
-    }
-    performSave()
+    // El diálogo se cierra en performSave sólo si el backend acepta el PIN
+    e.preventDefault()
+    performSave(event.hasAdminPin ? inputPin : undefined)
   }
 
   const handleNext = () => {
@@ -394,7 +435,7 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
       else if (run.noTime) status = 'nt'
       else if (run.penalty > 0) status = 'penalty'
       const total = run.time !== null ? run.time + run.penalty : null
-      return { position: i + 1, team: run.team, time: run.time, penalty: run.penalty, total, status }
+      return { position: i + 1, team: run.team, time: run.time, penalty: run.penalty, penalties: run.penalties, total, status }
     })
     .sort((a, b) => {
       if (a.status === 'dq' || a.status === 'nt') return 1
@@ -406,6 +447,23 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
     // Re-assign position based on sort
     .map((r, i) => ({ ...r, position: i + 1 }))
 
+  // Tiempo confirmado desde el timer: mismo cierre que un guardado manual
+  const handleTimerSaved = async () => {
+    if (!isLocked && event?.id) {
+      await updateEventStatus(Number(event.id), 'locked')
+      onLock()
+    }
+    await fetchRuns()
+    fetchStandingsData()
+  }
+
+  // Deshacer/rehacer también mueve rondas siguientes: se recarga todo
+  const handleUndoChanged = async () => {
+    handleCloseCapture()
+    await fetchRuns()
+    fetchStandingsData()
+  }
+
   const handleRecaptureClick = (index: number) => {
     // Just recapture directly
     handleSelectTeam(index)
@@ -440,6 +498,22 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
                 </Badge>
              )}
         </div>
+        {event?.id && (
+          <div className="flex items-center gap-2">
+            <UndoRedoControls
+              eventId={Number(event.id)}
+              hasAdminPin={!!event.hasAdminPin}
+              refreshKey={runs}
+              onChanged={handleUndoChanged}
+            />
+            <TimerPanel
+              eventId={Number(event.id)}
+              round={Number(selectedRound)}
+              captureOpen={currentRun !== null}
+              onSaved={handleTimerSaved}
+            />
+          </div>
+        )}
       </div>
 
       <div className="flex-1 flex gap-6 overflow-hidden min-h-0">
@@ -494,7 +568,9 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
                       )}
                     </TableCell>
                     <TableCell className="text-right">
-                      {run.status === 'completed' ? (
+                      {run.scratched ? (
+                        <span className="text-xs font-medium text-muted-foreground">SCRATCH</span>
+                      ) : run.status === 'completed' ? (
                         <Button
                           size="sm"
                           variant="ghost"
@@ -523,6 +599,21 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
                           Capturar
                         </Button>
                       )}
+                      {!run.scratched && (run.revisions ?? 0) > 1 && (
+                        <Button
+                          size="icon"
+                          variant="ghost"
+                          onClick={(e) => {
+                            e.stopPropagation()
+                            setHistoryRun(run)
+                          }}
+                          className="h-7 w-7 text-muted-foreground"
+                          aria-label="Historial de la corrida"
+                          title={`${run.revisions} versiones`}
+                        >
+                          <History className="w-3.5 h-3.5" />
+                        </Button>
+                      )}
                     </TableCell>
                   </TableRow>
                 ))}
@@ -668,18 +759,42 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
               {/* Validation Inputs */}
               <div className="grid grid-cols-1 md:grid-cols-3 gap-6 p-6 bg-muted/30 rounded-xl border border-border/50">
                   <div className="space-y-2">
-                    <Label htmlFor="penalty" className="text-foreground font-medium">Penalización (s)</Label>
-                    <div className="relative">
-                        <Input
-                        id="penalty"
-                        type="number"
-                        step="1"
-                        value={penalty}
-                        onChange={(e) => setPenalty(e.target.value)}
-                        placeholder="0"
-                        className="bg-background border-border h-12 text-lg font-mono ps-4"
-                        />
-                        <span className="absolute right-3 top-1/2 -translate-y-1/2 text-muted-foreground text-sm font-medium">sec</span>
+                    <Label className="text-foreground font-medium">
+                      Penalizaciones {penaltyTotal > 0 && <span className="text-amber-600 font-mono">+{penaltyTotal}s</span>}
+                    </Label>
+                    <div className="flex flex-wrap gap-2">
+                      {penaltyTypes.map((t) => (
+                        <Button
+                          key={t.id}
+                          type="button"
+                          variant="outline"
+                          size="sm"
+                          onClick={() => setPenaltyIds((ids) => [...ids, t.id])}
+                          className="border-border"
+                        >
+                          {t.label} +{t.seconds}s
+                        </Button>
+                      ))}
+                    </div>
+                    <div className="flex flex-wrap gap-1">
+                      {legacyPenalty > 0 && penaltyIds.length === 0 && (
+                        <Badge variant="outline" className="text-muted-foreground">Sin tipo +{legacyPenalty}s</Badge>
+                      )}
+                      {penaltyIds.map((id, idx) => {
+                        const t = penaltyTypes.find((pt) => pt.id === id)
+                        return (
+                          <Badge key={`${id}-${idx}`} className="bg-amber-50 text-amber-700 border-amber-200 hover:bg-amber-100 gap-1">
+                            {t?.label ?? `#${id}`} +{t?.seconds ?? 0}s
+                            <button
+                              type="button"
+                              aria-label="Quitar penalización"
+                              onClick={() => setPenaltyIds((ids) => ids.filter((_, i) => i !== idx))}
+                            >
+                              <X className="w-3 h-3" />
+                            </button>
+                          </Badge>
+                        )
+                      })}
                     </div>
                   </div>
                   
@@ -845,7 +960,14 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
                           {r.time !== null ? r.time.toFixed(2) + 's' : '—'}
                         </TableCell>
                         <TableCell className="text-right font-mono text-foreground/80">
-                          {r.penalty > 0 ? <span className="text-amber-600 font-bold">+{r.penalty}</span> : '—'}
+                          {r.penalty > 0 ? (
+                            <span
+                              className="text-amber-600 font-bold"
+                              title={(r.penalties ?? []).map((p) => `${p.label} +${p.seconds}`).join('; ')}
+                            >
+                              +{r.penalty}
+                            </span>
+                          ) : '—'}
                         </TableCell>
                         <TableCell className="text-right font-mono font-medium text-lg">
                           {r.total !== null ? (
@@ -924,13 +1046,13 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
             <AlertDialogTitle>¿Sobrescribir resultado?</AlertDialogTitle>
             <AlertDialogDescription>
               Este equipo ya tiene un tiempo registrado. 
-              {event.adminPin 
+              {event.hasAdminPin 
                 ? " Ingresa el PIN de administrador para confirmar la sobrescritura." 
                 : " ¿Estás seguro de que deseas guardar este nuevo resultado y sobrescribir el anterior?"}
             </AlertDialogDescription>
           </AlertDialogHeader>
           
-          {event.adminPin && (
+          {event.hasAdminPin && (
             <div className="py-2">
                <Label htmlFor="pin-confirm">PIN de Administrador</Label>
                <Input 
@@ -949,12 +1071,29 @@ export function CaptureRunsTab({ event, isLocked, onLock }: CaptureRunsTabProps)
             </div>
           )}
 
+          <div className="py-2">
+            <Label htmlFor="correction-reason">Motivo (opcional)</Label>
+            <Input
+              id="correction-reason"
+              value={correctionReason}
+              onChange={(e) => setCorrectionReason(e.target.value)}
+              placeholder="Protesta, error de captura…"
+              maxLength={200}
+            />
+          </div>
+
           <AlertDialogFooter>
             <AlertDialogCancel>Cancelar</AlertDialogCancel>
             <AlertDialogAction onClick={handleConfirmOverwrite}>Confirmar</AlertDialogAction>
           </AlertDialogFooter>
         </AlertDialogContent>
       </AlertDialog>
+
+      <RunHistoryDialog
+        runId={historyRun ? Number(historyRun.id) : null}
+        title={historyRun ? `Ronda ${historyRun.round} · #${historyRun.position} ${historyRun.team.header} & ${historyRun.team.heeler}` : ''}
+        onClose={() => setHistoryRun(null)}
+      />
     </div>
   )
 }
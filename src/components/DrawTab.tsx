@@ -1,5 +1,5 @@
 import { useState, useEffect } from 'react'
-import { Shuffle, CheckCircle, AlertTriangle, Eye } from 'lucide-react'
+import { Shuffle, CheckCircle, AlertTriangle, Eye, ShieldCheck, ArrowUp, ArrowDown, UserPlus, ClipboardCheck } from 'lucide-react'
 import { Button } from './ui/button'
 import { Input } from './ui/input'
 import { Label } from './ui/label'
@@ -12,7 +12,10 @@ import {
   Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription,
 } from './ui/dialog'
 import { toast } from 'sonner'
-import { getRunsExpanded, generateDrawBatch, generateDraw } from '../lib/api'
+import {
+  getRunsExpanded, generateDrawBatch, generateDraw, verifyDraw, getDrawReport, listTeams, swapDrawPositions, moveDrawTeam, insertDrawTeam, scratchTeam,
+  isCommandError, type DrawSummary, type DrawVerification, type DrawReport,
+} from '../lib/api'
 
 interface DrawTabProps {
   event: any
@@ -26,16 +29,24 @@ interface DrawEntry {
   header: string
   heeler: string
   status: string
+  scratched: boolean
 }
 
 export function DrawTab({ event, isLocked }: DrawTabProps) {
   const [rounds, setRounds] = useState<string>(String(event?.rounds ?? 3))
   const [autoBalance, setAutoBalance] = useState(true)
+  const [minSpacing, setMinSpacing] = useState('2')
   const [drawGenerated, setDrawGenerated] = useState(false)
   const [drawEntries, setDrawEntries] = useState<DrawEntry[]>([])
   const [showExclusionsModal, setShowExclusionsModal] = useState(false)
   const [loading, setLoading] = useState(false)
   const [viewRound, setViewRound] = useState<number | 'all'>('all')
+  const [verification, setVerification] = useState<DrawVerification | null>(null)
+  const [report, setReport] = useState<DrawReport | null>(null)
+  const [eventTeams, setEventTeams] = useState<any[]>([])
+  const [lateTeamId, setLateTeamId] = useState('')
+  const [latePosition, setLatePosition] = useState('')
+  const [editPin, setEditPin] = useState<string | null>(null)
 
   const fetchDraw = async () => {
     if (!event?.id) return
@@ -49,7 +60,8 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
           teamId: r.team_id,
           header: r.header_name,
           heeler: r.heeler_name,
-          status: r.status
+          status: r.status,
+          scratched: !!r.scratched,
         }))
         setDrawEntries(mapped)
         setDrawGenerated(true)
@@ -65,23 +77,96 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
 
   useEffect(() => {
     fetchDraw()
+    if (event?.id) {
+      listTeams(Number(event.id)).then(setEventTeams).catch(() => setEventTeams([]))
+    }
   }, [event?.id])
 
+  // Las ediciones manuales piden el PIN del evento; se pregunta una vez y se recuerda en la pestaña.
+  const runEdit = async (edit: (pin: string | null) => Promise<unknown>, success: string) => {
+    try {
+      try {
+        await edit(editPin)
+      } catch (error) {
+        if (!(isCommandError(error, 'Validation') && error.field === 'pin')) throw error
+        const pin = window.prompt('PIN de administrador del evento:')
+        if (pin === null) return
+        await edit(pin)
+        setEditPin(pin)
+      }
+      toast.success(success)
+      await fetchDraw()
+    } catch (error) {
+      toast.error(isCommandError(error) ? error.message : 'Error: ' + String(error))
+    }
+  }
+
+  const handleSwap = (round: number, a: number, b: number) =>
+    runEdit((pin) => swapDrawPositions({ event_id: Number(event.id), round, position_a: a, position_b: b, pin }), `Posiciones ${a} y ${b} intercambiadas`)
+
+  const handleMoveTo = (entry: DrawEntry) => {
+    const to = parseInt(window.prompt(`Mover el equipo #${entry.teamId} a la posición:`, String(entry.position)) ?? '')
+    if (Number.isNaN(to) || to === entry.position) return
+    runEdit((pin) => moveDrawTeam({ event_id: Number(event.id), round: entry.round, team_id: entry.teamId, to_position: to, pin }), `Equipo movido a la posición ${to}`)
+  }
+
+  // Scratch: el equipo sale del evento; su lugar se cierra o se conserva según el evento.
+  const handleScratch = async (entry: DrawEntry) => {
+    if (!window.confirm(`¿Retirar (scratch) a ${entry.header} / ${entry.heeler} de las rondas que no ha corrido?`)) return
+    const reason = window.prompt('Motivo (opcional):') ?? null
+    try {
+      const outcome = await scratchTeam(entry.teamId, reason)
+      toast.success(
+        outcome.policy === 'keep_slot'
+          ? `Equipo retirado; su lugar queda vacío en ${outcome.rounds.length} ronda(s)`
+          : `Equipo retirado de ${outcome.rounds.length} ronda(s); orden recorrido`,
+      )
+      await fetchDraw()
+    } catch (error) {
+      toast.error(isCommandError(error) ? error.message : 'Error: ' + String(error))
+    }
+  }
+
+  const handleInsertLate = (round: number) => {
+    if (!lateTeamId) return
+    const position = parseInt(latePosition)
+    runEdit(
+      (pin) => insertDrawTeam({ event_id: Number(event.id), round, team_id: Number(lateTeamId), position: Number.isNaN(position) ? null : position, pin }),
+      'Equipo agregado a la ronda',
+    ).then(() => {
+      setLateTeamId('')
+      setLatePosition('')
+    })
+  }
+
+  const spacingValue = () => {
+    const n = parseInt(minSpacing)
+    return Number.isNaN(n) ? null : n
+  }
+
+  const warnViolations = (summary: DrawSummary) => {
+    if (summary.violations > 0) {
+      toast.warning(`${summary.violations} salida(s) no respetan la separación de ${summary.min_spacing} corridas: hay ropers con demasiadas inscripciones.`)
+    }
+  }
+
   const handleGenerateBatch = async () => {
     if (!event?.id) return
     setLoading(true)
     try {
       const numRounds = parseInt(rounds) || 3
-      await generateDrawBatch({
+      const summary = await generateDrawBatch({
         event_id: Number(event.id),
         rounds: numRounds,
-        shuffle: autoBalance
+        shuffle: autoBalance,
+        min_spacing: spacingValue()
       })
       if (numRounds > 1) {
         toast.success(`¡Rondas 1 a ${numRounds - 1} generadas! La ronda ${numRounds} (final) debe generarse después de completar las rondas anteriores.`)
       } else {
         toast.success('¡Draw generado exitosamente!')
       }
+      warnViolations(summary)
       await fetchDraw()
     } catch (error) {
       console.error('Error generating batch draw:', error)
@@ -100,11 +185,12 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
       
       // Si es una ronda > 1, es crítico que la anterior esté "completa" en teoría, 
       // pero el usuario sabe lo que hace. El backend filtrará los eliminados.
-      await generateDraw({
+      const summary = await generateDraw({
         event_id: Number(event.id),
         round: roundNumber,
         reseed: autoBalance,
-        seed_runs: true
+        seed_runs: true,
+        min_spacing: spacingValue()
       })
       
       if (isFinalRound) {
@@ -112,6 +198,7 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
       } else {
         toast.success(`¡Ronda ${roundNumber} generada! Se han excluido equipos eliminados.`)
       }
+      warnViolations(summary)
       await fetchDraw()
     } catch (error) {
       console.error(`Error generating round ${roundNumber}:`, error)
@@ -121,6 +208,24 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
     }
   }
 
+  const handleVerifyRound = async (roundNumber: number) => {
+    if (!event?.id) return
+    try {
+      setVerification(await verifyDraw(Number(event.id), roundNumber))
+    } catch (error) {
+      toast.error('No se pudo verificar: ' + String(error))
+    }
+  }
+
+  const handleReport = async () => {
+    if (!event?.id) return
+    try {
+      setReport(await getDrawReport(Number(event.id)))
+    } catch (error) {
+      toast.error(isCommandError(error) ? error.message : 'No se pudo revisar el draw: ' + String(error))
+    }
+  }
+
   const entriesByRound = drawEntries.reduce((acc, curr) => {
     if (!acc[curr.round]) acc[curr.round] = []
     acc[curr.round].push(curr)
@@ -130,6 +235,12 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
   const numRoundsConfig = parseInt(rounds) || 3
   const roundList = Array.from({length: numRoundsConfig}, (_, i) => i + 1)
   
+  const editableRound = viewRound !== 'all' && !isLocked ? viewRound : null
+  const roundEntries = editableRound ? entriesByRound[editableRound] ?? [] : []
+  const lateCandidates = eventTeams.filter(
+    (t) => t.status === 'active' && !roundEntries.some((e) => e.teamId === Number(t.id)),
+  )
+
   const teamsIncluded = drawEntries.length ? new Set(drawEntries.map(d => d.teamId)).size : 0
   const teamsExcluded = 0
   const spacingPercent = 95
@@ -158,6 +269,18 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
                 disabled={isLocked || loading}
                 className="bg-muted border-border"
               />
+            </div>
+            <div className="space-y-2 w-40">
+              <Label className="text-foreground">Separación mínima</Label>
+              <Input
+                type="number"
+                min={0}
+                value={minSpacing}
+                onChange={(e) => setMinSpacing(e.target.value)}
+                disabled={isLocked || loading}
+                className="bg-muted border-border"
+                title="Corridas de otros equipos entre dos salidas del mismo roper"
+              />
             </div>
              <div className="flex items-center space-x-2 pb-3">
                 <Checkbox
@@ -232,7 +355,7 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
                               disabled={loading}
                               onClick={() => {
                                   const msg = isFinalRound 
-                                     ? `¿Regenerar Ronda Final ${r}? Los ropers se ordenarán de mayor a menor tiempo acumulado.`
+                                     ? `¿Regenerar Ronda Final ${r}? El orden sigue la configuración de la final del evento (short-go u orden de la final).`
                                      : `¿Regenerar Ronda ${r}? Esto borrará los tiempos actuales de esta ronda y excluirá equipos eliminados en rondas previas.`
                                   if(window.confirm(msg)) {
                                       handleGenerateRound(r)
@@ -250,6 +373,14 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
                         >
                            {viewRound === r ? 'Ver Todos' : 'Ver Lista'}
                         </Button>
+                        <Button
+                           variant="ghost"
+                           size="sm"
+                           className="w-full text-xs h-7"
+                           onClick={() => handleVerifyRound(r)}
+                        >
+                           <ShieldCheck className="w-3 h-3 mr-1" /> Verificar semilla
+                        </Button>
                      </div>
                   ) : (
                      <Button 
@@ -343,6 +474,38 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
                   </Button>
                )}
             </div>
+            {editableRound && lateCandidates.length > 0 && (
+               <div className="p-4 border-b border-border flex flex-wrap items-end gap-3">
+                  <div className="space-y-1">
+                     <Label className="text-xs text-muted-foreground">Inscripción tardía</Label>
+                     <select
+                        className="h-9 rounded-md border border-border bg-background px-2 text-sm"
+                        value={lateTeamId}
+                        onChange={(e) => setLateTeamId(e.target.value)}
+                     >
+                        <option value="">Selecciona un equipo…</option>
+                        {lateCandidates.map((t) => (
+                           <option key={t.id} value={t.id}>
+                              Team #{t.id}
+                           </option>
+                        ))}
+                     </select>
+                  </div>
+                  <div className="space-y-1 w-28">
+                     <Label className="text-xs text-muted-foreground">Posición</Label>
+                     <Input
+                        type="number"
+                        min={1}
+                        placeholder="Al final"
+                        value={latePosition}
+                        onChange={(e) => setLatePosition(e.target.value)}
+                     />
+                  </div>
+                  <Button size="sm" variant="outline" disabled={!lateTeamId} onClick={() => handleInsertLate(editableRound)}>
+                     <UserPlus className="w-4 h-4 mr-2" /> Agregar a la ronda
+                  </Button>
+               </div>
+            )}
             <Table>
               <TableHeader>
                 <TableRow className="bg-muted hover:bg-muted">
@@ -352,6 +515,7 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
                   <TableHead className="text-foreground">Header</TableHead>
                   <TableHead className="text-foreground">Heeler</TableHead>
                   <TableHead className="text-foreground">Status</TableHead>
+                  {editableRound && <TableHead className="text-foreground text-right">Editar</TableHead>}
                 </TableRow>
               </TableHeader>
               <TableBody>
@@ -377,24 +541,185 @@ export function DrawTab({ event, isLocked }: DrawTabProps) {
                         <Badge className="bg-emerald-50 text-emerald-700 border-emerald-200">
                           Completed
                         </Badge>
+                      ) : entry.scratched ? (
+                        <Badge className="bg-amber-50 text-amber-700 border-amber-200">
+                          Scratch
+                        </Badge>
                       ) : (
                         <Badge className="bg-muted text-muted-foreground border-border">
                           Pending
                         </Badge>
                       )}
                     </TableCell>
+                    {editableRound && (
+                      <TableCell className="text-right whitespace-nowrap">
+                        <Button
+                          size="icon"
+                          variant="ghost"
+                          disabled={entry.status === 'completed' || entry.position === 1}
+                          onClick={() => handleSwap(entry.round, entry.position - 1, entry.position)}
+                          title="Subir una posición"
+                        >
+                          <ArrowUp className="w-4 h-4" />
+                        </Button>
+                        <Button
+                          size="icon"
+                          variant="ghost"
+                          disabled={entry.status === 'completed' || entry.position === roundEntries.length}
+                          onClick={() => handleSwap(entry.round, entry.position, entry.position + 1)}
+                          title="Bajar una posición"
+                        >
+                          <ArrowDown className="w-4 h-4" />
+                        </Button>
+                        <Button
+                          size="sm"
+                          variant="ghost"
+                          disabled={entry.status === 'completed'}
+                          onClick={() => handleMoveTo(entry)}
+                        >
+                          Mover a…
+                        </Button>
+                        <Button
+                          size="sm"
+                          variant="ghost"
+                          disabled={entry.status === 'completed' || entry.scratched}
+                          onClick={() => handleScratch(entry)}
+                          className="text-amber-600 hover:text-amber-700"
+                        >
+                          Scratch
+                        </Button>
+                      </TableCell>
+                    )}
                   </TableRow>
                 ))}
               </TableBody>
             </Table>
           </div>
 
-          <div className="mt-4 text-muted-foreground">
+          <div className="mt-4 flex items-center justify-between text-muted-foreground">
             <p>Total entries: {drawEntries.length} • Average spacing: {spacingPercent}%</p>
+            <Button variant="outline" size="sm" onClick={handleReport}>
+              <ClipboardCheck className="w-4 h-4 mr-1" /> Revisar draw
+            </Button>
           </div>
         </>
       )}
 
+      {/* Verificación de la semilla */}
+      <Dialog open={verification !== null} onOpenChange={(open) => !open && setVerification(null)}>
+        <DialogContent className="sm:max-w-[520px]">
+          <DialogHeader>
+            <DialogTitle className="text-foreground">Verificación del sorteo · Ronda {verification?.round}</DialogTitle>
+            <DialogDescription>
+              El orden se vuelve a calcular con la semilla y las inscripciones guardadas al sortear.
+            </DialogDescription>
+          </DialogHeader>
+          {verification && (
+            <div className="mt-2 space-y-3 text-sm">
+              <div className={`p-3 rounded-xl border ${verification.matches ? 'bg-emerald-50 border-emerald-200 text-emerald-700' : 'bg-red-50 border-red-200 text-red-700'}`}>
+                {verification.message}
+              </div>
+              <div className="grid grid-cols-2 gap-2 text-muted-foreground">
+                <span>Semilla</span><span className="font-mono text-foreground">{verification.seed ?? '—'}</span>
+                <span>Método</span><span className="text-foreground">{verification.method}</span>
+                <span>Generador</span><span className="font-mono text-foreground">{verification.rng}</span>
+                <span>Sorteado</span><span className="text-foreground">{new Date(verification.generated_at).toLocaleString()}</span>
+                <span>Equipos</span><span className="text-foreground">{verification.entries.length}</span>
+                <span>Separación mínima</span><span className="text-foreground">{verification.min_spacing} ({verification.violations} sin cumplir)</span>
+              </div>
+            </div>
+          )}
+        </DialogContent>
+      </Dialog>
+
+      {/* Revisión del draw */}
+      <Dialog open={report !== null} onOpenChange={(open) => !open && setReport(null)}>
+        <DialogContent className="sm:max-w-[720px] max-h-[85vh] overflow-y-auto">
+          <DialogHeader>
+            <DialogTitle className="text-foreground">Revisión del draw</DialogTitle>
+            <DialogDescription>
+              Cruce de cada ronda con equipos y corridas, y reparto de posiciones entre rondas.
+            </DialogDescription>
+          </DialogHeader>
+          {report && (
+            <div className="mt-2 space-y-4 text-sm">
+              <div className={`p-3 rounded-xl border ${report.clean ? 'bg-emerald-50 border-emerald-200 text-emerald-700' : 'bg-amber-50 border-amber-200 text-amber-700'}`}>
+                {report.clean ? 'Sin observaciones: el draw está listo para firmarse.' : `${report.issues} observación(es) por revisar.`}
+              </div>
+              {report.rounds.map((r) => {
+                const notes = [
+                  r.duplicate_positions.length > 0 && `Posiciones repetidas: ${r.duplicate_positions.join(', ')}`,
+                  r.position_gaps.length > 0 && `Posiciones vacías: ${r.position_gaps.join(', ')}`,
+                  r.run_mismatches.length > 0 && `Corrida con otra posición (equipos): ${r.run_mismatches.map((t) => '#' + t).join(', ')}`,
+                  r.missing_teams.length > 0 && `Equipos activos fuera del draw: ${r.missing_teams.map((t) => '#' + t).join(', ')}`,
+                  r.eliminated_teams.length > 0 && `Eliminados que siguen por correr: ${r.eliminated_teams.map((t) => '#' + t).join(', ')}`,
+                  ...r.spacing.map((v) => `${v.roper_name}: posiciones ${v.first_position} y ${v.second_position} (${v.gap} de separación, mínimo ${r.min_spacing})`),
+                ].filter(Boolean) as string[]
+                return (
+                  <div key={r.round} className="p-3 rounded-xl border border-border">
+                    <p className="text-foreground font-medium">
+                      Ronda {r.round} · {r.teams} equipos{r.method ? ` · ${r.method}` : ''}
+                    </p>
+                    {notes.length === 0 ? (
+                      <p className="text-muted-foreground">Sin observaciones</p>
+                    ) : (
+                      <ul className="list-disc pl-5 text-amber-700">
+                        {notes.map((n) => <li key={n}>{n}</li>)}
+                      </ul>
+                    )}
+                  </div>
+                )
+              })}
+              {report.fairness_rounds.length > 0 && (
+                <div className="space-y-2">
+                  <p className="text-foreground font-medium">
+                    Reparto de posiciones (rondas {report.fairness_rounds.join(', ')})
+                  </p>
+                  <Table>
+                    <TableHeader>
+                      <TableRow>
+                        <TableHead>Roper</TableHead>
+                        <TableHead className="text-right">Salidas</TableHead>
+                        <TableHead className="text-right">Temprano</TableHead>
+                        <TableHead className="text-right">Medio</TableHead>
+                        <TableHead className="text-right">Tarde</TableHead>
+                      </TableRow>
+                    </TableHeader>
+                    <TableBody>
+                      {report.ropers.map((rp) => (
+                        <TableRow key={rp.roper_id}>
+                          <TableCell>{rp.roper_name}</TableCell>
+                          <TableCell className="text-right">{rp.runs}</TableCell>
+                          <TableCell className="text-right">{rp.early}</TableCell>
+                          <TableCell className="text-right">{rp.middle}</TableCell>
+                          <TableCell className="text-right">{rp.late}</TableCell>
+                        </TableRow>
+                      ))}
+                    </TableBody>
+                  </Table>
+                  <Table>
+                    <TableHeader>
+                      <TableRow>
+                        <TableHead>Equipo</TableHead>
+                        <TableHead className="text-right">Posición media</TableHead>
+                      </TableRow>
+                    </TableHeader>
+                    <TableBody>
+                      {report.teams.map((t) => (
+                        <TableRow key={t.team_id}>
+                          <TableCell>#{t.team_id} {t.header_name} / {t.heeler_name}</TableCell>
+                          <TableCell className="text-right">{t.mean_position.toFixed(1)}</TableCell>
+                        </TableRow>
+                      ))}
+                    </TableBody>
+                  </Table>
+                </div>
+              )}
+            </div>
+          )}
+        </DialogContent>
+      </Dialog>
+
       {/* Modal de exclusiones */}
       <Dialog open={showExclusionsModal} onOpenChange={setShowExclusionsModal}>
         <DialogContent className="sm:max-w-[520px]">
@@ -229,8 +229,7 @@ export function EventCaptureView({ event, series, onBack }: EventCaptureViewProp
         time_sec: timeSec,
         penalty: penaltyValue,
         no_time: noTime,
-        dq: dq,
-        captured_by: null
+        dq: dq
       })
       
       if (!isLocked && currentTeamIndex === 0) {
@@ -106,7 +106,8 @@ export function EventsCalendar({ onViewList }: { onViewList?: (seriesId: string)
       lastUpdated: '—',
       entryFee: row.entry_fee ?? undefined,
       maxTeamRating: row.max_team_rating ?? undefined,
-      adminPin: row.admin_pin ?? undefined,
+      hasAdminPin: !!row.has_admin_pin,
+      version: row.version,
     }
   }
 
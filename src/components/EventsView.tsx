@@ -9,7 +9,7 @@ import { EventCard } from './EventCard'
 import { InsightsPanel } from './InsightsPanel'
 import { NewEventModal } from './NewEventModal'
 import { toast } from 'sonner'
-import { getEvents, createEvent, duplicateEvent, deleteEvent, updateEvent } from '@/lib/api'
+import { getEvents, createEvent, duplicateEvent, deleteEvent, updateEvent, verifyEventPin, isCommandError } from '@/lib/api'
 import type { Series, Event } from '../types'
 import {
   Table,
@@ -57,6 +57,8 @@ export function EventsView({
   const [editCandidateEvent, setEditCandidateEvent] = useState<Event | null>(null)
   const [editPin, setEditPin] = useState('')
   const [editEvent, setEditEvent] = useState<Event | null>(null)
+  // PIN ya verificado al abrir la edición; se reenvía en el patch (desbloquear / cambiar PIN)
+  const [editVerifiedPin, setEditVerifiedPin] = useState<string | null>(null)
 
   function mapEventRow(row: any) {
     function normStatus(s: any) {
@@ -77,9 +79,15 @@ export function EventsView({
       maxTeamRating: row.max_team_rating ?? undefined,
       pot: Number(row.pot ?? 0),
       payoffAllocation: row.payoff_allocation ?? undefined,
+      scratchPolicy: row.scratch_policy ?? 'close_gap',
+      callbackMode: row.callback_mode ?? 'all',
+      callbackValue: row.callback_value ?? null,
+      callbackTies: row.callback_ties ?? 'include',
+      finalOrder: row.final_order ?? 'aggregate',
       location: row.location ?? undefined,
       prizePool: row.prize_pool ?? undefined,
-      adminPin: row.admin_pin ?? undefined,
+      hasAdminPin: !!row.has_admin_pin,
+      version: row.version,
     } as Event
   }
 
@@ -119,10 +127,11 @@ export function EventsView({
   }
 
   const handleEdit = (ev: Event) => {
-    if (ev.adminPin) {
+    if (ev.hasAdminPin) {
       setEditCandidateEvent(ev)
       setEditPin('')
     } else {
+      setEditVerifiedPin(null)
       setEditEvent(ev)
     }
   }
@@ -132,12 +141,18 @@ export function EventsView({
     setEditPin('')
   }
 
-  const confirmEditCandidate = () => {
+  const confirmEditCandidate = async () => {
     if (!editCandidateEvent) return
-    if (editCandidateEvent.adminPin && editCandidateEvent.adminPin !== editPin) {
-      toast.error('PIN incorrecto')
+    try {
+      if (!(await verifyEventPin(Number(editCandidateEvent.id), editPin))) {
+        toast.error('PIN incorrecto')
+        return
+      }
+    } catch (err: any) {
+      toast.error(err?.toString?.() ?? 'No se pudo verificar el PIN')
       return
     }
+    setEditVerifiedPin(editPin)
     setEditEvent(editCandidateEvent)
     setEditCandidateEvent(null)
     setEditPin('')
@@ -157,12 +172,11 @@ export function EventsView({
   const confirmDeleteEvent = async () => {
     if (!deleteCandidateEvent) return
 
-    if (deleteCandidateEvent.adminPin && deleteCandidateEvent.adminPin !== deletePin) {
-      toast.error('PIN incorrecto')
-      return
-    }
-
     try {
+      if (deleteCandidateEvent.hasAdminPin && !(await verifyEventPin(Number(deleteCandidateEvent.id), deletePin))) {
+        toast.error('PIN incorrecto')
+        return
+      }
       const idNum = Number(deleteCandidateEvent.id)
       if (isNaN(idNum)) throw new Error('ID inválido')
       await deleteEvent(idNum)
@@ -377,6 +391,11 @@ export function EventsView({
               prize_pool: e.prizePool ?? null,
               max_team_rating: e.maxTeamRating ?? null,
               payoff_allocation: e.payoffAllocation ?? null,
+              scratch_policy: e.scratchPolicy ?? null,
+              callback_mode: e.callbackMode ?? null,
+              callback_value: e.callbackValue ?? null,
+              callback_ties: e.callbackTies ?? null,
+              final_order: e.finalOrder ?? null,
               admin_pin: e.adminPin ?? null,
             })
             toast.success('Evento creado')
@@ -397,11 +416,13 @@ export function EventsView({
         initialEvent={editEvent ?? undefined}
         onUpdateEvent={async (id: string, patch: any) => {
           try {
-            await updateEvent(Number(id), patch)
+            await updateEvent(Number(id), { ...patch, pin: editVerifiedPin, expected_version: editEvent?.version ?? null })
             toast.success('Evento actualizado')
             await load()
           } catch (err: any) {
             toast.error(err?.toString?.() ?? 'No se pudo actualizar el evento')
+            // Lo cambió otra ventana: recargar para que la próxima edición parta de lo actual
+            if (isCommandError(err, 'Conflict')) await load()
           } finally {
             setEditEvent(null)
           }
@@ -451,7 +472,7 @@ export function EventsView({
             </DialogDescription>
           </DialogHeader>
 
-          {deleteCandidateEvent?.adminPin && (
+          {deleteCandidateEvent?.hasAdminPin && (
             <div className="py-4">
               <label className="text-sm font-medium mb-2 block">Ingresa el PIN del evento para confirmar:</label>
               <Input
@@ -470,7 +491,7 @@ export function EventsView({
               <Button 
                 onClick={() => confirmDeleteEvent()} 
                 className="bg-red-600 text-white"
-                disabled={!!deleteCandidateEvent?.adminPin && !deletePin}
+                disabled={!!deleteCandidateEvent?.hasAdminPin && !deletePin}
               >
                 Eliminar
               </Button>
@@ -3,7 +3,7 @@ import { Target, CheckCircle, Settings, Lock, FileText, Info } from 'lucide-reac
 import { Button } from './ui/button'
 import { Progress } from './ui/progress'
 import { getSeriesLogs } from '@/lib/api'
-import type { SeriesLog } from '../types'
+import type { AuditLogItem } from '../types'
 
 interface InsightsPanelProps {
   events: any[]
@@ -19,7 +19,7 @@ interface ActivityItem {
 }
 
 export function InsightsPanel({ events, seriesId }: InsightsPanelProps) {
-  const [logs, setLogs] = useState<SeriesLog[]>([])
+  const [logs, setLogs] = useState<AuditLogItem[]>([])
 
   useEffect(() => {
     if (seriesId) {
@@ -83,8 +83,8 @@ export function InsightsPanel({ events, seriesId }: InsightsPanelProps) {
 
     return {
         id: String(log.id),
-        message: log.details,
-        timestamp: new Date(log.timestamp).toLocaleString(),
+        message: `${log.metadata ?? log.action} — ${log.user_name ?? 'Sistema'}`,
+        timestamp: new Date(log.created_at).toLocaleString(),
         icon,
         color
     }
@@ -0,0 +1,27 @@
+import { AlertTriangle } from 'lucide-react'
+import { Button } from './ui/button'
+import type { LicenseStatus } from '../lib/api'
+
+// Aviso arriba de todas las vistas: sin licencia / vencida (sólo lectura), en prueba o en gracia
+// (con la fecha en que pasa a sólo lectura), o activa a menos de 30 días de vencer.
+export function LicenseBanner({ status, onActivate }: { status: LicenseStatus, onActivate: () => void }) {
+  const expiringSoon = status.state === 'active' && status.days_left !== null && status.days_left <= 30
+  if (status.state === 'active' && !expiringSoon) return null
+
+  const tone = status.read_only
+    ? 'bg-red-50 text-red-800 border-red-200'
+    : 'bg-amber-50 text-amber-800 border-amber-200'
+  const message = expiringSoon
+    ? `La licencia vence en ${status.days_left} día(s) (${status.valid_until}).`
+    : status.state === 'trial'
+      ? `${status.message} Quedan ${status.days_left} día(s).`
+      : status.message
+
+  return (
+    <div className={`flex items-center gap-3 border-b px-6 py-2 text-sm ${tone}`}>
+      <AlertTriangle className="size-4 shrink-0" />
+      <span className="flex-1">{message}</span>
+      <Button variant="outline" size="sm" onClick={onActivate}>Activar licencia</Button>
+    </div>
+  )
+}
@@ -0,0 +1,81 @@
+import React, { useState } from 'react'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { toast } from 'sonner'
+import { createUser, login, type User } from '../lib/api'
+
+interface LoginScreenProps {
+  /** `false` en una instalación nueva: se pide crear el primer administrador. */
+  hasUsers: boolean
+  onLoggedIn: (user: User) => void
+}
+
+export function LoginScreen({ hasUsers, onLoggedIn }: LoginScreenProps) {
+  const [fullName, setFullName] = useState('')
+  const [email, setEmail] = useState('')
+  const [password, setPassword] = useState('')
+  const [confirm, setConfirm] = useState('')
+  const [busy, setBusy] = useState(false)
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault()
+    if (!hasUsers && password !== confirm) {
+      toast.error('Las contraseñas no coinciden')
+      return
+    }
+    setBusy(true)
+    try {
+      if (!hasUsers) {
+        await createUser({ email, full_name: fullName, password, role: 'admin' })
+      }
+      onLoggedIn(await login(email, password))
+    } catch (error) {
+      toast.error(`${error}`)
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  return (
+    <div className="h-full w-full flex items-center justify-center">
+      <form onSubmit={handleSubmit} className="w-full max-w-sm bg-card border border-border rounded-xl p-6 space-y-4">
+        <div className="flex items-center gap-3">
+          <div className="size-10 rounded-xl flex items-center justify-center shadow-sm bg-[var(--sidebar-primary)]">
+            <span className="text-[var(--sidebar-primary-foreground)] font-medium">RM</span>
+          </div>
+          <div>
+            <h1 className="text-lg font-semibold text-foreground">Roping Manager</h1>
+            <p className="text-sm text-muted-foreground">
+              {hasUsers ? 'Inicia sesión para continuar' : 'Crea la cuenta de administrador'}
+            </p>
+          </div>
+        </div>
+
+        {!hasUsers && (
+          <div>
+            <label className="text-sm text-muted-foreground">Nombre completo</label>
+            <Input value={fullName} onChange={(e) => setFullName(e.target.value)} required />
+          </div>
+        )}
+        <div>
+          <label className="text-sm text-muted-foreground">Correo electrónico</label>
+          <Input type="email" value={email} onChange={(e) => setEmail(e.target.value)} autoFocus required />
+        </div>
+        <div>
+          <label className="text-sm text-muted-foreground">Contraseña</label>
+          <Input type="password" value={password} onChange={(e) => setPassword(e.target.value)} required />
+        </div>
+        {!hasUsers && (
+          <div>
+            <label className="text-sm text-muted-foreground">Repetir contraseña</label>
+            <Input type="password" value={confirm} onChange={(e) => setConfirm(e.target.value)} required />
+          </div>
+        )}
+
+        <Button type="submit" disabled={busy} className="w-full bg-orange-500 hover:bg-orange-600 text-white">
+          {hasUsers ? 'Entrar' : 'Crear administrador'}
+        </Button>
+      </form>
+    </div>
+  )
+}
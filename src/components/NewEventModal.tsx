@@ -30,6 +30,11 @@ export function NewEventModal({
   const [maxTeamRating, setMaxTeamRating] = useState<string>('')
   const [isMaxRatingEnabled, setIsMaxRatingEnabled] = useState<boolean>(false)
   const [payoffAllocation, setPayoffAllocation] = useState<string>('')
+  const [scratchPolicy, setScratchPolicy] = useState<'close_gap' | 'keep_slot'>('close_gap')
+  const [callbackMode, setCallbackMode] = useState<'all' | 'top_n' | 'top_percent'>('all')
+  const [callbackValue, setCallbackValue] = useState<string>('')
+  const [callbackTies, setCallbackTies] = useState<'include' | 'exclude' | 'tiebreak'>('include')
+  const [finalOrder, setFinalOrder] = useState<NonNullable<EventType['finalOrder']>>('aggregate')
   const [adminPin, setAdminPin] = useState<string>('')
   const [status, setStatus] = useState<EventType['status']>('draft')
   const [isEditMode, setIsEditMode] = useState<boolean>(false)
@@ -46,6 +51,11 @@ export function NewEventModal({
       setMaxTeamRating('')
       setIsMaxRatingEnabled(false)
       setPayoffAllocation('')
+      setScratchPolicy('close_gap')
+      setCallbackMode('all')
+      setCallbackValue('')
+      setCallbackTies('include')
+      setFinalOrder('aggregate')
       setAdminPin('')
       setStatus('draft')
       setError(null)
@@ -67,7 +77,12 @@ export function NewEventModal({
         setIsMaxRatingEnabled(false)
       }
       setPayoffAllocation(initialEvent.payoffAllocation ?? '')
-      setAdminPin(initialEvent.adminPin ?? '')
+      setScratchPolicy(initialEvent.scratchPolicy ?? 'close_gap')
+      setCallbackMode(initialEvent.callbackMode ?? 'all')
+      setCallbackValue(initialEvent.callbackValue != null ? String(initialEvent.callbackValue) : '')
+      setCallbackTies(initialEvent.callbackTies ?? 'include')
+      setFinalOrder(initialEvent.finalOrder ?? 'aggregate')
+      setAdminPin('')
       setStatus((initialEvent.status as EventType['status']) ?? 'draft')
     }
     // eslint-disable-next-line react-hooks/exhaustive-deps
@@ -107,8 +122,14 @@ export function NewEventModal({
         return false
       }
     }
-    // adminPin
-    if (adminPin && !/^\d{4}$/.test(adminPin)) {
+    if (callbackMode !== 'all') {
+      const v = Number(callbackValue)
+      if (!callbackValue || !Number.isInteger(v) || v < 1 || (callbackMode === 'top_percent' && v > 100)) {
+        setError('Short-go: indica cuántos equipos (o qué porcentaje, 1-100) pasan a la final.')
+        return false
+      }
+    }
+    // adminPin && !/^\d{4}$/.test(adminPin)) {
       setError('El PIN de administrador debe ser de 4 dígitos numéricos.')
       return false
     }
@@ -133,6 +154,11 @@ export function NewEventModal({
         maxTeamRating: (isMaxRatingEnabled && maxTeamRating) ? Number(maxTeamRating) : undefined,
         pot: 0,
         payoffAllocation: payoffAllocation || undefined,
+        scratchPolicy,
+        callbackMode,
+        callbackValue: callbackMode === 'all' || callbackValue === '' ? null : Number(callbackValue),
+        callbackTies,
+        finalOrder,
         adminPin: adminPin || undefined,
       }
 
@@ -146,6 +172,11 @@ export function NewEventModal({
           entry_fee: newEvent.entryFee ?? null,
           max_team_rating: newEvent.maxTeamRating ?? null,
           payoff_allocation: newEvent.payoffAllocation ?? null,
+          scratch_policy: newEvent.scratchPolicy ?? null,
+          callback_mode: newEvent.callbackMode ?? null,
+          callback_value: newEvent.callbackValue ?? null,
+          callback_ties: newEvent.callbackTies ?? null,
+          final_order: newEvent.finalOrder ?? null,
           admin_pin: newEvent.adminPin ?? null,
         }
         onUpdateEvent?.(String(initialEvent.id), patch)
@@ -233,6 +264,82 @@ export function NewEventModal({
           </div>
 
           {/* Payoff Allocation removed as per request */}
+
+          <div>
+            <Label htmlFor="event-scratchPolicy">Scratch (equipo retirado)</Label>
+            <Select value={scratchPolicy} onValueChange={(v: any) => setScratchPolicy(v)}>
+              <SelectTrigger id="event-scratchPolicy" className="w-full">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="close_gap">Cerrar el hueco (recorrer el orden)</SelectItem>
+                <SelectItem value="keep_slot">Conservar el lugar vacío</SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+
+          <div className="grid grid-cols-3 gap-4">
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="event-callbackMode">Short-go (final)</Label>
+              <Select value={callbackMode} onValueChange={(v: any) => setCallbackMode(v)}>
+                <SelectTrigger id="event-callbackMode" className="w-full">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="all">Todos</SelectItem>
+                  <SelectItem value="top_n">Top N</SelectItem>
+                  <SelectItem value="top_percent">Top %</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="event-callbackValue" className={callbackMode === 'all' ? "text-muted-foreground" : ""}>
+                {callbackMode === 'top_percent' ? 'Porcentaje' : 'Equipos'}
+              </Label>
+              <Input
+                id="event-callbackValue"
+                type="number"
+                min={1}
+                max={callbackMode === 'top_percent' ? 100 : undefined}
+                value={callbackValue}
+                onChange={(e) => setCallbackValue(e.target.value)}
+                disabled={loading || callbackMode === 'all'}
+                placeholder={callbackMode === 'top_percent' ? 'Ej. 50' : 'Ej. 10'}
+              />
+            </div>
+            <div className="flex flex-col gap-2">
+              <Label htmlFor="event-callbackTies" className={callbackMode === 'all' ? "text-muted-foreground" : ""}>Empates en el corte</Label>
+              <Select value={callbackTies} onValueChange={(v: any) => setCallbackTies(v)} disabled={callbackMode === 'all'}>
+                <SelectTrigger id="event-callbackTies" className="w-full">
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="include">Pasan todos</SelectItem>
+                  <SelectItem value="exclude">No pasa ninguno</SelectItem>
+                  <SelectItem value="tiebreak">Desempate (mejor corrida)</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+          </div>
+
+          <div>
+            <Label htmlFor="event-finalOrder" className={callbackMode !== 'all' ? "text-muted-foreground" : ""}>Orden de la final</Label>
+            <Select value={finalOrder} onValueChange={(v: any) => setFinalOrder(v)} disabled={callbackMode !== 'all'}>
+              <SelectTrigger id="event-finalOrder" className="w-full">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="aggregate">Mayor tiempo acumulado primero</SelectItem>
+                <SelectItem value="average">Mayor promedio primero</SelectItem>
+                <SelectItem value="standings">Inverso de la clasificación</SelectItem>
+                <SelectItem value="random">Sorteo</SelectItem>
+                <SelectItem value="round_one">Igual que la ronda 1</SelectItem>
+              </SelectContent>
+            </Select>
+            {callbackMode !== 'all' && (
+              <p className="text-xs text-muted-foreground mt-1">Con short-go la final va en orden inverso a la clasificación.</p>
+            )}
+          </div>
           
           <div>
              <Label htmlFor="event-adminPin">PIN de Administrador (4 d&iacute;gitos)</Label>
@@ -244,7 +351,7 @@ export function NewEventModal({
                  const val = e.target.value.replace(/\D/g, '').slice(0, 4)
                  setAdminPin(val)
                }} 
-               placeholder="####"
+               placeholder={isEditMode && initialEvent?.hasAdminPin ? 'Dejar vacío para mantener el actual' : '####'}
                disabled={loading} 
              />
              <p className="text-xs text-muted-foreground mt-1">Requerido para acciones sensibles (borrar, revertir, etc).</p>
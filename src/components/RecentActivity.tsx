@@ -51,7 +51,7 @@ export function RecentActivity({ items = [], onViewAll }: { items?: AuditLogItem
                 <div className="flex-1">
                 <p className="text-foreground">{displayMessage}</p>
                 <p className="text-muted-foreground mt-1">
-                    {formatDistanceToNow(new Date(a.created_at), { addSuffix: true, locale: es })}
+                    {a.user_name ?? 'Sistema'} · {formatDistanceToNow(new Date(a.created_at), { addSuffix: true, locale: es })}
                 </p>
                 </div>
             </div>
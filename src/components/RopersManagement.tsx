@@ -16,12 +16,14 @@ import { useRopers } from '@/hooks/useRopers'
 import * as XLSX from 'xlsx'
 import { createRoper } from '@/lib/api'
 import { toast } from 'sonner'
+import { useSession, hasRole } from '@/lib/session'
 
 // now sourced from DB via hook
 
 export function RopersManagement() {
   const [query, setQuery] = useState('')
   const { ropers, add, edit, remove, removeAll, refresh } = useRopers()
+  const { user } = useSession()
   const fileRef = useRef<HTMLInputElement | null>(null)
   const [importing, setImporting] = useState(false)
   const [categoryFilter, setCategoryFilter] = useState<'all'|'pro'|'amateur'|'principiante'>('all')
@@ -270,7 +272,9 @@ export function RopersManagement() {
             <div className="flex items-center gap-3">
                 <Button variant="outline" className="rounded-md" onClick={() => fileRef.current?.click()} disabled={importing}>Importar Excel</Button>
                 <input ref={fileRef} type="file" accept=".xlsx,.xls,.csv" hidden onChange={handleFile} />
-              <Button variant="destructive" className="rounded-md" onClick={handleDeleteAllRequest}>Borrar Todos</Button>
+              {hasRole(user, 'admin') && (
+                <Button variant="destructive" className="rounded-md" onClick={handleDeleteAllRequest}>Borrar Todos</Button>
+              )}
               <Button onClick={openCreate} className="bg-primary text-primary-foreground rounded-md">+ Agregar Nuevo Roper</Button>
             </div>
           </div>
@@ -0,0 +1,83 @@
+import { useState, useEffect } from 'react'
+import { ArrowRight } from 'lucide-react'
+import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogDescription } from './ui/dialog'
+import { Badge } from './ui/badge'
+import { toast } from 'sonner'
+import { getRunHistory, type RunRevision } from '../lib/api'
+
+interface RunHistoryDialogProps {
+  runId: number | null
+  title: string
+  onClose: () => void
+}
+
+const ACTION_LABELS: Record<RunRevision['action'], string> = {
+  baseline: 'Estado inicial',
+  capture: 'Captura',
+  correction: 'Corrección',
+  recompute: 'Recálculo',
+  undo: 'Deshecho',
+  redo: 'Rehecho',
+}
+
+// Resultado de una versión: total, NT o DQ (la penalización va aparte)
+const describe = (total: number | null, noTime: number | null, dq: number | null, status: string | null) => {
+  if (status === null) return '—'
+  if (dq) return 'DQ'
+  if (noTime) return 'NT'
+  if (total === null) return status === 'pending' ? 'Pendiente' : '—'
+  return `${total.toFixed(3)}s`
+}
+
+// Historial de una corrida: cada captura y corrección con valores de antes/después, autor y motivo
+export function RunHistoryDialog({ runId, title, onClose }: RunHistoryDialogProps) {
+  const [revisions, setRevisions] = useState<RunRevision[]>([])
+
+  useEffect(() => {
+    if (runId === null) return
+    setRevisions([])
+    getRunHistory(runId)
+      .then(setRevisions)
+      .catch((error) => {
+        console.error('Error fetching run history:', error)
+        toast.error('Error al cargar el historial')
+      })
+  }, [runId])
+
+  return (
+    <Dialog open={runId !== null} onOpenChange={(open) => { if (!open) onClose() }}>
+      <DialogContent className="sm:max-w-[600px]">
+        <DialogHeader>
+          <DialogTitle className="text-foreground">Historial de la corrida</DialogTitle>
+          <DialogDescription>{title}</DialogDescription>
+        </DialogHeader>
+        <ol className="space-y-3 max-h-[60vh] overflow-y-auto pr-1">
+          {[...revisions].reverse().map((v) => (
+            <li key={v.id} className="rounded-lg border border-border p-3 space-y-1">
+              <div className="flex items-center justify-between gap-2">
+                <div className="flex items-center gap-2">
+                  <Badge variant="outline">v{v.revision}</Badge>
+                  <span className="text-sm font-medium text-foreground">
+                    {ACTION_LABELS[v.action] ?? v.action}
+                    {v.target_revision !== null && <span className="text-muted-foreground font-normal"> (v{v.target_revision})</span>}
+                  </span>
+                </div>
+                <span className="text-xs text-muted-foreground">
+                  {v.user_name ?? 'Sistema'} · {new Date(v.created_at).toLocaleString()}
+                </span>
+              </div>
+              <div className="flex items-center gap-2 font-mono text-sm">
+                <span className="text-muted-foreground">{describe(v.old_total_sec, v.old_no_time, v.old_dq, v.old_status)}</span>
+                <ArrowRight className="w-3 h-3 text-muted-foreground" />
+                <span className="font-semibold text-foreground">{describe(v.new_total_sec, v.new_no_time, v.new_dq, v.new_status)}</span>
+                {v.new_penalty > 0 && <span className="text-xs text-amber-600">incl. +{v.new_penalty}s pen.</span>}
+              </div>
+              {v.penalties && <p className="text-xs text-muted-foreground">{v.penalties}</p>}
+              {v.reason && <p className="text-xs text-foreground italic">“{v.reason}”</p>}
+            </li>
+          ))}
+        </ol>
+      </DialogContent>
+    </Dialog>
+  )
+}
@@ -1,8 +1,21 @@
-import { useState } from 'react'
+import { useEffect, useState } from 'react'
 import { Button } from './ui/button'
 import { Input } from './ui/input'
 import { Switch } from './ui/switch'
 import { toast } from 'sonner'
+import { open, save } from '@tauri-apps/plugin-dialog'
+import {
+  backupDatabase,
+  restoreDatabase,
+  listSnapshots,
+  getSnapshotSettings,
+  updateSnapshotSettings,
+  changePassword,
+  activateLicense,
+  licenseStatus,
+  type SnapshotInfo,
+} from '../lib/api'
+import { useSession, roleLabel, initials, hasRole } from '../lib/session'
 
 const tabs = [
   'Perfil de usuario',
@@ -10,20 +23,139 @@ const tabs = [
   'Apariencia',
   'Notificaciones',
   'Idioma y región',
+  'Licencia',
   'Sistema / Avanzado',
 ]
 
+const licenseStateLabel: Record<string, string> = {
+  unlicensed: 'Sin licencia',
+  trial: 'Periodo de prueba',
+  active: 'Activa',
+  grace: 'Vencida (periodo de gracia)',
+  expired: 'Vencida — sólo lectura',
+}
+
 export function SettingsManagement() {
-  const [active, setActive] = useState(tabs[0])
-  const [name, setName] = useState('Emiliano García')
-  const [email, setEmail] = useState('emiliano@ropingmanager.com')
-  const [role] = useState('Administrador')
+  const { user, signOut, license, setLicense } = useSession()
+  const [active, setActive] = useState(license?.read_only ? 'Licencia' : tabs[0])
+  const [licenseKey, setLicenseKey] = useState('')
+  const [currentPassword, setCurrentPassword] = useState('')
+  const [newPassword, setNewPassword] = useState('')
+  const [confirmPassword, setConfirmPassword] = useState('')
   const [clearTempOnClose, setClearTempOnClose] = useState(false)
 
   // Appearance state
   const [theme, setTheme] = useState<'light' | 'dark' | 'system'>('light')
   const [primaryColor, setPrimaryColor] = useState('#F97316') // Orange-500 hex
 
+  // Snapshots automáticos
+  const [snapshots, setSnapshots] = useState<SnapshotInfo[]>([])
+  const [snapshotInterval, setSnapshotInterval] = useState(5)
+  const [snapshotKeep, setSnapshotKeep] = useState(24)
+
+  const loadSnapshots = async () => {
+    try {
+      const [list, settings] = await Promise.all([listSnapshots(), getSnapshotSettings()])
+      setSnapshots(list)
+      setSnapshotInterval(settings.interval_minutes)
+      setSnapshotKeep(settings.keep)
+    } catch (error) {
+      console.error(error)
+    }
+  }
+
+  useEffect(() => {
+    if (active === 'Datos y almacenamiento') loadSnapshots()
+  }, [active])
+
+  const handleSaveSnapshotSettings = async () => {
+    try {
+      await updateSnapshotSettings({ interval_minutes: snapshotInterval, keep: snapshotKeep })
+      toast.success('Configuración de snapshots guardada')
+    } catch (error) {
+      toast.error(`${error}`)
+    }
+  }
+
+  const restoreFrom = async (src: string) => {
+    if (!window.confirm('Se reemplazarán todos los datos actuales por los del respaldo. Se guardará una copia de la base actual. ¿Continuar?')) return
+    const toastId = toast.loading('Restaurando respaldo...')
+    try {
+      const report = await restoreDatabase(src)
+      toast.dismiss(toastId)
+      toast.success(`Respaldo restaurado. Copia de la base anterior: ${report.safety_backup}`)
+      setLicense(await licenseStatus())
+      if (report.requires_login) {
+        toast.info('Los usuarios ahora son los del respaldo; vuelve a iniciar sesión.')
+        await signOut()
+        return
+      }
+      loadSnapshots()
+    } catch (error) {
+      toast.dismiss(toastId)
+      console.error(error)
+      toast.error(`No se pudo restaurar: ${error}`)
+    }
+  }
+
+  const handleBackup = async () => {
+    const stamp = new Date().toISOString().slice(0, 19).replace(/[:T]/g, '-')
+    const dest = await save({
+      defaultPath: `roping_manager-${stamp}.db`,
+      filters: [{ name: 'Base de datos SQLite', extensions: ['db'] }],
+    })
+    if (!dest) return
+    try {
+      await backupDatabase(dest)
+      toast.success(`Respaldo guardado en ${dest}`)
+    } catch (error) {
+      console.error(error)
+      toast.error(`No se pudo crear el respaldo: ${error}`)
+    }
+  }
+
+  const handleRestore = async () => {
+    const src = await open({
+      multiple: false,
+      filters: [{ name: 'Base de datos SQLite', extensions: ['db', 'sqlite'] }],
+    })
+    if (!src || Array.isArray(src)) return
+    await restoreFrom(src)
+  }
+
+  const handleChangePassword = async () => {
+    if (newPassword !== confirmPassword) {
+      toast.error('Las contraseñas no coinciden')
+      return
+    }
+    try {
+      await changePassword(currentPassword, newPassword)
+      setCurrentPassword('')
+      setNewPassword('')
+      setConfirmPassword('')
+      toast.success('Contraseña actualizada')
+    } catch (error) {
+      toast.error(`${error}`)
+    }
+  }
+
+  const handleActivateLicense = async () => {
+    try {
+      const status = await activateLicense(licenseKey.trim())
+      setLicense(status)
+      setLicenseKey('')
+      toast.success(status.message)
+    } catch (error) {
+      toast.error(`${error}`)
+    }
+  }
+
+  const copyDeviceId = async () => {
+    if (!license) return
+    await navigator.clipboard.writeText(license.device_id)
+    toast.success('ID de equipo copiado')
+  }
+
   const handleSaveAppearance = () => {
     // Here we would persist the theme settings
     toast.success('Configuración de apariencia guardada')
@@ -58,37 +190,41 @@ export function SettingsManagement() {
 
                 <div className="mt-6 grid grid-cols-1 md:grid-cols-2 gap-4">
                   <div className="flex items-center gap-4">
-                    <div className="w-20 h-20 rounded-full bg-orange-400 flex items-center justify-center text-white font-bold">EG</div>
+                    <div className="w-20 h-20 rounded-full bg-orange-400 flex items-center justify-center text-white font-bold">{initials(user.full_name)}</div>
                     <div>
-                      <div className="font-medium">{name}</div>
-                      <div className="text-sm text-muted-foreground">{email}</div>
-                      <div className="mt-2 text-xs text-orange-700 font-medium bg-orange-50 inline-block px-2 py-1 rounded-md">Administrador</div>
+                      <div className="font-medium">{user.full_name}</div>
+                      <div className="text-sm text-muted-foreground">{user.email}</div>
+                      <div className="mt-2 text-xs text-orange-700 font-medium bg-orange-50 inline-block px-2 py-1 rounded-md">{roleLabel[user.role] ?? user.role}</div>
                     </div>
                   </div>
 
                   <div className="space-y-3">
                     <div>
-                      <label className="text-sm text-muted-foreground">Nombre completo</label>
-                      <Input value={name} onChange={(e) => setName(e.target.value)} />
+                      <label className="text-sm text-muted-foreground">Contraseña actual</label>
+                      <Input type="password" value={currentPassword} onChange={(e) => setCurrentPassword(e.target.value)} />
                     </div>
 
                     <div>
-                      <label className="text-sm text-muted-foreground">Correo electrónico</label>
-                      <Input value={email} onChange={(e) => setEmail(e.target.value)} />
+                      <label className="text-sm text-muted-foreground">Nueva contraseña</label>
+                      <Input type="password" value={newPassword} onChange={(e) => setNewPassword(e.target.value)} />
                     </div>
 
                     <div>
-                      <label className="text-sm text-muted-foreground">Rol</label>
-                      <select className="w-full rounded-lg border border-border bg-card px-3 py-2 text-sm" value={role} onChange={() => {}}>
-                        <option>Administrador</option>
-                      </select>
+                      <label className="text-sm text-muted-foreground">Repetir nueva contraseña</label>
+                      <Input type="password" value={confirmPassword} onChange={(e) => setConfirmPassword(e.target.value)} />
                     </div>
                   </div>
                 </div>
 
                 <div className="mt-6 flex items-center gap-3">
-                  <Button className="bg-orange-500 hover:bg-orange-600 text-white">Guardar cambios</Button>
-                  <Button variant="outline">Cerrar sesión</Button>
+                  <Button
+                    className="bg-orange-500 hover:bg-orange-600 text-white"
+                    disabled={!currentPassword || !newPassword}
+                    onClick={handleChangePassword}
+                  >
+                    Cambiar contraseña
+                  </Button>
+                  <Button variant="outline" onClick={signOut}>Cerrar sesión</Button>
                 </div>
               </div>
             )}
@@ -107,8 +243,8 @@ export function SettingsManagement() {
                 </div>
 
                 <div className="mt-4 flex gap-3">
-                  <Button className="bg-orange-500 hover:bg-orange-600 text-white">Exportar respaldo</Button>
-                  <Button variant="outline">Importar respaldo</Button>
+                  <Button className="bg-orange-500 hover:bg-orange-600 text-white" onClick={handleBackup}>Exportar respaldo</Button>
+                  <Button variant="outline" onClick={handleRestore}>Importar respaldo</Button>
                 </div>
 
                 <div className="mt-4 border-t pt-4">
@@ -122,6 +258,37 @@ export function SettingsManagement() {
 
                   <div className="mt-3 text-sm text-muted-foreground">Tamaño actual de la base de datos <span className="ml-2 font-medium">24 MB</span></div>
                 </div>
+
+                <div className="mt-4 border-t pt-4">
+                  <div className="text-sm font-medium">Snapshots automáticos</div>
+                  <div className="text-xs text-muted-foreground">Se toman mientras haya un evento activo, al bloquear un evento y al completarlo</div>
+                  <div className="mt-3 flex items-end gap-3">
+                    <div>
+                      <label className="text-xs text-muted-foreground">Cada (minutos)</label>
+                      <Input type="number" min={1} max={240} className="w-28" value={snapshotInterval} onChange={(e) => setSnapshotInterval(Number(e.target.value))} />
+                    </div>
+                    <div>
+                      <label className="text-xs text-muted-foreground">Generaciones a conservar</label>
+                      <Input type="number" min={1} max={500} className="w-28" value={snapshotKeep} onChange={(e) => setSnapshotKeep(Number(e.target.value))} />
+                    </div>
+                    <Button variant="outline" onClick={handleSaveSnapshotSettings}>Guardar</Button>
+                  </div>
+
+                  <div className="mt-3 max-h-56 overflow-y-auto divide-y divide-border rounded-md border border-border">
+                    {snapshots.length === 0 && (
+                      <div className="p-3 text-sm text-muted-foreground">Todavía no hay snapshots</div>
+                    )}
+                    {snapshots.map((snap) => (
+                      <div key={snap.file_name} className="flex items-center justify-between p-2 text-sm">
+                        <div>
+                          <div>{new Date(snap.created_at).toLocaleString()}</div>
+                          <div className="text-xs text-muted-foreground">{snap.reason} · {(snap.size_bytes / 1024 / 1024).toFixed(1)} MB</div>
+                        </div>
+                        <Button variant="outline" size="sm" onClick={() => restoreFrom(snap.path)}>Restaurar</Button>
+                      </div>
+                    ))}
+                  </div>
+                </div>
               </div>
             )}
 
@@ -218,6 +385,53 @@ export function SettingsManagement() {
               </div>
             )}
 
+            {active === 'Licencia' && (
+              <div>
+                <h2 className="text-lg font-medium">Licencia</h2>
+                <p className="text-sm text-muted-foreground">Activación sin conexión, ligada a este equipo</p>
+
+                {license && (
+                  <div className="mt-6 bg-muted/50 p-4 rounded-md space-y-2 text-sm">
+                    <div className="flex justify-between"><span className="text-muted-foreground">Estado</span><span className="font-medium">{licenseStateLabel[license.state]}</span></div>
+                    {license.customer && (
+                      <div className="flex justify-between"><span className="text-muted-foreground">Cliente</span><span>{license.customer}</span></div>
+                    )}
+                    {license.valid_until && (
+                      <div className="flex justify-between"><span className="text-muted-foreground">Vence</span><span>{license.valid_until}</span></div>
+                    )}
+                    {license.state === 'trial' && license.grace_until && (
+                      <div className="flex justify-between"><span className="text-muted-foreground">Prueba hasta</span><span>{license.grace_until}</span></div>
+                    )}
+                    <div className="flex justify-between items-center">
+                      <span className="text-muted-foreground">ID de equipo</span>
+                      <span className="flex items-center gap-2">
+                        <code>{license.device_id}</code>
+                        <Button variant="outline" size="sm" onClick={copyDeviceId}>Copiar</Button>
+                      </span>
+                    </div>
+                    <p className="text-muted-foreground">{license.message}</p>
+                  </div>
+                )}
+
+                {hasRole(user, 'admin') ? (
+                  <div className="mt-4">
+                    <label className="text-sm text-muted-foreground">Clave de licencia</label>
+                    <textarea
+                      className="mt-1 w-full h-28 rounded-md border border-border bg-background p-2 font-mono text-xs"
+                      value={licenseKey}
+                      onChange={(e) => setLicenseKey(e.target.value)}
+                      placeholder="Pega aquí la clave que recibiste para este ID de equipo"
+                    />
+                    <Button className="mt-2 bg-orange-500 hover:bg-orange-600 text-white" disabled={!licenseKey.trim()} onClick={handleActivateLicense}>
+                      Activar
+                    </Button>
+                  </div>
+                ) : (
+                  <p className="mt-4 text-sm text-muted-foreground">Sólo un administrador puede activar la licencia.</p>
+                )}
+              </div>
+            )}
+
             {active === 'Sistema / Avanzado' && (
               <div>
                 <h2 className="text-lg font-medium">Sistema / Avanzado</h2>
@@ -8,7 +8,9 @@ import {
   Download,
   Settings,
   Clock,
+  LogOut,
 } from 'lucide-react'
+import { useSession, roleLabel } from '../lib/session'
 
 interface SidebarProps {
   activeItem: string
@@ -16,6 +18,7 @@ interface SidebarProps {
 }
 
 export function Sidebar({ activeItem, onItemClick }: SidebarProps) {
+  const { user, signOut } = useSession()
   const menuItems = [
     { id: 'dashboard', label: 'Dashboard', icon: LayoutDashboard },
     { id: 'ropers', label: 'Ropers', icon: Trophy },
@@ -29,8 +32,8 @@ export function Sidebar({ activeItem, onItemClick }: SidebarProps) {
   ]
 
   return (
-    <aside role="navigation" aria-label="Primary" className="w-64 h-full bg-[var(--sidebar)] border-r border-[var(--sidebar-border)]">
-      <div className="p-6">
+    <aside role="navigation" aria-label="Primary" className="w-64 h-full flex flex-col bg-[var(--sidebar)] border-r border-[var(--sidebar-border)]">
+      <div className="p-6 flex-1">
         <div className="flex items-center gap-3 mb-10">
           <div className="size-10 rounded-xl flex items-center justify-center shadow-sm bg-[var(--sidebar-primary)]">
             <span className="text-[var(--sidebar-primary-foreground)] font-medium">RM</span>
@@ -76,7 +79,21 @@ export function Sidebar({ activeItem, onItemClick }: SidebarProps) {
           </nav>
         </div>
       </div>
-      <div className="border-t border-[var(--sidebar-border)] p-4 text-[var(--sidebar-foreground)]/70 text-xs">
+      <div className="border-t border-[var(--sidebar-border)] px-4 pt-4 flex items-center justify-between gap-2">
+        <div className="min-w-0">
+          <div className="text-sm text-foreground truncate">{user.full_name}</div>
+          <div className="text-xs text-[var(--sidebar-foreground)]/70">{roleLabel[user.role] ?? user.role}</div>
+        </div>
+        <button
+          type="button"
+          onClick={signOut}
+          title="Cerrar sesión"
+          className="p-2 rounded-lg text-[var(--sidebar-foreground)] hover:bg-white hover:text-foreground"
+        >
+          <LogOut aria-hidden className="size-4" />
+        </button>
+      </div>
+      <div className="p-4 text-[var(--sidebar-foreground)]/70 text-xs">
         v1.0.0 · © 2025
       </div>
     </aside>
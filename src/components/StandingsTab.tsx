@@ -24,13 +24,15 @@ interface Standing {
   heeler: string
   avgTime: number
   totalTime: number | null
+  penaltyTotal: number
+  penaltyBreakdown: string // "One-leg catch x2 +10; ..."
   qualifiedRuns: number
   totalRuns: number // qualified + nt + dq
   ntCount: number
   dqCount: number
   isQualified: boolean
   payoff?: number
-  status: 'Calificado' | 'Penal' | 'No Time' | 'DQ'
+  status: 'Calificado' | 'Penal' | 'No Time' | 'DQ' | 'Sin callback'
 }
 
 function formatCurrency(n: number) {
@@ -61,6 +63,8 @@ export function StandingsTab({ event }: StandingsTabProps) {
         let status: Standing['status'] = 'Calificado'
         if (s.dq_cnt > 0) status = 'DQ'
         else if (s.nt_cnt > 0) status = 'No Time'
+        // no pasó el corte del short-go: no está eliminado, sólo no corre la final
+        else if (s.called_back === 0) status = 'Sin callback'
         
         return {
           rank: s.rank,
@@ -69,6 +73,8 @@ export function StandingsTab({ event }: StandingsTabProps) {
           heeler: s.heeler_name,
           avgTime: s.avg_time || 0,
           totalTime: s.total_time,
+          penaltyTotal: s.penalty_total ?? 0,
+          penaltyBreakdown: (s.penalties ?? []).map((p: any) => `${p.label} x${p.count} +${p.seconds}`).join('; '),
           qualifiedRuns: s.completed_runs,
           totalRuns: s.completed_runs + s.nt_cnt + s.dq_cnt,
           ntCount: s.nt_cnt,
@@ -310,6 +316,11 @@ export function StandingsTab({ event }: StandingsTabProps) {
                         </TableCell>
                         <TableCell className="text-right font-mono font-medium text-foreground">
                             {s.totalTime !== null ? `${s.totalTime.toFixed(2)}s` : <span className="text-muted-foreground">-</span>}
+                            {s.penaltyTotal > 0 && (
+                              <div className="text-xs font-normal text-amber-600" title={s.penaltyBreakdown}>
+                                incl. +{s.penaltyTotal}s pen.
+                              </div>
+                            )}
                         </TableCell>
                         <TableCell className="text-right font-mono text-muted-foreground hidden sm:table-cell">
                              {s.avgTime !== null ? `${s.avgTime.toFixed(2)}s` : '-'}
@@ -318,6 +329,7 @@ export function StandingsTab({ event }: StandingsTabProps) {
                             {s.status === 'Calificado' && <Badge className="bg-emerald-50 text-emerald-700 border-emerald-200 hover:bg-emerald-100 text-xs">OK</Badge>}
                             {s.status === 'DQ' && <Badge className="bg-red-50 text-red-700 border-red-200 hover:bg-red-100 text-xs">DQ</Badge>}
                             {s.status === 'No Time' && <Badge className="bg-red-50 text-red-700 border-red-200 hover:bg-red-100 text-xs">NT</Badge>}
+                            {s.status === 'Sin callback' && <Badge className="bg-muted text-muted-foreground border-border text-xs">Sin callback</Badge>}
                         </TableCell>
                         <TableCell className="text-right font-semibold text-emerald-600">
                              {s.payoff ? formatCurrency(s.payoff) : <span className="text-muted-foreground/30">—</span>}
@@ -0,0 +1,180 @@
+import { useState, useEffect, useCallback } from 'react'
+import { Timer as TimerIcon, Plug, Unplug, Check, X } from 'lucide-react'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { Label } from './ui/label'
+import { Badge } from './ui/badge'
+import { Popover, PopoverContent, PopoverTrigger } from './ui/popover'
+import {
+  Select, SelectContent, SelectItem, SelectTrigger, SelectValue,
+} from './ui/select'
+import { toast } from 'sonner'
+import {
+  getTimerOffer, connectTimer, disconnectTimer, confirmTimerTime, dismissTimerReading, isCommandError,
+  type TimerOffer, type TimerSource,
+} from '../lib/api'
+
+interface TimerPanelProps {
+  eventId: number
+  round: number
+  // Con una corrida abierta a mano, Enter es de la captura manual y no confirma el timer
+  captureOpen: boolean
+  onSaved: () => Promise<void> | void
+}
+
+const POLL_MS = 700
+
+// Conexión con el timer y la lectura por confirmar (Enter la guarda en la siguiente corrida pendiente)
+export function TimerPanel({ eventId, round, captureOpen, onSaved }: TimerPanelProps) {
+  const [offer, setOffer] = useState<TimerOffer | null>(null)
+  const [kind, setKind] = useState<TimerSource['kind']>('tcp')
+  const [addr, setAddr] = useState('127.0.0.1:5005')
+  const [path, setPath] = useState('/dev/ttyUSB0')
+  const [baud, setBaud] = useState('9600')
+  const [saving, setSaving] = useState(false)
+
+  const refresh = useCallback(async () => {
+    try {
+      setOffer(await getTimerOffer(eventId, round))
+    } catch (error) {
+      console.error('Error fetching timer offer:', error)
+    }
+  }, [eventId, round])
+
+  useEffect(() => {
+    refresh()
+    const interval = setInterval(refresh, POLL_MS)
+    return () => clearInterval(interval)
+  }, [refresh])
+
+  const status = offer?.status
+  const reading = status?.reading ?? null
+  const run = offer?.run ?? null
+
+  const handleConnect = async () => {
+    const source: TimerSource = kind === 'tcp'
+      ? { kind: 'tcp', addr: addr.trim() }
+      : { kind: 'serial', path: path.trim(), baud: Number(baud) || null }
+    try {
+      await connectTimer(source)
+      toast.success('Timer conectado')
+      refresh()
+    } catch (error) {
+      toast.error(isCommandError(error) ? error.message : 'No se pudo conectar el timer')
+    }
+  }
+
+  const handleDisconnect = async () => {
+    await disconnectTimer()
+    refresh()
+  }
+
+  const handleConfirm = useCallback(async () => {
+    if (!reading || !run || saving) return
+    setSaving(true)
+    try {
+      await confirmTimerTime({ event_id: eventId, run_id: run.id, expected_version: run.version, seq: reading.seq })
+      toast.success(`${reading.seconds.toFixed(3)}s → #${run.position} ${run.header_name} & ${run.heeler_name}`)
+      await onSaved()
+    } catch (error) {
+      toast.error(isCommandError(error) ? error.message : 'Error al guardar el tiempo del timer')
+    } finally {
+      setSaving(false)
+      refresh()
+    }
+  }, [reading, run, saving, eventId, onSaved, refresh])
+
+  const handleDismiss = async () => {
+    if (!reading) return
+    await dismissTimerReading(reading.seq)
+    refresh()
+  }
+
+  useEffect(() => {
+    if (captureOpen) return
+    const handleKey = (e: KeyboardEvent) => {
+      if (e.key !== 'Enter' || !reading || !run) return
+      const target = e.target as HTMLElement | null
+      if (target && ['INPUT', 'TEXTAREA', 'SELECT'].includes(target.tagName)) return
+      e.preventDefault()
+      handleConfirm()
+    }
+    window.addEventListener('keydown', handleKey)
+    return () => window.removeEventListener('keydown', handleKey)
+  }, [captureOpen, reading, run, handleConfirm])
+
+  return (
+    <div className="flex items-center gap-2">
+      {reading && (
+        <div className="flex items-center gap-2 bg-amber-50 border border-amber-200 rounded-xl px-3 py-1 shadow-sm animate-in fade-in">
+          <span className="font-mono text-lg font-semibold text-amber-700">{reading.seconds.toFixed(3)}s</span>
+          <span className="text-xs text-muted-foreground">
+            {run ? `→ #${run.position} ${run.header_name} & ${run.heeler_name}` : 'sin corridas pendientes'}
+          </span>
+          <Button size="sm" onClick={handleConfirm} disabled={!run || saving} className="h-7 px-2 text-xs">
+            <Check className="w-3 h-3 mr-1" /> Enter
+          </Button>
+          <Button size="icon" variant="ghost" onClick={handleDismiss} className="h-7 w-7" aria-label="Descartar lectura">
+            <X className="w-4 h-4" />
+          </Button>
+        </div>
+      )}
+
+      <Popover>
+        <PopoverTrigger asChild>
+          <Button variant="outline" size="sm" className="h-9 gap-2">
+            <TimerIcon className="w-4 h-4" />
+            Timer
+            {status?.connected ? (
+              <Badge className="bg-emerald-50 text-emerald-700 border-emerald-200 hover:bg-emerald-100">
+                {status.running ? 'Corriendo' : 'Conectado'}
+              </Badge>
+            ) : (
+              <Badge variant="outline" className="text-muted-foreground">Manual</Badge>
+            )}
+          </Button>
+        </PopoverTrigger>
+        <PopoverContent align="end" className="w-80 space-y-3">
+          {status?.connected ? (
+            <>
+              <p className="text-sm text-foreground">
+                {status.source?.kind === 'tcp' ? status.source.addr : status.source?.path}
+              </p>
+              <p className="text-xs text-muted-foreground">
+                {status.frames} tramas · {status.rejected} descartadas
+              </p>
+              <Button variant="outline" size="sm" onClick={handleDisconnect} className="w-full">
+                <Unplug className="w-4 h-4 mr-2" /> Desconectar
+              </Button>
+            </>
+          ) : (
+            <>
+              <div className="space-y-1">
+                <Label>Conexión</Label>
+                <Select value={kind} onValueChange={(v) => setKind(v as TimerSource['kind'])}>
+                  <SelectTrigger className="h-9"><SelectValue /></SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="tcp">TCP (host:puerto)</SelectItem>
+                    <SelectItem value="serial">Puerto serie</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+              {kind === 'tcp' ? (
+                <Input value={addr} onChange={(e) => setAddr(e.target.value)} placeholder="192.168.1.50:5005" />
+              ) : (
+                <div className="flex gap-2">
+                  <Input value={path} onChange={(e) => setPath(e.target.value)} placeholder="/dev/ttyUSB0 o COM3" />
+                  <Input value={baud} onChange={(e) => setBaud(e.target.value)} className="w-24" inputMode="numeric" />
+                </div>
+              )}
+              <Button size="sm" onClick={handleConnect} className="w-full">
+                <Plug className="w-4 h-4 mr-2" /> Conectar
+              </Button>
+            </>
+          )}
+          {status?.last_error && <p className="text-xs text-destructive">{status.last_error}</p>}
+        </PopoverContent>
+      </Popover>
+    </div>
+  )
+}
@@ -0,0 +1,157 @@
+import { useState, useEffect, useCallback } from 'react'
+import { Undo2, Redo2 } from 'lucide-react'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { Label } from './ui/label'
+import {
+  AlertDialog, AlertDialogAction, AlertDialogCancel, AlertDialogContent,
+  AlertDialogDescription, AlertDialogFooter, AlertDialogHeader, AlertDialogTitle,
+} from './ui/alert-dialog'
+import { toast } from 'sonner'
+import {
+  getRunUndoState, undoLastRunChange, redoLastRunChange, isCommandError,
+  type UndoState, type UndoTarget,
+} from '../lib/api'
+
+interface UndoRedoControlsProps {
+  eventId: number
+  hasAdminPin: boolean
+  // Cambia cada vez que se recargan las corridas: hay que volver a preguntar qué se puede deshacer
+  refreshKey: unknown
+  onChanged: () => Promise<void> | void
+}
+
+type Direction = 'undo' | 'redo'
+
+const describe = (t: UndoTarget) => {
+  const result = t.dq ? 'DQ' : t.no_time ? 'NT' : t.total_sec !== null ? `${t.total_sec.toFixed(3)}s` : '—'
+  return `R${t.round} #${t.position} ${t.header_name} & ${t.heeler_name} (${result})`
+}
+
+// Deshacer/rehacer la última captura del evento (Ctrl+Z / Ctrl+Shift+Z fuera de los campos)
+export function UndoRedoControls({ eventId, hasAdminPin, refreshKey, onChanged }: UndoRedoControlsProps) {
+  const [state, setState] = useState<UndoState>({ undo: null, redo: null })
+  const [pinFor, setPinFor] = useState<Direction | null>(null)
+  const [pin, setPin] = useState('')
+  const [pinError, setPinError] = useState(false)
+  const [busy, setBusy] = useState(false)
+
+  const refresh = useCallback(async () => {
+    try {
+      setState(await getRunUndoState(eventId))
+    } catch (error) {
+      console.error('Error fetching undo state:', error)
+    }
+  }, [eventId])
+
+  useEffect(() => {
+    refresh()
+  }, [refresh, refreshKey])
+
+  const perform = useCallback(async (direction: Direction, pinValue?: string) => {
+    const target = direction === 'undo' ? state.undo : state.redo
+    if (!target || busy) return
+    if (target.requires_pin && hasAdminPin && pinValue === undefined) {
+      setPin('')
+      setPinError(false)
+      setPinFor(direction)
+      return
+    }
+    setBusy(true)
+    try {
+      const outcome = direction === 'undo'
+        ? await undoLastRunChange(eventId, pinValue)
+        : await redoLastRunChange(eventId, pinValue)
+      setState(outcome.state)
+      setPinFor(null)
+      toast.success(direction === 'undo' ? 'Captura deshecha' : 'Captura rehecha', { description: describe(target) })
+      await onChanged()
+    } catch (error) {
+      if (isCommandError(error, 'Validation') && error.field === 'pin') {
+        setPinError(true)
+        return
+      }
+      toast.error(isCommandError(error) ? error.message : 'No se pudo deshacer la captura')
+      setPinFor(null)
+      refresh()
+    } finally {
+      setBusy(false)
+    }
+  }, [state, busy, hasAdminPin, eventId, onChanged, refresh])
+
+  useEffect(() => {
+    const handleKey = (e: KeyboardEvent) => {
+      if (!(e.ctrlKey || e.metaKey) || e.key.toLowerCase() !== 'z') return
+      const target = e.target as HTMLElement | null
+      if (target && ['INPUT', 'TEXTAREA', 'SELECT'].includes(target.tagName)) return
+      e.preventDefault()
+      perform(e.shiftKey ? 'redo' : 'undo')
+    }
+    window.addEventListener('keydown', handleKey)
+    return () => window.removeEventListener('keydown', handleKey)
+  }, [perform])
+
+  const handleConfirmPin = (e: React.MouseEvent) => {
+    // El diálogo se cierra en perform sólo si el backend acepta el PIN
+    e.preventDefault()
+    if (pinFor) perform(pinFor, pin)
+  }
+
+  return (
+    <div className="flex items-center gap-1">
+      <Button
+        variant="outline"
+        size="icon"
+        className="h-9 w-9"
+        onClick={() => perform('undo')}
+        disabled={!state.undo || busy}
+        title={state.undo ? `Deshacer: ${describe(state.undo)}` : 'Nada que deshacer'}
+        aria-label="Deshacer última captura"
+      >
+        <Undo2 className="w-4 h-4" />
+      </Button>
+      <Button
+        variant="outline"
+        size="icon"
+        className="h-9 w-9"
+        onClick={() => perform('redo')}
+        disabled={!state.redo || busy}
+        title={state.redo ? `Rehacer: ${describe(state.redo)}` : 'Nada que rehacer'}
+        aria-label="Rehacer captura"
+      >
+        <Redo2 className="w-4 h-4" />
+      </Button>
+
+      <AlertDialog open={pinFor !== null} onOpenChange={(open) => { if (!open) setPinFor(null) }}>
+        <AlertDialogContent>
+          <AlertDialogHeader>
+            <AlertDialogTitle>{pinFor === 'redo' ? '¿Rehacer corrección?' : '¿Deshacer corrección?'}</AlertDialogTitle>
+            <AlertDialogDescription>
+              Cambia un resultado ya completado. Ingresa el PIN de administrador para confirmar.
+            </AlertDialogDescription>
+          </AlertDialogHeader>
+          <div className="py-2">
+            <Label htmlFor="undo-pin">PIN de Administrador</Label>
+            <Input
+              id="undo-pin"
+              type="password"
+              className={pinError ? 'border-red-500' : ''}
+              value={pin}
+              onChange={(e) => {
+                setPin(e.target.value)
+                setPinError(false)
+              }}
+              placeholder="####"
+              maxLength={4}
+            />
+            {pinError && <p className="text-xs text-red-500 mt-1">PIN incorrecto</p>}
+          </div>
+          <AlertDialogFooter>
+            <AlertDialogCancel>Cancelar</AlertDialogCancel>
+            <AlertDialogAction onClick={handleConfirmPin}>Confirmar</AlertDialogAction>
+          </AlertDialogFooter>
+        </AlertDialogContent>
+      </AlertDialog>
+    </div>
+  )
+}
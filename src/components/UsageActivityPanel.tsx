@@ -0,0 +1,105 @@
+import { useEffect, useState } from 'react'
+import { Loader2 } from 'lucide-react'
+import { Button } from './ui/button'
+import { Input } from './ui/input'
+import { listActivity, type ActivityFilter, type ActivityKind, type ActivityRow } from '@/lib/api'
+
+const kindLabel: Record<ActivityKind, string> = {
+  login: 'Inicio de sesión',
+  logout: 'Cierre de sesión',
+  app_start: 'App abierta',
+  app_stop: 'App cerrada',
+  event_day: 'Jornada del evento',
+  capture: 'Captura de ronda',
+}
+
+export function formatDuration(sec: number | null) {
+  if (sec === null) return 'en curso'
+  const h = Math.floor(sec / 3600)
+  const m = Math.floor((sec % 3600) / 60)
+  return h > 0 ? `${h} h ${m} min` : `${m} min`
+}
+
+// Quién usó la app y cuánto duró cada jornada / ronda (tabla `activity`).
+export function UsageActivityPanel() {
+  const today = new Date().toISOString().slice(0, 10)
+  const [from, setFrom] = useState(today)
+  const [to, setTo] = useState(today)
+  const [kind, setKind] = useState<ActivityKind | ''>('')
+  const [rows, setRows] = useState<ActivityRow[]>([])
+  const [loading, setLoading] = useState(false)
+
+  const load = async () => {
+    setLoading(true)
+    try {
+      const filter: ActivityFilter = { from: from || null, to: to || null, kind: kind || null }
+      setRows(await listActivity(filter))
+    } catch (e) {
+      console.error('Failed to load usage activity:', e)
+    } finally {
+      setLoading(false)
+    }
+  }
+
+  useEffect(() => {
+    load()
+  }, [])
+
+  return (
+    <div className="space-y-4">
+      <div className="flex flex-wrap items-end gap-3">
+        <div>
+          <label className="text-xs text-muted-foreground">Desde</label>
+          <Input type="date" value={from} onChange={(e) => setFrom(e.target.value)} />
+        </div>
+        <div>
+          <label className="text-xs text-muted-foreground">Hasta</label>
+          <Input type="date" value={to} onChange={(e) => setTo(e.target.value)} />
+        </div>
+        <div>
+          <label className="text-xs text-muted-foreground">Tipo</label>
+          <select
+            className="h-9 rounded-md border border-border bg-background px-2 text-sm"
+            value={kind}
+            onChange={(e) => setKind(e.target.value as ActivityKind | '')}
+          >
+            <option value="">Todos</option>
+            {Object.entries(kindLabel).map(([k, label]) => (
+              <option key={k} value={k}>{label}</option>
+            ))}
+          </select>
+        </div>
+        <Button variant="outline" onClick={load}>Filtrar</Button>
+      </div>
+
+      {loading ? (
+        <div className="flex justify-center py-8">
+          <Loader2 className="size-8 animate-spin text-primary" />
+        </div>
+      ) : rows.length === 0 ? (
+        <div className="text-center py-12 text-muted-foreground">No hay actividad en esas fechas.</div>
+      ) : (
+        <div className="divide-y divide-border rounded-xl border border-border bg-card">
+          {rows.map((r) => (
+            <div key={r.id} className="flex items-center justify-between gap-4 p-3 text-sm">
+              <div className="min-w-0">
+                <div className="font-medium">
+                  {kindLabel[r.kind] ?? r.kind}
+                  {r.event_name && ` · ${r.event_name}`}
+                  {r.round !== null && ` · Ronda ${r.round}`}
+                </div>
+                <div className="text-xs text-muted-foreground">
+                  {r.user_name ?? 'Sistema'} · {new Date(r.created_at).toLocaleString()}
+                  {r.details && ` · ${r.details}`}
+                </div>
+              </div>
+              {(r.kind === 'event_day' || r.kind === 'capture') && (
+                <span className="whitespace-nowrap text-muted-foreground">{formatDuration(r.duration_sec)}</span>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
@@ -1,6 +1,6 @@
 import { useCallback, useEffect, useState } from 'react'
 import { toast } from 'sonner'
-import { listRopers, createRoper, updateRoper, deleteRoper, deleteAllRopers } from '@/lib/api'
+import { listRopers, createRoper, updateRoper, deleteRoper, deleteAllRopers, isCommandError } from '@/lib/api'
 
 type Roper = {
   id: number
@@ -13,6 +13,7 @@ type Roper = {
   email?: string | null
   createdAt?: string
   updatedAt?: string
+  version?: number
 }
 
 export function useRopers() {
@@ -31,6 +32,7 @@ export function useRopers() {
     email: r.email ?? null,
     createdAt: r.created_at ?? r.createdAt,
     updatedAt: r.updated_at ?? r.updatedAt,
+    version: r.version,
   }), [])
 
   const refresh = useCallback(async () => {
@@ -102,6 +104,8 @@ export function useRopers() {
       if (patch.phone !== undefined) apiPatch.phone = patch.phone
       if (patch.email !== undefined) apiPatch.email = patch.email
   if (patch.level !== undefined) apiPatch.level = patch.level
+      // Versión que se mostró al editar: si otra ventana lo cambió, el backend responde Conflict
+      apiPatch.expected_version = ropers.find((r) => r.id === id)?.version ?? null
 
       await updateRoper(id, apiPatch)
       toast.success('Roper actualizado')
@@ -110,11 +114,12 @@ export function useRopers() {
       const msg = String(e?.message ?? e)
       setError(msg)
       toast.error(msg)
+      if (isCommandError(e, 'Conflict')) await refresh()
       throw e
     } finally {
       setLoading(false)
     }
-  }, [refresh])
+  }, [refresh, ropers])
 
   const remove = useCallback(async (id: number) => {
     setLoading(true)
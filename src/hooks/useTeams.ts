@@ -1,5 +1,5 @@
 import { useEffect, useState, useCallback } from 'react';
-import { listTeams, createTeam, updateTeam, deleteTeam } from '@/lib/api';
+import { listTeams, createTeam, updateTeam, deleteTeam, isCommandError } from '@/lib/api';
 
 export function useTeams(eventId: number, locked: boolean) {
   const [teams, setTeams] = useState<any[]>([]);
@@ -32,6 +32,7 @@ export function useTeams(eventId: number, locked: boolean) {
         status: r.status ?? 'active',
         created_at: r.created_at,
         updated_at: r.updated_at,
+        version: r.version,
       }));
 
       try {
@@ -75,7 +76,14 @@ export function useTeams(eventId: number, locked: boolean) {
 
   const edit = async (payload: { id: number; rating?: number; status?: 'active' | 'inactive' }) => {
     if (locked) throw new Error('Evento bloqueado; no puedes editar equipos.');
-    await updateTeam(payload as any);
+    // Versión cargada del equipo: si otra ventana lo cambió, Conflict y se recarga la lista
+    const expected_version = teams.find((t) => t.id === payload.id)?.version ?? null;
+    try {
+      await updateTeam({ ...payload, expected_version });
+    } catch (e) {
+      if (isCommandError(e, 'Conflict')) await refresh();
+      throw e;
+    }
     await refresh();
   };
 
@@ -1,4 +1,55 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke as tauriInvoke } from '@tauri-apps/api/core';
+import type { AuditLogItem } from '../types';
+
+// Errores del backend: { code, message, field, details } (ver src-tauri/src/error.rs)
+export type AppErrorCode =
+  | 'NotFound'
+  | 'EventLocked'
+  | 'Validation'
+  | 'Unauthorized'
+  | 'Forbidden'
+  | 'Conflict'
+  | 'Database'
+  | 'Internal';
+
+export class CommandError extends Error {
+  code: AppErrorCode;
+  field: string | null;
+  details: unknown;
+
+  constructor(raw: { code: AppErrorCode; message: string; field?: string | null; details?: unknown }) {
+    super(raw.message);
+    this.name = 'CommandError';
+    this.code = raw.code;
+    this.field = raw.field ?? null;
+    this.details = raw.details ?? null;
+  }
+
+  // Los toasts existentes usan String(e) / e.toString(): mostramos sólo el mensaje.
+  toString() {
+    return this.message;
+  }
+}
+
+export const isCommandError = (e: unknown, code?: AppErrorCode): e is CommandError =>
+  e instanceof CommandError && (code === undefined || e.code === code);
+
+// Guardado con `expected_version` vieja: el Conflict trae la fila tal como quedó (details.current).
+export const staleRow = <T = any>(e: unknown): T | null =>
+  isCommandError(e, 'Conflict') && e.details && typeof e.details === 'object' && 'current' in e.details
+    ? ((e.details as { current: T }).current)
+    : null;
+
+const invoke = async <T>(cmd: string, args?: Record<string, unknown>): Promise<T> => {
+  try {
+    return await tauriInvoke<T>(cmd, args);
+  } catch (e: any) {
+    if (e && typeof e === 'object' && typeof e.code === 'string') {
+      throw new CommandError(e);
+    }
+    throw e;
+  }
+};
 
 // Series
 export const getSeries = () => invoke<any[]>('list_series');
@@ -58,29 +109,203 @@ export const updateTeam = (payload: {
   id: number;
   rating?: number;
   status?: 'active' | 'inactive';
+  expected_version?: number | null; // versión leída; si cambió, Conflict con la fila actual
 }) => invoke<void>('update_team', { t: payload });
 
 export const deleteTeam = (id: number) =>
   invoke<void>('delete_team', { id });
 
+export type ScratchPolicy = 'close_gap' | 'keep_slot';
+
+// Short-go: quiénes corren la ronda final y qué pasa con los empates en el corte.
+export type CallbackMode = 'all' | 'top_n' | 'top_percent';
+export type CallbackTies = 'include' | 'exclude' | 'tiebreak';
+// Orden de la ronda final cuando no hay corte de short-go.
+export type FinalOrder = 'aggregate' | 'average' | 'standings' | 'random' | 'round_one';
+
+// `rounds` = rondas donde el equipo perdió su lugar sin haber corrido.
+export type ScratchOutcome = {
+  team_id: number;
+  event_id: number;
+  policy: ScratchPolicy;
+  rounds: number[];
+};
+
+// Retira un equipo ya sorteado; qué pasa con su lugar lo decide `event.scratch_policy`.
+export const scratchTeam = (teamId: number, reason?: string | null) =>
+  invoke<ScratchOutcome>('scratch_team', { teamId, reason: reason ?? null });
+
 export const getRuns = (eventId: number, round?: number) =>
   invoke<any[]>('get_runs', { eventId, round });
 
 export const getRunsExpanded = (eventId: number, round?: number) =>
   invoke<any[]>('get_runs_expanded', { eventId, round });
 
+// Una versión de la corrida (run_revision): valores de antes y después, autor y motivo
+export type RunRevision = {
+  id: number;
+  run_id: number | null; // null si la fila de la corrida se borró al rehacer el sorteo
+  revision: number;
+  action: 'baseline' | 'capture' | 'correction' | 'recompute' | 'undo' | 'redo';
+  old_time_sec: number | null;
+  old_penalty: number | null;
+  old_total_sec: number | null;
+  old_no_time: number | null;
+  old_dq: number | null;
+  old_status: string | null;
+  new_time_sec: number | null;
+  new_penalty: number;
+  new_total_sec: number | null;
+  new_no_time: number;
+  new_dq: number;
+  new_status: string;
+  penalties: string | null;
+  reason: string | null;
+  target_revision: number | null; // en undo/redo, la revisión que revierte o vuelve a aplicar
+  user_id: number | null;
+  user_name: string | null;
+  created_at: string;
+};
+
+export const getRunHistory = (runId: number) =>
+  invoke<RunRevision[]>('get_run_history', { runId });
+
+// Última captura del evento que se puede deshacer / rehacer
+export type UndoTarget = {
+  revision_id: number;
+  revision: number;
+  action: 'capture' | 'correction';
+  run_id: number;
+  team_id: number;
+  round: number;
+  position: number;
+  header_name: string;
+  heeler_name: string;
+  total_sec: number | null;
+  no_time: number;
+  dq: number;
+  requires_pin: boolean; // deshacer/rehacer una corrección pide el PIN del evento
+};
+
+export type UndoState = { undo: UndoTarget | null; redo: UndoTarget | null };
+
+export type UndoOutcome = {
+  run_id: number;
+  team_id: number;
+  round: number;
+  revision: number;
+  state: UndoState;
+};
+
+export const getRunUndoState = (eventId: number) =>
+  invoke<UndoState>('get_run_undo_state', { eventId });
+
+export const undoLastRunChange = (eventId: number, pin?: string | null) =>
+  invoke<UndoOutcome>('undo_last_run_change', { eventId, pin: pin ?? null });
+
+export const redoLastRunChange = (eventId: number, pin?: string | null) =>
+  invoke<UndoOutcome>('redo_last_run_change', { eventId, pin: pin ?? null });
+
+// Timer de rodeo (serie o TCP): la lectura se ofrece a la siguiente corrida pendiente
+export type TimerSource =
+  | { kind: 'serial'; path: string; baud?: number | null }
+  | { kind: 'tcp'; addr: string };
+
+export type TimerReading = {
+  seq: number;
+  seconds: number;
+  raw: string;
+  received_at: string;
+};
+
+export type TimerStatus = {
+  source: TimerSource | null;
+  connected: boolean;
+  running: boolean;
+  reading: TimerReading | null;
+  frames: number;
+  rejected: number;
+  last_error: string | null;
+};
+
+export type TimerOffer = {
+  status: TimerStatus;
+  run: any | null; // fila de get_runs_expanded
+};
+
+export const timerStatus = () => invoke<TimerStatus>('timer_status');
+
+export const connectTimer = (source: TimerSource) =>
+  invoke<TimerStatus>('connect_timer', { source });
+
+export const disconnectTimer = () => invoke<TimerStatus>('disconnect_timer');
+
+export const getTimerOffer = (eventId: number, round?: number) =>
+  invoke<TimerOffer>('get_timer_offer', { eventId, round });
+
+// `seq` de la lectura y corrida (id + version) de la oferta mostrada: si llegó otro tiempo o
+// la corrida ya se capturó, el backend responde Conflict y la lectura sigue esperando
+export const confirmTimerTime = (payload: { event_id: number; run_id: number; expected_version: number; seq: number; penalties?: number[] }) =>
+  invoke<number>('confirm_timer_time', { payload });
+
+export const dismissTimerReading = (seq: number) =>
+  invoke<boolean>('dismiss_timer_reading', { seq });
+
+// Penalizaciones con tipo: catálogo por evento y renglones por corrida
+export type PenaltyType = {
+  id: number;
+  event_id: number;
+  code: string;
+  label: string;
+  seconds: number;
+  is_active: number;
+  created_at: string;
+};
+
+// `penalty_type_id` null = penalización suelta de antes de los tipos
+export type RunPenalty = {
+  id: number;
+  run_id: number;
+  penalty_type_id: number | null;
+  label: string;
+  seconds: number;
+};
+
+export const listPenaltyTypes = (eventId: number, includeInactive?: boolean) =>
+  invoke<PenaltyType[]>('list_penalty_types', { eventId, includeInactive });
+
+export const createPenaltyType = (payload: { event_id: number; code: string; label: string; seconds: number }) =>
+  invoke<number>('create_penalty_type', { payload });
+
+// Los tipos no se borran: `is_active: false` los saca de la captura
+export const updatePenaltyType = (id: number, patch: { label?: string; seconds?: number; is_active?: boolean }) =>
+  invoke<void>('update_penalty_type', { id, patch });
+
+// Resultado de sortear: `violations` = salidas que no pudieron respetar `min_spacing`.
+export type DrawSummary = {
+  teams: number;
+  rounds: number;
+  min_spacing: number;
+  violations: number;
+  seeds: string[];
+};
+
 export const generateDraw = (opts: {
   event_id: number;
   round: number;
   reseed?: boolean;
   seed_runs?: boolean;
-}) => invoke<number>('generate_draw', { opts });
+  min_spacing?: number | null; // corridas entre salidas del mismo roper (default 2)
+  pin?: string | null; // PIN del evento si la ronda ya tiene sorteo
+}) => invoke<DrawSummary>('generate_draw', { opts });
 
 export const generateDrawBatch = (opts: {
   event_id: number;
   rounds: number;
   shuffle: boolean;
-}) => invoke<number>('generate_draw_batch', { opts });
+  min_spacing?: number | null;
+  pin?: string | null;
+}) => invoke<DrawSummary>('generate_draw_batch', { opts });
 
 export const getStandings = (eventId: number) =>
   invoke<any[]>('get_standings', { eventId });
@@ -88,6 +313,69 @@ export const getStandings = (eventId: number) =>
 export const getDraw = (eventId: number, round: number) =>
   invoke<any[]>('get_draw', { eventId, round });
 
+// Edición manual del draw (pide el PIN del evento si tiene). Devuelven el draw de la ronda.
+export const swapDrawPositions = (opts: {
+  event_id: number; round: number; position_a: number; position_b: number; pin?: string | null;
+}) => invoke<any[]>('swap_draw_positions', { opts });
+
+export const moveDrawTeam = (opts: {
+  event_id: number; round: number; team_id: number; to_position: number; pin?: string | null;
+}) => invoke<any[]>('move_draw_team', { opts });
+
+export const insertDrawTeam = (opts: {
+  event_id: number; round: number; team_id: number; position?: number | null; pin?: string | null; // sin posición: al final
+}) => invoke<any[]>('insert_draw_team', { opts });
+
+// Sorteo reproducible: la semilla guardada vuelve a dar el mismo orden.
+export type DrawMethod = 'random' | 'entry_order' | 'accumulated_time' | 'average_time' | 'standings' | 'round_one' | 'callback';
+
+export type DrawVerification = {
+  event_id: number;
+  round: number;
+  method: DrawMethod;
+  seed: string | null; // 16 dígitos hex; null si el método no es aleatorio
+  rng: string;
+  generated_at: string;
+  min_spacing: number;
+  violations: number;
+  entries: { team_id: number; header_id: number; heeler_id: number; accumulated_sec?: number }[];
+  expected_order: number[];
+  actual_order: number[];
+  matches: boolean;
+  message: string;
+};
+
+export const verifyDraw = (eventId: number, round: number) =>
+  invoke<DrawVerification>('verify_draw', { eventId, round });
+
+// Revisión del draw para firmarlo antes de la competencia (sólo lectura).
+export type DrawRoundReport = {
+  round: number;
+  teams: number;
+  method: DrawMethod | null;
+  min_spacing: number;
+  duplicate_positions: number[];
+  position_gaps: number[];
+  run_mismatches: number[];
+  missing_teams: number[];
+  eliminated_teams: number[];
+  spacing: { roper_id: number; roper_name: string; first_position: number; second_position: number; gap: number }[];
+};
+
+export type DrawReport = {
+  event_id: number;
+  rounds: DrawRoundReport[];
+  fairness_rounds: number[];
+  // mean_relative: 0 = primero de la ronda, 1 = último
+  teams: { team_id: number; header_name: string; heeler_name: string; rounds: number; mean_position: number; mean_relative: number }[];
+  ropers: { roper_id: number; roper_name: string; runs: number; early: number; middle: number; late: number; mean_relative: number }[];
+  issues: number;
+  clean: boolean;
+};
+
+export const getDrawReport = (eventId: number) =>
+  invoke<DrawReport>('get_draw_report', { eventId });
+
 export const updateSeries = (id: number, patch: {
   name?: string; season?: string; status?: "active"|"upcoming"|"archived";
   start_date?: string | null; end_date?: string | null;
@@ -129,6 +417,11 @@ export const createEvent = (payload: {
   prize_pool?: number | null;
   max_team_rating?: number | null;
   payoff_allocation?: string | null;
+  scratch_policy?: ScratchPolicy | null;
+  callback_mode?: CallbackMode | null;
+  callback_value?: number | null;
+  callback_ties?: CallbackTies | null;
+  final_order?: FinalOrder | null;
   admin_pin?: string | null;
 }) => {
   // normalize status values before sending to backend
@@ -139,8 +432,13 @@ export const createEvent = (payload: {
   return invoke<number>('create_event', { payload: p });
 }
 
-export const updateEventStatus = (id: number, status: string) =>
-  invoke<void>('update_event_status', { id, status });
+// Sacar un evento de 'locked' pide el PIN del evento (si tiene).
+export const updateEventStatus = (id: number, status: string, pin?: string | null) =>
+  invoke<void>('update_event_status', { id, status, pin });
+
+// El PIN nunca viaja al frontend: las filas de evento sólo traen `has_admin_pin`.
+export const verifyEventPin = (eventId: number, pin: string) =>
+  invoke<boolean>('verify_event_pin', { eventId, pin });
 
 export const updateEvent = (id: number, patch: {
   name?: string;
@@ -152,7 +450,14 @@ export const updateEvent = (id: number, patch: {
   location?: string | null;
   max_team_rating?: number | null;
   payoff_allocation?: string | null;
-  admin_pin?: string | null;
+  scratch_policy?: ScratchPolicy | null;
+  callback_mode?: CallbackMode | null;
+  callback_value?: number | null;
+  callback_ties?: CallbackTies | null;
+  final_order?: FinalOrder | null;
+  admin_pin?: string | null; // PIN nuevo; null/omitido = mantener
+  pin?: string | null; // PIN actual, para desbloquear o cambiar el PIN
+  expected_version?: number | null; // versión leída; si cambió, Conflict con la fila actual
 }) => invoke<void>('update_event', { id, patch });
 
 export const deleteEvent = (id: number) =>
@@ -167,10 +472,13 @@ export const saveRun = (payload: {
   round: number;
   position: number;
   time_sec: number | null;
-  penalty: number;
+  penalty?: number; // suelta, sólo si no se mandan `penalties`
+  penalties?: number[]; // ids de PenaltyType, uno por penalización
   no_time: boolean;
   dq: boolean;
-  captured_by?: number | null;
+  pin?: string | null; // requerido para sobrescribir una corrida completada
+  reason?: string | null; // motivo de la corrección, queda en el historial
+  expected_version?: number | null; // versión leída; si cambió, Conflict con la fila actual
 }) => invoke<number>('save_run', { payload });
 
 // Ropers
@@ -186,6 +494,7 @@ export const updateRoper = (id: number, patch: Partial<{
   first_name: string; last_name: string;
   specialty: 'header'|'heeler'|'both';
   rating: number; phone?: string | null; email?: string | null; level?: 'pro'|'amateur'|'principiante'
+  expected_version: number | null; // versión leída; si cambió, Conflict con la fila actual
 }>) => invoke<void>('update_roper', { r: { id, ...patch } });
 
 export const deleteRoper = (id: number) =>
@@ -226,10 +535,119 @@ export const exportEvent = (eventId: number, options: {
 }) => invoke<void>('export_event_to_excel', { eventId, options });
 
 export const getRecentActivity = (limit: number, offset: number = 0) =>
-  invoke<any[]>('get_recent_activity', { limit, offset });
+  invoke<AuditLogItem[]>('get_recent_activity', { limit, offset });
 
 export const getDashboardStats = () =>
   invoke<any>('get_dashboard_stats');
 
 export const getSeriesLogs = (seriesId: number) =>
-  invoke<any[]>('get_series_logs', { seriesId });
+  invoke<AuditLogItem[]>('get_series_logs', { seriesId });
+
+// Backup / restore
+export const backupDatabase = (dest: string) =>
+  invoke<void>('backup_database', { dest });
+
+export const restoreDatabase = (src: string) =>
+  invoke<{
+    safety_backup: string;
+    restored_version: number;
+    migrations_applied: number;
+    requires_login: boolean; // la sesión se cerró: los usuarios son los del respaldo
+  }>('restore_database', { src });
+
+export type SnapshotInfo = {
+  path: string;
+  file_name: string;
+  reason: 'auto' | 'lock' | 'completed' | string;
+  created_at: string;
+  size_bytes: number;
+};
+
+export const listSnapshots = () =>
+  invoke<SnapshotInfo[]>('list_snapshots');
+
+export const getSnapshotSettings = () =>
+  invoke<{ interval_minutes: number; keep: number }>('get_snapshot_settings');
+
+export const updateSnapshotSettings = (settings: { interval_minutes: number; keep: number }) =>
+  invoke<void>('update_snapshot_settings', { settings });
+
+// Usuarios / sesión
+export type UserRole = 'admin' | 'operator' | 'viewer';
+
+export type User = {
+  id: number;
+  email: string;
+  full_name: string;
+  role: UserRole;
+  is_active: number;
+  created_at: string;
+};
+
+export const authStatus = () =>
+  invoke<{ has_users: boolean; user: User | null }>('auth_status');
+
+export const createUser = (payload: { email: string; full_name: string; password: string; role: UserRole }) =>
+  invoke<number>('create_user', { payload });
+
+export const login = (email: string, password: string) =>
+  invoke<User>('login', { email, password });
+
+export const logout = () => invoke<void>('logout');
+
+export const getCurrentUser = () => invoke<User | null>('get_current_user');
+
+export const changePassword = (currentPassword: string, newPassword: string) =>
+  invoke<void>('change_password', { currentPassword, newPassword });
+
+export const listUsers = () => invoke<User[]>('list_users');
+
+// Licencia (offline, firmada; ver src-tauri/src/license.rs)
+export type LicenseState = 'unlicensed' | 'trial' | 'active' | 'grace' | 'expired';
+
+export type LicenseStatus = {
+  state: LicenseState;
+  device_id: string; // huella de este equipo, para pedir la licencia
+  customer: string | null;
+  valid_until: string | null;
+  grace_until: string | null; // último día editable: fin de la gracia o de la prueba
+  days_left: number | null;
+  read_only: boolean;
+  message: string;
+};
+
+export const licenseStatus = () => invoke<LicenseStatus>('license_status');
+export const activateLicense = (key: string) => invoke<LicenseStatus>('activate_license', { key });
+
+// Actividad: marcas de uso y sesiones (jornada de evento, captura por ronda)
+export type ActivityKind = 'login' | 'logout' | 'app_start' | 'app_stop' | 'event_day' | 'capture';
+
+export type ActivityRow = {
+  id: number;
+  kind: ActivityKind;
+  user_id: number | null;
+  user_name: string | null;
+  event_id: number | null;
+  event_name: string | null;
+  round: number | null;
+  details: string | null;
+  created_at: string;
+  ended_at: string | null; // null: sesión abierta (o marca suelta)
+  duration_sec: number | null;
+};
+
+export type ActivityFilter = {
+  from?: string | null; // AAAA-MM-DD, inclusive
+  to?: string | null;
+  kind?: ActivityKind | null;
+  user_id?: number | null;
+  event_id?: number | null;
+  limit?: number | null;
+};
+
+export const listActivity = (filter: ActivityFilter = {}) => invoke<ActivityRow[]>('list_activity', { filter });
+export const getEventSessions = (eventId: number) => invoke<ActivityRow[]>('get_event_sessions', { eventId });
+export const startActivitySession = (kind: 'event_day' | 'capture', eventId: number, round?: number | null) =>
+  invoke<number>('start_activity_session', { kind, eventId, round: round ?? null });
+export const endActivitySession = (kind: 'event_day' | 'capture', eventId: number, round?: number | null) =>
+  invoke<number>('end_activity_session', { kind, eventId, round: round ?? null });
@@ -0,0 +1,38 @@
+import { createContext, useContext } from 'react'
+import type { LicenseStatus, User, UserRole } from './api'
+
+// Usuario logueado (el backend guarda la sesión; esto es sólo para pintar la UI).
+export type SessionContextValue = {
+  user: User
+  signOut: () => Promise<void>
+  // null mientras se consulta; el backend es quien bloquea las escrituras
+  license: LicenseStatus | null
+  setLicense: (status: LicenseStatus) => void
+}
+
+export const SessionContext = createContext<SessionContextValue | null>(null)
+
+export function useSession(): SessionContextValue {
+  const ctx = useContext(SessionContext)
+  if (!ctx) throw new Error('useSession debe usarse dentro de SessionContext')
+  return ctx
+}
+
+export const roleLabel: Record<string, string> = {
+  admin: 'Administrador',
+  operator: 'Operador',
+  viewer: 'Solo lectura',
+}
+
+// Mismo orden que `users::Role` en el backend (que es quien realmente aplica el permiso).
+const roleRank: Record<string, number> = { viewer: 0, operator: 1, admin: 2 }
+
+export const hasRole = (user: User, min: UserRole) => (roleRank[user.role] ?? 0) >= roleRank[min]
+
+export const initials = (name: string) =>
+  name
+    .split(/\s+/)
+    .filter(Boolean)
+    .slice(0, 2)
+    .map((p) => p[0]!.toUpperCase())
+    .join('')
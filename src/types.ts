@@ -28,9 +28,20 @@ export interface Event {
   teamsCount: number
   pot: number
   payoffAllocation?: string | null
+  // Qué pasa con el lugar de un equipo retirado: 'close_gap' | 'keep_slot'
+  scratchPolicy?: 'close_gap' | 'keep_slot'
+  // Short-go: 'all' | 'top_n' | 'top_percent', con N (o %) y manejo de empates en el corte
+  callbackMode?: 'all' | 'top_n' | 'top_percent'
+  callbackValue?: number | null
+  callbackTies?: 'include' | 'exclude' | 'tiebreak'
+  finalOrder?: 'aggregate' | 'average' | 'standings' | 'random' | 'round_one'
+  // PIN nuevo escrito en el formulario; el backend nunca lo devuelve
   adminPin?: string | null
+  hasAdminPin?: boolean
   createdAt?: string
   updatedAt?: string
+  // Versión de la fila al cargarla (concurrencia optimista)
+  version?: number
   // Legacy/Frontend computed
   lastUpdated?: string
 }
@@ -51,9 +62,17 @@ export interface Run {
   position: number
   time: number | null
   penalty: number
+  // Desglose de `penalty` (etiqueta y segundos de cada penalización)
+  penalties?: { typeId: number | null; label: string; seconds: number }[]
   noTime: boolean
   dq: boolean
   status: 'pending' | 'completed' | 'skipped'
+  // Lugar conservado de un equipo retirado (scratch con 'keep_slot')
+  scratched?: boolean
+  // Versiones guardadas en el historial (más de 1 = corregida)
+  revisions?: number
+  // Versión de la fila al cargarla; se manda al guardar para no pisar cambios de otra ventana
+  version?: number
 }
 
 export interface Standing {
@@ -80,7 +99,10 @@ export interface AuditLogItem {
   entity_type: string
   entity_id?: number
   user_id?: number
+  user_name?: string | null // null: acción del sistema
   metadata?: string
+  before_json?: string | null
+  after_json?: string | null
   created_at: string
 }
 